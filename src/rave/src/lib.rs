@@ -1,5 +1,7 @@
 //! Under development. Check back later.
 
+#[cfg(feature = "g711")]
+pub use rave_g711 as g711;
 #[cfg(feature = "h264")]
 pub use rave_h264 as h264;
 #[cfg(feature = "h264_nvidia")]
@@ -0,0 +1,188 @@
+use std::marker::PhantomData;
+
+use rave_types::audio_format::{ChannelLayout, S16};
+use rave_types::audio_frame::AudioFrame;
+use rave_types::codec::{Pcma, Pcmu};
+use rave_types::decode::DecodeAudio;
+use rave_types::device::Local;
+use rave_types::unit::Unit;
+
+use crate::error::Error;
+use crate::law::{Law, SAMPLE_RATE};
+use crate::pcm::Pcm;
+
+type Result<T> = std::result::Result<T, Error>;
+
+pub type MuLawDecoder<F = S16> = Decoder<Pcmu, F>;
+pub type ALawDecoder<F = S16> = Decoder<Pcma, F>;
+
+/// G.711 decoder.
+///
+/// Decodes into samples at 8000 Hz in sample format `F`, which is interleaved 16-bit samples by
+/// default. G.711 does not signal the number of channels in-band, so it must be configured if it
+/// is not mono.
+pub struct Decoder<L: Law, F: Pcm = S16> {
+    channel_layout: ChannelLayout,
+    _law: PhantomData<L>,
+    _format: PhantomData<F>,
+}
+
+impl<L: Law, F: Pcm> Decoder<L, F> {
+    pub fn new() -> Self {
+        Self {
+            channel_layout: ChannelLayout::Mono,
+            _law: PhantomData,
+            _format: PhantomData,
+        }
+    }
+
+    /// Set channel layout of decoded frames. Mono by default.
+    pub fn with_channel_layout(mut self, channel_layout: ChannelLayout) -> Self {
+        self.channel_layout = channel_layout;
+        self
+    }
+}
+
+impl<L: Law, F: Pcm> Default for Decoder<L, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Law, F: Pcm> DecodeAudio for Decoder<L, F> {
+    type Device = Local;
+    type Codec = L;
+    type SampleFormat = F;
+    type Error = Error;
+
+    fn decode(&mut self, unit: Unit<L>) -> Result<Option<AudioFrame<Local, F>>> {
+        if unit.data.is_empty() {
+            return Ok(None);
+        }
+        let num_channels = self.channel_layout.num_channels();
+        if num_channels == 0 || !unit.data.len().is_multiple_of(num_channels) {
+            return Err(Error::UnitLengthInvalid {
+                len: unit.data.len(),
+                num_channels,
+            });
+        }
+        let samples = unit.data.iter().map(|code| L::expand(*code)).collect();
+        Ok(Some(AudioFrame::new(
+            F::from_interleaved(samples, num_channels),
+            SAMPLE_RATE,
+            self.channel_layout,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rave_types::audio_format::{F32p, S16p, F32};
+
+    use super::*;
+
+    /// Expanded samples `[0, 32124]` and `[-32124, 0]`, interleaved.
+    const STEREO: [u8; 4] = [0xff, 0x80, 0x00, 0xff];
+
+    const MAX: f32 = 32124.0 / 32768.0;
+
+    fn decode<F: Pcm>(data: &[u8], channel_layout: ChannelLayout) -> AudioFrame<Local, F> {
+        MuLawDecoder::<F>::new()
+            .with_channel_layout(channel_layout)
+            .decode(Unit::new(data.to_vec().into()))
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn decode_mono() {
+        let frame = MuLawDecoder::<S16>::new()
+            .decode(Unit::new(vec![0xff, 0x80, 0x00].into()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.data, vec![0, 32124, -32124]);
+        assert_eq!(frame.sample_rate, SAMPLE_RATE);
+        assert_eq!(frame.channel_layout, ChannelLayout::Mono);
+        assert_eq!(frame.num_samples(), 3);
+    }
+
+    #[test]
+    fn decode_s16() {
+        let frame = decode::<S16>(&STEREO, ChannelLayout::Stereo);
+        assert_eq!(frame.data, vec![0, 32124, -32124, 0]);
+        assert_eq!(frame.sample_rate, SAMPLE_RATE);
+        assert_eq!(frame.channel_layout, ChannelLayout::Stereo);
+        assert_eq!(frame.num_samples(), 2);
+    }
+
+    #[test]
+    fn decode_s16_planar() {
+        let frame = decode::<S16p>(&STEREO, ChannelLayout::Stereo);
+        assert_eq!(frame.data, vec![vec![0, -32124], vec![32124, 0]]);
+        assert_eq!(frame.sample_rate, SAMPLE_RATE);
+        assert_eq!(frame.channel_layout, ChannelLayout::Stereo);
+        assert_eq!(frame.num_samples(), 2);
+    }
+
+    #[test]
+    fn decode_f32() {
+        let frame = decode::<F32>(&STEREO, ChannelLayout::Stereo);
+        assert_eq!(frame.data, vec![0.0, MAX, -MAX, 0.0]);
+        assert_eq!(frame.channel_layout, ChannelLayout::Stereo);
+        assert_eq!(frame.num_samples(), 2);
+    }
+
+    #[test]
+    fn decode_f32_planar() {
+        let frame = decode::<F32p>(&STEREO, ChannelLayout::Stereo);
+        assert_eq!(frame.data, vec![vec![0.0, -MAX], vec![MAX, 0.0]]);
+        assert_eq!(frame.channel_layout, ChannelLayout::Stereo);
+        assert_eq!(frame.num_samples(), 2);
+    }
+
+    #[test]
+    fn decode_multiple_channels() {
+        let frame = decode::<S16p>(
+            &[0xff, 0x80, 0x00, 0xff, 0x80, 0x00],
+            ChannelLayout::Discrete(3),
+        );
+        assert_eq!(
+            frame.data,
+            vec![vec![0, 0], vec![32124, 32124], vec![-32124, -32124]],
+        );
+        assert_eq!(frame.channel_layout, ChannelLayout::Discrete(3));
+        assert_eq!(frame.num_channels(), 3);
+        assert_eq!(frame.num_samples(), 2);
+    }
+
+    #[test]
+    fn decode_a_law() {
+        let frame = ALawDecoder::<S16>::new()
+            .decode(Unit::new(vec![0xd5, 0xaa, 0x2a].into()))
+            .unwrap()
+            .unwrap();
+        assert_eq!(frame.data, vec![8, 32256, -32256]);
+    }
+
+    #[test]
+    fn decode_empty_unit() {
+        assert!(MuLawDecoder::<S16>::new()
+            .decode(Unit::new(Vec::new().into()))
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn reject_unit_not_matching_channels() {
+        let result = MuLawDecoder::<S16p>::new()
+            .with_channel_layout(ChannelLayout::Stereo)
+            .decode(Unit::new(vec![0xff; 3].into()));
+        assert!(matches!(
+            result,
+            Err(Error::UnitLengthInvalid {
+                len: 3,
+                num_channels: 2
+            })
+        ));
+    }
+}
@@ -0,0 +1,184 @@
+use std::marker::PhantomData;
+
+use rave_types::audio_format::S16;
+use rave_types::audio_frame::AudioFrame;
+use rave_types::codec::{Pcma, Pcmu};
+use rave_types::device::Local;
+use rave_types::encode::EncodeAudio;
+use rave_types::unit::Unit;
+
+use crate::error::Error;
+use crate::law::{Law, SAMPLE_RATE};
+use crate::pcm::Pcm;
+
+type Result<T> = std::result::Result<T, Error>;
+
+pub type MuLawEncoder<F = S16> = Encoder<Pcmu, F>;
+pub type ALawEncoder<F = S16> = Encoder<Pcma, F>;
+
+/// G.711 encoder.
+///
+/// Encodes samples at 8000 Hz in sample format `F`, which is interleaved 16-bit samples by
+/// default. Every sample is compressed into a single byte, so each frame is encoded into exactly
+/// one unit, in which the channels are interleaved.
+pub struct Encoder<L: Law, F: Pcm = S16> {
+    _law: PhantomData<L>,
+    _format: PhantomData<F>,
+}
+
+impl<L: Law, F: Pcm> Encoder<L, F> {
+    pub fn new() -> Self {
+        Self {
+            _law: PhantomData,
+            _format: PhantomData,
+        }
+    }
+}
+
+impl<L: Law, F: Pcm> Default for Encoder<L, F> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<L: Law, F: Pcm> EncodeAudio for Encoder<L, F> {
+    type Device = Local;
+    type Codec = L;
+    type SampleFormat = F;
+    type Error = Error;
+
+    fn encode(&mut self, frame: AudioFrame<Local, F>) -> Result<Vec<Unit<L>>> {
+        if frame.sample_rate != SAMPLE_RATE {
+            return Err(Error::SampleRateUnsupported {
+                sample_rate: frame.sample_rate,
+            });
+        }
+        let num_channels = frame.num_channels();
+        let samples = F::to_interleaved(&frame.data, num_channels)
+            .ok_or(Error::FrameDataInvalid { num_channels })?;
+        if samples.is_empty() {
+            return Ok(Vec::new());
+        }
+        let data = samples.into_iter().map(L::compress).collect::<Vec<_>>();
+        Ok(vec![Unit::new(data.into())])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rave_types::audio_format::{ChannelLayout, F32p, S16p, F32};
+
+    use super::*;
+
+    /// Compressed samples `[0, i16::MAX]` and `[i16::MIN, 0]`, interleaved.
+    const STEREO: [u8; 4] = [0xff, 0x80, 0x00, 0xff];
+
+    fn encode<F: Pcm>(data: F::Data, channel_layout: ChannelLayout) -> Result<Vec<Unit<Pcmu>>> {
+        MuLawEncoder::<F>::new().encode(AudioFrame::new(data, SAMPLE_RATE, channel_layout))
+    }
+
+    fn encoded<L: Law>(units: Vec<Unit<L>>) -> Vec<Vec<u8>> {
+        units.into_iter().map(|unit| unit.data.to_vec()).collect()
+    }
+
+    #[test]
+    fn encode_s16() {
+        let units = encode::<S16>(vec![0, i16::MAX, i16::MIN, 0], ChannelLayout::Stereo);
+        assert_eq!(encoded(units.unwrap()), vec![STEREO.to_vec()]);
+    }
+
+    #[test]
+    fn encode_s16_planar() {
+        let units = encode::<S16p>(
+            vec![vec![0, i16::MIN], vec![i16::MAX, 0]],
+            ChannelLayout::Stereo,
+        );
+        assert_eq!(encoded(units.unwrap()), vec![STEREO.to_vec()]);
+    }
+
+    #[test]
+    fn encode_f32() {
+        let units = encode::<F32>(vec![0.0, 1.0, -1.0, 0.0], ChannelLayout::Stereo);
+        assert_eq!(encoded(units.unwrap()), vec![STEREO.to_vec()]);
+    }
+
+    #[test]
+    fn encode_f32_planar() {
+        // Samples out of range are clipped.
+        let units = encode::<F32p>(vec![vec![0.0, -2.0], vec![2.0, 0.0]], ChannelLayout::Stereo);
+        assert_eq!(encoded(units.unwrap()), vec![STEREO.to_vec()]);
+    }
+
+    #[test]
+    fn encode_multiple_channels() {
+        let units = encode::<S16p>(
+            vec![
+                vec![0, 0],
+                vec![i16::MAX, i16::MAX],
+                vec![i16::MIN, i16::MIN],
+            ],
+            ChannelLayout::Discrete(3),
+        );
+        assert_eq!(
+            encoded(units.unwrap()),
+            vec![vec![0xff, 0x80, 0x00, 0xff, 0x80, 0x00]],
+        );
+    }
+
+    #[test]
+    fn encode_a_law() {
+        let units = ALawEncoder::<S16>::new()
+            .encode(AudioFrame::new(
+                vec![0, i16::MAX, i16::MIN],
+                SAMPLE_RATE,
+                ChannelLayout::Mono,
+            ))
+            .unwrap();
+        assert_eq!(encoded(units), vec![vec![0xd5, 0xaa, 0x2a]]);
+    }
+
+    #[test]
+    fn encode_empty_frame() {
+        assert!(encode::<S16>(Vec::new(), ChannelLayout::Mono)
+            .unwrap()
+            .is_empty());
+        assert!(
+            encode::<F32p>(vec![Vec::new(), Vec::new()], ChannelLayout::Stereo)
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn reject_unsupported_sample_rate() {
+        let result = MuLawEncoder::<S16>::new().encode(AudioFrame::new(
+            vec![0; 16],
+            16000,
+            ChannelLayout::Mono,
+        ));
+        assert!(matches!(
+            result,
+            Err(Error::SampleRateUnsupported { sample_rate: 16000 })
+        ));
+    }
+
+    #[test]
+    fn reject_data_not_matching_channels() {
+        assert!(matches!(
+            encode::<S16>(vec![0; 3], ChannelLayout::Stereo),
+            Err(Error::FrameDataInvalid { num_channels: 2 })
+        ));
+        assert!(matches!(
+            encode::<F32>(vec![0.0; 2], ChannelLayout::Discrete(0)),
+            Err(Error::FrameDataInvalid { num_channels: 0 })
+        ));
+        assert!(matches!(
+            encode::<S16p>(vec![vec![0; 2]], ChannelLayout::Stereo),
+            Err(Error::FrameDataInvalid { num_channels: 2 })
+        ));
+        assert!(matches!(
+            encode::<F32p>(vec![vec![0.0; 2], vec![0.0; 3]], ChannelLayout::Stereo),
+            Err(Error::FrameDataInvalid { num_channels: 2 })
+        ));
+    }
+}
@@ -0,0 +1,33 @@
+#[derive(Debug)]
+pub enum Error {
+    SampleRateUnsupported { sample_rate: u32 },
+    UnitLengthInvalid { len: usize, num_channels: usize },
+    FrameDataInvalid { num_channels: usize },
+}
+
+impl std::fmt::Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Error::SampleRateUnsupported { sample_rate } => {
+                write!(
+                    f,
+                    "sample rate not supported (G.711 requires 8000 Hz): {sample_rate}"
+                )
+            }
+            Error::UnitLengthInvalid { len, num_channels } => {
+                write!(
+                    f,
+                    "unit length {len} is not a multiple of number of channels {num_channels}"
+                )
+            }
+            Error::FrameDataInvalid { num_channels } => {
+                write!(
+                    f,
+                    "frame data does not match number of channels {num_channels}"
+                )
+            }
+        }
+    }
+}
+
+impl std::error::Error for Error {}
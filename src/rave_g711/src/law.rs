@@ -0,0 +1,113 @@
+use bytes::Bytes;
+
+use rave_types::codec::{Codec, Pcma, Pcmu};
+
+/// G.711 sampling rate.
+pub const SAMPLE_RATE: u32 = 8_000;
+
+/// G.711 companding law.
+///
+/// Implemented for [`Pcmu`] (µ-law) and [`Pcma`] (A-law).
+pub trait Law: Codec<Data = Bytes> {
+    /// Compress 16-bit linear sample into 8-bit code.
+    fn compress(sample: i16) -> u8;
+
+    /// Expand 8-bit code into 16-bit linear sample.
+    fn expand(code: u8) -> i16;
+}
+
+impl Law for Pcmu {
+    fn compress(sample: i16) -> u8 {
+        const BIAS: i32 = 0x84;
+        const CLIP: i32 = 32635;
+
+        let sign = if sample < 0 { 0x80 } else { 0x00 };
+        let magnitude = (sample as i32).abs().min(CLIP) + BIAS;
+        // Position of highest bit in 7..=14 determines segment.
+        let exponent = (31 - (magnitude as u32).leading_zeros()) as i32 - 7;
+        let mantissa = (magnitude >> (exponent + 3)) & 0x0f;
+        !(sign | (exponent << 4) as u8 | mantissa as u8)
+    }
+
+    fn expand(code: u8) -> i16 {
+        let code = !code;
+        let exponent = (code >> 4) & 0x07;
+        let mantissa = (code & 0x0f) as i32;
+        let magnitude = (((mantissa << 3) + 0x84) << exponent) - 0x84;
+        if code & 0x80 != 0 {
+            -magnitude as i16
+        } else {
+            magnitude as i16
+        }
+    }
+}
+
+impl Law for Pcma {
+    fn compress(sample: i16) -> u8 {
+        // A-law operates on 13-bit samples.
+        let sample = (sample >> 3) as i32;
+        let (mask, magnitude) = if sample >= 0 {
+            (0xd5, sample)
+        } else {
+            (0x55, -sample - 1)
+        };
+        let segment = match magnitude {
+            0..=0x1f => 0,
+            _ => (31 - (magnitude as u32).leading_zeros()) as i32 - 4,
+        };
+        if segment >= 8 {
+            return 0x7f ^ mask;
+        }
+        let shift = if segment < 2 { 1 } else { segment };
+        let code = (segment << 4) | ((magnitude >> shift) & 0x0f);
+        code as u8 ^ mask
+    }
+
+    fn expand(code: u8) -> i16 {
+        let code = code ^ 0x55;
+        let segment = (code & 0x70) >> 4;
+        let magnitude = ((code & 0x0f) as i32) << 4;
+        let magnitude = match segment {
+            0 => magnitude + 0x08,
+            1 => magnitude + 0x108,
+            _ => (magnitude + 0x108) << (segment - 1),
+        };
+        if code & 0x80 != 0 {
+            magnitude as i16
+        } else {
+            -magnitude as i16
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mu_law() {
+        assert_eq!(Pcmu::compress(0), 0xff);
+        assert_eq!(Pcmu::compress(i16::MAX), 0x80);
+        assert_eq!(Pcmu::compress(i16::MIN), 0x00);
+        assert_eq!(Pcmu::expand(0xff), 0);
+        assert_eq!(Pcmu::expand(0x80), 32124);
+        assert_eq!(Pcmu::expand(0x00), -32124);
+        // 0x7f is negative zero, which compresses to positive zero.
+        for code in (0..=255).filter(|code| *code != 0x7f) {
+            assert_eq!(Pcmu::compress(Pcmu::expand(code)), code);
+        }
+    }
+
+    #[test]
+    fn a_law() {
+        assert_eq!(Pcma::compress(0), 0xd5);
+        assert_eq!(Pcma::compress(i16::MAX), 0xaa);
+        assert_eq!(Pcma::compress(i16::MIN), 0x2a);
+        assert_eq!(Pcma::expand(0xd5), 8);
+        assert_eq!(Pcma::expand(0xaa), 32256);
+        assert_eq!(Pcma::expand(0x2a), -32256);
+        for code in 0..=255 {
+            assert_eq!(Pcma::compress(Pcma::expand(code)), code);
+        }
+    }
+}
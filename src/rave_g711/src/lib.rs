@@ -0,0 +1,11 @@
+pub mod decode;
+pub mod encode;
+pub mod error;
+pub mod law;
+pub mod pcm;
+
+pub use decode::{ALawDecoder, Decoder, MuLawDecoder};
+pub use encode::{ALawEncoder, Encoder, MuLawEncoder};
+pub use error::Error;
+pub use law::{Law, SAMPLE_RATE};
+pub use pcm::Pcm;
@@ -0,0 +1,104 @@
+use rave_types::audio_format::{F32p, S16p, SampleFormat, F32, S16};
+
+/// Sample format that can be converted from and into interleaved 16-bit samples, which is what
+/// G.711 compresses.
+pub trait Pcm: SampleFormat {
+    /// Convert data with `num_channels` channels into interleaved 16-bit samples.
+    ///
+    /// Returns `None` if the data does not match the number of channels.
+    fn to_interleaved(data: &Self::Data, num_channels: usize) -> Option<Vec<i16>>;
+
+    /// Convert interleaved 16-bit samples with `num_channels` channels into data.
+    fn from_interleaved(samples: Vec<i16>, num_channels: usize) -> Self::Data;
+}
+
+impl Pcm for S16 {
+    fn to_interleaved(data: &Vec<i16>, num_channels: usize) -> Option<Vec<i16>> {
+        check_interleaved(data, num_channels)?;
+        Some(data.clone())
+    }
+
+    fn from_interleaved(samples: Vec<i16>, _num_channels: usize) -> Vec<i16> {
+        samples
+    }
+}
+
+impl Pcm for S16p {
+    fn to_interleaved(data: &Vec<Vec<i16>>, num_channels: usize) -> Option<Vec<i16>> {
+        interleave(data, num_channels, |sample| sample)
+    }
+
+    fn from_interleaved(samples: Vec<i16>, num_channels: usize) -> Vec<Vec<i16>> {
+        deinterleave(&samples, num_channels, |sample| sample)
+    }
+}
+
+impl Pcm for F32 {
+    fn to_interleaved(data: &Vec<f32>, num_channels: usize) -> Option<Vec<i16>> {
+        check_interleaved(data, num_channels)?;
+        Some(data.iter().copied().map(f32_to_s16).collect())
+    }
+
+    fn from_interleaved(samples: Vec<i16>, _num_channels: usize) -> Vec<f32> {
+        samples.into_iter().map(s16_to_f32).collect()
+    }
+}
+
+impl Pcm for F32p {
+    fn to_interleaved(data: &Vec<Vec<f32>>, num_channels: usize) -> Option<Vec<i16>> {
+        interleave(data, num_channels, f32_to_s16)
+    }
+
+    fn from_interleaved(samples: Vec<i16>, num_channels: usize) -> Vec<Vec<f32>> {
+        deinterleave(&samples, num_channels, s16_to_f32)
+    }
+}
+
+fn check_interleaved<T>(data: &[T], num_channels: usize) -> Option<()> {
+    (num_channels > 0 && data.len().is_multiple_of(num_channels)).then_some(())
+}
+
+fn interleave<T: Copy>(
+    planes: &[Vec<T>],
+    num_channels: usize,
+    convert: impl Fn(T) -> i16,
+) -> Option<Vec<i16>> {
+    let num_samples = planes.first()?.len();
+    if planes.len() != num_channels || planes.iter().any(|plane| plane.len() != num_samples) {
+        return None;
+    }
+    Some(
+        (0..num_samples)
+            .flat_map(|index| planes.iter().map(move |plane| plane[index]))
+            .map(convert)
+            .collect(),
+    )
+}
+
+fn deinterleave<T>(
+    samples: &[i16],
+    num_channels: usize,
+    convert: impl Fn(i16) -> T,
+) -> Vec<Vec<T>> {
+    (0..num_channels)
+        .map(|channel| {
+            samples
+                .iter()
+                .skip(channel)
+                .step_by(num_channels)
+                .copied()
+                .map(&convert)
+                .collect()
+        })
+        .collect()
+}
+
+/// Convert floating point sample in range `[-1.0, 1.0]` to 16-bit sample, clipping samples that
+/// are out of range.
+fn f32_to_s16(sample: f32) -> i16 {
+    (sample * 32768.0).clamp(i16::MIN as f32, i16::MAX as f32) as i16
+}
+
+fn s16_to_f32(sample: i16) -> f32 {
+    sample as f32 / 32768.0
+}
@@ -4,13 +4,18 @@ use crate::packet::Packet;
 use crate::packetization::h264::H264PacketizationMode;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     VersionUnknown { version: usize },
     CsrcCountInvalid { count: usize },
     ExtensionLengthInvalid { len: usize },
+    ExtensionProfileUnknown { profile_identifier: u16 },
+    ExtensionElementInvalid { id: u8, len: usize },
+    ExtensionDataLengthInvalid { uri: &'static str, len: usize },
     PaddingLengthInvalid { padding_divisor: u8, len: usize },
     NotEnoughData { have: usize, need: usize },
     PacketSizeExceedsMtu { packet: Packet, mtu: usize },
+    MtuTooSmall { mtu: usize, need: usize },
     H264PacketizationModeUnknown { mode: usize },
     H264PacketizationModeUnsupported { mode: H264PacketizationMode },
     H264NalUnitDataLengthInvalid { len: usize },
@@ -22,6 +27,40 @@ pub enum Error {
     H264FragmentationUnitHeaderInvalid { len: usize },
     H264FragmentedStateAlreadyStarted,
     H264FragmentedStateNeverStarted,
+    H264DecodingOrderNumberMissing { len: usize },
+    H265NalUnitDataLengthInvalid { len: usize },
+    H265NalUnitLengthTooSmall { len: usize },
+    H265AggregationUnitHeaderInvalid { len: usize },
+    H265AggregationUnitDataTooSmall { have: usize, need: usize },
+    H265FragmentationUnitHeaderInvalid { len: usize },
+    H265FragmentedStateAlreadyStarted,
+    H265FragmentedStateNeverStarted,
+    H265DecodingOrderNumberMissing { len: usize },
+    AacParameterMissing { name: String },
+    AacParameterInvalid { name: String, value: String },
+    AacAuHeadersLengthInvalid { len: usize },
+    AacAuDataTooSmall { have: usize, need: usize },
+    AacAuSizeInvalid { size: usize },
+    AacLatmConfigUnsupported,
+    AacLatmPayloadLengthInvalid { have: usize, need: usize },
+    OpusPacketInvalid { len: usize },
+    Vp9ReferenceIndicesTooMany { max: usize },
+    Av1ObuHeaderInvalid { header: u8 },
+    Av1ObuDataTooSmall { have: usize, need: usize },
+    Av1Leb128Invalid,
+    JpegTypeUnsupported { jpeg_type: u8 },
+    JpegQuantizationTablesMissing { q: u8 },
+    JpegImageInvalid { reason: &'static str },
+    JpegImageUnsupported { reason: &'static str },
+    RtcpCountInvalid { count: usize },
+    RtcpLengthInvalid { len: usize },
+    RtcpTextLengthInvalid { len: usize },
+    SrtpProtectionProfileUnknown { name: String },
+    SrtpMasterKeyLengthInvalid { have: usize, need: usize },
+    SrtpMasterSaltLengthInvalid { have: usize, need: usize },
+    SrtpAuthenticationFailed,
+    SrtpPacketReplayed { index: u64 },
+    SrtcpIndexExhausted { ssrc: u32 },
 }
 
 impl std::fmt::Display for Error {
@@ -34,6 +73,18 @@ impl std::fmt::Display for Error {
             Error::ExtensionLengthInvalid { len: length } => {
                 write!(f, "extension length invalid (overflow): {length}")
             }
+            Error::ExtensionProfileUnknown { profile_identifier } => {
+                write!(
+                    f,
+                    "extension profile identifier unknown: {profile_identifier:#06x}"
+                )
+            }
+            Error::ExtensionElementInvalid { id, len } => {
+                write!(f, "extension element invalid: id {id} (len {len})")
+            }
+            Error::ExtensionDataLengthInvalid { uri, len } => {
+                write!(f, "extension data length invalid: {len} (for {uri})")
+            }
             Error::PaddingLengthInvalid {
                 padding_divisor,
                 len,
@@ -50,6 +101,9 @@ impl std::fmt::Display for Error {
             Error::PacketSizeExceedsMtu { packet, mtu } => {
                 write!(f, "packet size exceeds mtu: {packet:?} > {mtu})")
             }
+            Error::MtuTooSmall { mtu, need } => {
+                write!(f, "mtu too small: {mtu} (need at least {need})")
+            }
             Error::H264PacketizationModeUnknown { mode } => {
                 write!(f, "h264 packetization mode unknown: {mode})")
             }
@@ -105,6 +159,150 @@ impl std::fmt::Display for Error {
             Error::H264FragmentedStateNeverStarted => {
                 write!(f, "received unexpected fragmented unit")
             }
+            Error::H264DecodingOrderNumberMissing { len } => {
+                write!(
+                    f,
+                    "nal unit too small to hold decoding order number (need 3 bytes): {len}"
+                )
+            }
+            Error::H265NalUnitDataLengthInvalid { len } => {
+                write!(f, "h265 nal unit data length invalid (overflow): {len}")
+            }
+            Error::H265NalUnitLengthTooSmall { len } => {
+                write!(
+                    f,
+                    "h265 nal unit data length too small (must be at least three bytes): {len}"
+                )
+            }
+            Error::H265AggregationUnitHeaderInvalid { len } => {
+                write!(f, "h265 aggregation unit header too small: {len}")
+            }
+            Error::H265AggregationUnitDataTooSmall { have, need } => {
+                write!(
+                    f,
+                    "h265 aggregation unit payload too small: {have} (need {need})"
+                )
+            }
+            Error::H265FragmentationUnitHeaderInvalid { len } => {
+                write!(f, "h265 fragmentation unit header too small: {len}")
+            }
+            Error::H265FragmentedStateAlreadyStarted => {
+                write!(
+                    f,
+                    "received h265 fragmented unit with start bit set \
+                        but never finished previous fragmented unit"
+                )
+            }
+            Error::H265FragmentedStateNeverStarted => {
+                write!(f, "received unexpected h265 fragmented unit")
+            }
+            Error::H265DecodingOrderNumberMissing { len } => {
+                write!(
+                    f,
+                    "h265 nal unit too small to hold decoding order number: {len}"
+                )
+            }
+            Error::AacParameterMissing { name } => {
+                write!(f, "aac format parameter missing: {name}")
+            }
+            Error::AacParameterInvalid { name, value } => {
+                write!(f, "aac format parameter invalid: {name}={value}")
+            }
+            Error::AacAuHeadersLengthInvalid { len } => {
+                write!(f, "aac au headers length invalid: {len} bits")
+            }
+            Error::AacAuDataTooSmall { have, need } => {
+                write!(f, "aac au data too small: {have} (need {need})")
+            }
+            Error::AacAuSizeInvalid { size } => {
+                write!(
+                    f,
+                    "aac au size invalid (does not fit size length or constant size): {size}"
+                )
+            }
+            Error::AacLatmConfigUnsupported => {
+                write!(
+                    f,
+                    "aac latm config unsupported (only audio mux version 0 with \
+                        a single program and layer is supported)"
+                )
+            }
+            Error::AacLatmPayloadLengthInvalid { have, need } => {
+                write!(
+                    f,
+                    "aac latm payload length exceeds audio mux element: {have} (need {need})"
+                )
+            }
+            Error::OpusPacketInvalid { len } => {
+                write!(
+                    f,
+                    "opus packet invalid (too small to hold toc and frame count): {len}"
+                )
+            }
+            Error::Vp9ReferenceIndicesTooMany { max } => {
+                write!(f, "vp9 reference indices too many (max {max})")
+            }
+            Error::Av1ObuHeaderInvalid { header } => {
+                write!(
+                    f,
+                    "av1 obu header invalid (forbidden bit set): {header:#04x}"
+                )
+            }
+            Error::Av1ObuDataTooSmall { have, need } => {
+                write!(
+                    f,
+                    "av1 obu data too small to hold obu of size {need} (have {have})"
+                )
+            }
+            Error::Av1Leb128Invalid => {
+                write!(f, "av1 leb128 value invalid (truncated or too large)")
+            }
+            Error::JpegTypeUnsupported { jpeg_type } => {
+                write!(
+                    f,
+                    "jpeg type not supported (must be 0, 1, 64 or 65): {jpeg_type}"
+                )
+            }
+            Error::JpegQuantizationTablesMissing { q } => {
+                write!(f, "jpeg quantization tables missing for q: {q}")
+            }
+            Error::JpegImageInvalid { reason } => {
+                write!(f, "jpeg image invalid: {reason}")
+            }
+            Error::JpegImageUnsupported { reason } => {
+                write!(f, "jpeg image not supported: {reason}")
+            }
+            Error::RtcpCountInvalid { count } => {
+                write!(f, "rtcp item count invalid (overflow): {count}")
+            }
+            Error::RtcpLengthInvalid { len } => {
+                write!(
+                    f,
+                    "rtcp packet length invalid (must be multiple of 4 and fit length field): {len}"
+                )
+            }
+            Error::RtcpTextLengthInvalid { len } => {
+                write!(f, "rtcp text length invalid (overflow): {len}")
+            }
+            Error::SrtpProtectionProfileUnknown { name } => {
+                write!(f, "srtp protection profile unknown: {name}")
+            }
+            Error::SrtpMasterKeyLengthInvalid { have, need } => {
+                write!(f, "srtp master key length invalid: {have} (need {need})")
+            }
+            Error::SrtpMasterSaltLengthInvalid { have, need } => {
+                write!(f, "srtp master salt length invalid: {have} (need {need})")
+            }
+            Error::SrtpAuthenticationFailed => write!(f, "srtp authentication failed"),
+            Error::SrtpPacketReplayed { index } => {
+                write!(f, "srtp packet replayed or too old: index {index}")
+            }
+            Error::SrtcpIndexExhausted { ssrc } => {
+                write!(
+                    f,
+                    "srtcp index exhausted (master key must be replaced): ssrc {ssrc}"
+                )
+            }
         }
     }
 }
@@ -0,0 +1,575 @@
+use std::collections::HashMap;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use crate::error::{Error, Result};
+use crate::packet::Extension;
+
+/// Profile identifier of one-byte header extensions (RFC 8285 section 4.2).
+pub const ONE_BYTE_PROFILE_IDENTIFIER: u16 = 0xbede;
+/// Profile identifier of two-byte header extensions (RFC 8285 section 4.3). The lower four bits
+/// are application bits and are ignored.
+pub const TWO_BYTE_PROFILE_IDENTIFIER: u16 = 0x1000;
+/// Profile identifier of ONVIF replay header extension (ONVIF Streaming Specification section
+/// 6.3).
+pub const ONVIF_REPLAY_PROFILE_IDENTIFIER: u16 = 0xabac;
+
+/// Seconds between NTP epoch (1900) and Unix epoch (1970).
+const NTP_UNIX_EPOCH_OFFSET: u64 = 2_208_988_800;
+
+/// Single header extension element.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionElement {
+    /// Local identifier, as negotiated with `a=extmap`.
+    pub id: u8,
+    pub data: Bytes,
+}
+
+impl ExtensionElement {
+    pub fn new(id: u8, data: Bytes) -> Self {
+        Self { id, data }
+    }
+}
+
+impl Extension {
+    /// Create header extension from elements.
+    ///
+    /// The one-byte form is used if possible, and the two-byte form otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `elements` - Header extension elements.
+    pub fn from_elements(elements: &[ExtensionElement]) -> Result<Self> {
+        let one_byte = elements.iter().all(|element| {
+            (1..=14).contains(&element.id) && (1..=16).contains(&element.data.len())
+        });
+
+        let mut data = BytesMut::new();
+        for element in elements {
+            let len = element.data.len();
+            if element.id == 0 || len > u8::MAX as usize {
+                return Err(Error::ExtensionElementInvalid {
+                    id: element.id,
+                    len,
+                });
+            }
+            if one_byte {
+                data.put_u8((element.id << 4) | (len - 1) as u8);
+            } else {
+                data.put_u8(element.id);
+                data.put_u8(len as u8);
+            }
+            data.put_slice(&element.data);
+        }
+        // Pad with zeros to multiple of 32 bits.
+        data.put_bytes(0, data.len().next_multiple_of(4) - data.len());
+
+        let data = data
+            .as_chunks::<4>()
+            .0
+            .iter()
+            .map(|word| u32::from_be_bytes(*word))
+            .collect();
+        Ok(Extension {
+            profile_identifier: if one_byte {
+                ONE_BYTE_PROFILE_IDENTIFIER
+            } else {
+                TWO_BYTE_PROFILE_IDENTIFIER
+            },
+            data,
+        })
+    }
+
+    /// Parse header extension elements (RFC 8285).
+    ///
+    /// # Return value
+    ///
+    /// Elements in order of appearance, or an error if the header extension is not in the one-byte
+    /// or two-byte form.
+    pub fn elements(&self) -> Result<Vec<ExtensionElement>> {
+        let one_byte = if self.profile_identifier == ONE_BYTE_PROFILE_IDENTIFIER {
+            true
+        } else if self.profile_identifier & 0xfff0 == TWO_BYTE_PROFILE_IDENTIFIER {
+            false
+        } else {
+            return Err(Error::ExtensionProfileUnknown {
+                profile_identifier: self.profile_identifier,
+            });
+        };
+
+        let data = self
+            .data
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Bytes>();
+        let mut elements = Vec::new();
+        let mut offset = 0;
+        while offset < data.len() {
+            let id = if one_byte {
+                data[offset] >> 4
+            } else {
+                data[offset]
+            };
+            // padding
+            if id == 0 {
+                offset += 1;
+                continue;
+            }
+            // Identifier 15 is reserved in the one-byte form and stops parsing.
+            if one_byte && id == 15 {
+                break;
+            }
+            let (header_len, len) = if one_byte {
+                (1, (data[offset] & 0x0f) as usize + 1)
+            } else {
+                let len = *data.get(offset + 1).ok_or(Error::NotEnoughData {
+                    have: data.len() - offset,
+                    need: 2,
+                })?;
+                (2, len as usize)
+            };
+            let start = offset + header_len;
+            if data.len() < start + len {
+                return Err(Error::NotEnoughData {
+                    have: data.len() - start,
+                    need: len,
+                });
+            }
+            elements.push(ExtensionElement::new(id, data.slice(start..start + len)));
+            offset = start + len;
+        }
+        Ok(elements)
+    }
+
+    /// Find data of header extension element by identifier.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - Local identifier of element.
+    pub fn element(&self, id: u8) -> Result<Option<Bytes>> {
+        Ok(self
+            .elements()?
+            .into_iter()
+            .find(|element| element.id == id)
+            .map(|element| element.data))
+    }
+}
+
+/// Typed header extension element that is identified by URI.
+pub trait HeaderExtension: Sized {
+    /// URI used in `a=extmap` to negotiate the header extension.
+    const URI: &'static str;
+
+    /// Parse element data.
+    fn parse(data: &[u8]) -> Result<Self>;
+
+    /// Serialize into element data.
+    fn to_bytes(&self) -> Bytes;
+
+    /// Convert into element with given local identifier.
+    fn to_element(&self, id: u8) -> ExtensionElement {
+        ExtensionElement::new(id, self.to_bytes())
+    }
+}
+
+/// Mapping between local identifiers and header extension URIs, as negotiated with `a=extmap`
+/// (RFC 8285 section 5).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExtensionMap {
+    uris: HashMap<u8, String>,
+}
+
+impl ExtensionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Map local identifier to URI, replacing any earlier mapping of the identifier.
+    pub fn insert(&mut self, id: u8, uri: impl Into<String>) {
+        self.uris.insert(id, uri.into());
+    }
+
+    /// Find local identifier of URI.
+    pub fn id(&self, uri: &str) -> Option<u8> {
+        self.uris
+            .iter()
+            .find(|(_, other)| other.as_str() == uri)
+            .map(|(id, _)| *id)
+    }
+
+    /// Find URI of local identifier.
+    pub fn uri(&self, id: u8) -> Option<&str> {
+        self.uris.get(&id).map(String::as_str)
+    }
+
+    /// Find and parse typed header extension element.
+    ///
+    /// # Arguments
+    ///
+    /// * `extension` - Header extension of packet.
+    ///
+    /// # Return value
+    ///
+    /// Typed element, or `None` if the URI is not mapped or the packet does not hold the element.
+    pub fn get<T: HeaderExtension>(&self, extension: &Extension) -> Result<Option<T>> {
+        let Some(id) = self.id(T::URI) else {
+            return Ok(None);
+        };
+        extension
+            .element(id)?
+            .map(|data| T::parse(&data))
+            .transpose()
+    }
+
+    /// Convert typed header extension to element, if its URI is mapped.
+    pub fn element<T: HeaderExtension>(&self, value: &T) -> Option<ExtensionElement> {
+        self.id(T::URI).map(|id| value.to_element(id))
+    }
+}
+
+impl FromIterator<(u8, String)> for ExtensionMap {
+    fn from_iter<I: IntoIterator<Item = (u8, String)>>(iter: I) -> Self {
+        Self {
+            uris: iter.into_iter().collect(),
+        }
+    }
+}
+
+/// Header extensions that [`Packetizer`](crate::packetization::common::Packetizer) writes to
+/// every packet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderExtensionParameters {
+    /// Elements with fixed data.
+    pub elements: Vec<ExtensionElement>,
+    /// Local identifier of [`TransportSequenceNumber`], which is increased by one for every
+    /// packet.
+    pub transport_sequence_number: Option<u8>,
+    /// Local identifier of [`AbsSendTime`], which is set to the time of packetization.
+    pub abs_send_time: Option<u8>,
+}
+
+impl HeaderExtensionParameters {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+            && self.transport_sequence_number.is_none()
+            && self.abs_send_time.is_none()
+    }
+}
+
+/// Absolute send time (abs-send-time): 6.18 fixed point NTP seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AbsSendTime(pub u32);
+
+impl AbsSendTime {
+    /// Absolute send time for current system time.
+    pub fn now() -> Self {
+        let since_unix_epoch = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+        Self::from_duration(since_unix_epoch + Duration::from_secs(NTP_UNIX_EPOCH_OFFSET))
+    }
+
+    /// Absolute send time for duration since NTP epoch. Only the lower 6 bits of the seconds are
+    /// kept.
+    pub fn from_duration(duration: Duration) -> Self {
+        let seconds = (duration.as_secs() & 0x3f) as u32;
+        let fraction = ((duration.subsec_nanos() as u64) << 18) / 1_000_000_000;
+        Self((seconds << 18) | fraction as u32)
+    }
+
+    /// Absolute send time as duration (modulo 64 seconds).
+    pub fn as_duration(&self) -> Duration {
+        let seconds = (self.0 >> 18) as u64;
+        let nanos = ((self.0 & 0x3ffff) as u64 * 1_000_000_000) >> 18;
+        Duration::from_secs(seconds) + Duration::from_nanos(nanos)
+    }
+}
+
+impl HeaderExtension for AbsSendTime {
+    const URI: &'static str = "http://www.webrtc.org/experiments/rtp-hdrext/abs-send-time";
+
+    fn parse(data: &[u8]) -> Result<Self> {
+        let [a, b, c] = *data else {
+            return Err(Error::ExtensionDataLengthInvalid {
+                uri: Self::URI,
+                len: data.len(),
+            });
+        };
+        Ok(Self(u32::from_be_bytes([0, a, b, c])))
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        Bytes::copy_from_slice(&self.0.to_be_bytes()[1..])
+    }
+}
+
+/// Transport-wide sequence number (transport-wide-cc), used for transport-wide congestion control.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransportSequenceNumber(pub u16);
+
+impl HeaderExtension for TransportSequenceNumber {
+    const URI: &'static str =
+        "http://www.ietf.org/id/draft-holmer-rmcat-transport-wide-cc-extensions-01";
+
+    fn parse(data: &[u8]) -> Result<Self> {
+        let [a, b] = *data else {
+            return Err(Error::ExtensionDataLengthInvalid {
+                uri: Self::URI,
+                len: data.len(),
+            });
+        };
+        Ok(Self(u16::from_be_bytes([a, b])))
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        Bytes::copy_from_slice(&self.0.to_be_bytes())
+    }
+}
+
+/// Coordination of video orientation (3GPP TS 26.114 section 7.4.5).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VideoOrientation {
+    /// Video is captured by back-facing camera (C).
+    pub back_facing_camera: bool,
+    /// Video is flipped horizontally (F).
+    pub flip: bool,
+    /// Clockwise rotation in steps of 90 degrees (R1 and R0).
+    pub rotation: u8,
+}
+
+impl VideoOrientation {
+    /// Clockwise rotation in degrees.
+    #[inline]
+    pub fn rotation_degrees(&self) -> u16 {
+        (self.rotation & 0x03) as u16 * 90
+    }
+}
+
+impl HeaderExtension for VideoOrientation {
+    const URI: &'static str = "urn:3gpp:video-orientation";
+
+    fn parse(data: &[u8]) -> Result<Self> {
+        let [byte] = *data else {
+            return Err(Error::ExtensionDataLengthInvalid {
+                uri: Self::URI,
+                len: data.len(),
+            });
+        };
+        Ok(Self {
+            back_facing_camera: byte & 0x08 != 0,
+            flip: byte & 0x04 != 0,
+            rotation: byte & 0x03,
+        })
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        Bytes::copy_from_slice(&[((self.back_facing_camera as u8) << 3)
+            | ((self.flip as u8) << 2)
+            | (self.rotation & 0x03)])
+    }
+}
+
+/// ONVIF replay header extension (ONVIF Streaming Specification section 6.3).
+///
+/// Unlike the other header extensions, this one is not an RFC 8285 element but occupies the
+/// entire header extension with its own profile identifier.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OnvifReplay {
+    /// Wallclock time of recording as 64-bit NTP timestamp.
+    pub ntp_timestamp: u64,
+    /// Packet belongs to an access unit that is a clean point, such as a key frame (C).
+    pub clean_point: bool,
+    /// Last packet of a contiguous section of the recording (E).
+    pub end_of_section: bool,
+    /// First packet after a discontinuity, such as a gap in the recording (D).
+    pub discontinuity: bool,
+    /// Lower 8 bits of the CSeq of the PLAY request that started the replay.
+    pub cseq: u8,
+}
+
+impl OnvifReplay {
+    /// Parse from header extension.
+    ///
+    /// # Return value
+    ///
+    /// Replay header extension, or `None` if the header extension has another profile identifier.
+    pub fn from_extension(extension: &Extension) -> Result<Option<Self>> {
+        if extension.profile_identifier != ONVIF_REPLAY_PROFILE_IDENTIFIER {
+            return Ok(None);
+        }
+        let [high, low, flags, ..] = extension.data[..] else {
+            return Err(Error::NotEnoughData {
+                have: extension.data.len() * 4,
+                need: 12,
+            });
+        };
+        let [flags, cseq, ..] = flags.to_be_bytes();
+        Ok(Some(Self {
+            ntp_timestamp: ((high as u64) << 32) | low as u64,
+            clean_point: flags & 0x80 != 0,
+            end_of_section: flags & 0x40 != 0,
+            discontinuity: flags & 0x20 != 0,
+            cseq,
+        }))
+    }
+
+    /// Convert to header extension.
+    pub fn to_extension(&self) -> Extension {
+        let flags = ((self.clean_point as u32) << 7)
+            | ((self.end_of_section as u32) << 6)
+            | ((self.discontinuity as u32) << 5);
+        Extension {
+            profile_identifier: ONVIF_REPLAY_PROFILE_IDENTIFIER,
+            data: vec![
+                (self.ntp_timestamp >> 32) as u32,
+                self.ntp_timestamp as u32,
+                (flags << 24) | ((self.cseq as u32) << 16),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packetization::common::{PacketizationParameters, Packetizer};
+    use crate::serialize::Serialize;
+
+    #[test]
+    fn one_byte_elements() {
+        let elements = vec![
+            TransportSequenceNumber(0x1234).to_element(3),
+            VideoOrientation {
+                back_facing_camera: true,
+                flip: false,
+                rotation: 1,
+            }
+            .to_element(4),
+        ];
+        let extension = Extension::from_elements(&elements).unwrap();
+        assert_eq!(extension.profile_identifier, ONE_BYTE_PROFILE_IDENTIFIER);
+        assert_eq!(extension.data, vec![0x3112_3440, 0x0900_0000]);
+        assert_eq!(extension.elements().unwrap(), elements);
+
+        let map = [
+            (3, TransportSequenceNumber::URI.to_string()),
+            (4, VideoOrientation::URI.to_string()),
+        ]
+        .into_iter()
+        .collect::<ExtensionMap>();
+        assert_eq!(
+            map.get::<TransportSequenceNumber>(&extension).unwrap(),
+            Some(TransportSequenceNumber(0x1234))
+        );
+        assert_eq!(
+            map.get::<VideoOrientation>(&extension)
+                .unwrap()
+                .unwrap()
+                .rotation_degrees(),
+            90
+        );
+        assert_eq!(map.get::<AbsSendTime>(&extension).unwrap(), None);
+    }
+
+    #[test]
+    fn two_byte_elements() {
+        let elements = vec![
+            ExtensionElement::new(1, Bytes::new()),
+            ExtensionElement::new(20, Bytes::from_static(&[0xaa, 0xbb, 0xcc])),
+        ];
+        let extension = Extension::from_elements(&elements).unwrap();
+        assert_eq!(extension.profile_identifier, TWO_BYTE_PROFILE_IDENTIFIER);
+        assert_eq!(extension.data, vec![0x0100_1403, 0xaabb_cc00]);
+        assert_eq!(extension.elements().unwrap(), elements);
+        assert!(Extension::from_elements(&[ExtensionElement::new(0, Bytes::new())]).is_err());
+    }
+
+    #[test]
+    fn abs_send_time() {
+        let abs_send_time = AbsSendTime::from_duration(Duration::from_millis(65_500));
+        assert_eq!(abs_send_time.0, (1 << 18) | (1 << 17));
+        assert_eq!(abs_send_time.as_duration(), Duration::from_millis(1_500));
+        assert_eq!(
+            AbsSendTime::parse(&abs_send_time.to_bytes()).unwrap(),
+            abs_send_time
+        );
+        assert!(AbsSendTime::parse(&[0x00, 0x01]).is_err());
+    }
+
+    #[test]
+    fn onvif_replay() {
+        let replay = OnvifReplay {
+            ntp_timestamp: 0xe6d4_2c00_8000_0000,
+            clean_point: true,
+            end_of_section: false,
+            discontinuity: true,
+            cseq: 4,
+        };
+        let extension = replay.to_extension();
+        assert_eq!(extension.data[2], 0xa004_0000);
+        assert_eq!(
+            OnvifReplay::from_extension(&extension).unwrap(),
+            Some(replay)
+        );
+        assert!(extension.elements().is_err());
+    }
+
+    #[test]
+    fn packetizer() {
+        let mut packetizer = Packetizer::from_packetization_parameters(PacketizationParameters {
+            payload_type: 96,
+            ssrc: 0,
+            csrc: Vec::new(),
+            mtu: Some(12 + 16 + 4),
+        })
+        .with_header_extensions(HeaderExtensionParameters {
+            elements: vec![VideoOrientation::default().to_element(4)],
+            transport_sequence_number: Some(3),
+            abs_send_time: Some(2),
+        })
+        .unwrap();
+        assert_eq!(packetizer.header_serialized_len(), 12 + 16);
+
+        let map = [
+            (2, AbsSendTime::URI.to_string()),
+            (3, TransportSequenceNumber::URI.to_string()),
+        ]
+        .into_iter()
+        .collect::<ExtensionMap>();
+        for expected in 0..3 {
+            let packet = packetizer
+                .packetize(Bytes::from_static(&[1, 2, 3, 4]), 0, false)
+                .unwrap();
+            assert_eq!(packet.serialized_len(), 12 + 16 + 4);
+            let extension = packet.header.extension.unwrap();
+            assert_eq!(
+                map.get::<TransportSequenceNumber>(&extension).unwrap(),
+                Some(TransportSequenceNumber(expected))
+            );
+            assert!(map.get::<AbsSendTime>(&extension).unwrap().is_some());
+            assert_eq!(extension.element(4).unwrap().unwrap()[..], [0x00]);
+        }
+        assert!(packetizer
+            .packetize(Bytes::from_static(&[1, 2, 3, 4, 5]), 0, false)
+            .is_err());
+    }
+    #[test]
+    fn packetizer_rejects_invalid_header_extensions() {
+        let packetizer = Packetizer::from_packetization_parameters(PacketizationParameters {
+            payload_type: 96,
+            ssrc: 0,
+            csrc: Vec::new(),
+            mtu: None,
+        });
+        let result = packetizer.with_header_extensions(HeaderExtensionParameters {
+            elements: vec![ExtensionElement::new(0, Bytes::from_static(&[1]))],
+            ..Default::default()
+        });
+        assert!(matches!(
+            result,
+            Err(Error::ExtensionElementInvalid { id: 0, len: 1 })
+        ));
+    }
+}
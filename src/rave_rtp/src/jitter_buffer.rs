@@ -0,0 +1,352 @@
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant};
+
+use crate::packet::Packet;
+
+/// Default maximum number of packets held by the buffer.
+const DEFAULT_CAPACITY: usize = 1024;
+/// Maximum forward jump in sequence numbers that is not considered a restart.
+const MAX_DROPOUT: i64 = 3000;
+/// Maximum backward jump in sequence numbers that is considered reordering.
+const MAX_MISORDER: i64 = 100;
+
+/// RTP jitter buffer.
+///
+/// Puts packets back in sequence number order. Packets that arrive in order are released right
+/// away. When a packet is missing, the packets after it are held until the missing packet arrives
+/// or until the oldest held packet has waited for longer than the latency. In the latter case, the
+/// missing packets are reported as lost and the buffer moves on. Duplicates and packets that
+/// arrive after they were released or reported as lost are dropped.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Duration, Instant};
+///
+/// use rave_rtp::jitter_buffer::{Event, JitterBuffer};
+/// # use rave_rtp::packet::Packet;
+///
+/// # fn example(packets: Vec<Packet>) {
+/// let mut jitter_buffer = JitterBuffer::new(Duration::from_millis(200));
+/// for packet in packets {
+///     jitter_buffer.push(packet, Instant::now());
+///     while let Some(event) = jitter_buffer.pop(Instant::now()) {
+///         match event {
+///             Event::Packet(packet) => { /* depacketize */ }
+///             Event::Lost { sequence_number, count } => { /* request retransmission */ }
+///             _ => {}
+///         }
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct JitterBuffer {
+    latency: Duration,
+    capacity: usize,
+    packets: BTreeMap<i64, (Packet, Instant)>,
+    /// Extended sequence number of next packet to release.
+    next: Option<i64>,
+    /// Extended sequence number of last pushed packet, used to extend the next one.
+    last: i64,
+    /// Sequence number that confirms a restart of the sequence if it arrives next.
+    bad_sequence_number: Option<u16>,
+}
+
+/// Result of pushing a packet into the [`JitterBuffer`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Push {
+    /// Packet was buffered.
+    Buffered,
+    /// Packet was already buffered, and was dropped.
+    Duplicate,
+    /// Packet was already released or reported as lost, or is part of a large jump in sequence
+    /// numbers, and was dropped.
+    Late,
+}
+
+/// Output of the [`JitterBuffer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// Next packet in sequence.
+    Packet(Packet),
+    /// One or more packets were lost.
+    Lost {
+        /// Sequence number of first lost packet.
+        sequence_number: u16,
+        /// Number of consecutive packets lost.
+        count: u16,
+    },
+}
+
+impl JitterBuffer {
+    /// Create jitter buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `latency` - Maximum time to wait for a missing packet.
+    pub fn new(latency: Duration) -> Self {
+        Self {
+            latency,
+            capacity: DEFAULT_CAPACITY,
+            packets: BTreeMap::new(),
+            next: None,
+            last: 0,
+            bad_sequence_number: None,
+        }
+    }
+
+    /// Set maximum number of packets held. When the buffer is full, it stops waiting for missing
+    /// packets.
+    #[inline]
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Push received packet into buffer.
+    ///
+    /// A large jump in sequence numbers is assumed to be a restart of the stream if the packet
+    /// after it is in sequence. Packets that were buffered before the restart are dropped.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - Received packet.
+    /// * `arrival` - Time at which the packet arrived.
+    pub fn push(&mut self, packet: Packet, arrival: Instant) -> Push {
+        let sequence_number = packet.header.sequence_number;
+        let extended = self.extend(sequence_number);
+        let Some(next) = self.next else {
+            self.restart(extended);
+            self.packets.insert(extended, (packet, arrival));
+            return Push::Buffered;
+        };
+
+        if extended - next > MAX_DROPOUT || next - extended > MAX_MISORDER {
+            if self.bad_sequence_number != Some(sequence_number) {
+                self.bad_sequence_number = Some(sequence_number.wrapping_add(1));
+                return Push::Late;
+            }
+            // two sequential packets after a large jump, so the other side restarted
+            self.restart(extended);
+            self.packets.insert(extended, (packet, arrival));
+            return Push::Buffered;
+        }
+        self.bad_sequence_number = None;
+
+        if extended < next {
+            return Push::Late;
+        }
+        if self.packets.contains_key(&extended) {
+            return Push::Duplicate;
+        }
+        self.last = extended;
+        self.packets.insert(extended, (packet, arrival));
+        Push::Buffered
+    }
+
+    /// Take next packet or loss event out of buffer.
+    ///
+    /// Call this repeatedly until it returns `None`, after every push and when the deadline
+    /// returned by [`JitterBuffer::deadline`] passes.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time.
+    pub fn pop(&mut self, now: Instant) -> Option<Event> {
+        let next = self.next?;
+        let (&first, _) = self.packets.first_key_value()?;
+        if first == next {
+            let (_, (packet, _)) = self.packets.pop_first()?;
+            self.next = Some(next + 1);
+            return Some(Event::Packet(packet));
+        }
+
+        let expired = self.deadline().is_some_and(|deadline| now >= deadline);
+        if expired || self.packets.len() > self.capacity {
+            let count = (first - next).min(u16::MAX as i64) as u16;
+            self.next = Some(first);
+            Some(Event::Lost {
+                sequence_number: next as u16,
+                count,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Time at which the buffer stops waiting for a missing packet.
+    ///
+    /// # Return value
+    ///
+    /// Deadline, or `None` if the buffer is empty.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.packets
+            .values()
+            .map(|(_, arrival)| *arrival)
+            .min()
+            .map(|arrival| arrival + self.latency)
+    }
+
+    /// Number of packets held.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.packets.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.packets.is_empty()
+    }
+
+    /// Drop all packets and start over with the next packet that is pushed.
+    ///
+    /// Use this when the stream is known to be discontinuous, for example after reconnecting.
+    pub fn reset(&mut self) {
+        self.packets.clear();
+        self.next = None;
+        self.bad_sequence_number = None;
+    }
+
+    /// Extend 16-bit sequence number to 64 bits, relative to the last pushed packet.
+    fn extend(&self, sequence_number: u16) -> i64 {
+        let delta = sequence_number.wrapping_sub(self.last as u16) as i16;
+        self.last + delta as i64
+    }
+
+    fn restart(&mut self, extended: i64) {
+        self.packets.clear();
+        self.next = Some(extended);
+        self.last = extended;
+        self.bad_sequence_number = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::packet::{Header, Version};
+
+    fn packet(sequence_number: u16) -> Packet {
+        Packet::new(
+            Header {
+                version: Version::Version2,
+                padding: false,
+                marker: false,
+                payload_type: 96,
+                sequence_number,
+                timestamp: 0,
+                ssrc: 1,
+                csrc: Vec::new(),
+                extension: None,
+            },
+            Bytes::new(),
+        )
+    }
+
+    fn drain(jitter_buffer: &mut JitterBuffer, now: Instant) -> Vec<Event> {
+        std::iter::from_fn(|| jitter_buffer.pop(now)).collect()
+    }
+
+    fn released(events: &[Event]) -> Vec<u16> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Packet(packet) => Some(packet.header.sequence_number),
+                Event::Lost { .. } => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn reorder_across_wraparound() {
+        let now = Instant::now();
+        let mut jitter_buffer = JitterBuffer::new(Duration::from_millis(100));
+        let mut events = Vec::new();
+        for sequence_number in [65534, 0, 65535, 2, 1] {
+            assert_eq!(
+                jitter_buffer.push(packet(sequence_number), now),
+                Push::Buffered
+            );
+            events.extend(drain(&mut jitter_buffer, now));
+        }
+        assert_eq!(released(&events), vec![65534, 65535, 0, 1, 2]);
+        assert!(jitter_buffer.is_empty());
+    }
+
+    #[test]
+    fn drop_duplicates_and_late_packets() {
+        let now = Instant::now();
+        let mut jitter_buffer = JitterBuffer::new(Duration::from_millis(100));
+        jitter_buffer.push(packet(1), now);
+        jitter_buffer.push(packet(3), now);
+        assert_eq!(jitter_buffer.push(packet(3), now), Push::Duplicate);
+        assert_eq!(released(&drain(&mut jitter_buffer, now)), vec![1]);
+        assert_eq!(jitter_buffer.push(packet(1), now), Push::Late);
+    }
+
+    #[test]
+    fn report_loss_after_latency() {
+        let now = Instant::now();
+        let mut jitter_buffer = JitterBuffer::new(Duration::from_millis(100));
+        jitter_buffer.push(packet(10), now);
+        jitter_buffer.push(packet(13), now);
+        jitter_buffer.push(packet(14), now);
+        assert_eq!(released(&drain(&mut jitter_buffer, now)), vec![10]);
+        assert_eq!(
+            jitter_buffer.deadline(),
+            Some(now + Duration::from_millis(100))
+        );
+
+        let later = now + Duration::from_millis(100);
+        assert_eq!(
+            drain(&mut jitter_buffer, later),
+            vec![
+                Event::Lost {
+                    sequence_number: 11,
+                    count: 2,
+                },
+                Event::Packet(packet(13)),
+                Event::Packet(packet(14)),
+            ],
+        );
+        assert_eq!(jitter_buffer.push(packet(12), later), Push::Late);
+    }
+
+    #[test]
+    fn stop_waiting_when_full() {
+        let now = Instant::now();
+        let mut jitter_buffer = JitterBuffer::new(Duration::from_secs(10)).with_capacity(2);
+        jitter_buffer.push(packet(0), now);
+        for sequence_number in [2, 3, 4] {
+            jitter_buffer.push(packet(sequence_number), now);
+        }
+        let events = drain(&mut jitter_buffer, now);
+        assert_eq!(
+            events[1],
+            Event::Lost {
+                sequence_number: 1,
+                count: 1,
+            },
+        );
+        assert_eq!(released(&events), vec![0, 2, 3, 4]);
+    }
+
+    #[test]
+    fn restart_after_large_jump() {
+        let now = Instant::now();
+        let mut jitter_buffer = JitterBuffer::new(Duration::from_millis(100));
+        jitter_buffer.push(packet(100), now);
+        drain(&mut jitter_buffer, now);
+        assert_eq!(jitter_buffer.push(packet(40000), now), Push::Late);
+        assert_eq!(jitter_buffer.push(packet(40001), now), Push::Buffered);
+        jitter_buffer.push(packet(40002), now);
+        assert_eq!(
+            released(&drain(&mut jitter_buffer, now)),
+            vec![40001, 40002]
+        );
+    }
+}
@@ -1,5 +1,10 @@
 pub mod error;
+pub mod header_extension;
+pub mod jitter_buffer;
 pub mod packet;
 pub mod packetization;
 pub mod parse;
+pub mod rtcp;
 pub mod serialize;
+#[cfg(feature = "srtp")]
+pub mod srtp;
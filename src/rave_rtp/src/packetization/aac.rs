@@ -0,0 +1,1182 @@
+use crate::error::Error;
+use crate::header_extension::HeaderExtensionParameters;
+use crate::packet::Packet;
+use crate::packetization::common::{PacketizationParameters, Packetizer};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use rave_types::codec::Aac;
+use rave_types::unit::Unit;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Number of samples in an AAC frame, which is the default frame duration in timestamp units (the
+/// RTP clock rate is the sampling rate).
+const DEFAULT_FRAME_DURATION: u32 = 1024;
+
+/// RFC 3640 `mpeg4-generic` format parameters.
+///
+/// These determine the layout of the AU headers in every packet, and are usually taken from the
+/// `a=fmtp` attribute in the SDP.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AacParameters {
+    /// Number of bits in AU size field (`sizeLength`).
+    pub size_length: u8,
+    /// Number of bits in AU index field of first AU header (`indexLength`).
+    pub index_length: u8,
+    /// Number of bits in AU index delta field of other AU headers (`indexDeltaLength`).
+    pub index_delta_length: u8,
+    /// Number of bits in CTS delta field (`CTSDeltaLength`).
+    pub cts_delta_length: u8,
+    /// Number of bits in DTS delta field (`DTSDeltaLength`).
+    pub dts_delta_length: u8,
+    /// Whether AU headers hold a random access point flag (`randomAccessIndication`).
+    pub random_access_indication: bool,
+    /// Number of bits in stream state field (`streamStateIndication`).
+    pub stream_state_indication: u8,
+    /// Number of bits in auxiliary data size field (`auxiliaryDataSizeLength`).
+    pub auxiliary_data_size_length: u8,
+    /// Size of every AU if it is constant (`constantSize`).
+    pub constant_size: Option<usize>,
+    /// Audio specific config (`config`).
+    pub config: Bytes,
+}
+
+impl AacParameters {
+    /// Parameters for high bitrate AAC (`mode=AAC-hbr`).
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Audio specific config.
+    pub fn high_bitrate(config: Bytes) -> Self {
+        Self {
+            size_length: 13,
+            index_length: 3,
+            index_delta_length: 3,
+            ..Self::generic(config)
+        }
+    }
+
+    /// Parameters for low bitrate AAC (`mode=AAC-lbr`).
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Audio specific config.
+    pub fn low_bitrate(config: Bytes) -> Self {
+        Self {
+            size_length: 6,
+            index_length: 2,
+            index_delta_length: 2,
+            ..Self::generic(config)
+        }
+    }
+
+    /// Parse parameters from `a=fmtp` attribute value (without payload type).
+    ///
+    /// Parameter names are case-insensitive. Parameters that are not relevant to packetization are
+    /// ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `fmtp` - Format parameters, for example `streamtype=5;mode=AAC-hbr;sizelength=13;...`.
+    pub fn from_fmtp(fmtp: &str) -> Result<Self> {
+        let parameters = parse_fmtp(fmtp);
+        let config = match find_parameter(&parameters, "config") {
+            Some(config) => parse_hex("config", config)?,
+            None => Bytes::new(),
+        };
+        let mut aac_parameters = match find_parameter(&parameters, "mode") {
+            Some(mode) if mode.eq_ignore_ascii_case("AAC-hbr") => Self::high_bitrate(config),
+            Some(mode) if mode.eq_ignore_ascii_case("AAC-lbr") => Self::low_bitrate(config),
+            _ => Self::generic(config),
+        };
+        for (name, value) in parameters {
+            let length =
+                || parse_number::<u8>(name, value).and_then(|length| check_length(name, length));
+            match name.to_ascii_lowercase().as_str() {
+                "sizelength" => aac_parameters.size_length = length()?,
+                "indexlength" => aac_parameters.index_length = length()?,
+                "indexdeltalength" => aac_parameters.index_delta_length = length()?,
+                "ctsdeltalength" => aac_parameters.cts_delta_length = length()?,
+                "dtsdeltalength" => aac_parameters.dts_delta_length = length()?,
+                "randomaccessindication" => {
+                    aac_parameters.random_access_indication = parse_number::<u8>(name, value)? > 0
+                }
+                "streamstateindication" => aac_parameters.stream_state_indication = length()?,
+                "auxiliarydatasizelength" => aac_parameters.auxiliary_data_size_length = length()?,
+                "constantsize" => aac_parameters.constant_size = Some(parse_number(name, value)?),
+                _ => {}
+            }
+        }
+        // Every AU header after the first must take up at least one bit, otherwise the number of
+        // AU headers in a packet is unbounded.
+        if aac_parameters.has_au_headers() && aac_parameters.au_header_len(false) == 0 {
+            return Err(Error::AacParameterInvalid {
+                name: "sizelength".to_string(),
+                value: aac_parameters.size_length.to_string(),
+            });
+        }
+        Ok(aac_parameters)
+    }
+
+    /// Format parameters as `a=fmtp` attribute value (without payload type).
+    pub fn to_fmtp(&self) -> String {
+        let mode = match (self.size_length, self.index_length, self.index_delta_length) {
+            (13, 3, 3) => "AAC-hbr",
+            (6, 2, 2) => "AAC-lbr",
+            _ => "generic",
+        };
+        let mut fmtp = format!(
+            "streamtype=5;profile-level-id=1;mode={mode};sizelength={};indexlength={};indexdeltalength={}",
+            self.size_length, self.index_length, self.index_delta_length,
+        );
+        let optional_lengths = [
+            ("ctsdeltalength", self.cts_delta_length),
+            ("dtsdeltalength", self.dts_delta_length),
+            (
+                "randomaccessindication",
+                self.random_access_indication as u8,
+            ),
+            ("streamstateindication", self.stream_state_indication),
+            ("auxiliarydatasizelength", self.auxiliary_data_size_length),
+        ];
+        for (name, value) in optional_lengths {
+            if value > 0 {
+                fmtp.push_str(&format!(";{name}={value}"));
+            }
+        }
+        if let Some(constant_size) = self.constant_size {
+            fmtp.push_str(&format!(";constantsize={constant_size}"));
+        }
+        if !self.config.is_empty() {
+            fmtp.push_str(";config=");
+            fmtp.extend(self.config.iter().map(|byte| format!("{byte:02x}")));
+        }
+        fmtp
+    }
+
+    fn generic(config: Bytes) -> Self {
+        Self {
+            size_length: 0,
+            index_length: 0,
+            index_delta_length: 0,
+            cts_delta_length: 0,
+            dts_delta_length: 0,
+            random_access_indication: false,
+            stream_state_indication: 0,
+            auxiliary_data_size_length: 0,
+            constant_size: None,
+            config,
+        }
+    }
+
+    /// Number of bits in AU header.
+    fn au_header_len(&self, first: bool) -> usize {
+        let index_length = if first {
+            self.index_length
+        } else {
+            self.index_delta_length
+        };
+        let optional_len = |length: u8| if length > 0 { 1 + length as usize } else { 0 };
+        self.size_length as usize
+            + index_length as usize
+            + optional_len(self.cts_delta_length)
+            + optional_len(self.dts_delta_length)
+            + self.random_access_indication as usize
+            + self.stream_state_indication as usize
+    }
+
+    /// Whether packets hold an AU header section (with AU-headers-length field).
+    fn has_au_headers(&self) -> bool {
+        self.au_header_len(true) > 0 || self.au_header_len(false) > 0
+    }
+}
+
+/// RTP AAC packetizer (RFC 3640 `mpeg4-generic`).
+///
+/// Access units (AAC frames) that fit within the MTU together are combined into one packet. Access
+/// units that do not fit within the MTU by themselves are fragmented over multiple packets.
+#[derive(Debug)]
+pub struct AacPacketizer {
+    inner: Packetizer,
+    mtu: Option<usize>,
+    parameters: AacParameters,
+    frame_duration: u32,
+}
+
+impl AacPacketizer {
+    /// Create a new packetizer to create RTP packets from AAC frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - RTP Packetization parameters to use for constructing packets.
+    /// * `parameters` - Format parameters that determine the AU header layout.
+    pub fn new(params: PacketizationParameters, parameters: AacParameters) -> Self {
+        let mtu = params.mtu;
+        Self {
+            inner: Packetizer::from_packetization_parameters(params),
+            mtu,
+            parameters,
+            frame_duration: DEFAULT_FRAME_DURATION,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    /// Set duration of one AAC frame in timestamp units. Defaults to 1024 samples.
+    pub fn with_frame_duration(mut self, frame_duration: u32) -> Self {
+        self.frame_duration = frame_duration;
+        self
+    }
+
+    /// Packetize one or more AAC frames.
+    ///
+    /// # Timestamps
+    ///
+    /// The frames must be consecutive. The first frame has the given timestamp, and every frame
+    /// after it is one frame duration later.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - One or more raw AAC frames (without ADTS header).
+    /// * `timestamp` - Presentation timestamp of first frame.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more RTP packets.
+    pub fn packetize(&mut self, data: Vec<Unit<Aac>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let mtu = self.mtu.unwrap_or(usize::MAX);
+        let header_len = self.inner.header_serialized_len();
+
+        let mut packets = Vec::new();
+        let mut group: Vec<Bytes> = Vec::new();
+        let mut group_timestamp = timestamp;
+        for (i, frame) in data.into_iter().map(Unit::into_data).enumerate() {
+            self.check_au_size(frame.len())?;
+            let frame_timestamp =
+                timestamp.wrapping_add((i as u32).wrapping_mul(self.frame_duration));
+            if header_len + self.payload_len(std::slice::from_ref(&frame)) > mtu {
+                // Does not fit in a single packet by itself, so fragment it.
+                if !group.is_empty() {
+                    let payload = self.payload(&std::mem::take(&mut group));
+                    packets.push(self.inner.packetize(payload, group_timestamp, true)?);
+                }
+                packets.extend(self.packetize_fragmented(frame, frame_timestamp, mtu)?);
+                continue;
+            }
+            group.push(frame);
+            if group.len() > 1 && header_len + self.payload_len(&group) > mtu {
+                let frame = group.pop().unwrap();
+                let payload = self.payload(&std::mem::replace(&mut group, vec![frame]));
+                packets.push(self.inner.packetize(payload, group_timestamp, true)?);
+            }
+            if group.len() == 1 {
+                group_timestamp = frame_timestamp;
+            }
+        }
+        if !group.is_empty() {
+            let payload = self.payload(&group);
+            packets.push(self.inner.packetize(payload, group_timestamp, true)?);
+        }
+
+        Ok(packets)
+    }
+
+    /// Fragment one AAC frame over multiple packets. Every fragment holds the AU header of the
+    /// entire frame, and only the last one has the marker bit set.
+    fn packetize_fragmented(
+        &mut self,
+        mut frame: Bytes,
+        timestamp: u32,
+        mtu: usize,
+    ) -> Result<Vec<Packet>> {
+        let au_size = frame.len();
+        // If the MTU is too small to fit any payload, packetizing will fail on the MTU check.
+        let fragment_max_len = mtu
+            .saturating_sub(self.inner.header_serialized_len() + self.au_headers_len(&[au_size]))
+            .max(1);
+        let mut packets = Vec::new();
+        while !frame.is_empty() {
+            let fragment = frame.split_to(fragment_max_len.min(frame.len()));
+            let mut payload = BytesMut::new();
+            self.put_au_headers(&mut payload, &[au_size]);
+            payload.put(fragment);
+            packets.push(
+                self.inner
+                    .packetize(payload.freeze(), timestamp, frame.is_empty())?,
+            );
+        }
+        Ok(packets)
+    }
+
+    fn payload(&self, frames: &[Bytes]) -> Bytes {
+        let mut payload = BytesMut::with_capacity(self.payload_len(frames));
+        let au_sizes = frames.iter().map(Bytes::len).collect::<Vec<_>>();
+        self.put_au_headers(&mut payload, &au_sizes);
+        for frame in frames {
+            payload.put_slice(frame);
+        }
+        payload.freeze()
+    }
+
+    fn payload_len(&self, frames: &[Bytes]) -> usize {
+        let au_sizes = frames.iter().map(Bytes::len).collect::<Vec<_>>();
+        self.au_headers_len(&au_sizes) + au_sizes.iter().sum::<usize>()
+    }
+
+    /// Size of AU header section and auxiliary section in bytes.
+    fn au_headers_len(&self, au_sizes: &[usize]) -> usize {
+        let auxiliary_len = if self.parameters.auxiliary_data_size_length > 0 {
+            (self.parameters.auxiliary_data_size_length as usize).div_ceil(8)
+        } else {
+            0
+        };
+        if !self.parameters.has_au_headers() {
+            return auxiliary_len;
+        }
+        let au_headers_len = (0..au_sizes.len())
+            .map(|i| self.parameters.au_header_len(i == 0))
+            .sum::<usize>();
+        2 + au_headers_len.div_ceil(8) + auxiliary_len
+    }
+
+    fn put_au_headers(&self, dst: &mut BytesMut, au_sizes: &[usize]) {
+        let parameters = &self.parameters;
+        if parameters.has_au_headers() {
+            let mut writer = BitWriter::new();
+            for (i, &au_size) in au_sizes.iter().enumerate() {
+                writer.write(au_size as u32, parameters.size_length);
+                // AUs are consecutive, so index and index delta are zero.
+                let index_length = if i == 0 {
+                    parameters.index_length
+                } else {
+                    parameters.index_delta_length
+                };
+                writer.write(0, index_length);
+                if parameters.cts_delta_length > 0 {
+                    writer.write(0, 1); // No CTS delta.
+                }
+                if parameters.dts_delta_length > 0 {
+                    writer.write(0, 1); // No DTS delta.
+                }
+                if parameters.random_access_indication {
+                    writer.write(1, 1); // Every AAC frame is a random access point.
+                }
+                writer.write(0, parameters.stream_state_indication);
+            }
+            dst.put_u16(writer.len() as u16);
+            dst.put(writer.into_bytes());
+        }
+        if parameters.auxiliary_data_size_length > 0 {
+            // Empty auxiliary section.
+            let mut writer = BitWriter::new();
+            writer.write(0, parameters.auxiliary_data_size_length);
+            dst.put(writer.into_bytes());
+        }
+    }
+
+    fn check_au_size(&self, au_size: usize) -> Result<()> {
+        let valid = match self.parameters.constant_size {
+            Some(constant_size) if self.parameters.size_length == 0 => au_size == constant_size,
+            _ => {
+                self.parameters.size_length > 0
+                    && self.parameters.size_length < 32
+                    && au_size < (1 << self.parameters.size_length)
+            }
+        };
+        if valid {
+            Ok(())
+        } else {
+            Err(Error::AacAuSizeInvalid { size: au_size })
+        }
+    }
+}
+
+/// RTP AAC depacketizer (RFC 3640 `mpeg4-generic`).
+///
+/// Packets must be passed in sequence number order (see
+/// [`JitterBuffer`](crate::jitter_buffer::JitterBuffer)). Fragmented frames that were hit by
+/// packet loss are discarded.
+#[derive(Debug)]
+pub struct AacDepacketizer {
+    parameters: AacParameters,
+    /// Fragmented frame being reassembled, and its size.
+    fragmented_frame: Option<(BytesMut, usize)>,
+    last_sequence_number: Option<u16>,
+}
+
+impl AacDepacketizer {
+    /// Create a new depacketizer to extract AAC frames from RTP packet stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameters` - Format parameters that determine the AU header layout.
+    pub fn new(parameters: AacParameters) -> Self {
+        Self {
+            parameters,
+            fragmented_frame: None,
+            last_sequence_number: None,
+        }
+    }
+
+    /// Discard fragmented frame that is being reassembled, if any.
+    ///
+    /// Gaps in sequence numbers are detected automatically, so this is only required when the
+    /// sequence numbers cannot be relied upon.
+    pub fn lost(&mut self) {
+        self.fragmented_frame = None;
+    }
+
+    /// Depacketize RTP packet and return raw AAC frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more AAC frames (without ADTS header).
+    ///
+    /// No frames may be produced if the packet contains part of a fragmented frame.
+    pub fn depacketize(&mut self, packet: &Packet) -> Result<Vec<Unit<Aac>>> {
+        let sequence_number = packet.header.sequence_number;
+        if self
+            .last_sequence_number
+            .replace(sequence_number)
+            .is_some_and(|last| sequence_number != last.wrapping_add(1))
+        {
+            self.lost();
+        }
+
+        let mut payload = packet.payload.clone();
+        let au_sizes = self.get_au_headers(&mut payload)?;
+        self.skip_auxiliary_section(&mut payload)?;
+
+        let au_sizes = match au_sizes {
+            Some(au_sizes) => au_sizes,
+            None => match self.parameters.constant_size {
+                // Without AU headers, frames have a constant size or there is one frame.
+                Some(constant_size) if constant_size > 0 => {
+                    vec![constant_size; payload.len() / constant_size]
+                }
+                _ => vec![payload.len()],
+            },
+        };
+
+        if let [au_size] = au_sizes[..] {
+            if au_size > payload.len() || self.fragmented_frame.is_some() {
+                return Ok(self
+                    .depacketize_fragment(au_size, payload, packet.header.marker)
+                    .into_iter()
+                    .collect());
+            }
+        }
+
+        let mut frames = Vec::with_capacity(au_sizes.len());
+        for au_size in au_sizes {
+            if payload.len() < au_size {
+                return Err(Error::AacAuDataTooSmall {
+                    have: payload.len(),
+                    need: au_size,
+                });
+            }
+            frames.push(Unit::new(payload.split_to(au_size)));
+        }
+        Ok(frames)
+    }
+
+    /// Add fragment to frame being reassembled.
+    ///
+    /// A fragment that is not preceded by the earlier fragments of the frame (because they were
+    /// lost) is accepted, but the reassembled frame is discarded when its size does not match.
+    fn depacketize_fragment(
+        &mut self,
+        au_size: usize,
+        fragment: Bytes,
+        marker: bool,
+    ) -> Option<Unit<Aac>> {
+        let (mut buffer, size) = match self.fragmented_frame.take() {
+            Some((buffer, size)) if size == au_size => (buffer, size),
+            _ => (BytesMut::with_capacity(au_size), au_size),
+        };
+        buffer.put(fragment);
+        if marker || buffer.len() >= size {
+            (buffer.len() == size).then(|| Unit::new(buffer.freeze()))
+        } else {
+            self.fragmented_frame = Some((buffer, size));
+            None
+        }
+    }
+
+    /// Read AU header section.
+    ///
+    /// # Return value
+    ///
+    /// AU sizes, or `None` if there is no AU header section.
+    fn get_au_headers(&self, payload: &mut Bytes) -> Result<Option<Vec<usize>>> {
+        let parameters = &self.parameters;
+        if !parameters.has_au_headers() {
+            return Ok(None);
+        }
+        if payload.remaining() < 2 {
+            return Err(Error::AacAuHeadersLengthInvalid {
+                len: payload.remaining() * 8,
+            });
+        }
+        let au_headers_len = payload.get_u16() as usize;
+        if payload.remaining() < au_headers_len.div_ceil(8) {
+            return Err(Error::AacAuHeadersLengthInvalid {
+                len: au_headers_len,
+            });
+        }
+        let au_headers = payload.split_to(au_headers_len.div_ceil(8));
+
+        let mut reader = BitReader::new(&au_headers, au_headers_len);
+        let mut au_sizes = Vec::new();
+        while reader.remaining() > 0 {
+            let first = au_sizes.is_empty();
+            let remaining = reader.remaining();
+            let header = (|| {
+                let au_size = reader.read(parameters.size_length)? as usize;
+                reader.read(if first {
+                    parameters.index_length
+                } else {
+                    parameters.index_delta_length
+                })?;
+                if parameters.cts_delta_length > 0 && reader.read(1)? > 0 {
+                    reader.read(parameters.cts_delta_length)?;
+                }
+                if parameters.dts_delta_length > 0 && reader.read(1)? > 0 {
+                    reader.read(parameters.dts_delta_length)?;
+                }
+                reader.read(parameters.random_access_indication as u8)?;
+                reader.read(parameters.stream_state_indication)?;
+                Some(au_size)
+            })();
+            let au_size = header.filter(|_| reader.remaining() < remaining).ok_or(
+                Error::AacAuHeadersLengthInvalid {
+                    len: au_headers_len,
+                },
+            )?;
+            au_sizes.push(match parameters.constant_size {
+                Some(constant_size) if parameters.size_length == 0 => constant_size,
+                _ => au_size,
+            });
+        }
+        Ok(Some(au_sizes))
+    }
+
+    fn skip_auxiliary_section(&self, payload: &mut Bytes) -> Result<()> {
+        let auxiliary_data_size_length = self.parameters.auxiliary_data_size_length;
+        if auxiliary_data_size_length == 0 {
+            return Ok(());
+        }
+        let auxiliary_data_size = BitReader::new(payload, payload.len() * 8)
+            .read(auxiliary_data_size_length)
+            .ok_or(Error::AacAuDataTooSmall {
+                have: payload.len(),
+                need: (auxiliary_data_size_length as usize).div_ceil(8),
+            })? as usize;
+        // Auxiliary section is padded to a whole number of bytes.
+        let auxiliary_section_len =
+            (auxiliary_data_size_length as usize + auxiliary_data_size).div_ceil(8);
+        if payload.len() < auxiliary_section_len {
+            return Err(Error::AacAuDataTooSmall {
+                have: payload.len(),
+                need: auxiliary_section_len,
+            });
+        }
+        payload.advance(auxiliary_section_len);
+        Ok(())
+    }
+}
+
+/// RFC 3016 `MP4A-LATM` format parameters.
+///
+/// Only out-of-band configuration (`cpresent=0`) with a single program and layer is supported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatmParameters {
+    /// Number of frames in every audio mux element, minus one.
+    pub num_sub_frames: u8,
+    /// Stream mux config (`config`).
+    pub config: Bytes,
+}
+
+impl LatmParameters {
+    /// Create parameters from stream mux config.
+    ///
+    /// # Arguments
+    ///
+    /// * `config` - Stream mux config.
+    pub fn from_config(config: Bytes) -> Result<Self> {
+        let mut reader = BitReader::new(&config, config.len() * 8);
+        let (
+            audio_mux_version,
+            all_streams_same_time_framing,
+            num_sub_frames,
+            num_program,
+            num_layer,
+        ) = (
+            reader.read(1),
+            reader.read(1),
+            reader.read(6),
+            reader.read(4),
+            reader.read(3),
+        );
+        match (
+            audio_mux_version,
+            all_streams_same_time_framing,
+            num_sub_frames,
+            num_program,
+            num_layer,
+        ) {
+            (Some(0), Some(1), Some(num_sub_frames), Some(0), Some(0)) => Ok(Self {
+                num_sub_frames: num_sub_frames as u8,
+                config,
+            }),
+            _ => Err(Error::AacLatmConfigUnsupported),
+        }
+    }
+
+    /// Parse parameters from `a=fmtp` attribute value (without payload type).
+    ///
+    /// # Arguments
+    ///
+    /// * `fmtp` - Format parameters, for example `profile-level-id=24;cpresent=0;config=...`.
+    pub fn from_fmtp(fmtp: &str) -> Result<Self> {
+        let parameters = parse_fmtp(fmtp);
+        // Configuration is in-band by default.
+        if find_parameter(&parameters, "cpresent").is_none_or(|cpresent| cpresent != "0") {
+            return Err(Error::AacLatmConfigUnsupported);
+        }
+        let config = find_parameter(&parameters, "config").ok_or(Error::AacParameterMissing {
+            name: "config".to_string(),
+        })?;
+        Self::from_config(parse_hex("config", config)?)
+    }
+}
+
+/// RTP AAC LATM packetizer (RFC 3016 `MP4A-LATM`).
+///
+/// Every frame is put in its own audio mux element, which means the stream mux config must have
+/// `numSubFrames` set to zero. Audio mux elements that do not fit within the MTU are fragmented
+/// over multiple packets.
+#[derive(Debug)]
+pub struct AacLatmPacketizer {
+    inner: Packetizer,
+    mtu: Option<usize>,
+    frame_duration: u32,
+}
+
+impl AacLatmPacketizer {
+    /// Create a new packetizer to create RTP packets from AAC frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - RTP Packetization parameters to use for constructing packets.
+    pub fn new(params: PacketizationParameters) -> Self {
+        let mtu = params.mtu;
+        Self {
+            inner: Packetizer::from_packetization_parameters(params),
+            mtu,
+            frame_duration: DEFAULT_FRAME_DURATION,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    /// Set duration of one AAC frame in timestamp units. Defaults to 1024 samples.
+    pub fn with_frame_duration(mut self, frame_duration: u32) -> Self {
+        self.frame_duration = frame_duration;
+        self
+    }
+
+    /// Packetize one or more AAC frames.
+    ///
+    /// # Timestamps
+    ///
+    /// The frames must be consecutive. The first frame has the given timestamp, and every frame
+    /// after it is one frame duration later.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - One or more raw AAC frames.
+    /// * `timestamp` - Presentation timestamp of first frame.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more RTP packets.
+    pub fn packetize(&mut self, data: Vec<Unit<Aac>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let fragment_max_len = self
+            .mtu
+            .map(|mtu| {
+                mtu.saturating_sub(self.inner.header_serialized_len())
+                    .max(1)
+            })
+            .unwrap_or(usize::MAX);
+
+        let mut packets = Vec::new();
+        for (i, frame) in data.into_iter().map(Unit::into_data).enumerate() {
+            let frame_timestamp =
+                timestamp.wrapping_add((i as u32).wrapping_mul(self.frame_duration));
+            let mut audio_mux_element =
+                BytesMut::with_capacity(frame.len() / 255 + 1 + frame.len());
+            // Payload length info is a sequence of 255 bytes terminated by the remainder.
+            audio_mux_element.put_bytes(0xff, frame.len() / 255);
+            audio_mux_element.put_u8((frame.len() % 255) as u8);
+            audio_mux_element.put(frame);
+
+            let mut audio_mux_element = audio_mux_element.freeze();
+            while !audio_mux_element.is_empty() {
+                let fragment =
+                    audio_mux_element.split_to(fragment_max_len.min(audio_mux_element.len()));
+                packets.push(self.inner.packetize(
+                    fragment,
+                    frame_timestamp,
+                    // Marker bit is set on the last fragment of an audio mux element.
+                    audio_mux_element.is_empty(),
+                )?);
+            }
+        }
+        Ok(packets)
+    }
+}
+
+/// RTP AAC LATM depacketizer (RFC 3016 `MP4A-LATM`).
+///
+/// Packets must be passed in sequence number order (see
+/// [`JitterBuffer`](crate::jitter_buffer::JitterBuffer)). Audio mux elements that were hit by
+/// packet loss are discarded.
+#[derive(Debug)]
+pub struct AacLatmDepacketizer {
+    parameters: LatmParameters,
+    buffer: BytesMut,
+    last_sequence_number: Option<u16>,
+    lost: bool,
+}
+
+impl AacLatmDepacketizer {
+    /// Create a new depacketizer to extract AAC frames from RTP packet stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `parameters` - Format parameters.
+    pub fn new(parameters: LatmParameters) -> Self {
+        Self {
+            parameters,
+            buffer: BytesMut::new(),
+            last_sequence_number: None,
+            lost: false,
+        }
+    }
+
+    /// Discard audio mux element that is being reassembled, if any.
+    ///
+    /// Gaps in sequence numbers are detected automatically, so this is only required when the
+    /// sequence numbers cannot be relied upon.
+    pub fn lost(&mut self) {
+        self.buffer.clear();
+        self.lost = true;
+    }
+
+    /// Depacketize RTP packet and return raw AAC frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more AAC frames.
+    ///
+    /// No frames are produced until the packet that completes the audio mux element (marker bit
+    /// set) is received.
+    pub fn depacketize(&mut self, packet: &Packet) -> Result<Vec<Unit<Aac>>> {
+        let sequence_number = packet.header.sequence_number;
+        if self
+            .last_sequence_number
+            .replace(sequence_number)
+            .is_some_and(|last| sequence_number != last.wrapping_add(1))
+        {
+            self.lost();
+        }
+
+        self.buffer.put_slice(&packet.payload);
+        if !packet.header.marker {
+            return Ok(Vec::new());
+        }
+
+        let mut payload = self.buffer.split().freeze();
+        let lost = std::mem::take(&mut self.lost);
+        let mut frames = Vec::new();
+        while !payload.is_empty() {
+            for _ in 0..=self.parameters.num_sub_frames {
+                match get_payload_mux(&mut payload) {
+                    Ok(frame) => frames.push(Unit::new(frame)),
+                    // After packet loss, the buffer may start halfway an audio mux element, in
+                    // which case it cannot be parsed.
+                    Err(_) if lost => return Ok(Vec::new()),
+                    Err(err) => return Err(err),
+                }
+            }
+        }
+        Ok(frames)
+    }
+}
+
+/// Read payload length info and payload mux of one frame in audio mux element.
+fn get_payload_mux(payload: &mut Bytes) -> Result<Bytes> {
+    let mut len = 0;
+    loop {
+        if payload.is_empty() {
+            return Err(Error::AacLatmPayloadLengthInvalid {
+                have: 0,
+                need: len + 1,
+            });
+        }
+        let byte = payload.get_u8();
+        len += byte as usize;
+        if byte != 0xff {
+            break;
+        }
+    }
+    if payload.len() < len {
+        return Err(Error::AacLatmPayloadLengthInvalid {
+            have: payload.len(),
+            need: len,
+        });
+    }
+    Ok(payload.split_to(len))
+}
+
+fn parse_fmtp(fmtp: &str) -> Vec<(&str, &str)> {
+    fmtp.split(';')
+        .filter_map(|parameter| parameter.split_once('='))
+        .map(|(name, value)| (name.trim(), value.trim()))
+        .collect()
+}
+
+fn find_parameter<'a>(parameters: &[(&str, &'a str)], name: &str) -> Option<&'a str> {
+    parameters
+        .iter()
+        .find(|(other, _)| other.eq_ignore_ascii_case(name))
+        .map(|(_, value)| *value)
+}
+
+fn parse_number<T: std::str::FromStr>(name: &str, value: &str) -> Result<T> {
+    value.parse().map_err(|_| Error::AacParameterInvalid {
+        name: name.to_string(),
+        value: value.to_string(),
+    })
+}
+
+fn check_length(name: &str, length: u8) -> Result<u8> {
+    if length <= 32 {
+        Ok(length)
+    } else {
+        Err(Error::AacParameterInvalid {
+            name: name.to_string(),
+            value: length.to_string(),
+        })
+    }
+}
+
+fn parse_hex(name: &str, value: &str) -> Result<Bytes> {
+    let invalid = || Error::AacParameterInvalid {
+        name: name.to_string(),
+        value: value.to_string(),
+    };
+    if !value.len().is_multiple_of(2) || !value.is_ascii() {
+        return Err(invalid());
+    }
+    (0..value.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&value[i..i + 2], 16).map_err(|_| invalid()))
+        .collect()
+}
+
+/// Reads fields of up to 32 bits from a bit string.
+struct BitReader<'a> {
+    data: &'a [u8],
+    position: usize,
+    len: usize,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(data: &'a [u8], len: usize) -> Self {
+        Self {
+            data,
+            position: 0,
+            len: len.min(data.len() * 8),
+        }
+    }
+
+    fn read(&mut self, bits: u8) -> Option<u32> {
+        if self.remaining() < bits as usize {
+            return None;
+        }
+        let mut value = 0u64;
+        for _ in 0..bits {
+            let bit = (self.data[self.position / 8] >> (7 - self.position % 8)) & 0x01;
+            value = (value << 1) | bit as u64;
+            self.position += 1;
+        }
+        Some(value as u32)
+    }
+
+    #[inline]
+    fn remaining(&self) -> usize {
+        self.len - self.position
+    }
+}
+
+/// Writes fields of up to 32 bits to a bit string, padded to a whole number of bytes.
+struct BitWriter {
+    data: Vec<u8>,
+    len: usize,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            data: Vec::new(),
+            len: 0,
+        }
+    }
+
+    fn write(&mut self, value: u32, bits: u8) {
+        for i in (0..bits).rev() {
+            if self.len.is_multiple_of(8) {
+                self.data.push(0);
+            }
+            let bit = ((value as u64 >> i) & 0x01) as u8;
+            *self.data.last_mut().unwrap() |= bit << (7 - self.len % 8);
+            self.len += 1;
+        }
+    }
+
+    #[inline]
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn into_bytes(self) -> Bytes {
+        Bytes::from(self.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::{Header, Version};
+    use crate::serialize::Serialize;
+
+    fn params(mtu: Option<usize>) -> PacketizationParameters {
+        PacketizationParameters {
+            payload_type: 97,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu,
+        }
+    }
+
+    fn packet(sequence_number: u16, marker: bool, payload: &'static [u8]) -> Packet {
+        Packet::new(
+            Header {
+                version: Version::Version2,
+                padding: false,
+                marker,
+                payload_type: 97,
+                sequence_number,
+                timestamp: 0,
+                ssrc: 1,
+                csrc: Vec::new(),
+                extension: None,
+            },
+            Bytes::from_static(payload),
+        )
+    }
+
+    fn frames() -> Vec<Bytes> {
+        (0..4u8)
+            .map(|i| Bytes::from(vec![i; 20 + i as usize * 20]))
+            .collect()
+    }
+
+    fn depacketize_all(
+        packets: &[Packet],
+        mut depacketize: impl FnMut(&Packet) -> Result<Vec<Unit<Aac>>>,
+    ) -> Vec<Bytes> {
+        packets
+            .iter()
+            .flat_map(|packet| depacketize(packet).unwrap())
+            .map(Unit::into_data)
+            .collect()
+    }
+
+    #[test]
+    fn parse_fmtp() {
+        let parameters = AacParameters::from_fmtp(
+            "streamtype=5; profile-level-id=15; mode=AAC-hbr; config=1210; SizeLength=13; \
+                IndexLength=3; IndexDeltaLength=3; Profile=1;",
+        )
+        .unwrap();
+        assert_eq!(
+            parameters,
+            AacParameters::high_bitrate(Bytes::from_static(&[0x12, 0x10]))
+        );
+        assert_eq!(
+            AacParameters::from_fmtp(&parameters.to_fmtp()).unwrap(),
+            parameters
+        );
+        assert!(AacParameters::from_fmtp("mode=AAC-hbr;config=121").is_err());
+    }
+
+    #[test]
+    fn reject_empty_au_headers() {
+        assert!(matches!(
+            AacParameters::from_fmtp("sizelength=0;indexlength=3;indexdeltalength=0"),
+            Err(Error::AacParameterInvalid { .. })
+        ));
+
+        let mut depacketizer = AacDepacketizer::new(AacParameters {
+            size_length: 0,
+            index_length: 3,
+            index_delta_length: 0,
+            constant_size: Some(1),
+            ..AacParameters::high_bitrate(Bytes::new())
+        });
+        assert!(matches!(
+            depacketizer.depacketize(&packet(1, true, &[0x00, 0x08, 0x00, 0xaa])),
+            Err(Error::AacAuHeadersLengthInvalid { len: 8 })
+        ));
+    }
+
+    #[test]
+    fn depacketize_au_headers() {
+        let mut depacketizer = AacDepacketizer::new(AacParameters::high_bitrate(Bytes::new()));
+        // two AU headers (32 bits) with sizes 2 and 1
+        let frames = depacketizer
+            .depacketize(&packet(
+                1,
+                true,
+                &[0x00, 0x20, 0x00, 0x10, 0x00, 0x08, 0xaa, 0xbb, 0xcc],
+            ))
+            .unwrap();
+        assert_eq!(
+            frames.into_iter().map(Unit::into_data).collect::<Vec<_>>(),
+            vec![
+                Bytes::from_static(&[0xaa, 0xbb]),
+                Bytes::from_static(&[0xcc]),
+            ],
+        );
+    }
+
+    #[test]
+    fn round_trip_aggregated_and_fragmented() {
+        let parameters = AacParameters::high_bitrate(Bytes::from_static(&[0x12, 0x10]));
+        let mut packetizer = AacPacketizer::new(params(Some(80)), parameters.clone());
+        let packets = packetizer
+            .packetize(frames().into_iter().map(Unit::new).collect(), 1000)
+            .unwrap();
+        assert!(packets.iter().all(|packet| packet.serialized_len() <= 80));
+        // frames 0 and 1 aggregated, frame 2 alone, frame 3 fragmented
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| (packet.header.timestamp, packet.header.marker))
+                .collect::<Vec<_>>(),
+            vec![(1000, true), (3048, true), (4072, false), (4072, true)],
+        );
+
+        let mut depacketizer = AacDepacketizer::new(parameters);
+        assert_eq!(
+            depacketize_all(&packets, |packet| depacketizer.depacketize(packet)),
+            frames(),
+        );
+    }
+
+    #[test]
+    fn discard_fragmented_frame_with_gap() {
+        let parameters = AacParameters::high_bitrate(Bytes::new());
+        let mut packetizer = AacPacketizer::new(params(Some(40)), parameters.clone());
+        let mut packets = packetizer
+            .packetize(frames().into_iter().skip(3).map(Unit::new).collect(), 0)
+            .unwrap();
+        assert_eq!(packets.len(), 4);
+        packets.remove(1);
+
+        let mut depacketizer = AacDepacketizer::new(parameters);
+        assert!(depacketize_all(&packets, |packet| depacketizer.depacketize(packet)).is_empty());
+    }
+
+    #[test]
+    fn parse_latm_config() {
+        let parameters = LatmParameters::from_fmtp(
+            "profile-level-id=24;object=23;cpresent=0;config=400024203fc0",
+        )
+        .unwrap();
+        assert_eq!(parameters.num_sub_frames, 0);
+        assert!(LatmParameters::from_fmtp("profile-level-id=24;config=400024203fc0").is_err());
+    }
+
+    #[test]
+    fn latm_round_trip() {
+        let mut packetizer = AacLatmPacketizer::new(params(Some(40)));
+        let packets = packetizer
+            .packetize(frames().into_iter().map(Unit::new).collect(), 0)
+            .unwrap();
+        assert!(packets.iter().all(|packet| packet.serialized_len() <= 40));
+        assert_eq!(
+            packets.iter().filter(|packet| packet.header.marker).count(),
+            4
+        );
+
+        let parameters =
+            LatmParameters::from_config(Bytes::from_static(&[0x40, 0x00, 0x24, 0x20, 0x3f, 0xc0]))
+                .unwrap();
+        let mut depacketizer = AacLatmDepacketizer::new(parameters);
+        assert_eq!(
+            depacketize_all(&packets, |packet| depacketizer.depacketize(packet)),
+            frames(),
+        );
+    }
+
+    #[test]
+    fn latm_discard_with_gap() {
+        let parameters =
+            LatmParameters::from_config(Bytes::from_static(&[0x40, 0x00, 0x24, 0x20, 0x3f, 0xc0]))
+                .unwrap();
+        let mut depacketizer = AacLatmDepacketizer::new(parameters);
+        assert!(depacketizer
+            .depacketize(&packet(1, false, &[0x04, 0x01]))
+            .unwrap()
+            .is_empty());
+        // packet 2 lost, packet 3 completes the audio mux element
+        assert!(depacketizer
+            .depacketize(&packet(3, true, &[0x04]))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            depacketizer
+                .depacketize(&packet(4, true, &[0x01, 0x05]))
+                .unwrap()
+                .len(),
+            1,
+        );
+    }
+}
@@ -0,0 +1,643 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::error::Error;
+use crate::header_extension::HeaderExtensionParameters;
+use crate::packet::Packet;
+use crate::packetization::common::{
+    ensure_remaining, AccessUnit, PacketizationParameters, Packetizer,
+};
+
+use rave_types::codec::Av1;
+use rave_types::unit::Unit;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// OBU types (AV1 bitstream specification section 6.2.2).
+const OBU_TYPE_SEQUENCE_HEADER: u8 = 1;
+const OBU_TYPE_TEMPORAL_DELIMITER: u8 = 2;
+const OBU_TYPE_TILE_LIST: u8 = 8;
+
+/// OBU header flags.
+const OBU_HEADER_FORBIDDEN_BIT: u8 = 0x80;
+const OBU_HEADER_EXTENSION_FLAG: u8 = 0x04;
+const OBU_HEADER_HAS_SIZE_FIELD: u8 = 0x02;
+
+/// Maximum number of OBU elements that can be signaled in the aggregation header. If there are
+/// more, every element must carry its own length.
+const MAX_COUNTED_OBU_ELEMENTS: usize = 3;
+
+/// Temporal delimiter OBU with size field, which starts every temporal unit.
+const TEMPORAL_DELIMITER: [u8; 2] = [
+    (OBU_TYPE_TEMPORAL_DELIMITER << 3) | OBU_HEADER_HAS_SIZE_FIELD,
+    0x00,
+];
+
+/// AV1 aggregation header (AV1 RTP payload format section 4.4).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Av1AggregationHeader {
+    /// First OBU element is the continuation of an OBU fragment from the previous packet (Z).
+    pub continuation: bool,
+    /// Last OBU element is continued in the next packet (Y).
+    pub continued: bool,
+    /// Number of OBU elements, or zero if every element carries its own length (W).
+    pub num_obu_elements: u8,
+    /// First packet of a coded video sequence (N).
+    pub new_coded_video_sequence: bool,
+}
+
+impl Av1AggregationHeader {
+    fn parse(byte: u8) -> Self {
+        Self {
+            continuation: byte & 0x80 != 0,
+            continued: byte & 0x40 != 0,
+            num_obu_elements: (byte >> 4) & 0x03,
+            new_coded_video_sequence: byte & 0x08 != 0,
+        }
+    }
+
+    fn serialize(&self) -> u8 {
+        ((self.continuation as u8) << 7)
+            | ((self.continued as u8) << 6)
+            | ((self.num_obu_elements & 0x03) << 4)
+            | ((self.new_coded_video_sequence as u8) << 3)
+    }
+}
+
+/// RTP AV1 packetizer (AV1 RTP payload format).
+///
+/// Every unit is a temporal unit in low overhead bitstream format (OBUs with size fields), as
+/// produced by most encoders. Temporal delimiters and tile lists are dropped, size fields are
+/// removed, and the remaining OBUs are aggregated and fragmented as required to fit the MTU.
+#[derive(Debug)]
+pub struct Av1Packetizer {
+    inner: Packetizer,
+    mtu: Option<usize>,
+    max_payload_len: usize,
+}
+
+impl Av1Packetizer {
+    /// Create a new packetizer to create RTP packets from AV1 temporal units.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - RTP Packetization parameters to use for constructing packets.
+    pub fn new(params: PacketizationParameters) -> Self {
+        let mtu = params.mtu;
+        let inner = Packetizer::from_packetization_parameters(params);
+        let max_payload_len = max_payload_len(&inner, mtu);
+        Self {
+            inner,
+            mtu,
+            max_payload_len,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        self.max_payload_len = max_payload_len(&self.inner, self.mtu);
+        Ok(self)
+    }
+
+    /// Packetize one or more AV1 temporal units.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - One or more temporal units in low overhead bitstream format.
+    /// * `timestamp` - Presentation timestamp of temporal units (90 kHz clock).
+    ///
+    /// # Return value
+    ///
+    /// One or more RTP packets. The marker bit is set on the last packet of every temporal unit.
+    pub fn packetize(&mut self, data: Vec<Unit<Av1>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let mut packets = Vec::new();
+        for temporal_unit in data {
+            let obus = split_obus(temporal_unit.into_data())?
+                .into_iter()
+                .filter(|obu| {
+                    !matches!(
+                        obu_type(obu),
+                        OBU_TYPE_TEMPORAL_DELIMITER | OBU_TYPE_TILE_LIST
+                    )
+                })
+                .collect::<Vec<_>>();
+            let new_coded_video_sequence = obus
+                .iter()
+                .any(|obu| obu_type(obu) == OBU_TYPE_SEQUENCE_HEADER);
+            let payloads = self.aggregate(obus, new_coded_video_sequence);
+            let num_payloads = payloads.len();
+            for (index, payload) in payloads.into_iter().enumerate() {
+                packets.push(self.inner.packetize(
+                    payload,
+                    timestamp,
+                    index + 1 == num_payloads,
+                )?);
+            }
+        }
+        Ok(packets)
+    }
+
+    /// Aggregate and fragment OBUs (without size fields) into packet payloads.
+    fn aggregate(&self, obus: Vec<Bytes>, new_coded_video_sequence: bool) -> Vec<Bytes> {
+        let mut payloads = Vec::new();
+        let mut aggregator = Aggregator::new(Av1AggregationHeader {
+            new_coded_video_sequence,
+            ..Default::default()
+        });
+        for mut obu in obus {
+            while !obu.is_empty() {
+                // Reserve room for the largest length field the element could need.
+                let available = self
+                    .max_payload_len
+                    .saturating_sub(aggregator.len + leb128_len(obu.len()));
+                if available == 0 && !aggregator.elements.is_empty() {
+                    payloads.push(aggregator.finish(false));
+                    aggregator = Aggregator::new(Av1AggregationHeader::default());
+                    continue;
+                }
+                // If the MTU is too small to hold anything, the packetizer reports it.
+                let element = obu.split_to(available.max(1).min(obu.len()));
+                aggregator.push(element);
+                if !obu.is_empty() {
+                    payloads.push(aggregator.finish(true));
+                    aggregator = Aggregator::new(Av1AggregationHeader {
+                        continuation: true,
+                        ..Default::default()
+                    });
+                }
+            }
+        }
+        if !aggregator.elements.is_empty() {
+            payloads.push(aggregator.finish(false));
+        }
+        payloads
+    }
+}
+
+/// Maximum size of packet payload, which is whatever is left of the MTU after the RTP header.
+#[inline]
+fn max_payload_len(inner: &Packetizer, mtu: Option<usize>) -> usize {
+    match mtu {
+        Some(mtu) => mtu.saturating_sub(inner.header_serialized_len()),
+        None => usize::MAX,
+    }
+}
+
+/// Collects OBU elements for a single packet.
+struct Aggregator {
+    header: Av1AggregationHeader,
+    elements: Vec<Bytes>,
+    /// Payload length if every element carries its own length.
+    len: usize,
+}
+
+impl Aggregator {
+    fn new(header: Av1AggregationHeader) -> Self {
+        Self {
+            header,
+            elements: Vec::new(),
+            len: 1,
+        }
+    }
+
+    fn push(&mut self, element: Bytes) {
+        self.len += leb128_len(element.len()) + element.len();
+        self.elements.push(element);
+    }
+
+    fn finish(mut self, continued: bool) -> Bytes {
+        self.header.continued = continued;
+        let num_elements = self.elements.len();
+        let counted = num_elements <= MAX_COUNTED_OBU_ELEMENTS;
+        if counted {
+            self.header.num_obu_elements = num_elements as u8;
+        }
+        let mut payload = BytesMut::with_capacity(self.len);
+        payload.put_u8(self.header.serialize());
+        for (index, element) in self.elements.into_iter().enumerate() {
+            // With a counted number of elements, the last element has no length field.
+            if !counted || index + 1 < num_elements {
+                write_leb128(&mut payload, element.len());
+            }
+            payload.put(element);
+        }
+        payload.freeze()
+    }
+}
+
+/// RTP AV1 depacketizer (AV1 RTP payload format).
+///
+/// Reassembles temporal units in low overhead bitstream format: every temporal unit starts with a
+/// temporal delimiter and every OBU carries a size field. Temporal units of which packets were
+/// lost are discarded.
+#[derive(Debug, Default)]
+pub struct Av1Depacketizer {
+    temporal_unit: Option<PartialTemporalUnit>,
+    /// Timestamp of temporal unit being discarded because of packet loss.
+    discarding: Option<u32>,
+    last_sequence_number: Option<u16>,
+}
+
+impl Av1Depacketizer {
+    /// Create a new depacketizer to extract AV1 temporal units from RTP packet stream.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Discard temporal unit that is being reassembled, if any.
+    ///
+    /// Gaps in sequence numbers are detected automatically, so this is only required when the
+    /// caller drops packets itself.
+    pub fn lost(&mut self) {
+        if let Some(temporal_unit) = self.temporal_unit.take() {
+            self.discarding = Some(temporal_unit.timestamp);
+        }
+    }
+
+    /// Depacketize RTP packet and return complete AV1 temporal units.
+    ///
+    /// A temporal unit is complete when the packet with the marker bit arrives, or when the first
+    /// packet of a temporal unit with another timestamp arrives.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more complete temporal units.
+    pub fn depacketize(&mut self, packet: &Packet) -> Result<Vec<AccessUnit<Av1>>> {
+        let sequence_number = packet.header.sequence_number;
+        let timestamp = packet.header.timestamp;
+        if self
+            .last_sequence_number
+            .replace(sequence_number)
+            .is_some_and(|last| sequence_number != last.wrapping_add(1))
+        {
+            self.lost();
+            // The lost packet may have been the first packet of this temporal unit.
+            self.discarding = Some(timestamp);
+        }
+
+        let mut temporal_units = Vec::new();
+        if self
+            .temporal_unit
+            .as_ref()
+            .is_some_and(|temporal_unit| temporal_unit.timestamp != timestamp)
+        {
+            temporal_units.extend(self.temporal_unit.take().map(PartialTemporalUnit::finish));
+        }
+        if self
+            .discarding
+            .is_some_and(|discarding| discarding != timestamp)
+        {
+            self.discarding = None;
+        }
+
+        if self.discarding.is_none() {
+            let mut payload = packet.payload.clone();
+            ensure_remaining(&payload, 1)?;
+            let header = Av1AggregationHeader::parse(payload.get_u8());
+            let elements = split_obu_elements(payload, header.num_obu_elements as usize)?;
+            let num_elements = elements.len();
+
+            let temporal_unit = self
+                .temporal_unit
+                .get_or_insert_with(|| PartialTemporalUnit::new(timestamp));
+            temporal_unit.keyframe |= header.new_coded_video_sequence;
+            for (index, element) in elements.into_iter().enumerate() {
+                let continuation = index == 0 && header.continuation;
+                let continued = index + 1 == num_elements && header.continued;
+                temporal_unit.push(element, continuation, continued)?;
+            }
+        }
+
+        if packet.header.marker {
+            temporal_units.extend(self.temporal_unit.take().map(PartialTemporalUnit::finish));
+            self.discarding = None;
+        }
+
+        Ok(temporal_units)
+    }
+}
+
+/// Temporal unit that is being reassembled.
+#[derive(Debug)]
+struct PartialTemporalUnit {
+    timestamp: u32,
+    keyframe: bool,
+    corrupted: bool,
+    /// Complete OBUs with size fields.
+    data: BytesMut,
+    /// OBU that is being reassembled from fragments.
+    fragment: Option<BytesMut>,
+}
+
+impl PartialTemporalUnit {
+    fn new(timestamp: u32) -> Self {
+        let mut data = BytesMut::new();
+        data.put_slice(&TEMPORAL_DELIMITER);
+        Self {
+            timestamp,
+            keyframe: false,
+            corrupted: false,
+            data,
+            fragment: None,
+        }
+    }
+
+    fn push(&mut self, element: Bytes, continuation: bool, continued: bool) -> Result<()> {
+        let obu = if continuation {
+            match self.fragment.as_mut() {
+                Some(fragment) => {
+                    fragment.put(element);
+                    if continued {
+                        return Ok(());
+                    }
+                    self.fragment.take().unwrap().freeze()
+                }
+                // Start of OBU was never received.
+                None => {
+                    self.corrupted = true;
+                    return Ok(());
+                }
+            }
+        } else {
+            if self.fragment.take().is_some() {
+                // Fragmented OBU was never finished.
+                self.corrupted = true;
+            }
+            if continued {
+                self.fragment = Some(BytesMut::from(element));
+                return Ok(());
+            }
+            element
+        };
+
+        if obu.is_empty() {
+            return Ok(());
+        }
+        if obu[0] & OBU_HEADER_FORBIDDEN_BIT != 0 {
+            return Err(Error::Av1ObuHeaderInvalid { header: obu[0] });
+        }
+        if matches!(
+            obu_type(&obu),
+            OBU_TYPE_TEMPORAL_DELIMITER | OBU_TYPE_TILE_LIST
+        ) {
+            return Ok(());
+        }
+        let (header, payload) = split_obu_header(obu)?;
+        self.data.put_u8(header[0] | OBU_HEADER_HAS_SIZE_FIELD);
+        self.data.put_slice(&header[1..]);
+        write_leb128(&mut self.data, payload.len());
+        self.data.put(payload);
+        Ok(())
+    }
+
+    fn finish(self) -> AccessUnit<Av1> {
+        let corrupted = self.corrupted || self.fragment.is_some();
+        let mut access_unit = AccessUnit::new(self.timestamp, corrupted);
+        access_unit.keyframe = self.keyframe;
+        access_unit.units.push(Unit::new(self.data.freeze()));
+        access_unit
+    }
+}
+
+/// Split temporal unit in low overhead bitstream format into OBUs without size fields.
+fn split_obus(mut data: Bytes) -> Result<Vec<Bytes>> {
+    let mut obus = Vec::new();
+    while !data.is_empty() {
+        let header = data[0];
+        if header & OBU_HEADER_FORBIDDEN_BIT != 0 {
+            return Err(Error::Av1ObuHeaderInvalid { header });
+        }
+        let header_len = obu_header_len(header);
+        ensure_remaining(&data, header_len)?;
+        let obu = if header & OBU_HEADER_HAS_SIZE_FIELD != 0 {
+            let mut rest = data.slice(header_len..);
+            let size = read_leb128(&mut rest)?;
+            if rest.len() < size {
+                return Err(Error::Av1ObuDataTooSmall {
+                    have: rest.len(),
+                    need: size,
+                });
+            }
+            let mut obu = BytesMut::with_capacity(header_len + size);
+            obu.put_u8(header & !OBU_HEADER_HAS_SIZE_FIELD);
+            obu.put_slice(&data[1..header_len]);
+            obu.put(rest.split_to(size));
+            data = rest;
+            obu.freeze()
+        } else {
+            // OBU without size field extends to the end of the data.
+            data.split_off(0)
+        };
+        obus.push(obu);
+    }
+    Ok(obus)
+}
+
+/// Split OBU into header (including extension) and payload, removing the size field if present.
+fn split_obu_header(mut obu: Bytes) -> Result<(Bytes, Bytes)> {
+    let header_len = obu_header_len(obu[0]);
+    ensure_remaining(&obu, header_len)?;
+    let header = obu.split_to(header_len);
+    if header[0] & OBU_HEADER_HAS_SIZE_FIELD != 0 {
+        let size = read_leb128(&mut obu)?;
+        if obu.len() < size {
+            return Err(Error::Av1ObuDataTooSmall {
+                have: obu.len(),
+                need: size,
+            });
+        }
+        obu.truncate(size);
+    }
+    Ok((header, obu))
+}
+
+/// Split RTP payload (after aggregation header) into OBU elements.
+fn split_obu_elements(mut payload: Bytes, num_elements: usize) -> Result<Vec<Bytes>> {
+    let mut elements = Vec::new();
+    while !payload.is_empty() {
+        if num_elements != 0 && elements.len() + 1 == num_elements {
+            elements.push(payload.split_off(0));
+            break;
+        }
+        let len = read_leb128(&mut payload)?;
+        if payload.len() < len {
+            return Err(Error::Av1ObuDataTooSmall {
+                have: payload.len(),
+                need: len,
+            });
+        }
+        elements.push(payload.split_to(len));
+    }
+    Ok(elements)
+}
+
+#[inline]
+fn obu_type(obu: &[u8]) -> u8 {
+    (obu[0] >> 3) & 0x0f
+}
+
+#[inline]
+fn obu_header_len(header: u8) -> usize {
+    if header & OBU_HEADER_EXTENSION_FLAG != 0 {
+        2
+    } else {
+        1
+    }
+}
+
+/// Read unsigned LEB128 value (AV1 bitstream specification section 4.10.5).
+fn read_leb128(src: &mut Bytes) -> Result<usize> {
+    let mut value: u64 = 0;
+    for index in 0..8 {
+        ensure_remaining(src, 1)?;
+        let byte = src.get_u8();
+        value |= ((byte & 0x7f) as u64) << (index * 7);
+        if byte & 0x80 == 0 {
+            return usize::try_from(value)
+                .ok()
+                .filter(|value| *value <= u32::MAX as usize)
+                .ok_or(Error::Av1Leb128Invalid);
+        }
+    }
+    Err(Error::Av1Leb128Invalid)
+}
+
+fn write_leb128(dst: &mut BytesMut, mut value: usize) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            dst.put_u8(byte);
+            break;
+        }
+        dst.put_u8(byte | 0x80);
+    }
+}
+
+#[inline]
+fn leb128_len(value: usize) -> usize {
+    let bits = usize::BITS - value.leading_zeros();
+    (bits as usize).div_ceil(7).max(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize::Serialize;
+
+    fn obu(obu_type: u8, payload: &[u8]) -> Vec<u8> {
+        let mut obu = vec![(obu_type << 3) | OBU_HEADER_HAS_SIZE_FIELD];
+        let mut size = BytesMut::new();
+        write_leb128(&mut size, payload.len());
+        obu.extend_from_slice(&size);
+        obu.extend_from_slice(payload);
+        obu
+    }
+
+    #[test]
+    fn leb128() {
+        for value in [0, 1, 127, 128, 300, 16383, 16384, u32::MAX as usize] {
+            let mut dst = BytesMut::new();
+            write_leb128(&mut dst, value);
+            assert_eq!(dst.len(), leb128_len(value));
+            assert_eq!(read_leb128(&mut dst.freeze()).unwrap(), value);
+        }
+        assert!(read_leb128(&mut Bytes::from_static(&[0x80, 0x80])).is_err());
+    }
+
+    #[test]
+    fn aggregate_and_reassemble() {
+        let temporal_unit = [
+            obu(OBU_TYPE_TEMPORAL_DELIMITER, &[]),
+            obu(OBU_TYPE_SEQUENCE_HEADER, &[0x01; 10]),
+            // frame
+            obu(6, &[0x02; 40]),
+        ]
+        .concat();
+        let mut packetizer = Av1Packetizer::new(PacketizationParameters {
+            payload_type: 96,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu: Some(12 + 32),
+        });
+        let packets = packetizer
+            .packetize(vec![Unit::new(Bytes::from(temporal_unit))], 1234)
+            .unwrap();
+        assert_eq!(packets.len(), 2);
+        // sequence header and first fragment of frame, with Y and N set and W = 2
+        assert_eq!(packets[0].payload[0], 0x68);
+        assert_eq!(&packets[0].payload[1..3], &[11, 0x08]);
+        // continuation of frame with Z set and W = 1
+        assert_eq!(packets[1].payload[0], 0x90);
+        assert!(packets[1].header.marker);
+
+        let mut depacketizer = Av1Depacketizer::new();
+        assert!(depacketizer.depacketize(&packets[0]).unwrap().is_empty());
+        let temporal_units = depacketizer.depacketize(&packets[1]).unwrap();
+        assert_eq!(temporal_units.len(), 1);
+        assert!(temporal_units[0].keyframe);
+        assert!(!temporal_units[0].corrupted);
+        assert_eq!(
+            temporal_units[0].units[0].data,
+            [
+                obu(OBU_TYPE_TEMPORAL_DELIMITER, &[]),
+                obu(OBU_TYPE_SEQUENCE_HEADER, &[0x01; 10]),
+                obu(6, &[0x02; 40]),
+            ]
+            .concat(),
+        );
+    }
+
+    #[test]
+    fn fit_header_extensions_in_mtu() {
+        let mut packetizer = Av1Packetizer::new(PacketizationParameters {
+            payload_type: 96,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu: Some(12 + 8 + 16),
+        })
+        .with_header_extensions(HeaderExtensionParameters {
+            transport_sequence_number: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        let packets = packetizer
+            .packetize(vec![Unit::new(Bytes::from(obu(6, &[0x02; 40])))], 0)
+            .unwrap();
+        assert_eq!(packets.len(), 3);
+        assert!(packets
+            .iter()
+            .all(|packet| packet.header.extension.is_some() && packet.serialized_len() <= 36));
+    }
+
+    #[test]
+    fn discard_temporal_unit_after_loss() {
+        let mut packetizer = Av1Packetizer::new(PacketizationParameters {
+            payload_type: 96,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu: Some(12 + 16),
+        });
+        let packets = packetizer
+            .packetize(vec![Unit::new(Bytes::from(obu(6, &[0x02; 40])))], 0)
+            .unwrap();
+        assert_eq!(packets.len(), 3);
+        let mut depacketizer = Av1Depacketizer::new();
+        assert!(depacketizer.depacketize(&packets[0]).unwrap().is_empty());
+        assert!(depacketizer.depacketize(&packets[2]).unwrap().is_empty());
+
+        let packets = packetizer
+            .packetize(vec![Unit::new(Bytes::from(obu(6, &[0x03; 4])))], 3000)
+            .unwrap();
+        let temporal_units = depacketizer.depacketize(&packets[0]).unwrap();
+        assert_eq!(temporal_units.len(), 1);
+        assert_eq!(temporal_units[0].timestamp, 3000);
+    }
+}
@@ -1,7 +1,15 @@
-use bytes::Bytes;
+use std::collections::BTreeMap;
+
+use bytes::{Buf, Bytes};
+
+use rave_types::codec::Codec;
+use rave_types::unit::Unit;
 
 use crate::error::{Error, Result};
-use crate::packet::{Header, Packet, Version};
+use crate::header_extension::{
+    AbsSendTime, HeaderExtension, HeaderExtensionParameters, TransportSequenceNumber,
+};
+use crate::packet::{Extension, Header, Packet, Version};
 use crate::serialize::Serialize;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -18,6 +26,8 @@ pub struct Packetizer {
     header_serialized_len: usize,
     sequence_number: u16,
     mtu: Option<usize>,
+    header_extensions: HeaderExtensionParameters,
+    transport_sequence_number: u16,
 }
 
 impl Packetizer {
@@ -39,9 +49,50 @@ impl Packetizer {
             header_serialized_len,
             sequence_number: rand::random::<u16>(),
             mtu,
+            header_extensions: HeaderExtensionParameters::default(),
+            transport_sequence_number: 0,
         }
     }
 
+    /// Write header extensions (RFC 8285) to every packet.
+    ///
+    /// The header extensions count towards the MTU, so less room is left for the payload of every
+    /// packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_extensions` - Header extensions to write.
+    ///
+    /// # Return value
+    ///
+    /// Packetizer, or an error if the header extensions cannot be written, for example because an
+    /// element has an invalid identifier or too much data.
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    pub(crate) fn set_header_extensions(
+        &mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<()> {
+        // The length of the header extension does not depend on the values of the elements that
+        // change per packet, so it can be determined up front. This also validates the elements,
+        // so that writing the header extension cannot fail later on.
+        let extension = extension(
+            &header_extensions,
+            AbsSendTime(0),
+            TransportSequenceNumber(0),
+        )?;
+        self.header_serialized_len = self.header.serialized_len()
+            + extension.map_or(0, |extension| extension.serialized_len());
+        self.header_extensions = header_extensions;
+        Ok(())
+    }
+
     pub fn from_packetization_parameters(
         packetization_parameters: PacketizationParameters,
     ) -> Self {
@@ -58,6 +109,14 @@ impl Packetizer {
         header.marker = marker;
         header.sequence_number = self.next_sequence_number();
         header.timestamp = timestamp;
+        if !self.header_extensions.is_empty() {
+            let transport_sequence_number = self.next_transport_sequence_number();
+            header.extension = extension(
+                &self.header_extensions,
+                AbsSendTime::now(),
+                transport_sequence_number,
+            )?;
+        }
 
         let packet = Packet::new(header, payload);
 
@@ -81,4 +140,183 @@ impl Packetizer {
         self.sequence_number = self.sequence_number.wrapping_add(1);
         sequence_number
     }
+
+    #[inline]
+    fn next_transport_sequence_number(&mut self) -> TransportSequenceNumber {
+        let transport_sequence_number = self.transport_sequence_number;
+        self.transport_sequence_number = self.transport_sequence_number.wrapping_add(1);
+        TransportSequenceNumber(transport_sequence_number)
+    }
+}
+
+/// Build header extension from parameters and the values of the elements that change per packet.
+fn extension(
+    header_extensions: &HeaderExtensionParameters,
+    abs_send_time: AbsSendTime,
+    transport_sequence_number: TransportSequenceNumber,
+) -> Result<Option<Extension>> {
+    if header_extensions.is_empty() {
+        return Ok(None);
+    }
+    let mut elements = header_extensions.elements.clone();
+    if let Some(id) = header_extensions.abs_send_time {
+        elements.push(abs_send_time.to_element(id));
+    }
+    if let Some(id) = header_extensions.transport_sequence_number {
+        elements.push(transport_sequence_number.to_element(id));
+    }
+    Extension::from_elements(&elements).map(Some)
+}
+
+/// Complete access unit (all units that belong to a single picture), reassembled from RTP packets.
+pub struct AccessUnit<C: Codec> {
+    /// RTP timestamp shared by all packets of the access unit.
+    pub timestamp: u32,
+    /// Units in decoding order.
+    pub units: Vec<Unit<C>>,
+    /// Whether the access unit can be decoded without reference to earlier access units.
+    pub keyframe: bool,
+    /// Whether packets were lost or could not be depacketized while assembling the access unit.
+    ///
+    /// Corrupted access units may be incomplete, or may be missing units that were lost together
+    /// with packets just before them.
+    pub corrupted: bool,
+}
+
+impl<C: Codec> AccessUnit<C> {
+    pub(crate) fn new(timestamp: u32, corrupted: bool) -> Self {
+        Self {
+            timestamp,
+            units: Vec::new(),
+            keyframe: false,
+            corrupted,
+        }
+    }
+}
+
+impl<C: Codec> std::fmt::Debug for AccessUnit<C>
+where
+    C::Data: std::fmt::Debug,
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("AccessUnit")
+            .field("codec", &C::ID)
+            .field("timestamp", &self.timestamp)
+            .field(
+                "units",
+                &self.units.iter().map(|unit| &unit.data).collect::<Vec<_>>(),
+            )
+            .field("keyframe", &self.keyframe)
+            .field("corrupted", &self.corrupted)
+            .finish()
+    }
+}
+
+/// Deinterleaving buffer (RFC 6184 section 7.2.2, RFC 7798 section 6).
+///
+/// Holds NAL units that carry a decoding order number (DON) and releases them in decoding order.
+#[derive(Debug)]
+pub(crate) struct Deinterleaver {
+    /// Number of counted NAL units held before releasing the first one.
+    pub(crate) depth: usize,
+    /// Number of bytes held before releasing the first NAL unit, regardless of depth.
+    pub(crate) max_size: Option<usize>,
+    /// Whether NAL unit counts towards depth.
+    counted: fn(&[u8]) -> bool,
+    /// NAL units by absolute DON and order of arrival.
+    nal_units: BTreeMap<(i64, u64), Bytes>,
+    num_counted: usize,
+    size: usize,
+    /// DON and absolute DON of last received NAL unit.
+    last: Option<(u16, i64)>,
+    /// Absolute DON of last released NAL unit.
+    released: Option<i64>,
+    num_received: u64,
+}
+
+impl Deinterleaver {
+    pub(crate) fn new(counted: fn(&[u8]) -> bool) -> Self {
+        Self {
+            depth: 0,
+            max_size: None,
+            counted,
+            nal_units: BTreeMap::new(),
+            num_counted: 0,
+            size: 0,
+            last: None,
+            released: None,
+            num_received: 0,
+        }
+    }
+
+    /// Add NAL unit to buffer.
+    ///
+    /// # Return value
+    ///
+    /// NAL units that can be released, in decoding order.
+    pub(crate) fn push(&mut self, decoding_order_number: u16, nal_unit: Bytes) -> Vec<Bytes> {
+        // DON values are compared within half of the 16-bit range (RFC 6184 section 5.5).
+        let absolute_decoding_order_number = match self.last {
+            Some((last, last_absolute)) => {
+                last_absolute + decoding_order_number.wrapping_sub(last) as i16 as i64
+            }
+            None => decoding_order_number as i64,
+        };
+        self.last = Some((decoding_order_number, absolute_decoding_order_number));
+        if self
+            .released
+            .is_some_and(|released| absolute_decoding_order_number < released)
+        {
+            // Arrived after NAL units that follow it in decoding order were released.
+            return Vec::new();
+        }
+
+        self.num_received += 1;
+        if (self.counted)(&nal_unit) {
+            self.num_counted += 1;
+        }
+        self.size += nal_unit.len();
+        self.nal_units.insert(
+            (absolute_decoding_order_number, self.num_received),
+            nal_unit,
+        );
+
+        let mut released = Vec::new();
+        while self.num_counted > self.depth
+            || self.max_size.is_some_and(|max_size| self.size > max_size)
+        {
+            match self.pop() {
+                Some(nal_unit) => released.push(nal_unit),
+                None => break,
+            }
+        }
+        released
+    }
+
+    /// Take all NAL units, in decoding order.
+    pub(crate) fn flush(&mut self) -> Vec<Bytes> {
+        std::iter::from_fn(|| self.pop()).collect()
+    }
+
+    fn pop(&mut self) -> Option<Bytes> {
+        let ((absolute_decoding_order_number, _), nal_unit) = self.nal_units.pop_first()?;
+        self.released = Some(absolute_decoding_order_number);
+        if (self.counted)(&nal_unit) {
+            self.num_counted -= 1;
+        }
+        self.size -= nal_unit.len();
+        Some(nal_unit)
+    }
+}
+
+/// Check that enough data remains to parse the next field.
+#[inline]
+pub(crate) fn ensure_remaining(src: &Bytes, need: usize) -> Result<()> {
+    if src.remaining() < need {
+        return Err(Error::NotEnoughData {
+            have: src.remaining(),
+            need,
+        });
+    }
+    Ok(())
 }
@@ -0,0 +1,244 @@
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use crate::error::Error;
+use crate::header_extension::HeaderExtensionParameters;
+use crate::packet::Packet;
+use crate::packetization::common::{PacketizationParameters, Packetizer};
+
+use bytes::{BufMut, Bytes, BytesMut};
+
+use rave_types::codec::Codec;
+use rave_types::unit::Unit;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// RTP clock rate for G.711, which is also its sampling rate.
+pub const G711_CLOCK_RATE: u32 = 8_000;
+/// Static payload type for G.711 µ-law (RFC 3551).
+pub const PAYLOAD_TYPE_PCMU: u8 = 0;
+/// Static payload type for G.711 A-law (RFC 3551).
+pub const PAYLOAD_TYPE_PCMA: u8 = 8;
+
+/// Default packet time.
+const DEFAULT_PACKET_TIME: Duration = Duration::from_millis(20);
+
+/// RTP G.711 packetizer (RFC 3551).
+///
+/// Works for both µ-law ([`Pcmu`](rave_types::codec::Pcmu)) and A-law
+/// ([`Pcma`](rave_types::codec::Pcma)). The payload type in the packetization parameters should
+/// be [`PAYLOAD_TYPE_PCMU`] or [`PAYLOAD_TYPE_PCMA`] respectively, unless a dynamic payload type
+/// was negotiated.
+///
+/// Samples are split into packets of the configured packet time (20 ms by default).
+pub struct G711Packetizer<C: Codec<Data = Bytes>> {
+    inner: Packetizer,
+    mtu: Option<usize>,
+    samples_per_packet: usize,
+    next_timestamp: Option<u32>,
+    _codec: PhantomData<C>,
+}
+
+impl<C: Codec<Data = Bytes>> G711Packetizer<C> {
+    /// Create a new packetizer to create RTP packets from G.711 samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - RTP Packetization parameters to use for constructing packets.
+    pub fn new(params: PacketizationParameters) -> Self {
+        let mtu = params.mtu;
+        Self {
+            inner: Packetizer::from_packetization_parameters(params),
+            mtu,
+            samples_per_packet: samples(DEFAULT_PACKET_TIME),
+            next_timestamp: None,
+            _codec: PhantomData,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    /// Set duration of audio in each packet (`a=ptime`). Packets are smaller if the MTU requires
+    /// it.
+    pub fn with_packet_time(mut self, packet_time: Duration) -> Self {
+        self.samples_per_packet = samples(packet_time).max(1);
+        self
+    }
+
+    /// Packetize G.711 samples.
+    ///
+    /// # Timestamps
+    ///
+    /// The samples in all units must be consecutive. The first sample has the given timestamp.
+    /// Samples are one byte each, so the timestamp advances by one per byte.
+    ///
+    /// If the timestamp does not continue where the previous call left off, the samples are the
+    /// start of a talkspurt (for example after silence suppression), and the marker bit is set on
+    /// the first packet (RFC 3551 section 4.1).
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - One or more units of G.711 samples.
+    /// * `timestamp` - Presentation timestamp of first sample (8 kHz clock).
+    ///
+    /// # Return value
+    ///
+    /// Zero or more RTP packets.
+    pub fn packetize(&mut self, data: Vec<Unit<C>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let samples_per_packet = match self.mtu {
+            Some(mtu) => self.samples_per_packet.min(
+                mtu.saturating_sub(self.inner.header_serialized_len())
+                    .max(1),
+            ),
+            None => self.samples_per_packet,
+        };
+
+        let mut samples = BytesMut::new();
+        for unit in data {
+            samples.put(unit.into_data());
+        }
+        let mut samples = samples.freeze();
+
+        let mut packets = Vec::with_capacity(samples.len().div_ceil(samples_per_packet));
+        let mut marker = self.next_timestamp != Some(timestamp);
+        let mut timestamp = timestamp;
+        while !samples.is_empty() {
+            let payload = samples.split_to(samples_per_packet.min(samples.len()));
+            let num_samples = payload.len() as u32;
+            packets.push(self.inner.packetize(payload, timestamp, marker)?);
+            marker = false;
+            timestamp = timestamp.wrapping_add(num_samples);
+        }
+        if !packets.is_empty() {
+            self.next_timestamp = Some(timestamp);
+        }
+        Ok(packets)
+    }
+}
+
+impl<C: Codec<Data = Bytes>> std::fmt::Debug for G711Packetizer<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("G711Packetizer")
+            .field("codec", &C::ID)
+            .field("inner", &self.inner)
+            .field("mtu", &self.mtu)
+            .field("samples_per_packet", &self.samples_per_packet)
+            .finish()
+    }
+}
+
+/// RTP G.711 depacketizer (RFC 3551).
+pub struct G711Depacketizer<C: Codec<Data = Bytes>> {
+    _codec: PhantomData<C>,
+}
+
+impl<C: Codec<Data = Bytes>> G711Depacketizer<C> {
+    /// Create a new depacketizer to extract G.711 samples from RTP packet stream.
+    pub fn new() -> Self {
+        Self {
+            _codec: PhantomData,
+        }
+    }
+
+    /// Depacketize RTP packet and return G.711 samples.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Samples in packet, or nothing if the packet is empty.
+    pub fn depacketize(&mut self, packet: &Packet) -> Result<Vec<Unit<C>>> {
+        if packet.payload.is_empty() {
+            return Ok(Vec::new());
+        }
+        Ok(vec![Unit::new(packet.payload.clone())])
+    }
+}
+
+impl<C: Codec<Data = Bytes>> Default for G711Depacketizer<C> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<C: Codec<Data = Bytes>> std::fmt::Debug for G711Depacketizer<C> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("G711Depacketizer")
+            .field("codec", &C::ID)
+            .finish()
+    }
+}
+
+/// Number of samples in duration at G.711 clock rate.
+#[inline]
+fn samples(duration: Duration) -> usize {
+    (duration.as_millis() as usize) * (G711_CLOCK_RATE as usize / 1000)
+}
+
+#[cfg(test)]
+mod tests {
+    use rave_types::codec::Pcmu;
+
+    use super::*;
+
+    #[test]
+    fn split_into_packet_time() {
+        let mut packetizer = G711Packetizer::<Pcmu>::new(PacketizationParameters {
+            payload_type: PAYLOAD_TYPE_PCMU,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu: None,
+        })
+        .with_packet_time(Duration::from_millis(10));
+        let packets = packetizer
+            .packetize(
+                vec![
+                    Unit::new(Bytes::from(vec![0xff; 100])),
+                    Unit::new(Bytes::from(vec![0xff; 100])),
+                ],
+                0,
+            )
+            .unwrap();
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| (
+                    packet.header.timestamp,
+                    packet.payload.len(),
+                    packet.header.marker
+                ))
+                .collect::<Vec<_>>(),
+            vec![(0, 80, true), (80, 80, false), (160, 40, false)],
+        );
+        assert!(packets
+            .iter()
+            .all(|packet| packet.header.payload_type == PAYLOAD_TYPE_PCMU));
+
+        let mut depacketizer = G711Depacketizer::<Pcmu>::new();
+        assert_eq!(
+            depacketizer.depacketize(&packets[2]).unwrap()[0].data.len(),
+            40
+        );
+
+        // Continues previous talkspurt.
+        let packets = packetizer
+            .packetize(vec![Unit::new(Bytes::from(vec![0xff; 80]))], 200)
+            .unwrap();
+        assert!(!packets[0].header.marker);
+        // Starts new talkspurt after silence.
+        let packets = packetizer
+            .packetize(vec![Unit::new(Bytes::from(vec![0xff; 80]))], 800)
+            .unwrap();
+        assert!(packets[0].header.marker);
+    }
+}
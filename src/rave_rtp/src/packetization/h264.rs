@@ -1,6 +1,9 @@
 use crate::error::Error;
+use crate::header_extension::HeaderExtensionParameters;
 use crate::packet::Packet;
-use crate::packetization::common::{PacketizationParameters, Packetizer};
+use crate::packetization::common::{
+    AccessUnit, Deinterleaver, PacketizationParameters, Packetizer,
+};
 
 use bytes::{Buf, BufMut, Bytes, BytesMut};
 
@@ -11,7 +14,7 @@ type Result<T> = std::result::Result<T, Error>;
 
 /// RTP H264 packetizer.
 pub struct H264Packetizer {
-    inner: Box<dyn H264Packetize>,
+    inner: Box<dyn H264PacketizeMode>,
 }
 
 impl H264Packetizer {
@@ -19,8 +22,8 @@ impl H264Packetizer {
     ///
     /// # Packetization mode support
     ///
-    /// The packetization modes currently supported are "Single NAL Unit mode" and "Non-Interleaved
-    /// Mode".
+    /// All packetization modes are supported: "Single NAL Unit Mode", "Non-Interleaved Mode" and
+    /// "Interleaved Mode".
     ///
     /// # Arguments
     ///
@@ -37,12 +40,23 @@ impl H264Packetizer {
                     Box::new(H264PacketizerMode1::new(params))
                 }
                 H264PacketizationMode::InterleavedMode => {
-                    return Err(Error::H264PacketizationModeUnsupported { mode })
+                    Box::new(H264PacketizerMode2::new(params))
                 }
             },
         })
     }
 
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner
+            .packetizer_mut()
+            .set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
     /// Packetize one or more H264 encoded packets.
     ///
     /// Refer to [`H264Packetize::packetize()`].
@@ -55,8 +69,8 @@ impl H264Packetizer {
     /// # Fragmentation
     ///
     /// If the packetizer is in single NAL unit mode, any data that exceed the MTU will produce an
-    /// error. If the packetizer is in non-interleaved Mode, any data that exceed the MTU will be
-    /// fragmented over multiple packets.
+    /// error. If the packetizer is in non-interleaved or interleaved mode, any data that exceed the
+    /// MTU will be fragmented over multiple packets.
     ///
     /// # Arguments
     ///
@@ -94,6 +108,11 @@ pub trait H264Packetize {
     fn packetize(&mut self, data: Vec<Unit<H264>>, timestamp: u32) -> Result<Vec<Packet>>;
 }
 
+/// H264 packetizer of any packetization mode, with access to the underlying packetizer.
+trait H264PacketizeMode: H264Packetize {
+    fn packetizer_mut(&mut self) -> &mut Packetizer;
+}
+
 /// Single NAL unit mode H264 packetizer.
 #[derive(Debug)]
 pub struct H264PacketizerMode0 {
@@ -111,6 +130,22 @@ impl H264PacketizerMode0 {
             inner: Packetizer::from_packetization_parameters(params),
         }
     }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+}
+
+impl H264PacketizeMode for H264PacketizerMode0 {
+    #[inline]
+    fn packetizer_mut(&mut self) -> &mut Packetizer {
+        &mut self.inner
+    }
 }
 
 impl H264Packetize for H264PacketizerMode0 {
@@ -175,6 +210,15 @@ impl H264PacketizerMode1 {
         }
     }
 
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
     /// Groups a set of NAL units such that packets that as much packets as possible are fit into a
     /// single STAP-A without exceeding the MTU.
     ///
@@ -217,15 +261,22 @@ impl H264PacketizerMode1 {
     ///
     /// # Return value
     ///
-    /// Fragmented NAL units (FU-A).
-    fn payload_fragmented_unit_a(&self, mut nal_unit: Bytes, mtu: usize) -> Vec<Bytes> {
-        let fu_payload_max_len = mtu - (self.inner.header_serialized_len() + 2);
+    /// Fragmented NAL units (FU-A), or an error if the MTU leaves no room for any payload.
+    fn payload_fragmented_unit_a(&self, mut nal_unit: Bytes, mtu: usize) -> Result<Vec<Bytes>> {
+        let header_len = self.inner.header_serialized_len() + 2;
+        let fu_payload_max_len =
+            mtu.checked_sub(header_len)
+                .filter(|len| *len > 0)
+                .ok_or(Error::MtuTooSmall {
+                    mtu,
+                    need: header_len + 1,
+                })?;
         let nal_unit_header = nal_unit.get_u8(); // Strip header.
         let nal_unit_type = nal_unit_header & 0x1f;
         let nal_ref_idc = nal_unit_header & 0x60;
         let chunks = nal_unit.chunks(fu_payload_max_len);
         let chunks_len = chunks.len();
-        chunks
+        Ok(chunks
             .enumerate()
             .map(|(i, fu_payload)| {
                 let mut fragmented_nal_unit = BytesMut::with_capacity(2 + fu_payload.len());
@@ -242,7 +293,7 @@ impl H264PacketizerMode1 {
                 fragmented_nal_unit.put(fu_payload);
                 fragmented_nal_unit.freeze()
             })
-            .collect()
+            .collect())
     }
 
     /// Combine one or more NAL units into single STAP-A NAL unit.
@@ -268,6 +319,13 @@ impl H264PacketizerMode1 {
     }
 }
 
+impl H264PacketizeMode for H264PacketizerMode1 {
+    #[inline]
+    fn packetizer_mut(&mut self) -> &mut Packetizer {
+        &mut self.inner
+    }
+}
+
 impl H264Packetize for H264PacketizerMode1 {
     /// Packetize one or more H264 encoded packets in non-interleaved mode.
     ///
@@ -313,7 +371,7 @@ impl H264Packetize for H264PacketizerMode1 {
                         packets.push(single_nal_unit_packet);
                     } else {
                         let fragmented_nal_unit_payloads =
-                            self.payload_fragmented_unit_a(single_nal_unit, mtu);
+                            self.payload_fragmented_unit_a(single_nal_unit, mtu)?;
                         let num_packets = fragmented_nal_unit_payloads.len();
                         let fragmented_nal_packets = fragmented_nal_unit_payloads
                             .into_iter()
@@ -357,11 +415,244 @@ impl H264Packetize for H264PacketizerMode1 {
     }
 }
 
+/// Interleaved Mode H264 packetizer.
+///
+/// NAL units are numbered with a decoding order number (DON) and aggregated in STAP-B packets, or
+/// fragmented over an FU-B packet followed by FU-A packets. NAL units are transmitted in decoding
+/// order, so the stream can be signaled with `sprop-interleaving-depth=0`.
+#[derive(Debug)]
+pub struct H264PacketizerMode2 {
+    inner: Packetizer,
+    mtu: Option<usize>,
+    decoding_order_number: u16,
+}
+
+impl H264PacketizerMode2 {
+    /// Create new H264 packetizer that packetizes in interleaved mode.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - Common RTP packetization parameters to use.
+    pub fn new(params: PacketizationParameters) -> Self {
+        let mtu = params.mtu;
+        Self {
+            inner: Packetizer::from_packetization_parameters(params),
+            mtu,
+            decoding_order_number: 0,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    /// Fragment one NAL unit over one FU-B NAL unit followed by FU-A NAL units.
+    ///
+    /// # Arguments
+    ///
+    /// * `nal_unit` - NAL unit to fragment.
+    /// * `decoding_order_number` - Decoding order number of NAL unit.
+    /// * `mtu` - Maximum transmission unit size to satisfy.
+    ///
+    /// # Return value
+    ///
+    /// Fragmented NAL units (FU-B and FU-A).
+    fn payload_fragmented_unit_b(
+        &self,
+        mut nal_unit: Bytes,
+        decoding_order_number: u16,
+        mtu: usize,
+    ) -> Vec<Bytes> {
+        let header_len = self.inner.header_serialized_len();
+        let nal_unit_header = nal_unit.get_u8(); // Strip header.
+        let nal_unit_type = nal_unit_header & 0x1f;
+        let nal_ref_idc = nal_unit_header & 0x60;
+
+        // If the MTU is too small to fit any payload, packetizing will fail on the MTU check.
+        let fu_b_payload_max_len = mtu.saturating_sub(header_len + 4).max(1);
+        let fu_a_payload_max_len = mtu.saturating_sub(header_len + 2).max(1);
+        let first = nal_unit.split_to(fu_b_payload_max_len.min(nal_unit.len()));
+        let rest = nal_unit
+            .chunks(fu_a_payload_max_len)
+            .map(Bytes::copy_from_slice)
+            .collect::<Vec<_>>();
+        let num_fragments = 1 + rest.len();
+
+        std::iter::once(first)
+            .chain(rest)
+            .enumerate()
+            .map(|(i, fu_payload)| {
+                let mut fragmented_nal_unit = BytesMut::with_capacity(4 + fu_payload.len());
+                // First fragment must be FU-B in interleaved mode, the remainder is FU-A.
+                let fragmented_nal_unit_indicator = if i == 0 { 29 } else { 28 } | nal_ref_idc;
+                fragmented_nal_unit.put_u8(fragmented_nal_unit_indicator);
+                let mut fragmented_nal_unit_header = nal_unit_type;
+                if i == 0 {
+                    fragmented_nal_unit_header |= 0x80; // Set start bit.
+                }
+                if i == num_fragments - 1 {
+                    fragmented_nal_unit_header |= 0x40; // Set end bit.
+                }
+                fragmented_nal_unit.put_u8(fragmented_nal_unit_header);
+                if i == 0 {
+                    fragmented_nal_unit.put_u16(decoding_order_number);
+                }
+                fragmented_nal_unit.put(fu_payload);
+                fragmented_nal_unit.freeze()
+            })
+            .collect()
+    }
+
+    /// Combine one or more NAL units with consecutive decoding order numbers into a single STAP-B
+    /// NAL unit.
+    ///
+    /// # Arguments
+    ///
+    /// * `nal_units` - NAL units to combine in STAP-B.
+    /// * `decoding_order_number` - Decoding order number of first NAL unit.
+    ///
+    /// # Return value
+    ///
+    /// STAP-B NAL unit.
+    fn payload_stap_b(nal_units: Vec<Bytes>, decoding_order_number: u16) -> Result<Bytes> {
+        let nal_ref_idc = nal_units
+            .iter()
+            .filter_map(|nal_unit| nal_unit.first())
+            .map(|nal_unit_header| nal_unit_header & 0x60)
+            .max()
+            .unwrap_or(0);
+        let mut payload = BytesMut::with_capacity(stap_b_len(&nal_units));
+        payload.put_u8(25 | nal_ref_idc);
+        payload.put_u16(decoding_order_number);
+        for nal_unit in nal_units {
+            payload.put_u16(nal_unit.len().try_into().map_err(|_| {
+                Error::H264NalUnitDataLengthInvalid {
+                    len: nal_unit.len(),
+                }
+            })?);
+            payload.put(nal_unit);
+        }
+        Ok(payload.freeze())
+    }
+
+    #[inline]
+    fn next_decoding_order_number(&mut self) -> u16 {
+        let decoding_order_number = self.decoding_order_number;
+        self.decoding_order_number = self.decoding_order_number.wrapping_add(1);
+        decoding_order_number
+    }
+}
+
+impl H264PacketizeMode for H264PacketizerMode2 {
+    #[inline]
+    fn packetizer_mut(&mut self) -> &mut Packetizer {
+        &mut self.inner
+    }
+}
+
+impl H264Packetize for H264PacketizerMode2 {
+    /// Packetize one or more H264 encoded packets in interleaved mode.
+    ///
+    /// Refer to [`H264Packetize::packetize()`].
+    ///
+    /// # Access unit
+    ///
+    /// The caller must call this function exactly once per "access unit" (once per encoded
+    /// picture).
+    ///
+    /// # Fragmentation
+    ///
+    /// Data may be fragmented over multiple packets to satisfy MTU.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - One or more H264 packets.
+    /// * `timestamp` - Presentation timestamp of NAL units.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more packets.
+    fn packetize(&mut self, data: Vec<Unit<H264>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let mtu = self.mtu.unwrap_or(usize::MAX);
+        let header_len = self.inner.header_serialized_len();
+
+        let mut payloads = Vec::new();
+        let mut group: Vec<Bytes> = Vec::new();
+        let mut group_decoding_order_number = self.decoding_order_number;
+        for nal_unit in data.into_iter().map(Unit::into_data) {
+            let decoding_order_number = self.next_decoding_order_number();
+            if header_len + stap_b_len(&[]) + 2 + nal_unit.len() > mtu {
+                // Does not fit in STAP-B by itself, so fragment it.
+                if !group.is_empty() {
+                    payloads.push(Self::payload_stap_b(
+                        std::mem::take(&mut group),
+                        group_decoding_order_number,
+                    )?);
+                }
+                payloads.extend(self.payload_fragmented_unit_b(
+                    nal_unit,
+                    decoding_order_number,
+                    mtu,
+                ));
+                continue;
+            }
+            if !group.is_empty() && header_len + stap_b_len(&group) + 2 + nal_unit.len() > mtu {
+                payloads.push(Self::payload_stap_b(
+                    std::mem::take(&mut group),
+                    group_decoding_order_number,
+                )?);
+            }
+            if group.is_empty() {
+                group_decoding_order_number = decoding_order_number;
+            }
+            group.push(nal_unit);
+        }
+        if !group.is_empty() {
+            payloads.push(Self::payload_stap_b(group, group_decoding_order_number)?);
+        }
+
+        let num_payloads = payloads.len();
+        payloads
+            .into_iter()
+            .enumerate()
+            .map(|(i, payload)| {
+                // Last packet of access unit -> set marker bit.
+                self.inner
+                    .packetize(payload, timestamp, i == num_payloads - 1)
+            })
+            .collect()
+    }
+}
+
+/// Size of STAP-B NAL unit holding the given NAL units.
+fn stap_b_len(nal_units: &[Bytes]) -> usize {
+    // STAP-B NAL unit header and DON
+    3 + nal_units
+        .iter()
+        .map(|nal_unit| 2 + nal_unit.len())
+        .sum::<usize>()
+}
+
 /// RTP H264 depacketizer.
+///
+/// Packets must be passed in sequence number order (see
+/// [`JitterBuffer`](crate::jitter_buffer::JitterBuffer)). When packets are missing, the fragmented
+/// unit that was being reassembled is discarded, as well as any remaining fragments of it.
+///
+/// NAL units that carry a decoding order number (interleaved mode) are put back in decoding order
+/// by a deinterleaving buffer (see [`H264Depacketizer::with_interleaving_depth`]).
 #[derive(Debug)]
 pub struct H264Depacketizer {
     fragmented_unit_buffer: Option<BytesMut>,
-    // TODO: resequencing here (actually kind of required for FU-A)
+    fragmented_unit_decoding_order_number: Option<u16>,
+    last_sequence_number: Option<u16>,
+    discarding_fragmented_unit: bool,
+    deinterleaver: Deinterleaver,
 }
 
 impl H264Depacketizer {
@@ -369,11 +660,66 @@ impl H264Depacketizer {
     ///
     /// # Packetization mode support
     ///
-    /// The packetization modes currently supported are "Single NAL Unit mode" and "Non-Interleaved
-    /// Mode".
+    /// All packetization modes are supported: "Single NAL Unit Mode", "Non-Interleaved Mode" and
+    /// "Interleaved Mode".
     pub fn new() -> Self {
         Self {
             fragmented_unit_buffer: None,
+            fragmented_unit_decoding_order_number: None,
+            last_sequence_number: None,
+            discarding_fragmented_unit: false,
+            deinterleaver: Deinterleaver::new(is_vcl_nal_unit),
+        }
+    }
+
+    /// Set interleaving depth for interleaved mode.
+    ///
+    /// This is the maximum number of VCL NAL units that precede any VCL NAL unit in transmission
+    /// order and follow it in decoding order, as signaled by `sprop-interleaving-depth`. It
+    /// determines how many VCL NAL units are held by the deinterleaving buffer. Defaults to zero,
+    /// which means NAL units are expected in decoding order.
+    ///
+    /// # Arguments
+    ///
+    /// * `interleaving_depth` - Value of `sprop-interleaving-depth`.
+    pub fn with_interleaving_depth(mut self, interleaving_depth: usize) -> Self {
+        self.deinterleaver.depth = interleaving_depth;
+        self
+    }
+
+    /// Set deinterleaving buffer size for interleaved mode.
+    ///
+    /// When the deinterleaving buffer holds more than this number of bytes, NAL units are released
+    /// even if the interleaving depth has not been reached. By default, the size is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `deinterleaving_buffer_size` - Value of `sprop-deint-buf-req` (bytes).
+    pub fn with_deinterleaving_buffer_size(mut self, deinterleaving_buffer_size: usize) -> Self {
+        self.deinterleaver.max_size = Some(deinterleaving_buffer_size);
+        self
+    }
+
+    /// Take all NAL units remaining in the deinterleaving buffer, in decoding order.
+    ///
+    /// Use this at the end of the stream.
+    pub fn flush(&mut self) -> Vec<Unit<H264>> {
+        self.deinterleaver
+            .flush()
+            .into_iter()
+            .map(Unit::new)
+            .collect()
+    }
+
+    /// Discard fragmented unit that is being reassembled, if any.
+    ///
+    /// Call this when packets are known to be lost. Remaining fragments of the unit are ignored.
+    /// Gaps in sequence numbers are detected automatically, so this is only required when the
+    /// sequence numbers cannot be relied upon.
+    pub fn lost(&mut self) {
+        self.fragmented_unit_decoding_order_number = None;
+        if self.fragmented_unit_buffer.take().is_some() {
+            self.discarding_fragmented_unit = true;
         }
     }
 
@@ -385,8 +731,8 @@ impl H264Depacketizer {
     ///
     /// # Packetization mode support
     ///
-    /// The packetization modes currently supported are "Single NAL Unit mode" and "Non-Interleaved
-    /// Mode".
+    /// All packetization modes are supported. In interleaved mode, NAL units are returned in
+    /// decoding order, which means they may be returned when depacketizing a later packet.
     ///
     /// # Arguments
     ///
@@ -405,7 +751,19 @@ impl H264Depacketizer {
             });
         }
 
+        let sequence_number = packet.header.sequence_number;
+        if self
+            .last_sequence_number
+            .replace(sequence_number)
+            .is_some_and(|last| sequence_number != last.wrapping_add(1))
+        {
+            self.lost();
+        }
+
         let nal_unit_type = packet.payload[0] & 0x1f;
+        if !matches!(nal_unit_type, 28 | 29) {
+            self.discarding_fragmented_unit = false;
+        }
         match nal_unit_type {
             // NAL
             1..=23 => {
@@ -440,20 +798,50 @@ impl H264Depacketizer {
             }
             // STAP-B
             25 => {
-                // STAP-B only supported in packetization mode 2 (not supported here).
-                Err(Error::H264DepacketizationNalUnitTypeUnsupported {
-                    nal_unit_type_name: "STAP-B".to_string(),
-                })
+                let mut payload = packet.payload.clone();
+                payload.advance(1); // Skip NAL unit type (already peeked in nal_unit_type).
+                let mut decoding_order_number = get_decoding_order_number(&mut payload)?;
+
+                let mut nal_units = Vec::new();
+                while !payload.is_empty() {
+                    let nal_unit = get_aggregation_unit(&mut payload, 0)?;
+                    nal_units.extend(
+                        self.deinterleaver
+                            .push(decoding_order_number, nal_unit)
+                            .into_iter()
+                            .map(Unit::new),
+                    );
+                    decoding_order_number = decoding_order_number.wrapping_add(1);
+                }
+                Ok(nal_units)
             }
-            // MTAP
+            // MTAP16 and MTAP24
             26..=27 => {
-                // MTAP only supported in packetization mode 2 (not supported here).
-                Err(Error::H264DepacketizationNalUnitTypeUnsupported {
-                    nal_unit_type_name: "MTAP".to_string(),
-                })
+                let mut payload = packet.payload.clone();
+                payload.advance(1); // Skip NAL unit type (already peeked in nal_unit_type).
+                let decoding_order_number_base = get_decoding_order_number(&mut payload)?;
+                // Timestamp offsets are not used, since units carry no timestamp of their own.
+                let timestamp_offset_len = if nal_unit_type == 26 { 2 } else { 3 };
+
+                let mut nal_units = Vec::new();
+                while !payload.is_empty() {
+                    // NAL unit size is followed by DOND and timestamp offset
+                    let mut header = payload.clone();
+                    let nal_unit = get_aggregation_unit(&mut payload, 1 + timestamp_offset_len)?;
+                    header.advance(2);
+                    let decoding_order_number =
+                        decoding_order_number_base.wrapping_add(header.get_u8() as u16);
+                    nal_units.extend(
+                        self.deinterleaver
+                            .push(decoding_order_number, nal_unit)
+                            .into_iter()
+                            .map(Unit::new),
+                    );
+                }
+                Ok(nal_units)
             }
-            // FU-A
-            28 => {
+            // FU-A and FU-B
+            28..=29 => {
                 let mut payload = packet.payload.clone();
                 payload.advance(1); // Skip NAL unit type (already peeked in nal_unit_type).
 
@@ -464,6 +852,20 @@ impl H264Depacketizer {
                 let fragmentation_unit_header = payload.get_u8();
                 let start = (fragmentation_unit_header & 0x80) > 0;
                 let end = (fragmentation_unit_header & 0x40) > 0;
+                // FU-B is the first fragment of a NAL unit in interleaved mode.
+                let decoding_order_number = if nal_unit_type == 29 {
+                    Some(get_decoding_order_number(&mut payload)?)
+                } else {
+                    None
+                };
+
+                if self.discarding_fragmented_unit {
+                    if !start {
+                        // Remainder of fragmented unit that was hit by packet loss.
+                        return Ok(Vec::new());
+                    }
+                    self.discarding_fragmented_unit = false;
+                }
 
                 let recovered_nal_unit_payload = {
                     if start && !end {
@@ -473,6 +875,7 @@ impl H264Depacketizer {
                         let mut fragmented_unit_buffer = BytesMut::new();
                         fragmented_unit_buffer.put(payload);
                         self.fragmented_unit_buffer = Some(fragmented_unit_buffer);
+                        self.fragmented_unit_decoding_order_number = decoding_order_number;
                         None
                     } else if !start && !end {
                         if let Some(fragmented_unit_buffer) = self.fragmented_unit_buffer.as_mut() {
@@ -485,35 +888,42 @@ impl H264Depacketizer {
                         if let Some(mut fragmented_unit_buffer) = self.fragmented_unit_buffer.take()
                         {
                             fragmented_unit_buffer.put(payload);
-                            Some(fragmented_unit_buffer.freeze())
+                            Some((
+                                fragmented_unit_buffer.freeze(),
+                                self.fragmented_unit_decoding_order_number.take(),
+                            ))
                         } else {
                             return Err(Error::H264FragmentedStateNeverStarted);
                         }
                     } else {
                         // FU-A with start AND end bit set is just one unit (maybe it is illegal).
-                        Some(payload)
+                        Some((payload, decoding_order_number))
                     }
                 };
 
-                if let Some(recovered_nal_unit_payload) = recovered_nal_unit_payload {
-                    let nal_ref_idc = nal_unit_type & 0x60; // Copy original ref idc.
+                if let Some((recovered_nal_unit_payload, decoding_order_number)) =
+                    recovered_nal_unit_payload
+                {
+                    let nal_ref_idc = packet.payload[0] & 0x60; // Copy original ref idc.
                     let nal_unit_type = fragmentation_unit_header & 0x1f;
                     let nal_unit_type = nal_unit_type | nal_ref_idc; // Recover original NALU type.
                     let mut nal_unit = BytesMut::new();
                     nal_unit.put_u8(nal_unit_type);
                     nal_unit.put(recovered_nal_unit_payload);
-                    Ok(vec![Unit::new(nal_unit.freeze())])
+                    let nal_unit = nal_unit.freeze();
+                    match decoding_order_number {
+                        Some(decoding_order_number) => Ok(self
+                            .deinterleaver
+                            .push(decoding_order_number, nal_unit)
+                            .into_iter()
+                            .map(Unit::new)
+                            .collect()),
+                        None => Ok(vec![Unit::new(nal_unit)]),
+                    }
                 } else {
                     Ok(Vec::new())
                 }
             }
-            // FU-B
-            29 => {
-                // FU-B only supported in packetization mode 2 (not supported here).
-                Err(Error::H264DepacketizationNalUnitTypeUnsupported {
-                    nal_unit_type_name: "FU-B".to_string(),
-                })
-            }
             // reserved
             30..=31 => {
                 // RFC dictates that these must be ignored.
@@ -531,6 +941,194 @@ impl Default for H264Depacketizer {
     }
 }
 
+#[inline]
+fn is_vcl_nal_unit(nal_unit: &[u8]) -> bool {
+    nal_unit
+        .first()
+        .is_some_and(|nal_unit_header| matches!(nal_unit_header & 0x1f, 1..=5))
+}
+
+/// Read 16-bit decoding order number from STAP-B, MTAP or FU-B payload.
+fn get_decoding_order_number(payload: &mut Bytes) -> Result<u16> {
+    if payload.remaining() < 2 {
+        return Err(Error::H264DecodingOrderNumberMissing {
+            len: payload.remaining(),
+        });
+    }
+    Ok(payload.get_u16())
+}
+
+/// Read NAL unit from aggregation packet, skipping any fields between NAL unit size and NAL unit.
+fn get_aggregation_unit(payload: &mut Bytes, skip: usize) -> Result<Bytes> {
+    if payload.remaining() < 2 + skip {
+        return Err(Error::H264AggregationUnitHeaderInvalid {
+            len: payload.remaining(),
+        });
+    }
+    let nal_unit_length = payload.get_u16() as usize;
+    payload.advance(skip);
+    if payload.remaining() < nal_unit_length {
+        return Err(Error::H264AggregationUnitDataTooSmall {
+            have: payload.remaining(),
+            need: nal_unit_length,
+        });
+    }
+    Ok(payload.split_to(nal_unit_length))
+}
+
+/// RTP H264 access unit assembler.
+///
+/// Depacketizes RTP packets (see [`H264Depacketizer`]) and groups the resulting NAL units into
+/// access units. An access unit ends when a packet with the marker bit set is received, when the
+/// timestamp changes, or when a NAL unit arrives that can only be the first of a new access unit
+/// (access unit delimiter, SPS, PPS, SEI, or a slice with `first_mb_in_slice` equal to zero after
+/// a slice was already seen).
+///
+/// Packets must be passed in sequence number order. Access units that were affected by packet loss
+/// are flagged as corrupted.
+///
+/// Interleaved mode is not supported, since NAL units are then not released together with the
+/// packets that carried them.
+#[derive(Debug)]
+pub struct H264AccessUnitAssembler {
+    depacketizer: H264Depacketizer,
+    access_unit: Option<AccessUnit<H264>>,
+    /// Whether the current access unit contains a slice.
+    has_slice: bool,
+    last_sequence_number: Option<u16>,
+    /// Set when loss was detected before the current access unit was started.
+    lost: bool,
+}
+
+impl H264AccessUnitAssembler {
+    /// Create a new access unit assembler.
+    pub fn new() -> Self {
+        Self {
+            depacketizer: H264Depacketizer::new(),
+            access_unit: None,
+            has_slice: false,
+            last_sequence_number: None,
+            lost: false,
+        }
+    }
+
+    /// Depacketize RTP packet and assemble access units.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more completed access units.
+    ///
+    /// If the packet cannot be depacketized, the error is returned and the access unit that is
+    /// being assembled is flagged as corrupted.
+    pub fn assemble(&mut self, packet: &Packet) -> Result<Vec<AccessUnit<H264>>> {
+        let sequence_number = packet.header.sequence_number;
+        if self
+            .last_sequence_number
+            .is_some_and(|last| sequence_number != last.wrapping_add(1))
+        {
+            self.lost();
+        }
+        self.last_sequence_number = Some(sequence_number);
+
+        let timestamp = packet.header.timestamp;
+        let mut access_units = Vec::new();
+        if self
+            .access_unit
+            .as_ref()
+            .is_some_and(|access_unit| access_unit.timestamp != timestamp)
+        {
+            access_units.extend(self.finish());
+        }
+
+        let nal_units = match self.depacketizer.depacketize(packet) {
+            Ok(nal_units) => nal_units,
+            Err(err) => {
+                self.current(timestamp).corrupted = true;
+                return Err(err);
+            }
+        };
+
+        for nal_unit in nal_units {
+            let Some(&header) = nal_unit.data.first() else {
+                continue;
+            };
+            let nal_unit_type = header & 0x1f;
+            let is_slice = matches!(nal_unit_type, 1 | 2 | 5);
+            let starts_access_unit = match nal_unit_type {
+                // first_mb_in_slice is the first field of the slice header, and is encoded as
+                // Exp-Golomb, so a value of zero is encoded as a single 1 bit
+                1 | 2 | 5 => nal_unit.data.get(1).is_some_and(|byte| byte & 0x80 > 0),
+                6..=9 | 14..=18 => true,
+                _ => false,
+            };
+            if starts_access_unit && self.has_slice {
+                access_units.extend(self.finish());
+            }
+
+            let access_unit = self.current(timestamp);
+            access_unit.keyframe |= nal_unit_type == 5;
+            access_unit.units.push(nal_unit);
+            self.has_slice |= is_slice;
+        }
+
+        if packet.header.marker {
+            access_units.extend(self.finish());
+        }
+
+        Ok(access_units)
+    }
+
+    /// Signal that packets were lost.
+    ///
+    /// Gaps in sequence numbers are detected automatically. Call this when loss is known by other
+    /// means, for example when the [`JitterBuffer`](crate::jitter_buffer::JitterBuffer) reports it.
+    pub fn lost(&mut self) {
+        self.depacketizer.lost();
+        match self.access_unit.as_mut() {
+            Some(access_unit) => access_unit.corrupted = true,
+            // lost packets may have been the start of the next access unit
+            None => self.lost = true,
+        }
+    }
+
+    /// Take access unit that is being assembled, if any.
+    ///
+    /// Use this at the end of the stream, since the last access unit may not be completed by a
+    /// packet with the marker bit set.
+    pub fn flush(&mut self) -> Option<AccessUnit<H264>> {
+        self.finish()
+    }
+
+    fn current(&mut self, timestamp: u32) -> &mut AccessUnit<H264> {
+        let lost = std::mem::take(&mut self.lost);
+        self.access_unit
+            .get_or_insert_with(|| AccessUnit::new(timestamp, lost))
+    }
+
+    fn finish(&mut self) -> Option<AccessUnit<H264>> {
+        self.has_slice = false;
+        let access_unit = self.access_unit.take()?;
+        if access_unit.units.is_empty() {
+            // nothing to emit, but the next access unit may still be affected
+            self.lost |= access_unit.corrupted;
+            None
+        } else {
+            Some(access_unit)
+        }
+    }
+}
+
+impl Default for H264AccessUnitAssembler {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 /// H264 packetization mode.
 ///
 /// The following table (from RFC 6184) specifies which payload types are supported per
@@ -589,3 +1187,326 @@ impl std::fmt::Display for H264PacketizationMode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::{Header, Version};
+    use crate::serialize::Serialize;
+
+    fn packet(sequence_number: u16, payload: &'static [u8]) -> Packet {
+        Packet::new(
+            Header {
+                version: Version::Version2,
+                padding: false,
+                marker: false,
+                payload_type: 96,
+                sequence_number,
+                timestamp: 0,
+                ssrc: 1,
+                csrc: Vec::new(),
+                extension: None,
+            },
+            Bytes::from_static(payload),
+        )
+    }
+
+    fn depacketize(depacketizer: &mut H264Depacketizer, packet: &Packet) -> Vec<Bytes> {
+        depacketizer
+            .depacketize(packet)
+            .unwrap()
+            .into_iter()
+            .map(Unit::into_data)
+            .collect()
+    }
+
+    #[test]
+    fn reassemble_fragmented_unit() {
+        let mut depacketizer = H264Depacketizer::new();
+        assert!(depacketize(&mut depacketizer, &packet(1, &[0x7c, 0x85, 1, 2])).is_empty());
+        assert!(depacketize(&mut depacketizer, &packet(2, &[0x7c, 0x05, 3])).is_empty());
+        assert_eq!(
+            depacketize(&mut depacketizer, &packet(3, &[0x7c, 0x45, 4])),
+            vec![Bytes::from_static(&[0x65, 1, 2, 3, 4])],
+        );
+    }
+
+    #[test]
+    fn discard_fragmented_unit_with_gap() {
+        let mut depacketizer = H264Depacketizer::new();
+        assert!(depacketize(&mut depacketizer, &packet(1, &[0x7c, 0x85, 1, 2])).is_empty());
+        // packet 2 lost
+        assert!(depacketize(&mut depacketizer, &packet(3, &[0x7c, 0x05, 4])).is_empty());
+        assert!(depacketize(&mut depacketizer, &packet(4, &[0x7c, 0x45, 5])).is_empty());
+        // next unit is not affected
+        assert_eq!(
+            depacketize(&mut depacketizer, &packet(5, &[0x41, 6])),
+            vec![Bytes::from_static(&[0x41, 6])],
+        );
+        assert!(depacketize(&mut depacketizer, &packet(6, &[0x7c, 0x81, 7])).is_empty());
+        assert_eq!(
+            depacketize(&mut depacketizer, &packet(7, &[0x7c, 0x41, 8])),
+            vec![Bytes::from_static(&[0x61, 7, 8])],
+        );
+    }
+
+    #[test]
+    fn discard_fragmented_unit_when_lost() {
+        let mut depacketizer = H264Depacketizer::new();
+        assert!(depacketize(&mut depacketizer, &packet(1, &[0x7c, 0x85, 1, 2])).is_empty());
+        depacketizer.lost();
+        assert!(depacketize(&mut depacketizer, &packet(2, &[0x7c, 0x45, 3])).is_empty());
+    }
+
+    fn access_unit_packet(
+        sequence_number: u16,
+        timestamp: u32,
+        marker: bool,
+        payload: &'static [u8],
+    ) -> Packet {
+        let mut packet = packet(sequence_number, payload);
+        packet.header.timestamp = timestamp;
+        packet.header.marker = marker;
+        packet
+    }
+
+    fn assemble(
+        assembler: &mut H264AccessUnitAssembler,
+        packet: &Packet,
+    ) -> Vec<(u32, Vec<Bytes>, bool, bool)> {
+        assembler
+            .assemble(packet)
+            .unwrap()
+            .into_iter()
+            .map(|access_unit| {
+                (
+                    access_unit.timestamp,
+                    access_unit.units.into_iter().map(Unit::into_data).collect(),
+                    access_unit.keyframe,
+                    access_unit.corrupted,
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn assemble_access_unit_with_marker() {
+        let mut assembler = H264AccessUnitAssembler::new();
+        // SPS, PPS and IDR slice in two packets
+        let stap_a = &[0x18, 0x00, 0x02, 0x67, 0x42, 0x00, 0x02, 0x68, 0xce];
+        assert!(assemble(&mut assembler, &access_unit_packet(1, 3000, false, stap_a)).is_empty());
+        assert_eq!(
+            assemble(
+                &mut assembler,
+                &access_unit_packet(2, 3000, true, &[0x65, 0x88, 0x01])
+            ),
+            vec![(
+                3000,
+                vec![
+                    Bytes::from_static(&[0x67, 0x42]),
+                    Bytes::from_static(&[0x68, 0xce]),
+                    Bytes::from_static(&[0x65, 0x88, 0x01]),
+                ],
+                true,
+                false,
+            )],
+        );
+    }
+
+    #[test]
+    fn assemble_access_unit_without_marker() {
+        let mut assembler = H264AccessUnitAssembler::new();
+        assert!(assemble(
+            &mut assembler,
+            &access_unit_packet(1, 0, false, &[0x41, 0x9a])
+        )
+        .is_empty());
+        // second slice of same picture
+        assert!(assemble(
+            &mut assembler,
+            &access_unit_packet(2, 0, false, &[0x41, 0x21])
+        )
+        .is_empty());
+        // timestamp changes
+        assert_eq!(
+            assemble(
+                &mut assembler,
+                &access_unit_packet(3, 3000, false, &[0x41, 0x9a])
+            ),
+            vec![(
+                0,
+                vec![
+                    Bytes::from_static(&[0x41, 0x9a]),
+                    Bytes::from_static(&[0x41, 0x21]),
+                ],
+                false,
+                false,
+            )],
+        );
+        // access unit delimiter with the same timestamp
+        assert_eq!(
+            assemble(
+                &mut assembler,
+                &access_unit_packet(4, 3000, false, &[0x09, 0x10])
+            )
+            .len(),
+            1,
+        );
+        // first slice of next picture with the same timestamp
+        assert!(assemble(
+            &mut assembler,
+            &access_unit_packet(5, 3000, false, &[0x41, 0x9a])
+        )
+        .is_empty());
+        assert_eq!(
+            assembler.flush().map(|access_unit| access_unit.units.len()),
+            Some(2),
+        );
+    }
+
+    #[test]
+    fn flag_corrupted_access_unit() {
+        let mut assembler = H264AccessUnitAssembler::new();
+        assert!(assemble(
+            &mut assembler,
+            &access_unit_packet(1, 0, false, &[0x7c, 0x85, 1])
+        )
+        .is_empty());
+        // packet 2 (end of fragmented IDR slice) lost, next slice in packet 3
+        let access_units = assemble(
+            &mut assembler,
+            &access_unit_packet(3, 0, true, &[0x65, 0x08]),
+        );
+        assert_eq!(access_units.len(), 1);
+        assert!(access_units[0].3);
+        // next access unit is fine
+        let access_units = assemble(
+            &mut assembler,
+            &access_unit_packet(4, 3000, true, &[0x41, 0x9a]),
+        );
+        assert!(!access_units[0].3);
+        // loss between access units affects the next one
+        let access_units = assemble(
+            &mut assembler,
+            &access_unit_packet(6, 6000, true, &[0x41, 0x9a]),
+        );
+        assert!(access_units[0].3);
+    }
+
+    fn params(mtu: Option<usize>) -> PacketizationParameters {
+        PacketizationParameters {
+            payload_type: 96,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu,
+        }
+    }
+
+    #[test]
+    fn non_interleaved_mode_rejects_mtu_without_room_for_header_extensions() {
+        let mut packetizer = H264Packetizer::from_packetization_mode(
+            H264PacketizationMode::NonInterleavedMode,
+            params(Some(16)),
+        )
+        .unwrap()
+        .with_header_extensions(HeaderExtensionParameters {
+            transport_sequence_number: Some(1),
+            ..Default::default()
+        })
+        .unwrap();
+        let result = packetizer.packetize(vec![Unit::new(Bytes::from_static(&[0x65; 20]))], 0);
+        assert!(matches!(
+            result,
+            Err(Error::MtuTooSmall { mtu: 16, need: 23 })
+        ));
+    }
+
+    #[test]
+    fn interleaved_mode_round_trip() {
+        let nal_units = [
+            Bytes::from_static(&[0x67, 0x42, 0x00, 0x1f]),
+            Bytes::from_static(&[0x68, 0xce, 0x38, 0x80]),
+            Bytes::from(
+                std::iter::once(0x65)
+                    .chain((0..100).map(|i| i as u8))
+                    .collect::<Vec<_>>(),
+            ),
+            Bytes::from_static(&[0x06, 0x05, 0x01]),
+        ];
+        let mut packetizer = H264Packetizer::from_packetization_mode(
+            H264PacketizationMode::InterleavedMode,
+            params(Some(48)),
+        )
+        .unwrap();
+        let packets = packetizer
+            .packetize(nal_units.iter().cloned().map(Unit::new).collect(), 3000)
+            .unwrap();
+        // STAP-B, FU-B, two FU-A, STAP-B
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| packet.payload[0] & 0x1f)
+                .collect::<Vec<_>>(),
+            vec![25, 29, 28, 28, 25],
+        );
+        assert!(packets.iter().all(|packet| packet.serialized_len() <= 48));
+        assert!(packets.last().unwrap().header.marker);
+
+        let mut depacketizer = H264Depacketizer::new();
+        let mut depacketized = Vec::new();
+        for packet in &packets {
+            depacketized.extend(depacketize(&mut depacketizer, packet));
+        }
+        depacketized.extend(depacketizer.flush().into_iter().map(Unit::into_data));
+        assert_eq!(depacketized, nal_units);
+    }
+
+    #[test]
+    fn deinterleave_in_decoding_order() {
+        let mut depacketizer = H264Depacketizer::new().with_interleaving_depth(1);
+        // STAP-B with DON 65535 (slice) and DON 0 (slice), sent after DON 1 (slice)
+        assert!(depacketize(
+            &mut depacketizer,
+            &packet(1, &[0x19, 0x00, 0x01, 0x00, 0x02, 0x41, 0x03])
+        )
+        .is_empty());
+        assert_eq!(
+            depacketize(
+                &mut depacketizer,
+                &packet(
+                    2,
+                    &[0x19, 0xff, 0xff, 0x00, 0x02, 0x41, 0x01, 0x00, 0x02, 0x41, 0x02]
+                )
+            ),
+            vec![
+                Bytes::from_static(&[0x41, 0x01]),
+                Bytes::from_static(&[0x41, 0x02]),
+            ],
+        );
+        // MTAP16 with DON base 2 and DOND 1 (DON 3) and DOND 0 (DON 2)
+        assert_eq!(
+            depacketize(
+                &mut depacketizer,
+                &packet(
+                    3,
+                    &[
+                        0x1a, 0x00, 0x02, 0x00, 0x02, 0x01, 0x00, 0x00, 0x41, 0x05, 0x00, 0x02,
+                        0x00, 0x00, 0x00, 0x41, 0x04
+                    ]
+                )
+            ),
+            vec![
+                Bytes::from_static(&[0x41, 0x03]),
+                Bytes::from_static(&[0x41, 0x04]),
+            ],
+        );
+        assert_eq!(
+            depacketizer
+                .flush()
+                .into_iter()
+                .map(Unit::into_data)
+                .collect::<Vec<_>>(),
+            vec![Bytes::from_static(&[0x41, 0x05])],
+        );
+    }
+}
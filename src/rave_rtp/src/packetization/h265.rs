@@ -0,0 +1,781 @@
+use crate::error::Error;
+use crate::header_extension::HeaderExtensionParameters;
+use crate::packet::Packet;
+use crate::packetization::common::{Deinterleaver, PacketizationParameters, Packetizer};
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use rave_types::codec::H265;
+use rave_types::unit::Unit;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Aggregation packet (AP) NAL unit type.
+const NAL_UNIT_TYPE_AGGREGATION_PACKET: u8 = 48;
+/// Fragmentation unit (FU) NAL unit type.
+const NAL_UNIT_TYPE_FRAGMENTATION_UNIT: u8 = 49;
+/// Payload content information (PACI) NAL unit type.
+const NAL_UNIT_TYPE_PAYLOAD_CONTENT_INFORMATION: u8 = 50;
+
+/// RTP H265 packetizer.
+///
+/// NAL units that fit within the MTU together are combined into aggregation packets (AP). NAL units
+/// that do not fit within the MTU by themselves are fragmented over multiple fragmentation units
+/// (FU).
+#[derive(Debug)]
+pub struct H265Packetizer {
+    inner: Packetizer,
+    mtu: Option<usize>,
+    decoding_order_number: Option<u16>,
+}
+
+impl H265Packetizer {
+    /// Create a new packetizer to create RTP packets from H265 encoded packets.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - RTP Packetization parameters to use for constructing packets.
+    pub fn new(params: PacketizationParameters) -> Self {
+        let mtu = params.mtu;
+        Self {
+            inner: Packetizer::from_packetization_parameters(params),
+            mtu,
+            decoding_order_number: None,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    /// Include decoding order number (DONL and DOND fields) in packets.
+    ///
+    /// The fields must be present when the stream is signaled with a `sprop-max-don-diff` greater
+    /// than zero. NAL units are always transmitted in decoding order.
+    pub fn with_decoding_order_number(mut self) -> Self {
+        self.decoding_order_number = Some(0);
+        self
+    }
+
+    /// Packetize one or more H265 encoded packets.
+    ///
+    /// # Access unit
+    ///
+    /// The caller must call this function exactly once per "access unit" (once per encoded
+    /// picture).
+    ///
+    /// # Fragmentation
+    ///
+    /// Any data that exceed the MTU will be fragmented over multiple packets.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - One or more H265 packets.
+    /// * `timestamp` - Presentation timestamp of NAL units.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more RTP packets.
+    pub fn packetize(&mut self, data: Vec<Unit<H265>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let mtu = self.mtu.unwrap_or(usize::MAX);
+        let header_len = self.inner.header_serialized_len();
+        let donl_len = self.donl_len();
+
+        let mut payloads = Vec::new();
+        let mut group: Vec<(Bytes, u16)> = Vec::new();
+        for nal_unit in data.into_iter().map(Unit::into_data) {
+            if nal_unit.len() < 3 {
+                return Err(Error::H265NalUnitLengthTooSmall {
+                    len: nal_unit.len(),
+                });
+            }
+            let decoding_order_number = self.next_decoding_order_number();
+            if header_len + donl_len + nal_unit.len() > mtu {
+                // Does not fit in a single packet by itself, so fragment it.
+                if !group.is_empty() {
+                    payloads.push(self.payload_group(std::mem::take(&mut group))?);
+                }
+                payloads.extend(self.payload_fragmentation_unit(
+                    nal_unit,
+                    decoding_order_number,
+                    mtu,
+                ));
+                continue;
+            }
+            if !group.is_empty()
+                && header_len
+                    + self.aggregation_packet_len(&group)
+                    + self.dond_len()
+                    + 2
+                    + nal_unit.len()
+                    > mtu
+            {
+                payloads.push(self.payload_group(std::mem::take(&mut group))?);
+            }
+            group.push((nal_unit, decoding_order_number));
+        }
+        if !group.is_empty() {
+            payloads.push(self.payload_group(group)?);
+        }
+
+        let num_payloads = payloads.len();
+        payloads
+            .into_iter()
+            .enumerate()
+            .map(|(i, payload)| {
+                // Last packet of access unit -> set marker bit.
+                self.inner
+                    .packetize(payload, timestamp, i == num_payloads - 1)
+            })
+            .collect()
+    }
+
+    /// Create single NAL unit packet payload if the group holds one NAL unit, or aggregation
+    /// packet payload otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `group` - NAL units with their decoding order numbers.
+    ///
+    /// # Return value
+    ///
+    /// Single NAL unit or AP payload.
+    fn payload_group(&self, mut group: Vec<(Bytes, u16)>) -> Result<Bytes> {
+        if group.len() == 1 {
+            let (nal_unit, decoding_order_number) = group.remove(0);
+            if self.decoding_order_number.is_none() {
+                return Ok(nal_unit);
+            }
+            // DONL goes between NAL unit header and NAL unit payload.
+            let mut payload = BytesMut::with_capacity(2 + nal_unit.len());
+            payload.put_slice(&nal_unit[..2]);
+            payload.put_u16(decoding_order_number);
+            payload.put_slice(&nal_unit[2..]);
+            return Ok(payload.freeze());
+        }
+
+        let forbidden = group.iter().any(|(nal_unit, _)| nal_unit[0] & 0x80 > 0);
+        let (layer_id, temporal_id) = group
+            .iter()
+            .map(|(nal_unit, _)| (layer_id(nal_unit), nal_unit[1] & 0x07))
+            .fold(
+                (0x3f, 0x07),
+                |(layer_id, temporal_id), (other_layer_id, other_temporal_id)| {
+                    (
+                        layer_id.min(other_layer_id),
+                        temporal_id.min(other_temporal_id),
+                    )
+                },
+            );
+
+        let mut payload = BytesMut::with_capacity(self.aggregation_packet_len(&group));
+        payload.put_u8(
+            if forbidden { 0x80 } else { 0x00 }
+                | (NAL_UNIT_TYPE_AGGREGATION_PACKET << 1)
+                | (layer_id >> 5),
+        );
+        payload.put_u8((layer_id << 3) | temporal_id);
+        let mut previous_decoding_order_number = None;
+        for (nal_unit, decoding_order_number) in group {
+            if self.decoding_order_number.is_some() {
+                match previous_decoding_order_number {
+                    // DOND is the difference with the previous DON minus one.
+                    Some(previous) => payload
+                        .put_u8(decoding_order_number.wrapping_sub(previous).wrapping_sub(1) as u8),
+                    None => payload.put_u16(decoding_order_number),
+                }
+                previous_decoding_order_number = Some(decoding_order_number);
+            }
+            payload.put_u16(nal_unit.len().try_into().map_err(|_| {
+                Error::H265NalUnitDataLengthInvalid {
+                    len: nal_unit.len(),
+                }
+            })?);
+            payload.put(nal_unit);
+        }
+        Ok(payload.freeze())
+    }
+
+    /// Fragment one NAL unit over multiple FU NAL units.
+    ///
+    /// # Arguments
+    ///
+    /// * `nal_unit` - NAL unit to fragment.
+    /// * `decoding_order_number` - Decoding order number of NAL unit.
+    /// * `mtu` - Maximum transmission unit size to satisfy.
+    ///
+    /// # Return value
+    ///
+    /// Fragmented NAL units (FU).
+    fn payload_fragmentation_unit(
+        &self,
+        mut nal_unit: Bytes,
+        decoding_order_number: u16,
+        mtu: usize,
+    ) -> Vec<Bytes> {
+        let header_len = self.inner.header_serialized_len();
+        let donl_len = self.donl_len();
+        let nal_unit_header = [nal_unit.get_u8(), nal_unit.get_u8()]; // Strip header.
+        let nal_unit_type = nal_unit_type(&nal_unit_header);
+
+        // If the MTU is too small to fit any payload, packetizing will fail on the MTU check.
+        let first_payload_max_len = mtu.saturating_sub(header_len + 3 + donl_len).max(1);
+        let payload_max_len = mtu.saturating_sub(header_len + 3).max(1);
+        let first = nal_unit.split_to(first_payload_max_len.min(nal_unit.len()));
+        let rest = nal_unit
+            .chunks(payload_max_len)
+            .map(Bytes::copy_from_slice)
+            .collect::<Vec<_>>();
+        let num_fragments = 1 + rest.len();
+
+        std::iter::once(first)
+            .chain(rest)
+            .enumerate()
+            .map(|(i, fu_payload)| {
+                let mut fragmented_nal_unit =
+                    BytesMut::with_capacity(3 + donl_len + fu_payload.len());
+                // Payload header is the NAL unit header with the type replaced.
+                fragmented_nal_unit
+                    .put_u8((nal_unit_header[0] & 0x81) | (NAL_UNIT_TYPE_FRAGMENTATION_UNIT << 1));
+                fragmented_nal_unit.put_u8(nal_unit_header[1]);
+                let mut fragmentation_unit_header = nal_unit_type;
+                if i == 0 {
+                    fragmentation_unit_header |= 0x80; // Set start bit.
+                }
+                if i == num_fragments - 1 {
+                    fragmentation_unit_header |= 0x40; // Set end bit.
+                }
+                fragmented_nal_unit.put_u8(fragmentation_unit_header);
+                if i == 0 && self.decoding_order_number.is_some() {
+                    fragmented_nal_unit.put_u16(decoding_order_number);
+                }
+                fragmented_nal_unit.put(fu_payload);
+                fragmented_nal_unit.freeze()
+            })
+            .collect()
+    }
+
+    /// Size of aggregation packet payload holding the given NAL units.
+    fn aggregation_packet_len(&self, group: &[(Bytes, u16)]) -> usize {
+        // Payload header, then DONL for first NAL unit and DOND for the others.
+        2 + group
+            .iter()
+            .enumerate()
+            .map(|(i, (nal_unit, _))| {
+                let decoding_order_number_len = if i == 0 {
+                    self.donl_len()
+                } else {
+                    self.dond_len()
+                };
+                decoding_order_number_len + 2 + nal_unit.len()
+            })
+            .sum::<usize>()
+    }
+
+    #[inline]
+    fn donl_len(&self) -> usize {
+        if self.decoding_order_number.is_some() {
+            2
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    fn dond_len(&self) -> usize {
+        if self.decoding_order_number.is_some() {
+            1
+        } else {
+            0
+        }
+    }
+
+    #[inline]
+    fn next_decoding_order_number(&mut self) -> u16 {
+        match self.decoding_order_number.as_mut() {
+            Some(decoding_order_number) => {
+                let next = *decoding_order_number;
+                *decoding_order_number = decoding_order_number.wrapping_add(1);
+                next
+            }
+            None => 0,
+        }
+    }
+}
+
+/// RTP H265 depacketizer.
+///
+/// Packets must be passed in sequence number order (see
+/// [`JitterBuffer`](crate::jitter_buffer::JitterBuffer)). When packets are missing, the fragmented
+/// unit that was being reassembled is discarded, as well as any remaining fragments of it.
+///
+/// If the stream carries decoding order numbers (see
+/// [`H265Depacketizer::with_decoding_order_number`]), NAL units are put back in decoding order.
+#[derive(Debug)]
+pub struct H265Depacketizer {
+    fragmented_unit_buffer: Option<BytesMut>,
+    fragmented_unit_decoding_order_number: Option<u16>,
+    last_sequence_number: Option<u16>,
+    discarding_fragmented_unit: bool,
+    deinterleaver: Option<Deinterleaver>,
+}
+
+impl H265Depacketizer {
+    /// Create a new depacketizer to extract H265 packets from RTP packet stream.
+    pub fn new() -> Self {
+        Self {
+            fragmented_unit_buffer: None,
+            fragmented_unit_decoding_order_number: None,
+            last_sequence_number: None,
+            discarding_fragmented_unit: false,
+            deinterleaver: None,
+        }
+    }
+
+    /// Expect decoding order number (DONL and DOND fields) in packets, and use them to put NAL
+    /// units back in decoding order.
+    ///
+    /// The fields are present when the stream is signaled with a `sprop-max-don-diff` greater
+    /// than zero.
+    ///
+    /// # Arguments
+    ///
+    /// * `depacketization_buffer_nal_units` - Value of `sprop-depack-buf-nalus`, the maximum
+    ///   number of NAL units that precede any NAL unit in transmission order and follow it in
+    ///   decoding order.
+    pub fn with_decoding_order_number(mut self, depacketization_buffer_nal_units: usize) -> Self {
+        let mut deinterleaver = Deinterleaver::new(|_| true);
+        deinterleaver.depth = depacketization_buffer_nal_units;
+        self.deinterleaver = Some(deinterleaver);
+        self
+    }
+
+    /// Set depacketization buffer size when decoding order numbers are used.
+    ///
+    /// When the buffer holds more than this number of bytes, NAL units are released even if the
+    /// number of NAL units set with [`H265Depacketizer::with_decoding_order_number`] has not been
+    /// reached. By default, the size is not limited.
+    ///
+    /// # Arguments
+    ///
+    /// * `depacketization_buffer_size` - Value of `sprop-depack-buf-bytes`.
+    pub fn with_depacketization_buffer_size(mut self, depacketization_buffer_size: usize) -> Self {
+        if let Some(deinterleaver) = self.deinterleaver.as_mut() {
+            deinterleaver.max_size = Some(depacketization_buffer_size);
+        }
+        self
+    }
+
+    /// Discard fragmented unit that is being reassembled, if any.
+    ///
+    /// Call this when packets are known to be lost. Remaining fragments of the unit are ignored.
+    /// Gaps in sequence numbers are detected automatically, so this is only required when the
+    /// sequence numbers cannot be relied upon.
+    pub fn lost(&mut self) {
+        self.fragmented_unit_decoding_order_number = None;
+        if self.fragmented_unit_buffer.take().is_some() {
+            self.discarding_fragmented_unit = true;
+        }
+    }
+
+    /// Take all NAL units remaining in the depacketization buffer, in decoding order.
+    ///
+    /// Use this at the end of the stream.
+    pub fn flush(&mut self) -> Vec<Unit<H265>> {
+        self.deinterleaver
+            .as_mut()
+            .map(|deinterleaver| deinterleaver.flush().into_iter().map(Unit::new).collect())
+            .unwrap_or_default()
+    }
+
+    /// Depacketize RTP packets and convert back to raw H265 NAL units that can be passed to a
+    /// decoder.
+    ///
+    /// This function will reconstruct fragmented NAL units, as well as split aggregation packets
+    /// back into separate H265 NAL units. Payload content information (PACI) packets are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more depacketized NAL units ready for decoding.
+    ///
+    /// No NAL units may be produced if the packet contains part of a fragmented unit. More packets
+    /// may be produced if the RTP packet payload is an aggregation packet.
+    pub fn depacketize(&mut self, packet: &Packet) -> Result<Vec<Unit<H265>>> {
+        if packet.payload.len() <= 2 {
+            return Err(Error::H265NalUnitLengthTooSmall {
+                len: packet.payload.len(),
+            });
+        }
+
+        let sequence_number = packet.header.sequence_number;
+        if self
+            .last_sequence_number
+            .replace(sequence_number)
+            .is_some_and(|last| sequence_number != last.wrapping_add(1))
+        {
+            self.lost();
+        }
+
+        let with_decoding_order_number = self.deinterleaver.is_some();
+        let nal_unit_type = nal_unit_type(&packet.payload);
+        if nal_unit_type != NAL_UNIT_TYPE_FRAGMENTATION_UNIT {
+            self.discarding_fragmented_unit = false;
+        }
+        match nal_unit_type {
+            // NAL
+            0..=47 => {
+                if with_decoding_order_number {
+                    let mut payload = packet.payload.clone();
+                    let nal_unit_header = payload.split_to(2);
+                    let decoding_order_number = get_decoding_order_number(&mut payload)?;
+                    let mut nal_unit = BytesMut::with_capacity(2 + payload.len());
+                    nal_unit.put(nal_unit_header);
+                    nal_unit.put(payload);
+                    Ok(self.output(Some(decoding_order_number), nal_unit.freeze()))
+                } else {
+                    // This is just a normal NAL unit and can be passed on to the decoder as is.
+                    Ok(vec![Unit::new(packet.payload.clone())])
+                }
+            }
+            // AP
+            NAL_UNIT_TYPE_AGGREGATION_PACKET => {
+                let mut payload = packet.payload.clone();
+                payload.advance(2); // Skip payload header (already peeked in nal_unit_type).
+
+                let mut nal_units = Vec::new();
+                let mut decoding_order_number: Option<u16> = None;
+                while !payload.is_empty() {
+                    if with_decoding_order_number {
+                        decoding_order_number = Some(match decoding_order_number {
+                            Some(previous) => {
+                                if payload.remaining() < 1 {
+                                    return Err(Error::H265DecodingOrderNumberMissing {
+                                        len: payload.remaining(),
+                                    });
+                                }
+                                // DOND is the difference with the previous DON minus one.
+                                previous.wrapping_add(payload.get_u8() as u16 + 1)
+                            }
+                            None => get_decoding_order_number(&mut payload)?,
+                        });
+                    }
+                    if payload.remaining() < 2 {
+                        return Err(Error::H265AggregationUnitHeaderInvalid {
+                            len: payload.remaining(),
+                        });
+                    }
+                    let nal_unit_length = payload.get_u16() as usize;
+                    if payload.remaining() < nal_unit_length {
+                        return Err(Error::H265AggregationUnitDataTooSmall {
+                            have: payload.remaining(),
+                            need: nal_unit_length,
+                        });
+                    }
+                    let nal_unit = payload.split_to(nal_unit_length);
+                    nal_units.extend(self.output(decoding_order_number, nal_unit));
+                }
+                Ok(nal_units)
+            }
+            // FU
+            NAL_UNIT_TYPE_FRAGMENTATION_UNIT => {
+                let mut payload = packet.payload.clone();
+                let payload_header = [payload.get_u8(), payload.get_u8()];
+
+                if payload.remaining() < 1 {
+                    return Err(Error::H265FragmentationUnitHeaderInvalid { len: payload.len() });
+                }
+
+                let fragmentation_unit_header = payload.get_u8();
+                let start = (fragmentation_unit_header & 0x80) > 0;
+                let end = (fragmentation_unit_header & 0x40) > 0;
+                // DONL is only present in the first fragment.
+                let decoding_order_number = if start && with_decoding_order_number {
+                    Some(get_decoding_order_number(&mut payload)?)
+                } else {
+                    None
+                };
+
+                if self.discarding_fragmented_unit {
+                    if !start {
+                        // Remainder of fragmented unit that was hit by packet loss.
+                        return Ok(Vec::new());
+                    }
+                    self.discarding_fragmented_unit = false;
+                }
+
+                let recovered_nal_unit_payload = {
+                    if start && !end {
+                        if self.fragmented_unit_buffer.is_some() {
+                            return Err(Error::H265FragmentedStateAlreadyStarted);
+                        }
+                        let mut fragmented_unit_buffer = BytesMut::new();
+                        fragmented_unit_buffer.put(payload);
+                        self.fragmented_unit_buffer = Some(fragmented_unit_buffer);
+                        self.fragmented_unit_decoding_order_number = decoding_order_number;
+                        None
+                    } else if !start && !end {
+                        if let Some(fragmented_unit_buffer) = self.fragmented_unit_buffer.as_mut() {
+                            fragmented_unit_buffer.put(payload);
+                        } else {
+                            return Err(Error::H265FragmentedStateNeverStarted);
+                        }
+                        None
+                    } else if !start && end {
+                        if let Some(mut fragmented_unit_buffer) = self.fragmented_unit_buffer.take()
+                        {
+                            fragmented_unit_buffer.put(payload);
+                            Some((
+                                fragmented_unit_buffer.freeze(),
+                                self.fragmented_unit_decoding_order_number.take(),
+                            ))
+                        } else {
+                            return Err(Error::H265FragmentedStateNeverStarted);
+                        }
+                    } else {
+                        // FU with start AND end bit set is illegal, but it is just one unit.
+                        Some((payload, decoding_order_number))
+                    }
+                };
+
+                if let Some((recovered_nal_unit_payload, decoding_order_number)) =
+                    recovered_nal_unit_payload
+                {
+                    // Recover original NAL unit header from payload header and FU type.
+                    let nal_unit_type = fragmentation_unit_header & 0x3f;
+                    let mut nal_unit =
+                        BytesMut::with_capacity(2 + recovered_nal_unit_payload.len());
+                    nal_unit.put_u8((payload_header[0] & 0x81) | (nal_unit_type << 1));
+                    nal_unit.put_u8(payload_header[1]);
+                    nal_unit.put(recovered_nal_unit_payload);
+                    Ok(self.output(decoding_order_number, nal_unit.freeze()))
+                } else {
+                    Ok(Vec::new())
+                }
+            }
+            // PACI and unspecified
+            NAL_UNIT_TYPE_PAYLOAD_CONTENT_INFORMATION..=63 => {
+                // RFC dictates that unspecified types must be ignored. PACI is not supported.
+                Ok(Vec::new())
+            }
+            _ => unreachable!("nal unit type is 6 bits"),
+        }
+    }
+
+    /// Pass NAL unit through depacketization buffer if decoding order numbers are used.
+    fn output(&mut self, decoding_order_number: Option<u16>, nal_unit: Bytes) -> Vec<Unit<H265>> {
+        match (self.deinterleaver.as_mut(), decoding_order_number) {
+            (Some(deinterleaver), Some(decoding_order_number)) => deinterleaver
+                .push(decoding_order_number, nal_unit)
+                .into_iter()
+                .map(Unit::new)
+                .collect(),
+            _ => vec![Unit::new(nal_unit)],
+        }
+    }
+}
+
+impl Default for H265Depacketizer {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[inline]
+fn nal_unit_type(nal_unit: &[u8]) -> u8 {
+    (nal_unit[0] >> 1) & 0x3f
+}
+
+#[inline]
+fn layer_id(nal_unit: &[u8]) -> u8 {
+    ((nal_unit[0] & 0x01) << 5) | (nal_unit[1] >> 3)
+}
+
+/// Read 16-bit decoding order number (DONL).
+fn get_decoding_order_number(payload: &mut Bytes) -> Result<u16> {
+    if payload.remaining() < 2 {
+        return Err(Error::H265DecodingOrderNumberMissing {
+            len: payload.remaining(),
+        });
+    }
+    Ok(payload.get_u16())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::{Header, Version};
+    use crate::serialize::Serialize;
+
+    fn params(mtu: Option<usize>) -> PacketizationParameters {
+        PacketizationParameters {
+            payload_type: 96,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu,
+        }
+    }
+
+    fn packet(sequence_number: u16, payload: &'static [u8]) -> Packet {
+        Packet::new(
+            Header {
+                version: Version::Version2,
+                padding: false,
+                marker: false,
+                payload_type: 96,
+                sequence_number,
+                timestamp: 0,
+                ssrc: 1,
+                csrc: Vec::new(),
+                extension: None,
+            },
+            Bytes::from_static(payload),
+        )
+    }
+
+    fn nal_units() -> Vec<Bytes> {
+        vec![
+            // VPS, SPS, PPS
+            Bytes::from_static(&[0x40, 0x01, 0x0c, 0x01]),
+            Bytes::from_static(&[0x42, 0x01, 0x01, 0x01]),
+            Bytes::from_static(&[0x44, 0x01, 0xc1, 0x72]),
+            // IDR_W_RADL
+            Bytes::from(
+                [0x26, 0x01]
+                    .into_iter()
+                    .chain((0..99).map(|i| i as u8))
+                    .collect::<Vec<_>>(),
+            ),
+        ]
+    }
+
+    fn round_trip(
+        mut packetizer: H265Packetizer,
+        mut depacketizer: H265Depacketizer,
+    ) -> (Vec<Packet>, Vec<Bytes>) {
+        let packets = packetizer
+            .packetize(nal_units().into_iter().map(Unit::new).collect(), 9000)
+            .unwrap();
+        let mut depacketized = Vec::new();
+        for packet in &packets {
+            depacketized.extend(
+                depacketizer
+                    .depacketize(packet)
+                    .unwrap()
+                    .into_iter()
+                    .map(Unit::into_data),
+            );
+        }
+        depacketized.extend(depacketizer.flush().into_iter().map(Unit::into_data));
+        (packets, depacketized)
+    }
+
+    fn payload_types(packets: &[Packet]) -> Vec<u8> {
+        packets
+            .iter()
+            .map(|packet| nal_unit_type(&packet.payload))
+            .collect()
+    }
+
+    #[test]
+    fn aggregate_without_mtu() {
+        let (packets, depacketized) =
+            round_trip(H265Packetizer::new(params(None)), H265Depacketizer::new());
+        assert_eq!(payload_types(&packets), vec![48]);
+        assert!(packets[0].header.marker);
+        assert_eq!(depacketized, nal_units());
+    }
+
+    #[test]
+    fn fragment_with_mtu() {
+        let (packets, depacketized) = round_trip(
+            H265Packetizer::new(params(Some(48))),
+            H265Depacketizer::new(),
+        );
+        assert_eq!(payload_types(&packets), vec![48, 49, 49, 49]);
+        assert!(packets.iter().all(|packet| packet.serialized_len() <= 48));
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| packet.header.marker)
+                .collect::<Vec<_>>(),
+            vec![false, false, false, true],
+        );
+        assert_eq!(depacketized, nal_units());
+    }
+
+    #[test]
+    fn round_trip_with_decoding_order_number() {
+        let (packets, depacketized) = round_trip(
+            H265Packetizer::new(params(Some(48))).with_decoding_order_number(),
+            H265Depacketizer::new().with_decoding_order_number(0),
+        );
+        assert!(packets.iter().all(|packet| packet.serialized_len() <= 48));
+        assert_eq!(depacketized, nal_units());
+
+        let (packets, depacketized) = round_trip(
+            H265Packetizer::new(params(Some(20))).with_decoding_order_number(),
+            H265Depacketizer::new().with_decoding_order_number(0),
+        );
+        // parameter sets only fit by themselves with DONL
+        assert_eq!(payload_types(&packets)[..4], [32, 33, 34, 49]);
+        assert_eq!(depacketized, nal_units());
+    }
+
+    #[test]
+    fn reorder_with_decoding_order_number() {
+        let mut depacketizer = H265Depacketizer::new().with_decoding_order_number(1);
+        // single NAL unit packets with DONL 1 and DONL 0
+        assert!(depacketizer
+            .depacketize(&packet(1, &[0x02, 0x01, 0x00, 0x01, 0xbb]))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            depacketizer
+                .depacketize(&packet(2, &[0x02, 0x01, 0x00, 0x00, 0xaa]))
+                .unwrap()
+                .into_iter()
+                .map(Unit::into_data)
+                .collect::<Vec<_>>(),
+            vec![Bytes::from_static(&[0x02, 0x01, 0xaa])],
+        );
+        assert_eq!(
+            depacketizer
+                .flush()
+                .into_iter()
+                .map(Unit::into_data)
+                .collect::<Vec<_>>(),
+            vec![Bytes::from_static(&[0x02, 0x01, 0xbb])],
+        );
+    }
+
+    #[test]
+    fn discard_fragmented_unit_with_gap() {
+        let mut depacketizer = H265Depacketizer::new();
+        assert!(depacketizer
+            .depacketize(&packet(1, &[0x62, 0x01, 0x93, 0x01]))
+            .unwrap()
+            .is_empty());
+        // packet 2 lost
+        assert!(depacketizer
+            .depacketize(&packet(3, &[0x62, 0x01, 0x53, 0x03]))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            depacketizer
+                .depacketize(&packet(4, &[0x02, 0x01, 0x04]))
+                .unwrap()
+                .len(),
+            1,
+        );
+    }
+}
@@ -0,0 +1,789 @@
+use std::collections::HashMap;
+
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::error::Error;
+use crate::header_extension::HeaderExtensionParameters;
+use crate::packet::Packet;
+use crate::packetization::common::{ensure_remaining, PacketizationParameters, Packetizer};
+
+use rave_types::codec::Jpeg;
+use rave_types::unit::Unit;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Static payload type for JPEG (RFC 3551).
+pub const PAYLOAD_TYPE_JPEG: u8 = 26;
+
+/// Length of JPEG header.
+const JPEG_HEADER_LEN: usize = 8;
+/// Length of restart marker header.
+const RESTART_MARKER_HEADER_LEN: usize = 4;
+/// Length of quantization table header (without tables).
+const QUANTIZATION_TABLE_HEADER_LEN: usize = 4;
+
+/// Q value for which quantization tables are sent in-band with every frame.
+const Q_IN_BAND: u8 = 255;
+
+/// Largest width and height that can be signaled (in 8-pixel blocks).
+const MAX_DIMENSION: usize = 255 * 8;
+/// Largest offset that fits in the fragment offset field.
+const MAX_FRAGMENT_OFFSET: usize = 0xff_ffff;
+
+/// JPEG markers.
+const MARKER_SOI: u8 = 0xd8;
+const MARKER_EOI: u8 = 0xd9;
+const MARKER_SOF0: u8 = 0xc0;
+const MARKER_DHT: u8 = 0xc4;
+const MARKER_DQT: u8 = 0xdb;
+const MARKER_DRI: u8 = 0xdd;
+const MARKER_SOS: u8 = 0xda;
+const MARKER_APP0: u8 = 0xe0;
+
+/// Luminance and chrominance quantization tables for Q = 50 in zigzag order (RFC 2435 appendix
+/// A). Tables for other values of Q are derived from these.
+#[rustfmt::skip]
+const DEFAULT_QUANTIZATION_TABLES: [[u8; 64]; 2] = [
+    [
+        16, 11, 12, 14, 12, 10, 16, 14,
+        13, 14, 18, 17, 16, 19, 24, 40,
+        26, 24, 22, 22, 24, 49, 35, 37,
+        29, 40, 58, 51, 61, 60, 57, 51,
+        56, 55, 64, 72, 92, 78, 64, 68,
+        87, 69, 55, 56, 80, 109, 81, 87,
+        95, 98, 103, 104, 103, 62, 77, 113,
+        121, 112, 100, 120, 92, 101, 103, 99,
+    ],
+    [
+        17, 18, 18, 24, 21, 24, 47, 26,
+        26, 47, 99, 66, 56, 66, 99, 99,
+        99, 99, 99, 99, 99, 99, 99, 99,
+        99, 99, 99, 99, 99, 99, 99, 99,
+        99, 99, 99, 99, 99, 99, 99, 99,
+        99, 99, 99, 99, 99, 99, 99, 99,
+        99, 99, 99, 99, 99, 99, 99, 99,
+        99, 99, 99, 99, 99, 99, 99, 99,
+    ],
+];
+
+/// Standard Huffman tables (ITU T.81 annex K.3), which all RFC 2435 streams use.
+const LUMINANCE_DC_CODE_LENGTHS: [u8; 16] = [0, 1, 5, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0, 0, 0];
+const LUMINANCE_DC_SYMBOLS: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const CHROMINANCE_DC_CODE_LENGTHS: [u8; 16] = [0, 3, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 0, 0, 0, 0];
+const CHROMINANCE_DC_SYMBOLS: [u8; 12] = [0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11];
+const LUMINANCE_AC_CODE_LENGTHS: [u8; 16] = [0, 2, 1, 3, 3, 2, 4, 3, 5, 5, 4, 4, 0, 0, 1, 0x7d];
+#[rustfmt::skip]
+const LUMINANCE_AC_SYMBOLS: [u8; 162] = [
+    0x01, 0x02, 0x03, 0x00, 0x04, 0x11, 0x05, 0x12,
+    0x21, 0x31, 0x41, 0x06, 0x13, 0x51, 0x61, 0x07,
+    0x22, 0x71, 0x14, 0x32, 0x81, 0x91, 0xa1, 0x08,
+    0x23, 0x42, 0xb1, 0xc1, 0x15, 0x52, 0xd1, 0xf0,
+    0x24, 0x33, 0x62, 0x72, 0x82, 0x09, 0x0a, 0x16,
+    0x17, 0x18, 0x19, 0x1a, 0x25, 0x26, 0x27, 0x28,
+    0x29, 0x2a, 0x34, 0x35, 0x36, 0x37, 0x38, 0x39,
+    0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48, 0x49,
+    0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58, 0x59,
+    0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68, 0x69,
+    0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78, 0x79,
+    0x7a, 0x83, 0x84, 0x85, 0x86, 0x87, 0x88, 0x89,
+    0x8a, 0x92, 0x93, 0x94, 0x95, 0x96, 0x97, 0x98,
+    0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7,
+    0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4, 0xb5, 0xb6,
+    0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3, 0xc4, 0xc5,
+    0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2, 0xd3, 0xd4,
+    0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda, 0xe1, 0xe2,
+    0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9, 0xea,
+    0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+    0xf9, 0xfa,
+];
+const CHROMINANCE_AC_CODE_LENGTHS: [u8; 16] = [0, 2, 1, 2, 4, 4, 3, 4, 7, 5, 4, 4, 0, 1, 2, 0x77];
+#[rustfmt::skip]
+const CHROMINANCE_AC_SYMBOLS: [u8; 162] = [
+    0x00, 0x01, 0x02, 0x03, 0x11, 0x04, 0x05, 0x21,
+    0x31, 0x06, 0x12, 0x41, 0x51, 0x07, 0x61, 0x71,
+    0x13, 0x22, 0x32, 0x81, 0x08, 0x14, 0x42, 0x91,
+    0xa1, 0xb1, 0xc1, 0x09, 0x23, 0x33, 0x52, 0xf0,
+    0x15, 0x62, 0x72, 0xd1, 0x0a, 0x16, 0x24, 0x34,
+    0xe1, 0x25, 0xf1, 0x17, 0x18, 0x19, 0x1a, 0x26,
+    0x27, 0x28, 0x29, 0x2a, 0x35, 0x36, 0x37, 0x38,
+    0x39, 0x3a, 0x43, 0x44, 0x45, 0x46, 0x47, 0x48,
+    0x49, 0x4a, 0x53, 0x54, 0x55, 0x56, 0x57, 0x58,
+    0x59, 0x5a, 0x63, 0x64, 0x65, 0x66, 0x67, 0x68,
+    0x69, 0x6a, 0x73, 0x74, 0x75, 0x76, 0x77, 0x78,
+    0x79, 0x7a, 0x82, 0x83, 0x84, 0x85, 0x86, 0x87,
+    0x88, 0x89, 0x8a, 0x92, 0x93, 0x94, 0x95, 0x96,
+    0x97, 0x98, 0x99, 0x9a, 0xa2, 0xa3, 0xa4, 0xa5,
+    0xa6, 0xa7, 0xa8, 0xa9, 0xaa, 0xb2, 0xb3, 0xb4,
+    0xb5, 0xb6, 0xb7, 0xb8, 0xb9, 0xba, 0xc2, 0xc3,
+    0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xd2,
+    0xd3, 0xd4, 0xd5, 0xd6, 0xd7, 0xd8, 0xd9, 0xda,
+    0xe2, 0xe3, 0xe4, 0xe5, 0xe6, 0xe7, 0xe8, 0xe9,
+    0xea, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8,
+    0xf9, 0xfa,
+];
+
+/// Main JPEG header (RFC 2435 section 3.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct JpegHeader {
+    type_specific: u8,
+    fragment_offset: usize,
+    jpeg_type: u8,
+    q: u8,
+    /// Width in 8-pixel blocks.
+    width: u8,
+    /// Height in 8-pixel blocks.
+    height: u8,
+}
+
+impl JpegHeader {
+    fn parse(src: &mut Bytes) -> Result<Self> {
+        ensure_remaining(src, JPEG_HEADER_LEN)?;
+        let type_specific = src.get_u8();
+        let fragment_offset = src.get_uint(3) as usize;
+        Ok(Self {
+            type_specific,
+            fragment_offset,
+            jpeg_type: src.get_u8(),
+            q: src.get_u8(),
+            width: src.get_u8(),
+            height: src.get_u8(),
+        })
+    }
+
+    fn serialize(&self, dst: &mut BytesMut) {
+        dst.put_u8(self.type_specific);
+        dst.put_uint(self.fragment_offset as u64, 3);
+        dst.put_u8(self.jpeg_type);
+        dst.put_u8(self.q);
+        dst.put_u8(self.width);
+        dst.put_u8(self.height);
+    }
+
+    /// Whether a restart marker header follows (types 64 to 127).
+    #[inline]
+    fn has_restart_marker_header(&self) -> bool {
+        (64..128).contains(&self.jpeg_type)
+    }
+
+    /// Type without restart marker bit. Only 0 (4:2:2) and 1 (4:2:0) are defined.
+    #[inline]
+    fn base_type(&self) -> u8 {
+        self.jpeg_type & !64
+    }
+}
+
+/// Quantization tables, each either 64 (8-bit precision) or 128 (16-bit precision) bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct QuantizationTables {
+    /// Bit `i` is set if table `i` has 16-bit precision.
+    precision: u8,
+    data: Bytes,
+}
+
+impl QuantizationTables {
+    /// Derive tables from Q value (RFC 2435 appendix A).
+    fn from_q(q: u8) -> Self {
+        let factor = q.clamp(1, 99) as u32;
+        let scale = if factor < 50 {
+            5000 / factor
+        } else {
+            200 - factor * 2
+        };
+        let data = DEFAULT_QUANTIZATION_TABLES
+            .iter()
+            .flatten()
+            .map(|value| ((*value as u32 * scale + 50) / 100).clamp(1, 255) as u8)
+            .collect::<Vec<_>>();
+        Self {
+            precision: 0,
+            data: data.into(),
+        }
+    }
+
+    fn parse(src: &mut Bytes) -> Result<Self> {
+        ensure_remaining(src, QUANTIZATION_TABLE_HEADER_LEN)?;
+        let _mbz = src.get_u8();
+        let precision = src.get_u8();
+        let len = src.get_u16() as usize;
+        ensure_remaining(src, len)?;
+        Ok(Self {
+            precision,
+            data: src.split_to(len),
+        })
+    }
+
+    fn serialize(&self, dst: &mut BytesMut) {
+        dst.put_u8(0);
+        dst.put_u8(self.precision);
+        dst.put_u16(self.data.len() as u16);
+        dst.put_slice(&self.data);
+    }
+
+    /// Split data into separate tables with their precision (0 for 8-bit and 1 for 16-bit).
+    fn tables(&self) -> Vec<(u8, &[u8])> {
+        let mut tables = Vec::new();
+        let mut data = self.data.as_ref();
+        while !data.is_empty() && tables.len() < 4 {
+            let precision = (self.precision >> tables.len()) & 0x01;
+            let len = if precision == 1 { 128 } else { 64 };
+            if data.len() < len {
+                break;
+            }
+            let (table, rest) = data.split_at(len);
+            tables.push((precision, table));
+            data = rest;
+        }
+        tables
+    }
+}
+
+/// RTP JPEG packetizer (RFC 2435).
+///
+/// Every unit is a baseline JFIF image with three components in 4:2:2 or 4:2:0 layout that uses
+/// the standard Huffman tables. Quantization tables are sent in-band with every frame (Q = 255).
+/// Widths and heights that are not a multiple of 8 are rounded up.
+#[derive(Debug)]
+pub struct JpegPacketizer {
+    inner: Packetizer,
+    mtu: Option<usize>,
+}
+
+impl JpegPacketizer {
+    /// Create a new packetizer to create RTP packets from JPEG images.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - RTP Packetization parameters to use for constructing packets. The payload type
+    ///   should be [`PAYLOAD_TYPE_JPEG`] unless a dynamic payload type was negotiated.
+    pub fn new(params: PacketizationParameters) -> Self {
+        let mtu = params.mtu;
+        Self {
+            inner: Packetizer::from_packetization_parameters(params),
+            mtu,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    /// Packetize one or more JPEG images.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - One or more JPEG images.
+    /// * `timestamp` - Presentation timestamp of images (90 kHz clock).
+    ///
+    /// # Return value
+    ///
+    /// One or more RTP packets. The marker bit is set on the last packet of every image.
+    pub fn packetize(&mut self, data: Vec<Unit<Jpeg>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let mut packets = Vec::new();
+        for image in data {
+            let image = JpegImage::parse(image.into_data())?;
+            let mut header = JpegHeader {
+                type_specific: 0,
+                fragment_offset: 0,
+                jpeg_type: image.jpeg_type
+                    + if image.restart_interval.is_some() {
+                        64
+                    } else {
+                        0
+                    },
+                q: Q_IN_BAND,
+                width: image.width.div_ceil(8) as u8,
+                height: image.height.div_ceil(8) as u8,
+            };
+
+            let mut scan = image.scan;
+            while !scan.is_empty() {
+                let mut payload = BytesMut::new();
+                header.serialize(&mut payload);
+                if let Some(restart_interval) = image.restart_interval {
+                    payload.put_u16(restart_interval);
+                    // First and last bit set and restart count 0x3fff, since fragments do not
+                    // align with restart intervals.
+                    payload.put_u16(0xffff);
+                }
+                if header.fragment_offset == 0 {
+                    image.quantization_tables.serialize(&mut payload);
+                }
+                let max_len = match self.mtu {
+                    Some(mtu) => mtu
+                        .saturating_sub(self.inner.header_serialized_len() + payload.len())
+                        .max(1),
+                    None => usize::MAX,
+                };
+                let chunk = scan.split_to(max_len.min(scan.len()));
+                header.fragment_offset += chunk.len();
+                payload.put(chunk);
+                packets.push(
+                    self.inner
+                        .packetize(payload.freeze(), timestamp, scan.is_empty())?,
+                );
+            }
+        }
+        Ok(packets)
+    }
+}
+
+/// JPEG image parsed into the parts that are sent over RTP.
+struct JpegImage {
+    jpeg_type: u8,
+    width: usize,
+    height: usize,
+    restart_interval: Option<u16>,
+    quantization_tables: QuantizationTables,
+    scan: Bytes,
+}
+
+impl JpegImage {
+    fn parse(mut data: Bytes) -> Result<Self> {
+        let invalid = |reason| Error::JpegImageInvalid { reason };
+        let unsupported = |reason| Error::JpegImageUnsupported { reason };
+
+        if data.len() < 2 || data.get_u8() != 0xff || data.get_u8() != MARKER_SOI {
+            return Err(invalid("missing start of image"));
+        }
+
+        let mut tables: [Option<(u8, Bytes)>; 4] = Default::default();
+        let mut frame = None;
+        let mut restart_interval = None;
+        let scan = loop {
+            ensure_remaining(&data, 2)?;
+            if data.get_u8() != 0xff {
+                return Err(invalid("expected marker"));
+            }
+            let mut marker = data.get_u8();
+            // Markers may be preceded by any number of fill bytes.
+            while marker == 0xff {
+                ensure_remaining(&data, 1)?;
+                marker = data.get_u8();
+            }
+            if marker == MARKER_EOI {
+                return Err(invalid("missing start of scan"));
+            }
+            ensure_remaining(&data, 2)?;
+            let len = (data.get_u16() as usize)
+                .checked_sub(2)
+                .ok_or_else(|| invalid("segment length invalid"))?;
+            ensure_remaining(&data, len)?;
+            let mut segment = data.split_to(len);
+            match marker {
+                MARKER_DQT => {
+                    while !segment.is_empty() {
+                        let byte = segment.get_u8();
+                        let (precision, id) = (byte >> 4, (byte & 0x0f) as usize);
+                        let len = if precision == 1 { 128 } else { 64 };
+                        if id >= tables.len() || segment.len() < len {
+                            return Err(invalid("quantization table invalid"));
+                        }
+                        tables[id] = Some((precision, segment.split_to(len)));
+                    }
+                }
+                MARKER_SOF0 => {
+                    ensure_remaining(&segment, 6)?;
+                    if segment.get_u8() != 8 {
+                        return Err(unsupported("precision must be 8 bits"));
+                    }
+                    let height = segment.get_u16() as usize;
+                    let width = segment.get_u16() as usize;
+                    let num_components = segment.get_u8() as usize;
+                    if num_components != 3 {
+                        return Err(unsupported("image must have three components"));
+                    }
+                    ensure_remaining(&segment, num_components * 3)?;
+                    let components = (0..num_components)
+                        .map(|_| {
+                            let _id = segment.get_u8();
+                            (segment.get_u8(), segment.get_u8() as usize)
+                        })
+                        .collect::<Vec<_>>();
+                    let jpeg_type = match components[0].0 {
+                        0x21 => 0,
+                        0x22 => 1,
+                        _ => return Err(unsupported("sampling must be 4:2:2 or 4:2:0")),
+                    };
+                    if components[1..]
+                        .iter()
+                        .any(|(sampling, _)| *sampling != 0x11)
+                    {
+                        return Err(unsupported("sampling must be 4:2:2 or 4:2:0"));
+                    }
+                    if width == 0 || height == 0 || width > MAX_DIMENSION || height > MAX_DIMENSION
+                    {
+                        return Err(unsupported("width and height must be at most 2040"));
+                    }
+                    frame = Some((jpeg_type, width, height, components[0].1, components[1].1));
+                }
+                0xc1..=0xcf if marker != MARKER_DHT && marker != 0xc8 && marker != 0xcc => {
+                    return Err(unsupported("image must be baseline"));
+                }
+                MARKER_DRI => {
+                    ensure_remaining(&segment, 2)?;
+                    restart_interval = Some(segment.get_u16()).filter(|interval| *interval > 0);
+                }
+                MARKER_SOS => {
+                    // Entropy-coded data runs until end of image.
+                    let scan = match data.len().checked_sub(2) {
+                        Some(end) if data[end..] == [0xff, MARKER_EOI] => data.slice(..end),
+                        _ => data,
+                    };
+                    break scan;
+                }
+                // Huffman tables are assumed to be the standard tables. Other segments are not
+                // carried over RTP.
+                _ => {}
+            }
+        };
+
+        let (jpeg_type, width, height, luminance_table, chrominance_table) =
+            frame.ok_or_else(|| invalid("missing start of frame"))?;
+        let table = |id: usize| {
+            tables
+                .get(id)
+                .cloned()
+                .flatten()
+                .ok_or_else(|| invalid("quantization table missing"))
+        };
+        let mut quantization_tables = vec![table(luminance_table)?];
+        if chrominance_table != luminance_table {
+            quantization_tables.push(table(chrominance_table)?);
+        }
+        let quantization_tables = QuantizationTables {
+            precision: quantization_tables
+                .iter()
+                .enumerate()
+                .map(|(index, (precision, _))| (precision & 0x01) << index)
+                .sum(),
+            data: quantization_tables
+                .into_iter()
+                .flat_map(|(_, table)| table)
+                .collect::<Vec<_>>()
+                .into(),
+        };
+
+        if scan.len() > MAX_FRAGMENT_OFFSET {
+            return Err(unsupported("image too large"));
+        }
+
+        Ok(Self {
+            jpeg_type,
+            width,
+            height,
+            restart_interval,
+            quantization_tables,
+            scan,
+        })
+    }
+}
+
+/// RTP JPEG depacketizer (RFC 2435).
+///
+/// Reassembles JFIF images from packets, restoring the headers that are not sent over RTP. Images
+/// of which packets were lost are discarded.
+#[derive(Debug, Default)]
+pub struct JpegDepacketizer {
+    frame: Option<PartialFrame>,
+    /// Quantization tables for Q values 128 to 254, which need only be sent once.
+    quantization_tables: HashMap<u8, QuantizationTables>,
+    last_sequence_number: Option<u16>,
+}
+
+impl JpegDepacketizer {
+    /// Create a new depacketizer to extract JPEG images from RTP packet stream.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Discard image that is being reassembled, if any.
+    ///
+    /// Gaps in sequence numbers are detected automatically, so this is only required when the
+    /// caller drops packets itself.
+    pub fn lost(&mut self) {
+        self.frame = None;
+    }
+
+    /// Depacketize RTP packet and return complete JPEG images.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Complete image, if the packet was the last packet of an image.
+    pub fn depacketize(&mut self, packet: &Packet) -> Result<Vec<Unit<Jpeg>>> {
+        let sequence_number = packet.header.sequence_number;
+        if self
+            .last_sequence_number
+            .replace(sequence_number)
+            .is_some_and(|last| sequence_number != last.wrapping_add(1))
+        {
+            self.lost();
+        }
+
+        let mut payload = packet.payload.clone();
+        let header = JpegHeader::parse(&mut payload)?;
+        let restart_interval = if header.has_restart_marker_header() {
+            ensure_remaining(&payload, RESTART_MARKER_HEADER_LEN)?;
+            let restart_interval = payload.get_u16();
+            let _restart_count = payload.get_u16();
+            Some(restart_interval)
+        } else {
+            None
+        };
+        if header.base_type() > 1 {
+            return Err(Error::JpegTypeUnsupported {
+                jpeg_type: header.jpeg_type,
+            });
+        }
+
+        if header.fragment_offset == 0 {
+            let quantization_tables = match header.q {
+                1..=99 => QuantizationTables::from_q(header.q),
+                128..=255 => {
+                    let quantization_tables = QuantizationTables::parse(&mut payload)?;
+                    if !quantization_tables.data.is_empty() {
+                        if header.q != Q_IN_BAND {
+                            self.quantization_tables
+                                .insert(header.q, quantization_tables.clone());
+                        }
+                        quantization_tables
+                    } else {
+                        self.quantization_tables
+                            .get(&header.q)
+                            .cloned()
+                            .ok_or(Error::JpegQuantizationTablesMissing { q: header.q })?
+                    }
+                }
+                _ => return Err(Error::JpegQuantizationTablesMissing { q: header.q }),
+            };
+            self.frame = Some(PartialFrame {
+                timestamp: packet.header.timestamp,
+                header,
+                restart_interval,
+                quantization_tables,
+                scan: BytesMut::new(),
+            });
+        }
+
+        if let Some(frame) = self.frame.as_mut() {
+            if frame.timestamp != packet.header.timestamp
+                || frame.scan.len() != header.fragment_offset
+            {
+                // Fragments from another image or missing fragments.
+                self.frame = None;
+            } else {
+                frame.scan.put(payload);
+            }
+        }
+
+        if packet.header.marker {
+            if let Some(frame) = self.frame.take() {
+                return Ok(vec![Unit::new(frame.finish())]);
+            }
+        }
+
+        Ok(Vec::new())
+    }
+}
+
+/// Image that is being reassembled.
+#[derive(Debug)]
+struct PartialFrame {
+    timestamp: u32,
+    header: JpegHeader,
+    restart_interval: Option<u16>,
+    quantization_tables: QuantizationTables,
+    scan: BytesMut,
+}
+
+impl PartialFrame {
+    fn finish(self) -> Bytes {
+        let mut image = BytesMut::new();
+        write_headers(
+            &mut image,
+            self.header.base_type(),
+            self.header.width as u16 * 8,
+            self.header.height as u16 * 8,
+            &self.quantization_tables,
+            self.restart_interval,
+        );
+        image.put(self.scan);
+        if !image.ends_with(&[0xff, MARKER_EOI]) {
+            image.put_slice(&[0xff, MARKER_EOI]);
+        }
+        image.freeze()
+    }
+}
+
+/// Write JFIF headers up to and including start of scan (RFC 2435 appendix B).
+fn write_headers(
+    dst: &mut BytesMut,
+    jpeg_type: u8,
+    width: u16,
+    height: u16,
+    quantization_tables: &QuantizationTables,
+    restart_interval: Option<u16>,
+) {
+    dst.put_slice(&[0xff, MARKER_SOI]);
+
+    dst.put_slice(&[0xff, MARKER_APP0]);
+    dst.put_u16(16);
+    dst.put_slice(b"JFIF\0");
+    // version 1.02, no units, 1:1 pixel aspect ratio, no thumbnail
+    dst.put_slice(&[0x01, 0x02, 0x00, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00]);
+
+    if let Some(restart_interval) = restart_interval {
+        dst.put_slice(&[0xff, MARKER_DRI]);
+        dst.put_u16(4);
+        dst.put_u16(restart_interval);
+    }
+
+    let tables = quantization_tables.tables();
+    for (id, (precision, table)) in tables.iter().enumerate() {
+        dst.put_slice(&[0xff, MARKER_DQT]);
+        dst.put_u16(3 + table.len() as u16);
+        dst.put_u8((precision << 4) | id as u8);
+        dst.put_slice(table);
+    }
+    let chrominance_table = if tables.len() > 1 { 1 } else { 0 };
+
+    dst.put_slice(&[0xff, MARKER_SOF0]);
+    dst.put_u16(17);
+    dst.put_u8(8);
+    dst.put_u16(height);
+    dst.put_u16(width);
+    dst.put_u8(3);
+    dst.put_slice(&[1, if jpeg_type == 0 { 0x21 } else { 0x22 }, 0]);
+    dst.put_slice(&[2, 0x11, chrominance_table]);
+    dst.put_slice(&[3, 0x11, chrominance_table]);
+
+    for (class_and_id, code_lengths, symbols) in [
+        (0x00, &LUMINANCE_DC_CODE_LENGTHS, &LUMINANCE_DC_SYMBOLS[..]),
+        (0x10, &LUMINANCE_AC_CODE_LENGTHS, &LUMINANCE_AC_SYMBOLS[..]),
+        (
+            0x01,
+            &CHROMINANCE_DC_CODE_LENGTHS,
+            &CHROMINANCE_DC_SYMBOLS[..],
+        ),
+        (
+            0x11,
+            &CHROMINANCE_AC_CODE_LENGTHS,
+            &CHROMINANCE_AC_SYMBOLS[..],
+        ),
+    ] {
+        dst.put_slice(&[0xff, MARKER_DHT]);
+        dst.put_u16(3 + code_lengths.len() as u16 + symbols.len() as u16);
+        dst.put_u8(class_and_id);
+        dst.put_slice(code_lengths);
+        dst.put_slice(symbols);
+    }
+
+    dst.put_slice(&[0xff, MARKER_SOS]);
+    dst.put_u16(12);
+    dst.put_u8(3);
+    dst.put_slice(&[1, 0x00, 2, 0x11, 3, 0x11]);
+    // spectral selection 0 to 63, no successive approximation
+    dst.put_slice(&[0, 63, 0]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packetization_parameters(mtu: usize) -> PacketizationParameters {
+        PacketizationParameters {
+            payload_type: PAYLOAD_TYPE_JPEG,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu: Some(mtu),
+        }
+    }
+
+    #[test]
+    fn huffman_tables() {
+        for (code_lengths, symbols) in [
+            (&LUMINANCE_DC_CODE_LENGTHS, &LUMINANCE_DC_SYMBOLS[..]),
+            (&LUMINANCE_AC_CODE_LENGTHS, &LUMINANCE_AC_SYMBOLS[..]),
+            (&CHROMINANCE_DC_CODE_LENGTHS, &CHROMINANCE_DC_SYMBOLS[..]),
+            (&CHROMINANCE_AC_CODE_LENGTHS, &CHROMINANCE_AC_SYMBOLS[..]),
+        ] {
+            assert_eq!(
+                code_lengths.iter().map(|len| *len as usize).sum::<usize>(),
+                symbols.len()
+            );
+        }
+    }
+
+    #[test]
+    fn packetize_and_reassemble() {
+        let quantization_tables = QuantizationTables::from_q(75);
+        let mut image = BytesMut::new();
+        write_headers(&mut image, 1, 64, 48, &quantization_tables, Some(4));
+        image.put_slice(&[0x12; 250]);
+        image.put_slice(&[0xff, MARKER_EOI]);
+        let image = image.freeze();
+
+        let mut packetizer = JpegPacketizer::new(packetization_parameters(200));
+        let packets = packetizer
+            .packetize(vec![Unit::new(image.clone())], 0)
+            .unwrap();
+        assert_eq!(packets.len(), 3);
+        // type 1 with restart markers, in-band tables, 64x48
+        assert_eq!(&packets[0].payload[4..8], &[65, 255, 8, 6]);
+
+        let mut depacketizer = JpegDepacketizer::new();
+        assert!(depacketizer.depacketize(&packets[0]).unwrap().is_empty());
+        assert!(depacketizer.depacketize(&packets[1]).unwrap().is_empty());
+        let images = depacketizer.depacketize(&packets[2]).unwrap();
+        assert_eq!(images.len(), 1);
+        assert_eq!(images[0].data, image);
+
+        // Image with missing packet is discarded.
+        let packets = packetizer.packetize(vec![Unit::new(image)], 3000).unwrap();
+        assert!(depacketizer.depacketize(&packets[0]).unwrap().is_empty());
+        assert!(depacketizer.depacketize(&packets[2]).unwrap().is_empty());
+    }
+
+    #[test]
+    fn depacketize_with_q_tables() {
+        let mut payload = BytesMut::new();
+        JpegHeader {
+            type_specific: 0,
+            fragment_offset: 0,
+            jpeg_type: 0,
+            q: 50,
+            width: 2,
+            height: 1,
+        }
+        .serialize(&mut payload);
+        payload.put_slice(&[0x34; 8]);
+        let mut packetizer =
+            Packetizer::from_packetization_parameters(packetization_parameters(1500));
+        let packet = packetizer.packetize(payload.freeze(), 0, true).unwrap();
+
+        let images = JpegDepacketizer::new().depacketize(&packet).unwrap();
+        let mut expected = BytesMut::new();
+        write_headers(
+            &mut expected,
+            0,
+            16,
+            8,
+            &QuantizationTables {
+                precision: 0,
+                data: DEFAULT_QUANTIZATION_TABLES.concat().into(),
+            },
+            None,
+        );
+        expected.put_slice(&[0x34; 8]);
+        expected.put_slice(&[0xff, MARKER_EOI]);
+        assert_eq!(images[0].data, expected);
+    }
+}
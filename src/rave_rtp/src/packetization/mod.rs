@@ -1,2 +1,10 @@
+pub mod aac;
+pub mod av1;
 pub mod common;
+pub mod g711;
 pub mod h264;
+pub mod h265;
+pub mod jpeg;
+pub mod opus;
+pub mod vp8;
+pub mod vp9;
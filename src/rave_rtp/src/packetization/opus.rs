@@ -0,0 +1,210 @@
+use crate::error::Error;
+use crate::header_extension::HeaderExtensionParameters;
+use crate::packet::Packet;
+use crate::packetization::common::{PacketizationParameters, Packetizer};
+
+use rave_types::codec::Opus;
+use rave_types::unit::Unit;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// RTP clock rate for Opus, regardless of the actual sampling rate (RFC 7587 section 4.1).
+pub const OPUS_CLOCK_RATE: u32 = 48_000;
+
+/// RTP Opus packetizer (RFC 7587).
+///
+/// Every Opus packet is sent in its own RTP packet.
+#[derive(Debug)]
+pub struct OpusPacketizer {
+    inner: Packetizer,
+    next_timestamp: Option<u32>,
+}
+
+impl OpusPacketizer {
+    /// Create a new packetizer to create RTP packets from Opus packets.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - RTP Packetization parameters to use for constructing packets.
+    pub fn new(params: PacketizationParameters) -> Self {
+        Self {
+            inner: Packetizer::from_packetization_parameters(params),
+            next_timestamp: None,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    /// Packetize one or more Opus packets.
+    ///
+    /// # Timestamps
+    ///
+    /// The packets must be consecutive. The first packet has the given timestamp, and the
+    /// timestamp of every packet after it is advanced by the duration of the packet before it (see
+    /// [`packet_duration`]).
+    ///
+    /// If the timestamp does not continue where the previous call left off, the packets are the
+    /// start of a talkspurt (for example after discontinuous transmission), and the marker bit is
+    /// set on the first packet (RFC 7587 section 4.1).
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - One or more Opus packets.
+    /// * `timestamp` - Presentation timestamp of first packet (48 kHz clock).
+    ///
+    /// # Return value
+    ///
+    /// One RTP packet per Opus packet.
+    pub fn packetize(&mut self, data: Vec<Unit<Opus>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let mut marker = self.next_timestamp != Some(timestamp);
+        let mut timestamp = timestamp;
+        let mut packets = Vec::with_capacity(data.len());
+        for packet in data {
+            let packet = packet.into_data();
+            let duration = packet_duration(&packet)?;
+            packets.push(self.inner.packetize(packet, timestamp, marker)?);
+            marker = false;
+            timestamp = timestamp.wrapping_add(duration);
+            self.next_timestamp = Some(timestamp);
+        }
+        Ok(packets)
+    }
+}
+
+/// RTP Opus depacketizer (RFC 7587).
+#[derive(Debug, Default)]
+pub struct OpusDepacketizer;
+
+impl OpusDepacketizer {
+    /// Create a new depacketizer to extract Opus packets from RTP packet stream.
+    pub fn new() -> Self {
+        Self
+    }
+
+    /// Depacketize RTP packet and return Opus packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Opus packet, or nothing if the payload is empty (which senders may use to signal
+    /// discontinuous transmission).
+    pub fn depacketize(&mut self, packet: &Packet) -> Result<Vec<Unit<Opus>>> {
+        if packet.payload.is_empty() {
+            return Ok(Vec::new());
+        }
+        packet_duration(&packet.payload)?;
+        Ok(vec![Unit::new(packet.payload.clone())])
+    }
+}
+
+/// Determine duration of Opus packet from its TOC byte (RFC 6716 section 3.1).
+///
+/// # Arguments
+///
+/// * `packet` - Opus packet.
+///
+/// # Return value
+///
+/// Duration in 48 kHz samples.
+pub fn packet_duration(packet: &[u8]) -> Result<u32> {
+    let invalid = || Error::OpusPacketInvalid { len: packet.len() };
+    let toc = *packet.first().ok_or_else(invalid)?;
+    let config = toc >> 3;
+    // Frame sizes in 48 kHz samples (2.5, 5, 10, 20, 40 and 60 ms).
+    let frame_size = match config {
+        // SILK-only
+        0..=11 => [480, 960, 1920, 2880][config as usize % 4],
+        // Hybrid
+        12..=15 => [480, 960][config as usize % 2],
+        // CELT-only
+        _ => [120, 240, 480, 960][config as usize % 4],
+    };
+    let num_frames = match toc & 0x03 {
+        0 => 1,
+        1 | 2 => 2,
+        _ => (*packet.get(1).ok_or_else(invalid)? & 0x3f) as u32,
+    };
+    Ok(frame_size * num_frames)
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+
+    #[test]
+    fn advance_timestamp_by_duration() {
+        let mut packetizer = OpusPacketizer::new(PacketizationParameters {
+            payload_type: 111,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu: None,
+        });
+        let packets = packetizer
+            .packetize(
+                vec![
+                    // CELT 20 ms, one frame
+                    Unit::new(Bytes::from_static(&[0xf8, 0x01])),
+                    // SILK 60 ms, two frames
+                    Unit::new(Bytes::from_static(&[0x19, 0x01])),
+                    // CELT 10 ms, three frames
+                    Unit::new(Bytes::from_static(&[0xf3, 0x03, 0x01])),
+                ],
+                1000,
+            )
+            .unwrap();
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| packet.header.timestamp)
+                .collect::<Vec<_>>(),
+            vec![1000, 1960, 7720],
+        );
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| packet.header.marker)
+                .collect::<Vec<_>>(),
+            vec![true, false, false],
+        );
+        assert_eq!(packet_duration(&packets[2].payload).unwrap(), 1440);
+
+        let mut depacketizer = OpusDepacketizer::new();
+        assert_eq!(depacketizer.depacketize(&packets[0]).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn mark_start_of_talkspurt() {
+        let mut packetizer = OpusPacketizer::new(PacketizationParameters {
+            payload_type: 111,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu: None,
+        });
+        let mut packetize = |timestamp| {
+            packetizer
+                .packetize(
+                    vec![Unit::new(Bytes::from_static(&[0xf8, 0x01]))],
+                    timestamp,
+                )
+                .unwrap()[0]
+                .header
+                .marker
+        };
+        assert!(packetize(0));
+        assert!(!packetize(960));
+        // Discontinuous transmission skipped 200 ms.
+        assert!(packetize(11520));
+    }
+}
@@ -0,0 +1,455 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::error::Error;
+use crate::header_extension::HeaderExtensionParameters;
+use crate::packet::Packet;
+use crate::packetization::common::{
+    ensure_remaining, AccessUnit, PacketizationParameters, Packetizer,
+};
+use crate::parse::Parse;
+use crate::serialize::Serialize;
+
+use rave_types::codec::Vp8;
+use rave_types::unit::Unit;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Picture ID as carried in VP8 and VP9 payload descriptors.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PictureId {
+    /// 7-bit picture ID.
+    Short(u8),
+    /// 15-bit picture ID.
+    Long(u16),
+}
+
+impl PictureId {
+    /// Parse picture ID from payload descriptor.
+    pub(crate) fn parse(src: &mut Bytes) -> Result<Self> {
+        ensure_remaining(src, 1)?;
+        let byte = src.get_u8();
+        if byte & 0x80 != 0 {
+            ensure_remaining(src, 1)?;
+            Ok(PictureId::Long(
+                (((byte & 0x7f) as u16) << 8) | src.get_u8() as u16,
+            ))
+        } else {
+            Ok(PictureId::Short(byte))
+        }
+    }
+
+    /// Write picture ID to payload descriptor.
+    pub(crate) fn serialize(self, dst: &mut BytesMut) {
+        match self {
+            PictureId::Short(picture_id) => dst.put_u8(picture_id & 0x7f),
+            PictureId::Long(picture_id) => dst.put_u16(0x8000 | (picture_id & 0x7fff)),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn serialized_len(&self) -> usize {
+        match self {
+            PictureId::Short(_) => 1,
+            PictureId::Long(_) => 2,
+        }
+    }
+
+    /// Picture ID of next picture.
+    #[inline]
+    pub(crate) fn next(self) -> Self {
+        match self {
+            PictureId::Short(picture_id) => PictureId::Short(picture_id.wrapping_add(1) & 0x7f),
+            PictureId::Long(picture_id) => PictureId::Long(picture_id.wrapping_add(1) & 0x7fff),
+        }
+    }
+}
+
+/// VP8 payload descriptor (RFC 7741 section 4.2).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vp8PayloadDescriptor {
+    /// Frame can be discarded without affecting any other frames (N).
+    pub non_reference_frame: bool,
+    /// First packet of a VP8 partition (S).
+    pub start_of_partition: bool,
+    /// Index of partition the packet belongs to (PID).
+    pub partition_index: u8,
+    /// Picture ID (I).
+    pub picture_id: Option<PictureId>,
+    /// Temporal level zero index (L).
+    pub tl0_pic_idx: Option<u8>,
+    /// Temporal layer index (T).
+    pub temporal_layer_index: Option<u8>,
+    /// Frame is a layer sync point (Y).
+    pub layer_sync: bool,
+    /// Key frame index (K).
+    pub key_index: Option<u8>,
+}
+
+impl Vp8PayloadDescriptor {
+    /// Whether packet holds the start of a VP8 frame.
+    #[inline]
+    pub fn is_start_of_frame(&self) -> bool {
+        self.start_of_partition && self.partition_index == 0
+    }
+
+    #[inline]
+    fn has_extension(&self) -> bool {
+        self.picture_id.is_some()
+            || self.tl0_pic_idx.is_some()
+            || self.temporal_layer_index.is_some()
+            || self.key_index.is_some()
+    }
+}
+
+impl Parse for Vp8PayloadDescriptor {
+    fn parse(src: &mut Bytes) -> Result<Self> {
+        ensure_remaining(src, 1)?;
+        let byte = src.get_u8();
+        let extension = byte & 0x80 != 0;
+        let mut descriptor = Vp8PayloadDescriptor {
+            non_reference_frame: byte & 0x20 != 0,
+            start_of_partition: byte & 0x10 != 0,
+            partition_index: byte & 0x07,
+            ..Default::default()
+        };
+        if extension {
+            ensure_remaining(src, 1)?;
+            let byte = src.get_u8();
+            if byte & 0x80 != 0 {
+                descriptor.picture_id = Some(PictureId::parse(src)?);
+            }
+            if byte & 0x40 != 0 {
+                ensure_remaining(src, 1)?;
+                descriptor.tl0_pic_idx = Some(src.get_u8());
+            }
+            let has_temporal_layer_index = byte & 0x20 != 0;
+            let has_key_index = byte & 0x10 != 0;
+            if has_temporal_layer_index || has_key_index {
+                ensure_remaining(src, 1)?;
+                let byte = src.get_u8();
+                if has_temporal_layer_index {
+                    descriptor.temporal_layer_index = Some(byte >> 6);
+                    descriptor.layer_sync = byte & 0x20 != 0;
+                }
+                if has_key_index {
+                    descriptor.key_index = Some(byte & 0x1f);
+                }
+            }
+        }
+        Ok(descriptor)
+    }
+}
+
+impl Serialize for Vp8PayloadDescriptor {
+    fn serialize(self, dst: &mut BytesMut) -> Result<()> {
+        dst.reserve(self.serialized_len());
+        let extension = self.has_extension();
+        dst.put_u8(
+            ((extension as u8) << 7)
+                | ((self.non_reference_frame as u8) << 5)
+                | ((self.start_of_partition as u8) << 4)
+                | (self.partition_index & 0x07),
+        );
+        if extension {
+            dst.put_u8(
+                ((self.picture_id.is_some() as u8) << 7)
+                    | ((self.tl0_pic_idx.is_some() as u8) << 6)
+                    | ((self.temporal_layer_index.is_some() as u8) << 5)
+                    | ((self.key_index.is_some() as u8) << 4),
+            );
+            if let Some(picture_id) = self.picture_id {
+                picture_id.serialize(dst);
+            }
+            if let Some(tl0_pic_idx) = self.tl0_pic_idx {
+                dst.put_u8(tl0_pic_idx);
+            }
+            if self.temporal_layer_index.is_some() || self.key_index.is_some() {
+                dst.put_u8(
+                    ((self.temporal_layer_index.unwrap_or(0) & 0x03) << 6)
+                        | ((self.layer_sync as u8) << 5)
+                        | (self.key_index.unwrap_or(0) & 0x1f),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    fn serialized_len(&self) -> usize {
+        if !self.has_extension() {
+            return 1;
+        }
+        2 + self
+            .picture_id
+            .map(|picture_id| picture_id.serialized_len())
+            .unwrap_or(0)
+            + self.tl0_pic_idx.is_some() as usize
+            + (self.temporal_layer_index.is_some() || self.key_index.is_some()) as usize
+    }
+}
+
+/// Determine whether VP8 frame is a key frame from its payload header (RFC 7741 section 4.3).
+///
+/// # Arguments
+///
+/// * `frame` - VP8 frame, or at least its first byte.
+pub fn is_keyframe(frame: &[u8]) -> bool {
+    frame.first().is_some_and(|byte| byte & 0x01 == 0)
+}
+
+/// RTP VP8 packetizer (RFC 7741).
+///
+/// Every unit is a single VP8 frame. Frames are not split on partition boundaries, so all packets
+/// carry partition index 0.
+#[derive(Debug)]
+pub struct Vp8Packetizer {
+    inner: Packetizer,
+    mtu: Option<usize>,
+    picture_id: Option<PictureId>,
+}
+
+impl Vp8Packetizer {
+    /// Create a new packetizer to create RTP packets from VP8 frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - RTP Packetization parameters to use for constructing packets.
+    pub fn new(params: PacketizationParameters) -> Self {
+        let mtu = params.mtu;
+        Self {
+            inner: Packetizer::from_packetization_parameters(params),
+            mtu,
+            picture_id: None,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    /// Include 15-bit picture ID in payload descriptor, starting at a random value and increasing
+    /// by one for every frame.
+    pub fn with_picture_id(mut self) -> Self {
+        self.picture_id = Some(PictureId::Long(rand::random::<u16>() & 0x7fff));
+        self
+    }
+
+    /// Packetize one or more VP8 frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - One or more VP8 frames.
+    /// * `timestamp` - Presentation timestamp of frames (90 kHz clock).
+    ///
+    /// # Return value
+    ///
+    /// One or more RTP packets. The marker bit is set on the last packet of every frame.
+    pub fn packetize(&mut self, data: Vec<Unit<Vp8>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let mut packets = Vec::new();
+        for frame in data {
+            let mut frame = frame.into_data();
+            let picture_id = self.picture_id;
+            self.picture_id = picture_id.map(PictureId::next);
+
+            let mut descriptor = Vp8PayloadDescriptor {
+                start_of_partition: true,
+                picture_id,
+                ..Default::default()
+            };
+            let max_len = self.max_payload_len(descriptor.serialized_len());
+            while !frame.is_empty() {
+                let chunk = frame.split_to(max_len.min(frame.len()));
+                let mut payload = BytesMut::new();
+                descriptor.clone().serialize(&mut payload)?;
+                payload.put(chunk);
+                packets.push(self.inner.packetize(
+                    payload.freeze(),
+                    timestamp,
+                    frame.is_empty(),
+                )?);
+                descriptor.start_of_partition = false;
+            }
+        }
+        Ok(packets)
+    }
+
+    fn max_payload_len(&self, descriptor_len: usize) -> usize {
+        match self.mtu {
+            Some(mtu) => mtu
+                .saturating_sub(self.inner.header_serialized_len() + descriptor_len)
+                .max(1),
+            None => usize::MAX,
+        }
+    }
+}
+
+/// RTP VP8 depacketizer (RFC 7741).
+///
+/// Reassembles VP8 frames from packets. Frames of which packets were lost are discarded.
+#[derive(Debug, Default)]
+pub struct Vp8Depacketizer {
+    frame: Option<PartialFrame>,
+    last_sequence_number: Option<u16>,
+}
+
+impl Vp8Depacketizer {
+    /// Create a new depacketizer to extract VP8 frames from RTP packet stream.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Discard frame that is being reassembled, if any.
+    ///
+    /// Gaps in sequence numbers are detected automatically, so this is only required when the
+    /// caller drops packets itself.
+    pub fn lost(&mut self) {
+        self.frame = None;
+    }
+
+    /// Depacketize RTP packet and return complete VP8 frames.
+    ///
+    /// A frame is complete when the packet with the marker bit arrives, or when the first packet of
+    /// a frame with another timestamp arrives.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more complete frames.
+    pub fn depacketize(&mut self, packet: &Packet) -> Result<Vec<AccessUnit<Vp8>>> {
+        let sequence_number = packet.header.sequence_number;
+        if self
+            .last_sequence_number
+            .replace(sequence_number)
+            .is_some_and(|last| sequence_number != last.wrapping_add(1))
+        {
+            self.lost();
+        }
+
+        let mut payload = packet.payload.clone();
+        let descriptor = Vp8PayloadDescriptor::parse(&mut payload)?;
+        if payload.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut frames = Vec::new();
+        let timestamp = packet.header.timestamp;
+        if self
+            .frame
+            .as_ref()
+            .is_some_and(|frame| frame.timestamp != timestamp)
+        {
+            frames.extend(self.frame.take().map(PartialFrame::finish));
+        }
+
+        if descriptor.is_start_of_frame() {
+            self.frame = Some(PartialFrame {
+                timestamp,
+                keyframe: is_keyframe(&payload),
+                data: BytesMut::from(payload),
+            });
+        } else if let Some(frame) = self.frame.as_mut() {
+            frame.data.put(payload);
+        }
+
+        if packet.header.marker {
+            frames.extend(self.frame.take().map(PartialFrame::finish));
+        }
+
+        Ok(frames)
+    }
+}
+
+/// Frame that is being reassembled.
+#[derive(Debug)]
+struct PartialFrame {
+    timestamp: u32,
+    keyframe: bool,
+    data: BytesMut,
+}
+
+impl PartialFrame {
+    fn finish(self) -> AccessUnit<Vp8> {
+        let mut access_unit = AccessUnit::new(self.timestamp, false);
+        access_unit.keyframe = self.keyframe;
+        access_unit.units.push(Unit::new(self.data.freeze()));
+        access_unit
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_payload_descriptor() {
+        let mut src = Bytes::from_static(&[0x90, 0xe0, 0x81, 0x23, 0x05, 0x40, 0xaa]);
+        let descriptor = Vp8PayloadDescriptor::parse(&mut src).unwrap();
+        assert_eq!(
+            descriptor,
+            Vp8PayloadDescriptor {
+                non_reference_frame: false,
+                start_of_partition: true,
+                partition_index: 0,
+                picture_id: Some(PictureId::Long(0x0123)),
+                tl0_pic_idx: Some(5),
+                temporal_layer_index: Some(1),
+                layer_sync: false,
+                key_index: None,
+            }
+        );
+        assert_eq!(src.as_ref(), &[0xaa]);
+        assert_eq!(descriptor.serialized_len(), 6);
+        let mut dst = BytesMut::new();
+        descriptor.serialize(&mut dst).unwrap();
+        assert_eq!(dst.as_ref(), &[0x90, 0xe0, 0x81, 0x23, 0x05, 0x40]);
+
+        let mut src = Bytes::from_static(&[0x80, 0x80]);
+        assert!(Vp8PayloadDescriptor::parse(&mut src).is_err());
+    }
+
+    #[test]
+    fn packetize_and_reassemble() {
+        let mut packetizer = Vp8Packetizer::new(PacketizationParameters {
+            payload_type: 96,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu: Some(12 + 3 + 10),
+        })
+        .with_picture_id();
+        let keyframe = Bytes::from((0..25).map(|i| (i as u8) << 1).collect::<Vec<_>>());
+        let packets = packetizer
+            .packetize(vec![Unit::new(keyframe.clone())], 3000)
+            .unwrap();
+        assert_eq!(packets.len(), 3);
+        assert_eq!(
+            packets
+                .iter()
+                .map(|packet| packet.header.marker)
+                .collect::<Vec<_>>(),
+            vec![false, false, true],
+        );
+
+        let mut depacketizer = Vp8Depacketizer::new();
+        assert!(depacketizer.depacketize(&packets[0]).unwrap().is_empty());
+        assert!(depacketizer.depacketize(&packets[1]).unwrap().is_empty());
+        let frames = depacketizer.depacketize(&packets[2]).unwrap();
+        assert_eq!(frames.len(), 1);
+        assert_eq!(frames[0].timestamp, 3000);
+        assert!(frames[0].keyframe);
+        assert_eq!(frames[0].units[0].data, keyframe);
+
+        // Frame with lost packet is discarded.
+        let packets = packetizer
+            .packetize(vec![Unit::new(Bytes::from(vec![0x01; 25]))], 6000)
+            .unwrap();
+        assert!(depacketizer.depacketize(&packets[0]).unwrap().is_empty());
+        assert!(depacketizer.depacketize(&packets[2]).unwrap().is_empty());
+    }
+}
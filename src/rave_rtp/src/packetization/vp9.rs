@@ -0,0 +1,668 @@
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+
+use crate::error::Error;
+use crate::header_extension::HeaderExtensionParameters;
+use crate::packet::Packet;
+use crate::packetization::common::{
+    ensure_remaining, AccessUnit, PacketizationParameters, Packetizer,
+};
+use crate::packetization::vp8::PictureId;
+use crate::parse::Parse;
+use crate::serialize::Serialize;
+
+use rave_types::codec::Vp9;
+use rave_types::unit::Unit;
+
+type Result<T> = std::result::Result<T, Error>;
+
+/// Maximum number of reference indices (P_DIFF) in flexible mode.
+const MAX_REFERENCE_INDICES: usize = 3;
+
+/// VP9 payload descriptor (RFC 9628 section 4.2).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vp9PayloadDescriptor {
+    /// Layer frame uses inter-picture prediction (P).
+    pub inter_picture_predicted: bool,
+    /// Flexible mode (F). References are signaled per picture in flexible mode, and through the
+    /// scalability structure in non-flexible mode.
+    pub flexible_mode: bool,
+    /// First packet of a layer frame (B).
+    pub start_of_frame: bool,
+    /// Last packet of a layer frame (E).
+    pub end_of_frame: bool,
+    /// Layer frame is not used for inter-layer prediction of higher spatial layers (Z).
+    pub not_upper_layer_reference: bool,
+    /// Picture ID (I).
+    pub picture_id: Option<PictureId>,
+    /// Layer indices (L).
+    pub layer_indices: Option<Vp9LayerIndices>,
+    /// Temporal level zero index. Only present in non-flexible mode with layer indices.
+    pub tl0_pic_idx: Option<u8>,
+    /// Reference indices (P_DIFF). Only present in flexible mode for inter-picture predicted
+    /// layer frames.
+    pub reference_indices: Vec<u8>,
+    /// Scalability structure (V).
+    pub scalability_structure: Option<Vp9ScalabilityStructure>,
+}
+
+/// VP9 layer indices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Vp9LayerIndices {
+    /// Temporal layer ID (TID).
+    pub temporal_layer_id: u8,
+    /// Switching up point (U).
+    pub switching_up_point: bool,
+    /// Spatial layer ID (SID).
+    pub spatial_layer_id: u8,
+    /// Inter-layer dependency used (D).
+    pub inter_layer_dependency: bool,
+}
+
+/// VP9 scalability structure (RFC 9628 section 4.2.1).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vp9ScalabilityStructure {
+    /// Number of spatial layers (N_S + 1).
+    pub num_spatial_layers: u8,
+    /// Width and height of every spatial layer (Y).
+    pub resolutions: Option<Vec<(u16, u16)>>,
+    /// Picture group description (G).
+    pub picture_group: Option<Vec<Vp9PictureGroupEntry>>,
+}
+
+/// Picture in VP9 picture group description.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Vp9PictureGroupEntry {
+    /// Temporal layer ID (TID).
+    pub temporal_layer_id: u8,
+    /// Switching up point (U).
+    pub switching_up_point: bool,
+    /// Reference indices (P_DIFF).
+    pub reference_indices: Vec<u8>,
+}
+
+impl Vp9PayloadDescriptor {
+    /// Whether packet holds the start of a picture, which is the start of its lowest spatial layer
+    /// frame.
+    #[inline]
+    pub fn is_start_of_picture(&self) -> bool {
+        self.start_of_frame
+            && self
+                .layer_indices
+                .is_none_or(|layer_indices| layer_indices.spatial_layer_id == 0)
+    }
+
+    /// Whether packet holds the start of a key frame: a picture whose lowest spatial layer does not
+    /// use inter-picture prediction.
+    #[inline]
+    pub fn is_keyframe(&self) -> bool {
+        self.is_start_of_picture() && !self.inter_picture_predicted
+    }
+}
+
+impl Parse for Vp9PayloadDescriptor {
+    fn parse(src: &mut Bytes) -> Result<Self> {
+        ensure_remaining(src, 1)?;
+        let byte = src.get_u8();
+        let mut descriptor = Vp9PayloadDescriptor {
+            inter_picture_predicted: byte & 0x40 != 0,
+            flexible_mode: byte & 0x10 != 0,
+            start_of_frame: byte & 0x08 != 0,
+            end_of_frame: byte & 0x04 != 0,
+            not_upper_layer_reference: byte & 0x01 != 0,
+            ..Default::default()
+        };
+        let has_picture_id = byte & 0x80 != 0;
+        let has_layer_indices = byte & 0x20 != 0;
+        let has_scalability_structure = byte & 0x02 != 0;
+
+        if has_picture_id {
+            descriptor.picture_id = Some(PictureId::parse(src)?);
+        }
+        if has_layer_indices {
+            ensure_remaining(src, 1)?;
+            let byte = src.get_u8();
+            descriptor.layer_indices = Some(Vp9LayerIndices {
+                temporal_layer_id: byte >> 5,
+                switching_up_point: byte & 0x10 != 0,
+                spatial_layer_id: (byte >> 1) & 0x07,
+                inter_layer_dependency: byte & 0x01 != 0,
+            });
+            if !descriptor.flexible_mode {
+                ensure_remaining(src, 1)?;
+                descriptor.tl0_pic_idx = Some(src.get_u8());
+            }
+        }
+        if descriptor.flexible_mode && descriptor.inter_picture_predicted {
+            loop {
+                if descriptor.reference_indices.len() == MAX_REFERENCE_INDICES {
+                    return Err(Error::Vp9ReferenceIndicesTooMany {
+                        max: MAX_REFERENCE_INDICES,
+                    });
+                }
+                ensure_remaining(src, 1)?;
+                let byte = src.get_u8();
+                descriptor.reference_indices.push(byte >> 1);
+                if byte & 0x01 == 0 {
+                    break;
+                }
+            }
+        }
+        if has_scalability_structure {
+            descriptor.scalability_structure = Some(Vp9ScalabilityStructure::parse(src)?);
+        }
+        Ok(descriptor)
+    }
+}
+
+impl Serialize for Vp9PayloadDescriptor {
+    fn serialize(self, dst: &mut BytesMut) -> Result<()> {
+        if self.reference_indices.len() > MAX_REFERENCE_INDICES {
+            return Err(Error::Vp9ReferenceIndicesTooMany {
+                max: MAX_REFERENCE_INDICES,
+            });
+        }
+        dst.reserve(self.serialized_len());
+        dst.put_u8(
+            ((self.picture_id.is_some() as u8) << 7)
+                | ((self.inter_picture_predicted as u8) << 6)
+                | ((self.layer_indices.is_some() as u8) << 5)
+                | ((self.flexible_mode as u8) << 4)
+                | ((self.start_of_frame as u8) << 3)
+                | ((self.end_of_frame as u8) << 2)
+                | ((self.scalability_structure.is_some() as u8) << 1)
+                | (self.not_upper_layer_reference as u8),
+        );
+        if let Some(picture_id) = self.picture_id {
+            picture_id.serialize(dst);
+        }
+        if let Some(layer_indices) = self.layer_indices {
+            dst.put_u8(
+                ((layer_indices.temporal_layer_id & 0x07) << 5)
+                    | ((layer_indices.switching_up_point as u8) << 4)
+                    | ((layer_indices.spatial_layer_id & 0x07) << 1)
+                    | (layer_indices.inter_layer_dependency as u8),
+            );
+            if !self.flexible_mode {
+                dst.put_u8(self.tl0_pic_idx.unwrap_or(0));
+            }
+        }
+        if self.flexible_mode && self.inter_picture_predicted {
+            let last = self.reference_indices.len().saturating_sub(1);
+            for (index, reference_index) in self.reference_indices.iter().enumerate() {
+                dst.put_u8((reference_index << 1) | (index != last) as u8);
+            }
+        }
+        if let Some(scalability_structure) = self.scalability_structure {
+            scalability_structure.serialize(dst)?;
+        }
+        Ok(())
+    }
+
+    fn serialized_len(&self) -> usize {
+        1 + self
+            .picture_id
+            .map(|picture_id| picture_id.serialized_len())
+            .unwrap_or(0)
+            + self
+                .layer_indices
+                .map(|_| if self.flexible_mode { 1 } else { 2 })
+                .unwrap_or(0)
+            + if self.flexible_mode && self.inter_picture_predicted {
+                self.reference_indices.len()
+            } else {
+                0
+            }
+            + self
+                .scalability_structure
+                .as_ref()
+                .map(|scalability_structure| scalability_structure.serialized_len())
+                .unwrap_or(0)
+    }
+}
+
+impl Parse for Vp9ScalabilityStructure {
+    fn parse(src: &mut Bytes) -> Result<Self> {
+        ensure_remaining(src, 1)?;
+        let byte = src.get_u8();
+        let num_spatial_layers = (byte >> 5) + 1;
+        let has_resolutions = byte & 0x10 != 0;
+        let has_picture_group = byte & 0x08 != 0;
+
+        let resolutions = if has_resolutions {
+            ensure_remaining(src, num_spatial_layers as usize * 4)?;
+            Some(
+                (0..num_spatial_layers)
+                    .map(|_| (src.get_u16(), src.get_u16()))
+                    .collect(),
+            )
+        } else {
+            None
+        };
+
+        let picture_group = if has_picture_group {
+            ensure_remaining(src, 1)?;
+            let num_pictures = src.get_u8();
+            let mut picture_group = Vec::with_capacity(num_pictures as usize);
+            for _ in 0..num_pictures {
+                ensure_remaining(src, 1)?;
+                let byte = src.get_u8();
+                let num_reference_indices = ((byte >> 2) & 0x03) as usize;
+                ensure_remaining(src, num_reference_indices)?;
+                picture_group.push(Vp9PictureGroupEntry {
+                    temporal_layer_id: byte >> 5,
+                    switching_up_point: byte & 0x10 != 0,
+                    reference_indices: src.copy_to_bytes(num_reference_indices).to_vec(),
+                });
+            }
+            Some(picture_group)
+        } else {
+            None
+        };
+
+        Ok(Vp9ScalabilityStructure {
+            num_spatial_layers,
+            resolutions,
+            picture_group,
+        })
+    }
+}
+
+impl Serialize for Vp9ScalabilityStructure {
+    fn serialize(self, dst: &mut BytesMut) -> Result<()> {
+        if self
+            .picture_group
+            .iter()
+            .flatten()
+            .any(|entry| entry.reference_indices.len() > MAX_REFERENCE_INDICES)
+        {
+            return Err(Error::Vp9ReferenceIndicesTooMany {
+                max: MAX_REFERENCE_INDICES,
+            });
+        }
+        dst.reserve(self.serialized_len());
+        dst.put_u8(
+            ((self.num_spatial_layers.saturating_sub(1) & 0x07) << 5)
+                | ((self.resolutions.is_some() as u8) << 4)
+                | ((self.picture_group.is_some() as u8) << 3),
+        );
+        for (width, height) in self.resolutions.into_iter().flatten() {
+            dst.put_u16(width);
+            dst.put_u16(height);
+        }
+        if let Some(picture_group) = self.picture_group {
+            dst.put_u8(picture_group.len() as u8);
+            for entry in picture_group {
+                dst.put_u8(
+                    ((entry.temporal_layer_id & 0x07) << 5)
+                        | ((entry.switching_up_point as u8) << 4)
+                        | ((entry.reference_indices.len() as u8) << 2),
+                );
+                dst.put_slice(&entry.reference_indices);
+            }
+        }
+        Ok(())
+    }
+
+    fn serialized_len(&self) -> usize {
+        1 + self
+            .resolutions
+            .as_ref()
+            .map(|resolutions| resolutions.len() * 4)
+            .unwrap_or(0)
+            + self
+                .picture_group
+                .as_ref()
+                .map(|picture_group| {
+                    1 + picture_group
+                        .iter()
+                        .map(|entry| 1 + entry.reference_indices.len())
+                        .sum::<usize>()
+                })
+                .unwrap_or(0)
+    }
+}
+
+/// Determine whether VP9 frame is a key frame from its uncompressed header (VP9 bitstream
+/// specification section 6.2).
+///
+/// # Arguments
+///
+/// * `frame` - VP9 frame, or at least its first byte.
+pub fn is_keyframe(frame: &[u8]) -> bool {
+    let Some(byte) = frame.first() else {
+        return false;
+    };
+    let bit = |position: u32| (byte >> (7 - position)) & 0x01;
+    // frame_marker
+    if byte >> 6 != 0x02 {
+        return false;
+    }
+    let profile = (bit(3) << 1) | bit(2);
+    // profile 3 has a reserved bit after the profile bits
+    let position = if profile == 3 { 5 } else { 4 };
+    let show_existing_frame = bit(position);
+    let frame_type = bit(position + 1);
+    show_existing_frame == 0 && frame_type == 0
+}
+
+/// RTP VP9 packetizer (RFC 9628).
+///
+/// Packets are sent in non-flexible mode with a 15-bit picture ID and without layer indices.
+#[derive(Debug)]
+pub struct Vp9Packetizer {
+    inner: Packetizer,
+    mtu: Option<usize>,
+    picture_id: PictureId,
+    scalability_structure: Option<Vp9ScalabilityStructure>,
+}
+
+impl Vp9Packetizer {
+    /// Create a new packetizer to create RTP packets from VP9 frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `params` - RTP Packetization parameters to use for constructing packets.
+    pub fn new(params: PacketizationParameters) -> Self {
+        let mtu = params.mtu;
+        Self {
+            inner: Packetizer::from_packetization_parameters(params),
+            mtu,
+            picture_id: PictureId::Long(rand::random::<u16>() & 0x7fff),
+            scalability_structure: None,
+        }
+    }
+
+    /// Write header extensions to every packet, see [`Packetizer::with_header_extensions`].
+    pub fn with_header_extensions(
+        mut self,
+        header_extensions: HeaderExtensionParameters,
+    ) -> Result<Self> {
+        self.inner.set_header_extensions(header_extensions)?;
+        Ok(self)
+    }
+
+    /// Include scalability structure in the first packet of every key frame.
+    pub fn with_scalability_structure(
+        mut self,
+        scalability_structure: Vp9ScalabilityStructure,
+    ) -> Self {
+        self.scalability_structure = Some(scalability_structure);
+        self
+    }
+
+    /// Packetize VP9 picture.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Layer frames of a single picture, in order of spatial layer.
+    /// * `timestamp` - Presentation timestamp of picture (90 kHz clock).
+    ///
+    /// # Return value
+    ///
+    /// One or more RTP packets. The marker bit is set on the last packet of the picture.
+    pub fn packetize(&mut self, data: Vec<Unit<Vp9>>, timestamp: u32) -> Result<Vec<Packet>> {
+        let picture_id = self.picture_id;
+        self.picture_id = picture_id.next();
+
+        let mut packets = Vec::new();
+        let num_frames = data.len();
+        for (index, frame) in data.into_iter().enumerate() {
+            let mut frame = frame.into_data();
+            let keyframe = is_keyframe(&frame);
+            let mut descriptor = Vp9PayloadDescriptor {
+                inter_picture_predicted: !keyframe,
+                start_of_frame: true,
+                picture_id: Some(picture_id),
+                scalability_structure: self
+                    .scalability_structure
+                    .clone()
+                    .filter(|_| keyframe && index == 0),
+                ..Default::default()
+            };
+            while !frame.is_empty() {
+                let max_len = self.max_payload_len(descriptor.serialized_len());
+                let chunk = frame.split_to(max_len.min(frame.len()));
+                descriptor.end_of_frame = frame.is_empty();
+                let mut payload = BytesMut::new();
+                descriptor.clone().serialize(&mut payload)?;
+                payload.put(chunk);
+                let marker = frame.is_empty() && index + 1 == num_frames;
+                packets.push(self.inner.packetize(payload.freeze(), timestamp, marker)?);
+                descriptor.start_of_frame = false;
+                descriptor.scalability_structure = None;
+            }
+        }
+        Ok(packets)
+    }
+
+    fn max_payload_len(&self, descriptor_len: usize) -> usize {
+        match self.mtu {
+            Some(mtu) => mtu
+                .saturating_sub(self.inner.header_serialized_len() + descriptor_len)
+                .max(1),
+            None => usize::MAX,
+        }
+    }
+}
+
+/// RTP VP9 depacketizer (RFC 9628).
+///
+/// Reassembles VP9 pictures from packets. Every unit in a picture is a layer frame. Layer frames of
+/// which packets were lost are discarded, as is the rest of the picture after them.
+#[derive(Debug, Default)]
+pub struct Vp9Depacketizer {
+    picture: Option<PartialPicture>,
+    scalability_structure: Option<Vp9ScalabilityStructure>,
+    last_sequence_number: Option<u16>,
+}
+
+impl Vp9Depacketizer {
+    /// Create a new depacketizer to extract VP9 pictures from RTP packet stream.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Most recently received scalability structure, if any.
+    pub fn scalability_structure(&self) -> Option<&Vp9ScalabilityStructure> {
+        self.scalability_structure.as_ref()
+    }
+
+    /// Discard picture that is being reassembled, if any.
+    ///
+    /// Gaps in sequence numbers are detected automatically, so this is only required when the
+    /// caller drops packets itself.
+    pub fn lost(&mut self) {
+        self.picture = None;
+    }
+
+    /// Depacketize RTP packet and return complete VP9 pictures.
+    ///
+    /// A picture is complete when the packet with the marker bit arrives, or when the first packet
+    /// of a picture with another timestamp arrives.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to depacketize.
+    ///
+    /// # Return value
+    ///
+    /// Zero or more complete pictures.
+    pub fn depacketize(&mut self, packet: &Packet) -> Result<Vec<AccessUnit<Vp9>>> {
+        let sequence_number = packet.header.sequence_number;
+        if self
+            .last_sequence_number
+            .replace(sequence_number)
+            .is_some_and(|last| sequence_number != last.wrapping_add(1))
+        {
+            self.lost();
+        }
+
+        let mut payload = packet.payload.clone();
+        let mut descriptor = Vp9PayloadDescriptor::parse(&mut payload)?;
+        if let Some(scalability_structure) = descriptor.scalability_structure.take() {
+            self.scalability_structure = Some(scalability_structure);
+        }
+        if payload.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let mut pictures = Vec::new();
+        let timestamp = packet.header.timestamp;
+        if self
+            .picture
+            .as_ref()
+            .is_some_and(|picture| picture.timestamp != timestamp)
+        {
+            pictures.extend(self.picture.take().and_then(PartialPicture::finish));
+        }
+
+        if descriptor.is_start_of_picture() && self.picture.is_none() {
+            self.picture = Some(PartialPicture {
+                timestamp,
+                keyframe: descriptor.is_keyframe(),
+                frames: Vec::new(),
+                frame: None,
+            });
+        }
+
+        if let Some(picture) = self.picture.as_mut() {
+            if descriptor.start_of_frame {
+                picture.frame = Some(BytesMut::from(payload));
+            } else if let Some(frame) = picture.frame.as_mut() {
+                frame.put(payload);
+            }
+            if descriptor.end_of_frame {
+                picture
+                    .frames
+                    .extend(picture.frame.take().map(BytesMut::freeze));
+            }
+        }
+
+        if packet.header.marker {
+            pictures.extend(self.picture.take().and_then(PartialPicture::finish));
+        }
+
+        Ok(pictures)
+    }
+}
+
+/// Picture that is being reassembled.
+#[derive(Debug)]
+struct PartialPicture {
+    timestamp: u32,
+    keyframe: bool,
+    /// Complete layer frames.
+    frames: Vec<Bytes>,
+    /// Layer frame that is being reassembled.
+    frame: Option<BytesMut>,
+}
+
+impl PartialPicture {
+    fn finish(self) -> Option<AccessUnit<Vp9>> {
+        if self.frames.is_empty() {
+            return None;
+        }
+        let mut access_unit = AccessUnit::new(self.timestamp, self.frame.is_some());
+        access_unit.keyframe = self.keyframe;
+        access_unit
+            .units
+            .extend(self.frames.into_iter().map(Unit::new));
+        Some(access_unit)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_payload_descriptor_flexible_mode() {
+        let mut src = Bytes::from_static(&[0xf8, 0x81, 0x00, 0x22, 0x03, 0x04, 0xaa]);
+        let descriptor = Vp9PayloadDescriptor::parse(&mut src).unwrap();
+        assert_eq!(
+            descriptor,
+            Vp9PayloadDescriptor {
+                inter_picture_predicted: true,
+                flexible_mode: true,
+                start_of_frame: true,
+                picture_id: Some(PictureId::Long(0x0100)),
+                layer_indices: Some(Vp9LayerIndices {
+                    temporal_layer_id: 1,
+                    switching_up_point: false,
+                    spatial_layer_id: 1,
+                    inter_layer_dependency: false,
+                }),
+                reference_indices: vec![1, 2],
+                ..Default::default()
+            }
+        );
+        assert_eq!(src.as_ref(), &[0xaa]);
+        assert!(!descriptor.is_start_of_picture());
+        let mut dst = BytesMut::new();
+        descriptor.serialize(&mut dst).unwrap();
+        assert_eq!(dst.as_ref(), &[0xf8, 0x81, 0x00, 0x22, 0x03, 0x04]);
+    }
+
+    #[test]
+    fn parse_payload_descriptor_non_flexible_mode() {
+        let mut src = Bytes::from_static(&[
+            0xaa, 0x12, 0x00, 0x07, 0x18, 0x02, 0x80, 0x01, 0x68, 0x01, 0x04, 0x01,
+        ]);
+        let descriptor = Vp9PayloadDescriptor::parse(&mut src).unwrap();
+        assert_eq!(
+            descriptor,
+            Vp9PayloadDescriptor {
+                start_of_frame: true,
+                picture_id: Some(PictureId::Short(0x12)),
+                layer_indices: Some(Vp9LayerIndices::default()),
+                tl0_pic_idx: Some(7),
+                scalability_structure: Some(Vp9ScalabilityStructure {
+                    num_spatial_layers: 1,
+                    resolutions: Some(vec![(640, 360)]),
+                    picture_group: Some(vec![Vp9PictureGroupEntry {
+                        temporal_layer_id: 0,
+                        switching_up_point: false,
+                        reference_indices: vec![1],
+                    }]),
+                }),
+                ..Default::default()
+            }
+        );
+        assert!(src.is_empty());
+        assert!(descriptor.is_keyframe());
+        assert_eq!(descriptor.serialized_len(), 12);
+    }
+
+    #[test]
+    fn packetize_and_reassemble() {
+        let mut packetizer = Vp9Packetizer::new(PacketizationParameters {
+            payload_type: 98,
+            ssrc: 1,
+            csrc: Vec::new(),
+            mtu: Some(12 + 3 + 10),
+        });
+        // frame marker 2, profile 0, not show existing frame, key frame
+        let keyframe = Bytes::from([vec![0x80], vec![0x00; 19]].concat());
+        let packets = packetizer
+            .packetize(vec![Unit::new(keyframe.clone())], 9000)
+            .unwrap();
+        assert_eq!(packets.len(), 2);
+        assert!(packets[1].header.marker);
+
+        let mut depacketizer = Vp9Depacketizer::new();
+        assert!(depacketizer.depacketize(&packets[0]).unwrap().is_empty());
+        let pictures = depacketizer.depacketize(&packets[1]).unwrap();
+        assert_eq!(pictures.len(), 1);
+        assert!(pictures[0].keyframe);
+        assert!(!pictures[0].corrupted);
+        assert_eq!(pictures[0].units[0].data, keyframe);
+
+        // Picture with lost packet is discarded.
+        let interframe = Bytes::from([vec![0x84], vec![0x00; 19]].concat());
+        assert!(!is_keyframe(&interframe));
+        let packets = packetizer
+            .packetize(vec![Unit::new(interframe)], 12000)
+            .unwrap();
+        assert!(depacketizer.depacketize(&packets[1]).unwrap().is_empty());
+    }
+}
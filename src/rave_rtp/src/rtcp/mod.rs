@@ -0,0 +1,431 @@
+//! RTCP packets (RFC 3550, section 6) and feedback messages (RFC 4585, RFC 5104).
+//!
+//! Packets are parsed with [`Parse`](crate::parse::Parse) and serialized with
+//! [`Serialize`](crate::serialize::Serialize). RTCP packets are usually sent as a [`Compound`]
+//! packet that starts with a sender or receiver report.
+
+mod parse;
+mod serialize;
+pub mod session;
+pub mod statistics;
+
+use bytes::Bytes;
+
+pub const PACKET_TYPE_SENDER_REPORT: u8 = 200;
+pub const PACKET_TYPE_RECEIVER_REPORT: u8 = 201;
+pub const PACKET_TYPE_SOURCE_DESCRIPTION: u8 = 202;
+pub const PACKET_TYPE_GOODBYE: u8 = 203;
+pub const PACKET_TYPE_APP: u8 = 204;
+pub const PACKET_TYPE_TRANSPORT_FEEDBACK: u8 = 205;
+pub const PACKET_TYPE_PAYLOAD_FEEDBACK: u8 = 206;
+
+/// Compound RTCP packet: one or more RTCP packets that are sent in a single datagram.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Compound(pub Vec<Packet>);
+
+/// RTCP packet.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Packet {
+    SenderReport(SenderReport),
+    ReceiverReport(ReceiverReport),
+    SourceDescription(SourceDescription),
+    Goodbye(Goodbye),
+    App(App),
+    /// Generic NACK (RTPFB, FMT 1).
+    Nack(Nack),
+    /// Picture Loss Indication (PSFB, FMT 1).
+    Pli(Pli),
+    /// Full Intra Request (PSFB, FMT 4).
+    Fir(Fir),
+    /// Receiver Estimated Maximum Bitrate (PSFB, FMT 15).
+    Remb(Remb),
+    /// Packet of a type that is not supported. Kept so that compound packets can still be parsed.
+    Unknown(Unknown),
+}
+
+/// Sender report (SR).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SenderReport {
+    pub ssrc: u32,
+    /// Wall clock time at which the report was sent, as 64-bit NTP timestamp.
+    pub ntp_timestamp: u64,
+    /// RTP timestamp that corresponds to `ntp_timestamp`.
+    pub rtp_timestamp: u32,
+    pub packet_count: u32,
+    pub octet_count: u32,
+    pub reports: Vec<ReportBlock>,
+}
+
+/// Receiver report (RR).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiverReport {
+    pub ssrc: u32,
+    pub reports: Vec<ReportBlock>,
+}
+
+/// Reception report block, part of sender and receiver reports.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ReportBlock {
+    /// Source that this report block is about.
+    pub ssrc: u32,
+    /// Fraction of packets lost since the previous report, as fixed point number (divide by 256).
+    pub fraction_lost: u8,
+    /// Total number of packets lost (24-bit signed, duplicates may make it negative).
+    pub cumulative_lost: i32,
+    /// Extended highest sequence number received.
+    pub highest_sequence_number: u32,
+    /// Interarrival jitter in RTP timestamp units.
+    pub jitter: u32,
+    /// Middle 32 bits of NTP timestamp of last sender report received (LSR).
+    pub last_sender_report: u32,
+    /// Delay since last sender report in units of 1/65536 seconds (DLSR).
+    pub delay_since_last_sender_report: u32,
+}
+
+/// Source description (SDES).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDescription {
+    pub chunks: Vec<SourceDescriptionChunk>,
+}
+
+/// Items that describe a single source.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDescriptionChunk {
+    pub ssrc: u32,
+    pub items: Vec<SourceDescriptionItem>,
+}
+
+/// Source description item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceDescriptionItem {
+    pub kind: SourceDescriptionItemKind,
+    pub value: Bytes,
+}
+
+impl SourceDescriptionItem {
+    /// Create canonical name (`CNAME`) item.
+    pub fn cname(cname: &str) -> Self {
+        Self {
+            kind: SourceDescriptionItemKind::Cname,
+            value: Bytes::copy_from_slice(cname.as_bytes()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SourceDescriptionItemKind {
+    Cname,
+    Name,
+    Email,
+    Phone,
+    Location,
+    Tool,
+    Note,
+    Private,
+    Unknown(u8),
+}
+
+impl SourceDescriptionItemKind {
+    pub fn from_number(number: u8) -> Self {
+        match number {
+            1 => SourceDescriptionItemKind::Cname,
+            2 => SourceDescriptionItemKind::Name,
+            3 => SourceDescriptionItemKind::Email,
+            4 => SourceDescriptionItemKind::Phone,
+            5 => SourceDescriptionItemKind::Location,
+            6 => SourceDescriptionItemKind::Tool,
+            7 => SourceDescriptionItemKind::Note,
+            8 => SourceDescriptionItemKind::Private,
+            number => SourceDescriptionItemKind::Unknown(number),
+        }
+    }
+
+    pub fn as_number(&self) -> u8 {
+        match self {
+            SourceDescriptionItemKind::Cname => 1,
+            SourceDescriptionItemKind::Name => 2,
+            SourceDescriptionItemKind::Email => 3,
+            SourceDescriptionItemKind::Phone => 4,
+            SourceDescriptionItemKind::Location => 5,
+            SourceDescriptionItemKind::Tool => 6,
+            SourceDescriptionItemKind::Note => 7,
+            SourceDescriptionItemKind::Private => 8,
+            SourceDescriptionItemKind::Unknown(number) => *number,
+        }
+    }
+}
+
+/// Goodbye (BYE).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Goodbye {
+    /// Sources that are leaving.
+    pub sources: Vec<u32>,
+    pub reason: Option<Bytes>,
+}
+
+/// Application-defined packet (APP).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct App {
+    pub subtype: u8,
+    pub ssrc: u32,
+    pub name: [u8; 4],
+    /// Application-dependent data. Length must be a multiple of four.
+    pub data: Bytes,
+}
+
+/// Generic NACK (RFC 4585, section 6.2.1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nack {
+    pub sender_ssrc: u32,
+    pub media_ssrc: u32,
+    /// Sequence numbers of lost packets.
+    ///
+    /// When serializing, consecutive sequence numbers that fall within 16 packets of each other
+    /// are combined into a single entry.
+    pub lost: Vec<u16>,
+}
+
+/// Picture Loss Indication (RFC 4585, section 6.3.1).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Pli {
+    pub sender_ssrc: u32,
+    pub media_ssrc: u32,
+}
+
+/// Full Intra Request (RFC 5104, section 4.3.1).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fir {
+    pub sender_ssrc: u32,
+    pub entries: Vec<FirEntry>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FirEntry {
+    /// Source that is requested to send a decoder refresh point.
+    pub ssrc: u32,
+    /// Command sequence number. Incremented for every new request.
+    pub sequence_number: u8,
+}
+
+/// Receiver Estimated Maximum Bitrate (draft-alvestrand-rmcat-remb).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remb {
+    pub sender_ssrc: u32,
+    /// Estimated maximum bitrate in bits per second.
+    ///
+    /// The bitrate is encoded as an 18-bit mantissa and 6-bit exponent, so large bitrates lose
+    /// some precision when serialized.
+    pub bitrate: u64,
+    /// Sources that the estimate applies to.
+    pub ssrcs: Vec<u32>,
+}
+
+/// RTCP packet of unsupported type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unknown {
+    pub packet_type: u8,
+    /// Value of count or format field.
+    pub count: u8,
+    /// Packet without common header and padding.
+    pub payload: Bytes,
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::BytesMut;
+
+    use super::*;
+    use crate::parse::Parse;
+    use crate::serialize::Serialize;
+
+    fn roundtrip(packet: Packet) {
+        let mut dst = BytesMut::new();
+        let len = packet.serialized_len();
+        packet.clone().serialize(&mut dst).unwrap();
+        assert_eq!(dst.len(), len);
+        assert_eq!(len % 4, 0);
+        assert_eq!(Packet::parse(&mut dst.freeze()).unwrap(), packet);
+    }
+
+    fn report_block(ssrc: u32) -> ReportBlock {
+        ReportBlock {
+            ssrc,
+            fraction_lost: 12,
+            cumulative_lost: -3,
+            highest_sequence_number: 0x0001_fffe,
+            jitter: 80,
+            last_sender_report: 0x1234_5678,
+            delay_since_last_sender_report: 65536,
+        }
+    }
+
+    #[test]
+    fn parse_sender_report() {
+        let mut src = Bytes::from_static(&[
+            0x81, 0xc8, 0x00, 0x0c, // V=2, RC=1, PT=SR, length=12
+            0x00, 0x00, 0x00, 0x01, // SSRC
+            0xe9, 0x5a, 0x6b, 0x7c, 0x80, 0x00, 0x00, 0x00, // NTP timestamp
+            0x00, 0x01, 0xe2, 0x40, // RTP timestamp
+            0x00, 0x00, 0x00, 0x0a, // packet count
+            0x00, 0x00, 0x03, 0xe8, // octet count
+            0x00, 0x00, 0x00, 0x02, // SSRC of report block
+            0x05, 0xff, 0xff, 0xff, // fraction lost, cumulative lost
+            0x00, 0x00, 0x10, 0x00, // extended highest sequence number
+            0x00, 0x00, 0x00, 0x20, // jitter
+            0x00, 0x00, 0x00, 0x00, // LSR
+            0x00, 0x00, 0x00, 0x00, // DLSR
+        ]);
+        assert_eq!(
+            Packet::parse(&mut src).unwrap(),
+            Packet::SenderReport(SenderReport {
+                ssrc: 1,
+                ntp_timestamp: 0xe95a_6b7c_8000_0000,
+                rtp_timestamp: 123456,
+                packet_count: 10,
+                octet_count: 1000,
+                reports: vec![ReportBlock {
+                    ssrc: 2,
+                    fraction_lost: 5,
+                    cumulative_lost: -1,
+                    highest_sequence_number: 4096,
+                    jitter: 32,
+                    last_sender_report: 0,
+                    delay_since_last_sender_report: 0,
+                }],
+            }),
+        );
+        assert!(src.is_empty());
+    }
+
+    #[test]
+    fn roundtrip_reports() {
+        roundtrip(Packet::SenderReport(SenderReport {
+            ssrc: 1,
+            ntp_timestamp: 0x0102_0304_0506_0708,
+            rtp_timestamp: 9000,
+            packet_count: 3,
+            octet_count: 4000,
+            reports: vec![report_block(2), report_block(3)],
+        }));
+        roundtrip(Packet::ReceiverReport(ReceiverReport {
+            ssrc: 1,
+            reports: vec![report_block(2)],
+        }));
+        roundtrip(Packet::ReceiverReport(ReceiverReport {
+            ssrc: 1,
+            reports: Vec::new(),
+        }));
+    }
+
+    #[test]
+    fn roundtrip_source_description() {
+        roundtrip(Packet::SourceDescription(SourceDescription {
+            chunks: vec![
+                SourceDescriptionChunk {
+                    ssrc: 1,
+                    items: vec![SourceDescriptionItem::cname("rave@example.com")],
+                },
+                SourceDescriptionChunk {
+                    ssrc: 2,
+                    items: vec![
+                        SourceDescriptionItem::cname("abc"),
+                        SourceDescriptionItem {
+                            kind: SourceDescriptionItemKind::Tool,
+                            value: Bytes::from_static(b"rave"),
+                        },
+                    ],
+                },
+            ],
+        }));
+    }
+
+    #[test]
+    fn roundtrip_goodbye_and_app() {
+        roundtrip(Packet::Goodbye(Goodbye {
+            sources: vec![1, 2],
+            reason: Some(Bytes::from_static(b"shutting down")),
+        }));
+        roundtrip(Packet::Goodbye(Goodbye {
+            sources: vec![1],
+            reason: None,
+        }));
+        roundtrip(Packet::App(App {
+            subtype: 3,
+            ssrc: 1,
+            name: *b"RAVE",
+            data: Bytes::from_static(&[1, 2, 3, 4]),
+        }));
+    }
+
+    #[test]
+    fn roundtrip_feedback() {
+        roundtrip(Packet::Nack(Nack {
+            sender_ssrc: 1,
+            media_ssrc: 2,
+            lost: vec![100, 101, 116, 117, 65535, 0],
+        }));
+        roundtrip(Packet::Pli(Pli {
+            sender_ssrc: 1,
+            media_ssrc: 2,
+        }));
+        roundtrip(Packet::Fir(Fir {
+            sender_ssrc: 1,
+            entries: vec![FirEntry {
+                ssrc: 2,
+                sequence_number: 7,
+            }],
+        }));
+        roundtrip(Packet::Remb(Remb {
+            sender_ssrc: 1,
+            bitrate: 1_000_000,
+            ssrcs: vec![2, 3],
+        }));
+    }
+
+    #[test]
+    fn serialize_nack_combines_sequence_numbers() {
+        let mut dst = BytesMut::new();
+        Packet::Nack(Nack {
+            sender_ssrc: 1,
+            media_ssrc: 2,
+            lost: vec![100, 101, 116, 117],
+        })
+        .serialize(&mut dst)
+        .unwrap();
+        assert_eq!(
+            &dst[12..],
+            &[0x00, 0x64, 0x80, 0x01, 0x00, 0x75, 0x00, 0x00],
+        );
+    }
+
+    #[test]
+    fn parse_compound_with_unknown_and_padding() {
+        let mut src = Bytes::from_static(&[
+            0x80, 0xc9, 0x00, 0x01, // RR, length=1
+            0x00, 0x00, 0x00, 0x01, // SSRC
+            0xa0, 0xcf, 0x00, 0x02, // XR (unknown), padding, length=2
+            0x00, 0x00, 0x00, 0x01, // SSRC
+            0x00, 0x00, 0x00, 0x04, // padding
+        ]);
+        assert_eq!(
+            Compound::parse(&mut src).unwrap(),
+            Compound(vec![
+                Packet::ReceiverReport(ReceiverReport {
+                    ssrc: 1,
+                    reports: Vec::new(),
+                }),
+                Packet::Unknown(Unknown {
+                    packet_type: 207,
+                    count: 0,
+                    payload: Bytes::from_static(&[0x00, 0x00, 0x00, 0x01]),
+                }),
+            ]),
+        );
+    }
+
+    #[test]
+    fn parse_truncated() {
+        let mut src = Bytes::from_static(&[0x81, 0xc9, 0x00, 0x07, 0x00, 0x00, 0x00, 0x01]);
+        assert!(Packet::parse(&mut src).is_err());
+    }
+}
@@ -0,0 +1,261 @@
+use bytes::{Buf, Bytes};
+
+use crate::error::{Error, Result};
+use crate::packet::Version;
+use crate::parse::Parse;
+use crate::rtcp::*;
+
+impl Parse for Compound {
+    fn parse(src: &mut Bytes) -> Result<Self> {
+        ensure(src, 4)?;
+        let mut packets = Vec::new();
+        while src.has_remaining() {
+            packets.push(Packet::parse(src)?);
+        }
+        Ok(Compound(packets))
+    }
+}
+
+impl Parse for Packet {
+    fn parse(src: &mut Bytes) -> Result<Self> {
+        ensure(src, 4)?;
+        let byte = src[0];
+        Version::try_from((byte >> 6 & 0x03) as usize)?;
+        let padding = (byte >> 5 & 0x01) > 0;
+        let count = byte & 0x1f;
+        let packet_type = src[1];
+        let len = (u16::from_be_bytes([src[2], src[3]]) as usize + 1) * 4;
+        ensure(src, len)?;
+
+        let mut body = src.split_to(len);
+        body.advance(4);
+        if padding {
+            let padding_len = *body
+                .last()
+                .ok_or(Error::NotEnoughData { have: 0, need: 1 })?
+                as usize;
+            if padding_len == 0 || padding_len > body.len() {
+                return Err(Error::NotEnoughData {
+                    have: body.len(),
+                    need: padding_len.max(1),
+                });
+            }
+            body.truncate(body.len() - padding_len);
+        }
+
+        Ok(match (packet_type, count) {
+            (PACKET_TYPE_SENDER_REPORT, _) => {
+                Packet::SenderReport(parse_sender_report(count, &mut body)?)
+            }
+            (PACKET_TYPE_RECEIVER_REPORT, _) => {
+                Packet::ReceiverReport(parse_receiver_report(count, &mut body)?)
+            }
+            (PACKET_TYPE_SOURCE_DESCRIPTION, _) => {
+                Packet::SourceDescription(parse_source_description(count, &mut body)?)
+            }
+            (PACKET_TYPE_GOODBYE, _) => Packet::Goodbye(parse_goodbye(count, &mut body)?),
+            (PACKET_TYPE_APP, _) => Packet::App(parse_app(count, &mut body)?),
+            (PACKET_TYPE_TRANSPORT_FEEDBACK, 1) => Packet::Nack(parse_nack(&mut body)?),
+            (PACKET_TYPE_PAYLOAD_FEEDBACK, 1) => {
+                let (sender_ssrc, media_ssrc) = parse_feedback_ssrcs(&mut body)?;
+                Packet::Pli(Pli {
+                    sender_ssrc,
+                    media_ssrc,
+                })
+            }
+            (PACKET_TYPE_PAYLOAD_FEEDBACK, 4) => Packet::Fir(parse_fir(&mut body)?),
+            (PACKET_TYPE_PAYLOAD_FEEDBACK, 15) if body.get(8..12) == Some(b"REMB") => {
+                Packet::Remb(parse_remb(&mut body)?)
+            }
+            _ => Packet::Unknown(Unknown {
+                packet_type,
+                count,
+                payload: body,
+            }),
+        })
+    }
+}
+
+impl Parse for ReportBlock {
+    fn parse(src: &mut Bytes) -> Result<Self> {
+        ensure(src, 24)?;
+        let ssrc = src.get_u32();
+        let fraction_lost = src.get_u8();
+        // sign-extend 24-bit cumulative number of packets lost
+        let cumulative_lost = ((src.get_uint(3) as u32) << 8) as i32 >> 8;
+        Ok(ReportBlock {
+            ssrc,
+            fraction_lost,
+            cumulative_lost,
+            highest_sequence_number: src.get_u32(),
+            jitter: src.get_u32(),
+            last_sender_report: src.get_u32(),
+            delay_since_last_sender_report: src.get_u32(),
+        })
+    }
+}
+
+fn parse_sender_report(count: u8, src: &mut Bytes) -> Result<SenderReport> {
+    ensure(src, 24)?;
+    let ssrc = src.get_u32();
+    let ntp_timestamp = src.get_u64();
+    let rtp_timestamp = src.get_u32();
+    let packet_count = src.get_u32();
+    let octet_count = src.get_u32();
+    // profile-specific extensions that follow the report blocks are ignored
+    let reports = parse_report_blocks(count, src)?;
+    Ok(SenderReport {
+        ssrc,
+        ntp_timestamp,
+        rtp_timestamp,
+        packet_count,
+        octet_count,
+        reports,
+    })
+}
+
+fn parse_receiver_report(count: u8, src: &mut Bytes) -> Result<ReceiverReport> {
+    ensure(src, 4)?;
+    let ssrc = src.get_u32();
+    let reports = parse_report_blocks(count, src)?;
+    Ok(ReceiverReport { ssrc, reports })
+}
+
+fn parse_report_blocks(count: u8, src: &mut Bytes) -> Result<Vec<ReportBlock>> {
+    (0..count).map(|_| ReportBlock::parse(src)).collect()
+}
+
+fn parse_source_description(count: u8, src: &mut Bytes) -> Result<SourceDescription> {
+    let mut chunks = Vec::with_capacity(count as usize);
+    for _ in 0..count {
+        let chunk_start = src.remaining();
+        ensure(src, 4)?;
+        let ssrc = src.get_u32();
+        let mut items = Vec::new();
+        loop {
+            ensure(src, 1)?;
+            let kind = src.get_u8();
+            if kind == 0 {
+                break;
+            }
+            ensure(src, 1)?;
+            let len = src.get_u8() as usize;
+            ensure(src, len)?;
+            items.push(SourceDescriptionItem {
+                kind: SourceDescriptionItemKind::from_number(kind),
+                value: src.copy_to_bytes(len),
+            });
+        }
+        // chunks are padded with null octets up to the next 32-bit boundary
+        let chunk_len = chunk_start - src.remaining();
+        let padding_len = (4 - chunk_len % 4) % 4;
+        src.advance(padding_len.min(src.remaining()));
+        chunks.push(SourceDescriptionChunk { ssrc, items });
+    }
+    Ok(SourceDescription { chunks })
+}
+
+fn parse_goodbye(count: u8, src: &mut Bytes) -> Result<Goodbye> {
+    ensure(src, count as usize * 4)?;
+    let sources = (0..count).map(|_| src.get_u32()).collect();
+    let reason = if src.has_remaining() {
+        let len = src.get_u8() as usize;
+        ensure(src, len)?;
+        Some(src.copy_to_bytes(len))
+    } else {
+        None
+    };
+    Ok(Goodbye { sources, reason })
+}
+
+fn parse_app(subtype: u8, src: &mut Bytes) -> Result<App> {
+    ensure(src, 8)?;
+    let ssrc = src.get_u32();
+    let mut name = [0; 4];
+    src.copy_to_slice(&mut name);
+    Ok(App {
+        subtype,
+        ssrc,
+        name,
+        data: src.copy_to_bytes(src.remaining()),
+    })
+}
+
+fn parse_feedback_ssrcs(src: &mut Bytes) -> Result<(u32, u32)> {
+    ensure(src, 8)?;
+    Ok((src.get_u32(), src.get_u32()))
+}
+
+fn parse_nack(src: &mut Bytes) -> Result<Nack> {
+    let (sender_ssrc, media_ssrc) = parse_feedback_ssrcs(src)?;
+    let mut lost = Vec::new();
+    while src.remaining() >= 4 {
+        let pid = src.get_u16();
+        let blp = src.get_u16();
+        lost.push(pid);
+        lost.extend(
+            (0..16)
+                .filter(|bit| blp & (1 << bit) != 0)
+                .map(|bit| pid.wrapping_add(bit + 1)),
+        );
+    }
+    Ok(Nack {
+        sender_ssrc,
+        media_ssrc,
+        lost,
+    })
+}
+
+fn parse_fir(src: &mut Bytes) -> Result<Fir> {
+    // media source ssrc is not used for FIR and must be zero
+    let (sender_ssrc, _) = parse_feedback_ssrcs(src)?;
+    let mut entries = Vec::new();
+    while src.remaining() >= 8 {
+        let ssrc = src.get_u32();
+        let sequence_number = src.get_u8();
+        src.advance(3);
+        entries.push(FirEntry {
+            ssrc,
+            sequence_number,
+        });
+    }
+    Ok(Fir {
+        sender_ssrc,
+        entries,
+    })
+}
+
+fn parse_remb(src: &mut Bytes) -> Result<Remb> {
+    let (sender_ssrc, _) = parse_feedback_ssrcs(src)?;
+    ensure(src, 8)?;
+    // unique identifier "REMB" was already checked
+    src.advance(4);
+    let num_ssrc = src.get_u8() as usize;
+    let exponent = src.get_u8();
+    let mantissa = ((exponent as u64 & 0x03) << 16) | src.get_u16() as u64;
+    let exponent = (exponent >> 2) as u32;
+    let bitrate = if mantissa.leading_zeros() > exponent {
+        mantissa << exponent
+    } else {
+        u64::MAX
+    };
+    ensure(src, num_ssrc * 4)?;
+    let ssrcs = (0..num_ssrc).map(|_| src.get_u32()).collect();
+    Ok(Remb {
+        sender_ssrc,
+        bitrate,
+        ssrcs,
+    })
+}
+
+#[inline]
+fn ensure(src: &Bytes, need: usize) -> Result<()> {
+    if src.remaining() < need {
+        Err(Error::NotEnoughData {
+            have: src.remaining(),
+            need,
+        })
+    } else {
+        Ok(())
+    }
+}
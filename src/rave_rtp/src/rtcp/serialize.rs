@@ -0,0 +1,261 @@
+use bytes::{BufMut, BytesMut};
+
+use crate::error::{Error, Result};
+use crate::rtcp::*;
+use crate::serialize::Serialize;
+
+impl Serialize for Compound {
+    fn serialize(self, dst: &mut BytesMut) -> Result<()> {
+        dst.reserve(self.serialized_len());
+        for packet in self.0 {
+            packet.serialize(dst)?;
+        }
+        Ok(())
+    }
+
+    fn serialized_len(&self) -> usize {
+        self.0.iter().map(Serialize::serialized_len).sum()
+    }
+}
+
+impl Serialize for Packet {
+    fn serialize(self, dst: &mut BytesMut) -> Result<()> {
+        let len = self.serialized_len();
+        dst.reserve(len);
+        match self {
+            Packet::SenderReport(sender_report) => {
+                put_header(
+                    dst,
+                    sender_report.reports.len(),
+                    PACKET_TYPE_SENDER_REPORT,
+                    len,
+                )?;
+                dst.put_u32(sender_report.ssrc);
+                dst.put_u64(sender_report.ntp_timestamp);
+                dst.put_u32(sender_report.rtp_timestamp);
+                dst.put_u32(sender_report.packet_count);
+                dst.put_u32(sender_report.octet_count);
+                for report in sender_report.reports {
+                    report.serialize(dst)?;
+                }
+            }
+            Packet::ReceiverReport(receiver_report) => {
+                put_header(
+                    dst,
+                    receiver_report.reports.len(),
+                    PACKET_TYPE_RECEIVER_REPORT,
+                    len,
+                )?;
+                dst.put_u32(receiver_report.ssrc);
+                for report in receiver_report.reports {
+                    report.serialize(dst)?;
+                }
+            }
+            Packet::SourceDescription(source_description) => {
+                put_header(
+                    dst,
+                    source_description.chunks.len(),
+                    PACKET_TYPE_SOURCE_DESCRIPTION,
+                    len,
+                )?;
+                for chunk in source_description.chunks {
+                    let chunk_len = chunk_serialized_len(&chunk);
+                    dst.put_u32(chunk.ssrc);
+                    let mut items_len = 4;
+                    for item in chunk.items {
+                        dst.put_u8(item.kind.as_number());
+                        dst.put_u8(text_len(item.value.len())?);
+                        items_len += 2 + item.value.len();
+                        dst.put(item.value);
+                    }
+                    // null item terminates the list and is followed by padding
+                    dst.put_bytes(0x00, chunk_len - items_len);
+                }
+            }
+            Packet::Goodbye(goodbye) => {
+                put_header(dst, goodbye.sources.len(), PACKET_TYPE_GOODBYE, len)?;
+                for source in goodbye.sources {
+                    dst.put_u32(source);
+                }
+                if let Some(reason) = goodbye.reason {
+                    let reason_len = reason.len();
+                    dst.put_u8(text_len(reason_len)?);
+                    dst.put(reason);
+                    dst.put_bytes(0x00, pad4(1 + reason_len) - (1 + reason_len));
+                }
+            }
+            Packet::App(app) => {
+                if app.data.len() % 4 != 0 {
+                    return Err(Error::RtcpLengthInvalid {
+                        len: app.data.len(),
+                    });
+                }
+                put_header(dst, app.subtype as usize, PACKET_TYPE_APP, len)?;
+                dst.put_u32(app.ssrc);
+                dst.put_slice(&app.name);
+                dst.put(app.data);
+            }
+            Packet::Nack(nack) => {
+                put_header(dst, 1, PACKET_TYPE_TRANSPORT_FEEDBACK, len)?;
+                dst.put_u32(nack.sender_ssrc);
+                dst.put_u32(nack.media_ssrc);
+                for (pid, blp) in nack_pairs(&nack.lost) {
+                    dst.put_u16(pid);
+                    dst.put_u16(blp);
+                }
+            }
+            Packet::Pli(pli) => {
+                put_header(dst, 1, PACKET_TYPE_PAYLOAD_FEEDBACK, len)?;
+                dst.put_u32(pli.sender_ssrc);
+                dst.put_u32(pli.media_ssrc);
+            }
+            Packet::Fir(fir) => {
+                put_header(dst, 4, PACKET_TYPE_PAYLOAD_FEEDBACK, len)?;
+                dst.put_u32(fir.sender_ssrc);
+                dst.put_u32(0);
+                for entry in fir.entries {
+                    dst.put_u32(entry.ssrc);
+                    dst.put_u8(entry.sequence_number);
+                    dst.put_bytes(0x00, 3);
+                }
+            }
+            Packet::Remb(remb) => {
+                let num_ssrc: u8 =
+                    remb.ssrcs
+                        .len()
+                        .try_into()
+                        .map_err(|_| Error::RtcpCountInvalid {
+                            count: remb.ssrcs.len(),
+                        })?;
+                put_header(dst, 15, PACKET_TYPE_PAYLOAD_FEEDBACK, len)?;
+                dst.put_u32(remb.sender_ssrc);
+                dst.put_u32(0);
+                dst.put_slice(b"REMB");
+                dst.put_u8(num_ssrc);
+                let mut exponent = 0;
+                while remb.bitrate >> exponent > 0x3ffff {
+                    exponent += 1;
+                }
+                let mantissa = (remb.bitrate >> exponent) as u32;
+                dst.put_u8((exponent << 2) as u8 | (mantissa >> 16) as u8);
+                dst.put_u16(mantissa as u16);
+                for ssrc in remb.ssrcs {
+                    dst.put_u32(ssrc);
+                }
+            }
+            Packet::Unknown(unknown) => {
+                if unknown.payload.len() % 4 != 0 {
+                    return Err(Error::RtcpLengthInvalid {
+                        len: unknown.payload.len(),
+                    });
+                }
+                put_header(dst, unknown.count as usize, unknown.packet_type, len)?;
+                dst.put(unknown.payload);
+            }
+        }
+        Ok(())
+    }
+
+    fn serialized_len(&self) -> usize {
+        4 + match self {
+            Packet::SenderReport(sender_report) => 24 + sender_report.reports.len() * 24,
+            Packet::ReceiverReport(receiver_report) => 4 + receiver_report.reports.len() * 24,
+            Packet::SourceDescription(source_description) => source_description
+                .chunks
+                .iter()
+                .map(chunk_serialized_len)
+                .sum(),
+            Packet::Goodbye(goodbye) => {
+                goodbye.sources.len() * 4
+                    + goodbye
+                        .reason
+                        .as_ref()
+                        .map(|reason| pad4(1 + reason.len()))
+                        .unwrap_or(0)
+            }
+            Packet::App(app) => 8 + app.data.len(),
+            Packet::Nack(nack) => 8 + nack_pairs(&nack.lost).len() * 4,
+            Packet::Pli(_) => 8,
+            Packet::Fir(fir) => 8 + fir.entries.len() * 8,
+            Packet::Remb(remb) => 16 + remb.ssrcs.len() * 4,
+            Packet::Unknown(unknown) => unknown.payload.len(),
+        }
+    }
+}
+
+impl Serialize for ReportBlock {
+    fn serialize(self, dst: &mut BytesMut) -> Result<()> {
+        dst.put_u32(self.ssrc);
+        dst.put_u8(self.fraction_lost);
+        // clamp to 24-bit signed range
+        let cumulative_lost = self.cumulative_lost.clamp(-0x80_0000, 0x7f_ffff);
+        dst.put_uint((cumulative_lost as u32 & 0x00ff_ffff) as u64, 3);
+        dst.put_u32(self.highest_sequence_number);
+        dst.put_u32(self.jitter);
+        dst.put_u32(self.last_sender_report);
+        dst.put_u32(self.delay_since_last_sender_report);
+        Ok(())
+    }
+
+    fn serialized_len(&self) -> usize {
+        24
+    }
+}
+
+/// Write common RTCP header.
+///
+/// # Arguments
+///
+/// * `dst` - Buffer to write to.
+/// * `count` - Value of count, subtype or format field.
+/// * `packet_type` - Packet type.
+/// * `len` - Length of entire packet in bytes, including header.
+fn put_header(dst: &mut BytesMut, count: usize, packet_type: u8, len: usize) -> Result<()> {
+    if count > 0x1f {
+        return Err(Error::RtcpCountInvalid { count });
+    }
+    let words: u16 = (len / 4 - 1)
+        .try_into()
+        .map_err(|_| Error::RtcpLengthInvalid { len })?;
+    dst.put_u8(0x80 | count as u8);
+    dst.put_u8(packet_type);
+    dst.put_u16(words);
+    Ok(())
+}
+
+fn chunk_serialized_len(chunk: &SourceDescriptionChunk) -> usize {
+    let items_len = chunk
+        .items
+        .iter()
+        .map(|item| 2 + item.value.len())
+        .sum::<usize>();
+    // there is always at least one null octet to terminate the list of items
+    pad4(4 + items_len + 1)
+}
+
+/// Group lost sequence numbers into packet identifier and bitmask of following lost packets.
+fn nack_pairs(lost: &[u16]) -> Vec<(u16, u16)> {
+    let mut pairs: Vec<(u16, u16)> = Vec::new();
+    for &sequence_number in lost {
+        if let Some((pid, blp)) = pairs.last_mut() {
+            let distance = sequence_number.wrapping_sub(*pid);
+            if (1..=16).contains(&distance) {
+                *blp |= 1 << (distance - 1);
+                continue;
+            }
+        }
+        pairs.push((sequence_number, 0));
+    }
+    pairs
+}
+
+#[inline]
+fn text_len(len: usize) -> Result<u8> {
+    len.try_into()
+        .map_err(|_| Error::RtcpTextLengthInvalid { len })
+}
+
+#[inline]
+fn pad4(len: usize) -> usize {
+    (len + 3) & !3
+}
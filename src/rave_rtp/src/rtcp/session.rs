@@ -0,0 +1,481 @@
+//! RTCP session: statistics of all sources and scheduling of reports (RFC 3550, section 6.3).
+
+use std::collections::BTreeMap;
+use std::time::{Duration, Instant, SystemTime};
+
+use bytes::Bytes;
+
+use crate::packet::{Header, Packet as RtpPacket};
+use crate::rtcp::statistics::{round_trip_time, ReceiverStatistics, SenderStatistics};
+use crate::rtcp::{
+    Compound, Goodbye, Packet, ReceiverReport, ReportBlock, SourceDescription,
+    SourceDescriptionChunk, SourceDescriptionItem, SourceDescriptionItemKind,
+};
+use crate::serialize::Serialize;
+
+/// Minimum interval between reports.
+const MIN_INTERVAL: f64 = 5.0;
+/// Fraction of RTCP bandwidth reserved for senders.
+const SENDER_BANDWIDTH_FRACTION: f64 = 0.25;
+/// Fraction of RTCP bandwidth reserved for receivers.
+const RECEIVER_BANDWIDTH_FRACTION: f64 = 1.0 - SENDER_BANDWIDTH_FRACTION;
+/// Compensates for the timer reconsideration algorithm converging to a lower value (e - 3/2).
+const COMPENSATION: f64 = std::f64::consts::E - 1.5;
+/// Size of IPv4 and UDP headers, which count towards the average RTCP packet size.
+const HEADER_OVERHEAD: usize = 28;
+/// Maximum number of report blocks in a single report.
+const MAX_REPORT_BLOCKS: usize = 31;
+
+/// RTCP session of a single local source.
+///
+/// Keeps track of reception statistics for every remote source, transmission statistics for the
+/// local source and round-trip time. Produces sender or receiver reports at the randomized
+/// interval described in RFC 3550, section 6.3.
+///
+/// # Example
+///
+/// ```
+/// use std::time::{Instant, SystemTime};
+///
+/// use rave_rtp::rtcp::session::Session;
+///
+/// let mut session = Session::new(0x1234, "rave@example.com", 90000, Instant::now());
+/// // call `session.receive_rtp(...)` for every received packet, then periodically:
+/// if let Some(report) = session.poll_report(Instant::now(), SystemTime::now()) {
+///     // serialize and send report
+/// }
+/// ```
+#[derive(Debug)]
+pub struct Session {
+    ssrc: u32,
+    cname: Bytes,
+    sender: SenderStatistics,
+    sources: BTreeMap<u32, ReceiverStatistics>,
+    clock_rate: u32,
+    round_trip_time: Option<Duration>,
+    rtcp_bandwidth: Option<f64>,
+    avg_rtcp_size: f64,
+    initial: bool,
+    we_sent: bool,
+    next_report: Instant,
+}
+
+impl Session {
+    /// Create new session.
+    ///
+    /// # Arguments
+    ///
+    /// * `ssrc` - Identifier of local source.
+    /// * `cname` - Canonical name of local endpoint, sent in every report.
+    /// * `clock_rate` - RTP clock rate of the media in this session.
+    /// * `now` - Current time. The first report is scheduled relative to it.
+    pub fn new(ssrc: u32, cname: &str, clock_rate: u32, now: Instant) -> Self {
+        let mut session = Self {
+            ssrc,
+            cname: Bytes::copy_from_slice(cname.as_bytes()),
+            sender: SenderStatistics::new(ssrc, clock_rate),
+            sources: BTreeMap::new(),
+            clock_rate,
+            round_trip_time: None,
+            rtcp_bandwidth: None,
+            avg_rtcp_size: 0.0,
+            initial: true,
+            we_sent: false,
+            next_report: now,
+        };
+        session.avg_rtcp_size =
+            (session.compound(Vec::new()).serialized_len() + HEADER_OVERHEAD) as f64;
+        session.next_report = now + session.interval();
+        session
+    }
+
+    /// Set session bandwidth. Five percent of it is used for RTCP.
+    ///
+    /// Without a session bandwidth, reports are sent at the minimum interval of five seconds.
+    ///
+    /// # Arguments
+    ///
+    /// * `bits_per_second` - Session bandwidth in bits per second.
+    pub fn with_session_bandwidth(mut self, bits_per_second: u64) -> Self {
+        self.rtcp_bandwidth = Some(bits_per_second as f64 * 0.05 / 8.0);
+        self
+    }
+
+    /// Update statistics with received RTP packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - Header of received packet.
+    /// * `arrival` - Time at which the packet arrived.
+    pub fn receive_rtp(&mut self, header: &Header, arrival: Instant) {
+        if header.ssrc == self.ssrc {
+            return;
+        }
+        self.sources
+            .entry(header.ssrc)
+            .or_insert_with(|| ReceiverStatistics::new(header.ssrc, self.clock_rate))
+            .update(header, arrival);
+    }
+
+    /// Update statistics with sent RTP packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - Packet that was sent.
+    /// * `sent` - Time at which the packet was sent.
+    pub fn send_rtp(&mut self, packet: &RtpPacket, sent: Instant) {
+        self.sender.update(packet, sent);
+        self.we_sent = true;
+    }
+
+    /// Process received RTCP packet.
+    ///
+    /// Sender reports are remembered so that they can be referred to in our own reports, and
+    /// report blocks about the local source are used to calculate the round-trip time. Sources
+    /// that say goodbye are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `compound` - Received packet.
+    /// * `arrival` - Time at which the packet arrived.
+    /// * `wall_clock` - Wall clock time at which the packet arrived.
+    pub fn receive_rtcp(&mut self, compound: &Compound, arrival: Instant, wall_clock: SystemTime) {
+        self.update_avg_rtcp_size(compound.serialized_len());
+        for packet in &compound.0 {
+            let reports = match packet {
+                Packet::SenderReport(sender_report) => {
+                    if sender_report.ssrc != self.ssrc {
+                        self.sources
+                            .entry(sender_report.ssrc)
+                            .or_insert_with(|| {
+                                ReceiverStatistics::new(sender_report.ssrc, self.clock_rate)
+                            })
+                            .update_with_sender_report(sender_report, arrival);
+                    }
+                    &sender_report.reports
+                }
+                Packet::ReceiverReport(receiver_report) => &receiver_report.reports,
+                Packet::Goodbye(goodbye) => {
+                    for source in &goodbye.sources {
+                        self.sources.remove(source);
+                    }
+                    continue;
+                }
+                _ => continue,
+            };
+            if let Some(rtt) = reports
+                .iter()
+                .filter(|report| report.ssrc == self.ssrc)
+                .find_map(|report| round_trip_time(report, wall_clock))
+            {
+                self.round_trip_time = Some(rtt);
+            }
+        }
+    }
+
+    /// Produce report if it is time to send one.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time.
+    /// * `wall_clock` - Current wall clock time.
+    ///
+    /// # Return value
+    ///
+    /// Report to send, or `None` if it is not time to send a report yet.
+    pub fn poll_report(&mut self, now: Instant, wall_clock: SystemTime) -> Option<Compound> {
+        (now >= self.next_report).then(|| self.report(now, wall_clock))
+    }
+
+    /// Produce report now and schedule the next one.
+    ///
+    /// The report is a sender report if packets were sent since the previous report, and a
+    /// receiver report otherwise. It is followed by a source description with the canonical name.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time.
+    /// * `wall_clock` - Current wall clock time.
+    pub fn report(&mut self, now: Instant, wall_clock: SystemTime) -> Compound {
+        let reports = self
+            .sources
+            .values_mut()
+            .filter(|source| source.is_valid())
+            .take(MAX_REPORT_BLOCKS)
+            .map(|source| source.report_block(now))
+            .collect::<Vec<_>>();
+        let sender_report = self
+            .sender
+            .sender_report(now, wall_clock, Vec::new())
+            .filter(|_| self.we_sent);
+        let compound = match sender_report {
+            Some(mut sender_report) => {
+                sender_report.reports = reports;
+                Compound(vec![
+                    Packet::SenderReport(sender_report),
+                    self.description(),
+                ])
+            }
+            None => self.compound(reports),
+        };
+        self.update_avg_rtcp_size(compound.serialized_len());
+        self.we_sent = false;
+        self.initial = false;
+        self.next_report = now + self.interval();
+        compound
+    }
+
+    /// Produce goodbye for the local source, to send when leaving the session.
+    pub fn goodbye(&self) -> Compound {
+        Compound(vec![
+            Packet::ReceiverReport(ReceiverReport {
+                ssrc: self.ssrc,
+                reports: Vec::new(),
+            }),
+            Packet::Goodbye(Goodbye {
+                sources: vec![self.ssrc],
+                reason: None,
+            }),
+        ])
+    }
+
+    /// Time at which the next report is due.
+    #[inline]
+    pub fn next_report(&self) -> Instant {
+        self.next_report
+    }
+
+    /// Statistics of remote source.
+    #[inline]
+    pub fn source(&self, ssrc: u32) -> Option<&ReceiverStatistics> {
+        self.sources.get(&ssrc)
+    }
+
+    /// Statistics of all remote sources.
+    pub fn sources(&self) -> impl Iterator<Item = &ReceiverStatistics> {
+        self.sources.values()
+    }
+
+    /// Statistics of local source.
+    #[inline]
+    pub fn sender(&self) -> &SenderStatistics {
+        &self.sender
+    }
+
+    /// Most recent round-trip time, calculated from reports that receivers sent about the local
+    /// source.
+    #[inline]
+    pub fn round_trip_time(&self) -> Option<Duration> {
+        self.round_trip_time
+    }
+
+    fn compound(&self, reports: Vec<ReportBlock>) -> Compound {
+        Compound(vec![
+            Packet::ReceiverReport(ReceiverReport {
+                ssrc: self.ssrc,
+                reports,
+            }),
+            self.description(),
+        ])
+    }
+
+    fn description(&self) -> Packet {
+        Packet::SourceDescription(SourceDescription {
+            chunks: vec![SourceDescriptionChunk {
+                ssrc: self.ssrc,
+                items: vec![SourceDescriptionItem {
+                    kind: SourceDescriptionItemKind::Cname,
+                    value: self.cname.clone(),
+                }],
+            }],
+        })
+    }
+
+    fn update_avg_rtcp_size(&mut self, len: usize) {
+        let size = (len + HEADER_OVERHEAD) as f64;
+        self.avg_rtcp_size = size / 16.0 + self.avg_rtcp_size * 15.0 / 16.0;
+    }
+
+    /// Calculate randomized interval until next report (RFC 3550, appendix A.7).
+    fn interval(&self) -> Duration {
+        let mut min_interval = MIN_INTERVAL;
+        if self.initial {
+            min_interval /= 2.0;
+        }
+
+        let interval = match self.rtcp_bandwidth {
+            Some(mut rtcp_bandwidth) if rtcp_bandwidth > 0.0 => {
+                let members = (self.sources.len() + 1) as f64;
+                let senders = (self.sources.len() + self.we_sent as usize) as f64;
+                let mut n = members;
+                // if there are only a few senders, they get a dedicated share of the bandwidth
+                if senders <= members * SENDER_BANDWIDTH_FRACTION {
+                    if self.we_sent {
+                        rtcp_bandwidth *= SENDER_BANDWIDTH_FRACTION;
+                        n = senders;
+                    } else {
+                        rtcp_bandwidth *= RECEIVER_BANDWIDTH_FRACTION;
+                        n -= senders;
+                    }
+                }
+                (self.avg_rtcp_size * n / rtcp_bandwidth).max(min_interval)
+            }
+            _ => min_interval,
+        };
+
+        // randomize between 0.5 and 1.5 times the calculated interval to avoid synchronization
+        let interval = interval * (rand::random::<f64>() + 0.5) / COMPENSATION;
+        Duration::from_secs_f64(interval)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::packet::Version;
+    use crate::rtcp::statistics::{ntp_short, ntp_timestamp};
+    use crate::rtcp::SenderReport;
+
+    fn header(ssrc: u32, sequence_number: u16) -> Header {
+        Header {
+            version: Version::Version2,
+            padding: false,
+            marker: false,
+            payload_type: 96,
+            sequence_number,
+            timestamp: 0,
+            ssrc,
+            csrc: Vec::new(),
+            extension: None,
+        }
+    }
+
+    #[test]
+    fn interval() {
+        let now = Instant::now();
+        let mut session = Session::new(1, "a", 90000, now);
+        let initial = session.next_report() - now;
+        assert!(initial >= Duration::from_secs_f64(1.25 / COMPENSATION));
+        assert!(initial <= Duration::from_secs_f64(3.75 / COMPENSATION));
+
+        assert!(session.poll_report(now, SystemTime::now()).is_none());
+        let later = now + Duration::from_secs(4);
+        assert!(session.poll_report(later, SystemTime::now()).is_some());
+        let interval = session.next_report() - later;
+        assert!(interval >= Duration::from_secs_f64(2.5 / COMPENSATION));
+        assert!(interval <= Duration::from_secs_f64(7.5 / COMPENSATION));
+    }
+
+    #[test]
+    fn receiver_report() {
+        let now = Instant::now();
+        let mut session = Session::new(1, "rave", 90000, now);
+        for seq in [1, 2, 3, 5] {
+            session.receive_rtp(&header(2, seq), now);
+        }
+        let report = session.report(now, SystemTime::now());
+        let Packet::ReceiverReport(receiver_report) = &report.0[0] else {
+            panic!("expected receiver report");
+        };
+        assert_eq!(receiver_report.ssrc, 1);
+        assert_eq!(receiver_report.reports.len(), 1);
+        assert_eq!(receiver_report.reports[0].ssrc, 2);
+        assert_eq!(receiver_report.reports[0].cumulative_lost, 1);
+        assert_eq!(receiver_report.reports[0].highest_sequence_number, 5);
+        assert_eq!(
+            report.0[1],
+            Packet::SourceDescription(SourceDescription {
+                chunks: vec![SourceDescriptionChunk {
+                    ssrc: 1,
+                    items: vec![SourceDescriptionItem::cname("rave")],
+                }],
+            }),
+        );
+    }
+
+    #[test]
+    fn sender_report_and_round_trip_time() {
+        let now = Instant::now();
+        let wall_clock = SystemTime::now();
+        let mut session = Session::new(1, "rave", 90000, now);
+        session.send_rtp(
+            &RtpPacket::new(header(1, 1), Bytes::from_static(&[0; 10])),
+            now,
+        );
+        let report = session.report(now, wall_clock);
+        let Packet::SenderReport(sender_report) = &report.0[0] else {
+            panic!("expected sender report");
+        };
+        assert_eq!(sender_report.packet_count, 1);
+        assert_eq!(sender_report.octet_count, 10);
+
+        // receiver answers 100ms later after holding on to the report for 50ms
+        let receiver_report = Compound(vec![Packet::ReceiverReport(ReceiverReport {
+            ssrc: 2,
+            reports: vec![ReportBlock {
+                ssrc: 1,
+                last_sender_report: ntp_short(sender_report.ntp_timestamp),
+                delay_since_last_sender_report: 3277,
+                ..Default::default()
+            }],
+        })]);
+        session.receive_rtcp(
+            &receiver_report,
+            now,
+            wall_clock + Duration::from_millis(100),
+        );
+        let rtt = session.round_trip_time().unwrap();
+        assert!(rtt > Duration::from_millis(49) && rtt < Duration::from_millis(51));
+
+        // no packets sent since previous report
+        let report = session.report(now, wall_clock);
+        assert!(matches!(report.0[0], Packet::ReceiverReport(_)));
+    }
+
+    #[test]
+    fn sender_report_and_goodbye_from_source() {
+        let now = Instant::now();
+        let wall_clock = SystemTime::now();
+        let mut session = Session::new(1, "rave", 90000, now);
+        for seq in [1, 2] {
+            session.receive_rtp(&header(2, seq), now);
+        }
+        let ntp = ntp_timestamp(wall_clock);
+        session.receive_rtcp(
+            &Compound(vec![Packet::SenderReport(SenderReport {
+                ssrc: 2,
+                ntp_timestamp: ntp,
+                rtp_timestamp: 0,
+                packet_count: 2,
+                octet_count: 0,
+                reports: Vec::new(),
+            })]),
+            now,
+            wall_clock,
+        );
+        let report = session.report(now + Duration::from_secs(1), wall_clock);
+        let Packet::ReceiverReport(receiver_report) = &report.0[0] else {
+            panic!("expected receiver report");
+        };
+        assert_eq!(
+            receiver_report.reports[0].last_sender_report,
+            ntp_short(ntp)
+        );
+        assert_eq!(
+            receiver_report.reports[0].delay_since_last_sender_report,
+            65536
+        );
+
+        session.receive_rtcp(
+            &Compound(vec![Packet::Goodbye(Goodbye {
+                sources: vec![2],
+                reason: None,
+            })]),
+            now,
+            wall_clock,
+        );
+        assert!(session.source(2).is_none());
+    }
+}
@@ -0,0 +1,535 @@
+//! Reception and transmission statistics (RFC 3550, appendix A).
+
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::packet::{Header, Packet};
+use crate::rtcp::{ReportBlock, SenderReport};
+
+/// Number of sequential packets required before a new source is considered valid.
+const MIN_SEQUENTIAL: u32 = 2;
+/// Maximum forward jump in sequence numbers that is not considered a restart.
+const MAX_DROPOUT: u16 = 3000;
+/// Maximum backward jump in sequence numbers that is considered reordering.
+const MAX_MISORDER: u16 = 100;
+const RTP_SEQ_MOD: u32 = 1 << 16;
+
+/// Seconds between NTP epoch (1900) and Unix epoch (1970).
+const NTP_UNIX_OFFSET: u64 = 2_208_988_800;
+
+/// Convert wall clock time to 64-bit NTP timestamp.
+pub fn ntp_timestamp(time: SystemTime) -> u64 {
+    let since_unix_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_unix_epoch.as_secs() + NTP_UNIX_OFFSET;
+    let fraction = ((since_unix_epoch.subsec_nanos() as u64) << 32) / 1_000_000_000;
+    (seconds << 32) | fraction
+}
+
+/// Middle 32 bits of NTP timestamp, as used for LSR, DLSR and round-trip time.
+#[inline]
+pub fn ntp_short(ntp_timestamp: u64) -> u32 {
+    (ntp_timestamp >> 16) as u32
+}
+
+/// Calculate round-trip time from report block that a receiver sent about one of our sources.
+///
+/// # Arguments
+///
+/// * `report` - Report block about our source.
+/// * `arrival` - Wall clock time at which the report arrived.
+///
+/// # Return value
+///
+/// Round-trip time, or `None` if the receiver did not receive a sender report yet.
+pub fn round_trip_time(report: &ReportBlock, arrival: SystemTime) -> Option<Duration> {
+    if report.last_sender_report == 0 {
+        return None;
+    }
+    let rtt = ntp_short(ntp_timestamp(arrival))
+        .wrapping_sub(report.last_sender_report)
+        .wrapping_sub(report.delay_since_last_sender_report);
+    // a negative round-trip time means the clocks are off
+    (rtt < 0x8000_0000).then(|| Duration::from_secs_f64(rtt as f64 / 65536.0))
+}
+
+/// Statistics of a source that we receive RTP packets from.
+#[derive(Debug, Clone)]
+pub struct ReceiverStatistics {
+    ssrc: u32,
+    clock_rate: u32,
+    max_seq: u16,
+    cycles: u32,
+    base_seq: u32,
+    bad_seq: u32,
+    probation: u32,
+    received: u32,
+    expected_prior: u32,
+    received_prior: u32,
+    fraction_lost: u8,
+    epoch: Option<Instant>,
+    transit: Option<u32>,
+    jitter: u32,
+    last_sender_report: Option<(u32, Instant)>,
+}
+
+impl ReceiverStatistics {
+    /// Create statistics for source.
+    ///
+    /// # Arguments
+    ///
+    /// * `ssrc` - Source identifier.
+    /// * `clock_rate` - RTP clock rate of source, used to calculate jitter.
+    pub fn new(ssrc: u32, clock_rate: u32) -> Self {
+        Self {
+            ssrc,
+            clock_rate,
+            max_seq: 0,
+            cycles: 0,
+            base_seq: 0,
+            bad_seq: RTP_SEQ_MOD + 1,
+            probation: MIN_SEQUENTIAL,
+            received: 0,
+            expected_prior: 0,
+            received_prior: 0,
+            fraction_lost: 0,
+            epoch: None,
+            transit: None,
+            jitter: 0,
+            last_sender_report: None,
+        }
+    }
+
+    /// Update statistics with received RTP packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `header` - Header of received packet.
+    /// * `arrival` - Time at which the packet arrived.
+    ///
+    /// # Return value
+    ///
+    /// `false` if the source is still on probation or the packet was ignored because of a large
+    /// jump in sequence numbers.
+    pub fn update(&mut self, header: &Header, arrival: Instant) -> bool {
+        if self.epoch.is_none() {
+            self.init_seq(header.sequence_number);
+            self.max_seq = header.sequence_number.wrapping_sub(1);
+            self.epoch = Some(arrival);
+        }
+        if !self.update_seq(header.sequence_number) {
+            return false;
+        }
+        self.update_jitter(header.timestamp, arrival);
+        true
+    }
+
+    /// Remember sender report received from source, so that LSR and DLSR can be reported.
+    ///
+    /// # Arguments
+    ///
+    /// * `sender_report` - Sender report sent by source.
+    /// * `arrival` - Time at which the report arrived.
+    pub fn update_with_sender_report(&mut self, sender_report: &SenderReport, arrival: Instant) {
+        self.last_sender_report = Some((ntp_short(sender_report.ntp_timestamp), arrival));
+    }
+
+    /// Produce report block for source.
+    ///
+    /// This also starts a new reporting interval for calculating the fraction of lost packets.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time, to calculate delay since last sender report.
+    pub fn report_block(&mut self, now: Instant) -> ReportBlock {
+        let expected = self.expected();
+        let expected_interval = expected.wrapping_sub(self.expected_prior);
+        self.expected_prior = expected;
+        let received_interval = self.received.wrapping_sub(self.received_prior);
+        self.received_prior = self.received;
+        let lost_interval = expected_interval as i64 - received_interval as i64;
+        self.fraction_lost = if expected_interval == 0 || lost_interval <= 0 {
+            0
+        } else {
+            ((lost_interval << 8) / expected_interval as i64).min(255) as u8
+        };
+
+        let (last_sender_report, delay_since_last_sender_report) = match self.last_sender_report {
+            Some((last_sender_report, arrival)) => (
+                last_sender_report,
+                (now.saturating_duration_since(arrival).as_secs_f64() * 65536.0) as u32,
+            ),
+            None => (0, 0),
+        };
+
+        ReportBlock {
+            ssrc: self.ssrc,
+            fraction_lost: self.fraction_lost,
+            cumulative_lost: self.cumulative_lost() as i32,
+            highest_sequence_number: self.extended_highest_sequence_number(),
+            jitter: self.jitter(),
+            last_sender_report,
+            delay_since_last_sender_report,
+        }
+    }
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        self.ssrc
+    }
+
+    /// Whether or not enough sequential packets were received to consider the source valid.
+    #[inline]
+    pub fn is_valid(&self) -> bool {
+        self.probation == 0
+    }
+
+    /// Number of packets received, including duplicates.
+    #[inline]
+    pub fn packets_received(&self) -> u32 {
+        self.received
+    }
+
+    /// Highest sequence number received, extended with the number of sequence number cycles.
+    #[inline]
+    pub fn extended_highest_sequence_number(&self) -> u32 {
+        self.cycles.wrapping_add(self.max_seq as u32)
+    }
+
+    /// Total number of packets lost, clamped to the 24-bit range of report blocks.
+    pub fn cumulative_lost(&self) -> i64 {
+        (self.expected() as i64 - self.received as i64).clamp(-0x80_0000, 0x7f_ffff)
+    }
+
+    /// Fraction of packets lost in the last reporting interval, as fixed point number (divide by
+    /// 256).
+    #[inline]
+    pub fn fraction_lost(&self) -> u8 {
+        self.fraction_lost
+    }
+
+    /// Interarrival jitter in RTP timestamp units.
+    #[inline]
+    pub fn jitter(&self) -> u32 {
+        self.jitter >> 4
+    }
+
+    /// Interarrival jitter as duration.
+    pub fn jitter_duration(&self) -> Duration {
+        if self.clock_rate == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_secs_f64(self.jitter() as f64 / self.clock_rate as f64)
+    }
+
+    fn expected(&self) -> u32 {
+        if self.received == 0 {
+            return 0;
+        }
+        self.extended_highest_sequence_number()
+            .wrapping_sub(self.base_seq)
+            .wrapping_add(1)
+    }
+
+    fn init_seq(&mut self, seq: u16) {
+        self.base_seq = seq as u32;
+        self.max_seq = seq;
+        self.bad_seq = RTP_SEQ_MOD + 1;
+        self.cycles = 0;
+        self.received = 0;
+        self.received_prior = 0;
+        self.expected_prior = 0;
+    }
+
+    fn update_seq(&mut self, seq: u16) -> bool {
+        let udelta = seq.wrapping_sub(self.max_seq);
+        if self.probation > 0 {
+            // packet is in sequence
+            if seq == self.max_seq.wrapping_add(1) {
+                self.probation -= 1;
+                self.max_seq = seq;
+                if self.probation == 0 {
+                    self.init_seq(seq);
+                    self.received += 1;
+                    return true;
+                }
+            } else {
+                self.probation = MIN_SEQUENTIAL - 1;
+                self.max_seq = seq;
+            }
+            return false;
+        } else if udelta < MAX_DROPOUT {
+            // in order, with permissible gap
+            if seq < self.max_seq {
+                // sequence number wrapped
+                self.cycles = self.cycles.wrapping_add(RTP_SEQ_MOD);
+            }
+            self.max_seq = seq;
+        } else if udelta as u32 <= RTP_SEQ_MOD - MAX_MISORDER as u32 {
+            // the sequence number made a very large jump
+            if seq as u32 == self.bad_seq {
+                // two sequential packets, assume that the other side restarted without telling us
+                self.init_seq(seq);
+                self.transit = None;
+            } else {
+                self.bad_seq = (seq as u32 + 1) & (RTP_SEQ_MOD - 1);
+                return false;
+            }
+        } else {
+            // duplicate or reordered packet
+        }
+        self.received += 1;
+        true
+    }
+
+    fn update_jitter(&mut self, timestamp: u32, arrival: Instant) {
+        let Some(epoch) = self.epoch else {
+            return;
+        };
+        let arrival = to_timestamp_units(arrival.saturating_duration_since(epoch), self.clock_rate);
+        let transit = arrival.wrapping_sub(timestamp);
+        if let Some(prior_transit) = self.transit.replace(transit) {
+            let d = (transit.wrapping_sub(prior_transit) as i32).unsigned_abs();
+            self.jitter = self
+                .jitter
+                .wrapping_add(d)
+                .wrapping_sub(self.jitter.wrapping_add(8) >> 4);
+        }
+    }
+}
+
+/// Statistics of a source that we send RTP packets from.
+#[derive(Debug, Clone)]
+pub struct SenderStatistics {
+    ssrc: u32,
+    clock_rate: u32,
+    packet_count: u32,
+    octet_count: u32,
+    last_sent: Option<(u32, Instant)>,
+}
+
+impl SenderStatistics {
+    /// Create statistics for source.
+    ///
+    /// # Arguments
+    ///
+    /// * `ssrc` - Source identifier.
+    /// * `clock_rate` - RTP clock rate of source, used to calculate RTP timestamps of reports.
+    pub fn new(ssrc: u32, clock_rate: u32) -> Self {
+        Self {
+            ssrc,
+            clock_rate,
+            packet_count: 0,
+            octet_count: 0,
+            last_sent: None,
+        }
+    }
+
+    /// Update statistics with sent RTP packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - Packet that was sent.
+    /// * `sent` - Time at which the packet was sent.
+    pub fn update(&mut self, packet: &Packet, sent: Instant) {
+        self.packet_count = self.packet_count.wrapping_add(1);
+        self.octet_count = self.octet_count.wrapping_add(packet.payload.len() as u32);
+        self.last_sent = Some((packet.header.timestamp, sent));
+    }
+
+    /// Produce sender report.
+    ///
+    /// The RTP timestamp of the report is extrapolated from the last packet that was sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - Current time.
+    /// * `wall_clock` - Current wall clock time.
+    /// * `reports` - Report blocks about sources that we receive.
+    ///
+    /// # Return value
+    ///
+    /// Sender report, or `None` if no packets were sent yet.
+    pub fn sender_report(
+        &self,
+        now: Instant,
+        wall_clock: SystemTime,
+        reports: Vec<ReportBlock>,
+    ) -> Option<SenderReport> {
+        let (timestamp, sent) = self.last_sent?;
+        let elapsed = to_timestamp_units(now.saturating_duration_since(sent), self.clock_rate);
+        Some(SenderReport {
+            ssrc: self.ssrc,
+            ntp_timestamp: ntp_timestamp(wall_clock),
+            rtp_timestamp: timestamp.wrapping_add(elapsed),
+            packet_count: self.packet_count,
+            octet_count: self.octet_count,
+            reports,
+        })
+    }
+
+    #[inline]
+    pub fn ssrc(&self) -> u32 {
+        self.ssrc
+    }
+
+    #[inline]
+    pub fn packet_count(&self) -> u32 {
+        self.packet_count
+    }
+
+    #[inline]
+    pub fn octet_count(&self) -> u32 {
+        self.octet_count
+    }
+}
+
+/// Convert duration to RTP timestamp units (wrapping).
+#[inline]
+fn to_timestamp_units(duration: Duration, clock_rate: u32) -> u32 {
+    (duration.as_nanos() * clock_rate as u128 / 1_000_000_000) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::packet::Version;
+
+    fn header(sequence_number: u16, timestamp: u32) -> Header {
+        Header {
+            version: Version::Version2,
+            padding: false,
+            marker: false,
+            payload_type: 96,
+            sequence_number,
+            timestamp,
+            ssrc: 1,
+            csrc: Vec::new(),
+            extension: None,
+        }
+    }
+
+    #[test]
+    fn probation() {
+        let now = Instant::now();
+        let mut statistics = ReceiverStatistics::new(1, 90000);
+        assert!(!statistics.update(&header(10, 0), now));
+        assert!(!statistics.is_valid());
+        assert!(statistics.update(&header(11, 0), now));
+        assert!(statistics.is_valid());
+        assert_eq!(statistics.packets_received(), 1);
+        assert_eq!(statistics.extended_highest_sequence_number(), 11);
+    }
+
+    #[test]
+    fn loss_and_wrap() {
+        let now = Instant::now();
+        let mut statistics = ReceiverStatistics::new(1, 90000);
+        for seq in [65533, 65534, 65535, 1, 2, 3] {
+            statistics.update(&header(seq, 0), now);
+        }
+        assert_eq!(statistics.extended_highest_sequence_number(), 65536 + 3);
+        assert_eq!(statistics.cumulative_lost(), 1);
+        let report = statistics.report_block(now);
+        assert_eq!(report.cumulative_lost, 1);
+        // 1 out of 6 expected packets lost
+        assert_eq!(report.fraction_lost, (256 / 6) as u8);
+
+        for seq in [4, 5, 6, 7] {
+            statistics.update(&header(seq, 0), now);
+        }
+        assert_eq!(statistics.report_block(now).fraction_lost, 0);
+    }
+
+    #[test]
+    fn duplicates_make_loss_negative() {
+        let now = Instant::now();
+        let mut statistics = ReceiverStatistics::new(1, 90000);
+        for seq in [1, 2, 3, 3, 3] {
+            statistics.update(&header(seq, 0), now);
+        }
+        assert_eq!(statistics.cumulative_lost(), -2);
+        assert_eq!(statistics.report_block(now).fraction_lost, 0);
+    }
+
+    #[test]
+    fn restart_after_large_jump() {
+        let now = Instant::now();
+        let mut statistics = ReceiverStatistics::new(1, 90000);
+        for seq in [1, 2, 3] {
+            statistics.update(&header(seq, 0), now);
+        }
+        assert!(!statistics.update(&header(20000, 0), now));
+        assert!(statistics.update(&header(20001, 0), now));
+        assert_eq!(statistics.extended_highest_sequence_number(), 20001);
+        assert_eq!(statistics.cumulative_lost(), 0);
+    }
+
+    #[test]
+    fn jitter() {
+        let start = Instant::now();
+        let mut statistics = ReceiverStatistics::new(1, 1000);
+        for i in 0..100_u16 {
+            // packets sent every 10ms but every other packet arrives 5ms late
+            let arrival = start + Duration::from_millis(i as u64 * 10 + (i as u64 % 2) * 5);
+            statistics.update(&header(i, i as u32 * 10), arrival);
+        }
+        // converges to the mean deviation of 5 timestamp units
+        assert!((4..=5).contains(&statistics.jitter()));
+        assert_eq!(statistics.report_block(start).jitter, statistics.jitter());
+    }
+
+    #[test]
+    fn delay_since_last_sender_report() {
+        let now = Instant::now();
+        let mut statistics = ReceiverStatistics::new(1, 90000);
+        statistics.update_with_sender_report(
+            &SenderReport {
+                ssrc: 1,
+                ntp_timestamp: 0x0000_1234_5678_0000,
+                rtp_timestamp: 0,
+                packet_count: 0,
+                octet_count: 0,
+                reports: Vec::new(),
+            },
+            now,
+        );
+        let report = statistics.report_block(now + Duration::from_millis(500));
+        assert_eq!(report.last_sender_report, 0x1234_5678);
+        assert_eq!(report.delay_since_last_sender_report, 32768);
+    }
+
+    #[test]
+    fn round_trip() {
+        let sent = SystemTime::now();
+        let arrival = sent + Duration::from_millis(300);
+        let report = ReportBlock {
+            last_sender_report: ntp_short(ntp_timestamp(sent)),
+            // receiver held on to sender report for 100ms
+            delay_since_last_sender_report: 6554,
+            ..Default::default()
+        };
+        let rtt = round_trip_time(&report, arrival).unwrap();
+        assert!(rtt > Duration::from_millis(199) && rtt < Duration::from_millis(201));
+        assert_eq!(round_trip_time(&ReportBlock::default(), arrival), None);
+    }
+
+    #[test]
+    fn sender_report() {
+        let now = Instant::now();
+        let mut statistics = SenderStatistics::new(7, 90000);
+        assert!(statistics
+            .sender_report(now, SystemTime::now(), Vec::new())
+            .is_none());
+        let packet = Packet::new(header(1, 1000), Bytes::from_static(&[0; 100]));
+        statistics.update(&packet, now);
+        statistics.update(&packet, now);
+        let report = statistics
+            .sender_report(now + Duration::from_secs(1), SystemTime::now(), Vec::new())
+            .unwrap();
+        assert_eq!(report.ssrc, 7);
+        assert_eq!(report.packet_count, 2);
+        assert_eq!(report.octet_count, 200);
+        assert_eq!(report.rtp_timestamp, 91000);
+    }
+}
@@ -0,0 +1,793 @@
+//! Secure RTP and secure RTCP (RFC 3711, RFC 7714).
+
+use std::collections::HashMap;
+
+use aes::cipher::{InnerIvInit, KeyInit, KeyIvInit, StreamCipher};
+use aes::{Aes128, Aes256};
+use aes_gcm::aead::AeadInPlace;
+use aes_gcm::{Aes128Gcm, Aes256Gcm, Nonce, Tag};
+use bytes::{Buf, BufMut, Bytes, BytesMut};
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+
+use crate::error::{Error, Result};
+use crate::packet::{Header, Packet};
+use crate::parse::Parse;
+use crate::rtcp::Compound;
+use crate::serialize::Serialize;
+
+type Aes128Ctr = ctr::Ctr128BE<Aes128>;
+type Aes128CtrCore = ctr::CtrCore<Aes128, ctr::flavors::Ctr128BE>;
+type Aes256Ctr = ctr::Ctr128BE<Aes256>;
+type HmacSha1 = Hmac<Sha1>;
+
+/// Key derivation labels (RFC 3711 section 4.3.1).
+const LABEL_RTP_ENCRYPTION: u8 = 0x00;
+const LABEL_RTCP_ENCRYPTION: u8 = 0x03;
+/// Offset of authentication and salt labels relative to the encryption label.
+const LABEL_OFFSET_AUTHENTICATION: u8 = 0x01;
+const LABEL_OFFSET_SALT: u8 = 0x02;
+
+/// Length of HMAC-SHA1 session authentication key.
+const AUTHENTICATION_KEY_LEN: usize = 20;
+/// Length of AES-GCM authentication tag (RFC 7714 section 14.2).
+const GCM_TAG_LEN: usize = 16;
+/// Length of SRTCP E flag and index.
+const SRTCP_INDEX_LEN: usize = 4;
+/// Highest SRTCP index, after which the master key must be replaced.
+const SRTCP_INDEX_MAX: u32 = 0x7fff_ffff;
+/// Number of packets covered by replay protection.
+const REPLAY_WINDOW_SIZE: u64 = 64;
+
+/// SRTP protection profile, named after the SDES crypto suite (RFC 4568 section 6.2, RFC 7714
+/// section 14.2).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ProtectionProfile {
+    /// AES-CM with 128-bit key and 80-bit HMAC-SHA1 authentication tag.
+    AesCm128HmacSha1_80,
+    /// AES-CM with 128-bit key and 32-bit HMAC-SHA1 authentication tag for SRTP (SRTCP still
+    /// uses an 80-bit tag).
+    AesCm128HmacSha1_32,
+    /// AEAD AES-GCM with 128-bit key.
+    AeadAes128Gcm,
+    /// AEAD AES-GCM with 256-bit key.
+    AeadAes256Gcm,
+}
+
+impl ProtectionProfile {
+    /// Length of master key in bytes.
+    pub fn master_key_len(&self) -> usize {
+        match self {
+            ProtectionProfile::AesCm128HmacSha1_80
+            | ProtectionProfile::AesCm128HmacSha1_32
+            | ProtectionProfile::AeadAes128Gcm => 16,
+            ProtectionProfile::AeadAes256Gcm => 32,
+        }
+    }
+
+    /// Length of master salt in bytes.
+    pub fn master_salt_len(&self) -> usize {
+        match self {
+            ProtectionProfile::AesCm128HmacSha1_80 | ProtectionProfile::AesCm128HmacSha1_32 => 14,
+            ProtectionProfile::AeadAes128Gcm | ProtectionProfile::AeadAes256Gcm => 12,
+        }
+    }
+
+    /// Number of bytes that protecting adds to an RTP packet.
+    pub fn rtp_overhead(&self) -> usize {
+        match self {
+            ProtectionProfile::AesCm128HmacSha1_80 => 10,
+            ProtectionProfile::AesCm128HmacSha1_32 => 4,
+            ProtectionProfile::AeadAes128Gcm | ProtectionProfile::AeadAes256Gcm => GCM_TAG_LEN,
+        }
+    }
+
+    /// Number of bytes that protecting adds to a compound RTCP packet.
+    pub fn rtcp_overhead(&self) -> usize {
+        SRTCP_INDEX_LEN
+            + match self {
+                ProtectionProfile::AesCm128HmacSha1_80 | ProtectionProfile::AesCm128HmacSha1_32 => {
+                    10
+                }
+                ProtectionProfile::AeadAes128Gcm | ProtectionProfile::AeadAes256Gcm => GCM_TAG_LEN,
+            }
+    }
+}
+
+impl std::fmt::Display for ProtectionProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ProtectionProfile::AesCm128HmacSha1_80 => write!(f, "AES_CM_128_HMAC_SHA1_80"),
+            ProtectionProfile::AesCm128HmacSha1_32 => write!(f, "AES_CM_128_HMAC_SHA1_32"),
+            ProtectionProfile::AeadAes128Gcm => write!(f, "AEAD_AES_128_GCM"),
+            ProtectionProfile::AeadAes256Gcm => write!(f, "AEAD_AES_256_GCM"),
+        }
+    }
+}
+
+impl std::str::FromStr for ProtectionProfile {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "AES_CM_128_HMAC_SHA1_80" => Ok(ProtectionProfile::AesCm128HmacSha1_80),
+            "AES_CM_128_HMAC_SHA1_32" => Ok(ProtectionProfile::AesCm128HmacSha1_32),
+            "AEAD_AES_128_GCM" => Ok(ProtectionProfile::AeadAes128Gcm),
+            "AEAD_AES_256_GCM" => Ok(ProtectionProfile::AeadAes256Gcm),
+            _ => Err(Error::SrtpProtectionProfileUnknown {
+                name: s.to_string(),
+            }),
+        }
+    }
+}
+
+/// SRTP cryptographic context (RFC 3711 section 3.2).
+///
+/// Protects and unprotects RTP and RTCP packets of all synchronization sources that share a
+/// master key, and keeps track of the rollover counter and replay list of each source.
+///
+/// Keys must never be shared between directions: use one context to protect outgoing packets,
+/// and another context (with the key of the remote side) to unprotect incoming packets.
+pub struct Context {
+    profile: ProtectionProfile,
+    rtp: SessionKeys,
+    rtcp: SessionKeys,
+    /// Highest RTP packet index and replay list per source.
+    rtp_streams: HashMap<u32, ReplayWindow>,
+    /// Highest SRTCP index and replay list per source.
+    rtcp_streams: HashMap<u32, ReplayWindow>,
+    /// Next SRTCP index to send per source.
+    rtcp_indices: HashMap<u32, u32>,
+}
+
+impl Context {
+    /// Create context from master key and master salt.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Protection profile.
+    /// * `master_key` - Master key.
+    /// * `master_salt` - Master salt.
+    pub fn new(profile: ProtectionProfile, master_key: &[u8], master_salt: &[u8]) -> Result<Self> {
+        if master_key.len() != profile.master_key_len() {
+            return Err(Error::SrtpMasterKeyLengthInvalid {
+                have: master_key.len(),
+                need: profile.master_key_len(),
+            });
+        }
+        if master_salt.len() != profile.master_salt_len() {
+            return Err(Error::SrtpMasterSaltLengthInvalid {
+                have: master_salt.len(),
+                need: profile.master_salt_len(),
+            });
+        }
+        Ok(Self {
+            profile,
+            rtp: SessionKeys::derive(profile, master_key, master_salt, LABEL_RTP_ENCRYPTION),
+            rtcp: SessionKeys::derive(profile, master_key, master_salt, LABEL_RTCP_ENCRYPTION),
+            rtp_streams: HashMap::new(),
+            rtcp_streams: HashMap::new(),
+            rtcp_indices: HashMap::new(),
+        })
+    }
+
+    /// Create context from master key and master salt concatenated, as used in SDES `inline` key
+    /// parameters (RFC 4568 section 6.1).
+    ///
+    /// Master key identifiers (MKI) are not supported, so key parameters with an MKI cannot be
+    /// used.
+    ///
+    /// # Arguments
+    ///
+    /// * `profile` - Protection profile.
+    /// * `key_and_salt` - Master key followed by master salt.
+    pub fn from_key_and_salt(profile: ProtectionProfile, key_and_salt: &[u8]) -> Result<Self> {
+        let (master_key, master_salt) =
+            key_and_salt.split_at(profile.master_key_len().min(key_and_salt.len()));
+        Self::new(profile, master_key, master_salt)
+    }
+
+    #[inline]
+    pub fn profile(&self) -> ProtectionProfile {
+        self.profile
+    }
+
+    /// Rollover counter of source, or `None` if no packets of the source were protected or
+    /// unprotected yet.
+    pub fn rollover_counter(&self, ssrc: u32) -> Option<u32> {
+        self.rtp_streams
+            .get(&ssrc)
+            .map(|window| (window.max_index >> 16) as u32)
+    }
+
+    /// Protect RTP packet.
+    ///
+    /// Packets must be protected in order of sequence number. Protecting two packets with the
+    /// same sequence number would reuse the keystream, and receivers will drop the second one
+    /// anyway.
+    ///
+    /// # Arguments
+    ///
+    /// * `packet` - RTP packet to protect.
+    ///
+    /// # Return value
+    ///
+    /// Serialized SRTP packet.
+    pub fn protect(&mut self, packet: Packet) -> Result<Bytes> {
+        let header_len = packet.header.serialized_len();
+        let ssrc = packet.header.ssrc;
+        let window = self.rtp_streams.get(&ssrc).copied();
+        let index = estimate_index(window.as_ref(), packet.header.sequence_number)?;
+
+        let mut buf =
+            BytesMut::with_capacity(packet.serialized_len() + self.profile.rtp_overhead());
+        packet.serialize(&mut buf)?;
+        match &self.rtp {
+            SessionKeys::AesCm {
+                cipher,
+                salt,
+                authentication,
+            } => {
+                aes_cm_apply_keystream(cipher, salt, ssrc, index, &mut buf[header_len..]);
+                let mut mac = authentication.clone();
+                mac.update(&buf);
+                mac.update(&rollover_counter(index).to_be_bytes());
+                let tag = mac.finalize().into_bytes();
+                buf.put_slice(&tag[..self.profile.rtp_overhead()]);
+            }
+            SessionKeys::AesGcm { cipher, salt } => {
+                let (header, payload) = buf.split_at_mut(header_len);
+                let tag = cipher.encrypt(&gcm_nonce(salt, ssrc, index), header, payload)?;
+                buf.put_slice(&tag);
+            }
+        }
+
+        update_window(&mut self.rtp_streams, ssrc, window, index);
+        Ok(buf.freeze())
+    }
+
+    /// Unprotect SRTP packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Serialized SRTP packet.
+    ///
+    /// # Return value
+    ///
+    /// RTP packet, or an error if the packet could not be authenticated or was replayed.
+    pub fn unprotect(&mut self, data: Bytes) -> Result<Packet> {
+        let mut src = data.clone();
+        let header = Header::parse(&mut src)?;
+        let header_len = data.len() - src.remaining();
+        let tag_len = self.profile.rtp_overhead();
+        if src.remaining() < tag_len {
+            return Err(Error::NotEnoughData {
+                have: src.remaining(),
+                need: tag_len,
+            });
+        }
+
+        let ssrc = header.ssrc;
+        let window = self.rtp_streams.get(&ssrc).copied();
+        let index = estimate_index(window.as_ref(), header.sequence_number)?;
+        ensure_not_replayed(window.as_ref(), index)?;
+
+        let mut buf = BytesMut::from(&data[..]);
+        let tag = buf.split_off(buf.len() - tag_len);
+        match &self.rtp {
+            SessionKeys::AesCm {
+                cipher,
+                salt,
+                authentication,
+            } => {
+                let mut mac = authentication.clone();
+                mac.update(&buf);
+                mac.update(&rollover_counter(index).to_be_bytes());
+                mac.verify_truncated_left(&tag)
+                    .map_err(|_| Error::SrtpAuthenticationFailed)?;
+                aes_cm_apply_keystream(cipher, salt, ssrc, index, &mut buf[header_len..]);
+            }
+            SessionKeys::AesGcm { cipher, salt } => {
+                let (header, payload) = buf.split_at_mut(header_len);
+                cipher.decrypt(&gcm_nonce(salt, ssrc, index), header, payload, &tag)?;
+            }
+        }
+
+        update_window(&mut self.rtp_streams, ssrc, window, index);
+        Packet::parse(&mut buf.freeze())
+    }
+
+    /// Protect compound RTCP packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `compound` - Compound RTCP packet to protect.
+    ///
+    /// # Return value
+    ///
+    /// Serialized SRTCP packet.
+    pub fn protect_rtcp(&mut self, compound: Compound) -> Result<Bytes> {
+        let mut buf =
+            BytesMut::with_capacity(compound.serialized_len() + self.profile.rtcp_overhead());
+        compound.serialize(&mut buf)?;
+        let ssrc = rtcp_ssrc(&buf)?;
+
+        let next_index = self.rtcp_indices.entry(ssrc).or_insert(0);
+        if *next_index > SRTCP_INDEX_MAX {
+            return Err(Error::SrtcpIndexExhausted { ssrc });
+        }
+        let index = *next_index;
+        *next_index += 1;
+        // The E flag is always set: all outgoing packets are encrypted.
+        let e_and_index = (0x8000_0000 | index).to_be_bytes();
+
+        match &self.rtcp {
+            SessionKeys::AesCm {
+                cipher,
+                salt,
+                authentication,
+            } => {
+                aes_cm_apply_keystream(cipher, salt, ssrc, index as u64, &mut buf[8..]);
+                buf.put_slice(&e_and_index);
+                let mut mac = authentication.clone();
+                mac.update(&buf);
+                let tag = mac.finalize().into_bytes();
+                buf.put_slice(&tag[..self.profile.rtcp_overhead() - SRTCP_INDEX_LEN]);
+            }
+            SessionKeys::AesGcm { cipher, salt } => {
+                let (header, payload) = buf.split_at_mut(8);
+                let aad = [&header[..], &e_and_index].concat();
+                let tag = cipher.encrypt(&gcm_nonce(salt, ssrc, index as u64), &aad, payload)?;
+                buf.put_slice(&tag);
+                buf.put_slice(&e_and_index);
+            }
+        }
+
+        Ok(buf.freeze())
+    }
+
+    /// Unprotect SRTCP packet.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - Serialized SRTCP packet.
+    ///
+    /// # Return value
+    ///
+    /// Compound RTCP packet, or an error if the packet could not be authenticated or was
+    /// replayed.
+    pub fn unprotect_rtcp(&mut self, data: Bytes) -> Result<Compound> {
+        let need = 8 + self.profile.rtcp_overhead();
+        if data.len() < need {
+            return Err(Error::NotEnoughData {
+                have: data.len(),
+                need,
+            });
+        }
+        let ssrc = rtcp_ssrc(&data)?;
+
+        let mut buf = BytesMut::from(&data[..]);
+        let tag = match &self.rtcp {
+            SessionKeys::AesCm { .. } => {
+                buf.split_off(buf.len() - (self.profile.rtcp_overhead() - SRTCP_INDEX_LEN))
+            }
+            SessionKeys::AesGcm { .. } => BytesMut::new(),
+        };
+        let e_and_index = buf.split_off(buf.len() - SRTCP_INDEX_LEN);
+        let e_and_index = u32::from_be_bytes([
+            e_and_index[0],
+            e_and_index[1],
+            e_and_index[2],
+            e_and_index[3],
+        ]);
+        let encrypted = e_and_index & 0x8000_0000 != 0;
+        let index = (e_and_index & SRTCP_INDEX_MAX) as u64;
+
+        let window = self.rtcp_streams.get(&ssrc).copied();
+        ensure_not_replayed(window.as_ref(), index)?;
+
+        match &self.rtcp {
+            SessionKeys::AesCm {
+                cipher,
+                salt,
+                authentication,
+            } => {
+                let mut mac = authentication.clone();
+                mac.update(&buf);
+                mac.update(&e_and_index.to_be_bytes());
+                mac.verify_truncated_left(&tag)
+                    .map_err(|_| Error::SrtpAuthenticationFailed)?;
+                if encrypted {
+                    aes_cm_apply_keystream(cipher, salt, ssrc, index, &mut buf[8..]);
+                }
+            }
+            SessionKeys::AesGcm { cipher, salt } => {
+                let tag = buf.split_off(buf.len() - GCM_TAG_LEN);
+                let nonce = gcm_nonce(salt, ssrc, index);
+                if encrypted {
+                    let (header, payload) = buf.split_at_mut(8);
+                    let aad = [&header[..], &e_and_index.to_be_bytes()].concat();
+                    cipher.decrypt(&nonce, &aad, payload, &tag)?;
+                } else {
+                    // Unencrypted packets are only authenticated (RFC 7714 section 9.2).
+                    let aad = [&buf[..], &e_and_index.to_be_bytes()].concat();
+                    cipher.decrypt(&nonce, &aad, &mut [], &tag)?;
+                }
+            }
+        }
+
+        update_window(&mut self.rtcp_streams, ssrc, window, index);
+        Compound::parse(&mut buf.freeze())
+    }
+}
+
+impl std::fmt::Debug for Context {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Context")
+            .field("profile", &self.profile)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Session keys derived from master key for either SRTP or SRTCP.
+enum SessionKeys {
+    AesCm {
+        cipher: Box<Aes128>,
+        salt: [u8; 14],
+        authentication: HmacSha1,
+    },
+    AesGcm {
+        cipher: GcmCipher,
+        salt: [u8; 12],
+    },
+}
+
+impl SessionKeys {
+    fn derive(
+        profile: ProtectionProfile,
+        master_key: &[u8],
+        master_salt: &[u8],
+        label: u8,
+    ) -> Self {
+        let derive = |label, len| derive_session_key(master_key, master_salt, label, len);
+        let key = derive(label, master_key.len());
+        let salt = derive(label + LABEL_OFFSET_SALT, master_salt.len());
+        match profile {
+            ProtectionProfile::AesCm128HmacSha1_80 | ProtectionProfile::AesCm128HmacSha1_32 => {
+                let authentication =
+                    derive(label + LABEL_OFFSET_AUTHENTICATION, AUTHENTICATION_KEY_LEN);
+                SessionKeys::AesCm {
+                    cipher: Box::new(Aes128::new_from_slice(&key).expect("key length is valid")),
+                    salt: salt.try_into().expect("salt length is valid"),
+                    authentication: <HmacSha1 as Mac>::new_from_slice(&authentication)
+                        .expect("hmac accepts keys of any length"),
+                }
+            }
+            ProtectionProfile::AeadAes128Gcm => SessionKeys::AesGcm {
+                cipher: GcmCipher::Aes128(Box::new(
+                    Aes128Gcm::new_from_slice(&key).expect("key length is valid"),
+                )),
+                salt: salt.try_into().expect("salt length is valid"),
+            },
+            ProtectionProfile::AeadAes256Gcm => SessionKeys::AesGcm {
+                cipher: GcmCipher::Aes256(Box::new(
+                    Aes256Gcm::new_from_slice(&key).expect("key length is valid"),
+                )),
+                salt: salt.try_into().expect("salt length is valid"),
+            },
+        }
+    }
+}
+
+enum GcmCipher {
+    Aes128(Box<Aes128Gcm>),
+    Aes256(Box<Aes256Gcm>),
+}
+
+impl GcmCipher {
+    fn encrypt(&self, nonce: &[u8; 12], aad: &[u8], buffer: &mut [u8]) -> Result<Tag> {
+        let nonce = Nonce::from_slice(nonce);
+        match self {
+            GcmCipher::Aes128(cipher) => cipher.encrypt_in_place_detached(nonce, aad, buffer),
+            GcmCipher::Aes256(cipher) => cipher.encrypt_in_place_detached(nonce, aad, buffer),
+        }
+        .map_err(|_| Error::SrtpAuthenticationFailed)
+    }
+
+    fn decrypt(&self, nonce: &[u8; 12], aad: &[u8], buffer: &mut [u8], tag: &[u8]) -> Result<()> {
+        let nonce = Nonce::from_slice(nonce);
+        let tag = Tag::from_slice(tag);
+        match self {
+            GcmCipher::Aes128(cipher) => cipher.decrypt_in_place_detached(nonce, aad, buffer, tag),
+            GcmCipher::Aes256(cipher) => cipher.decrypt_in_place_detached(nonce, aad, buffer, tag),
+        }
+        .map_err(|_| Error::SrtpAuthenticationFailed)
+    }
+}
+
+/// Highest index and bitmask of recently received indices (RFC 3711 section 3.3.2).
+#[derive(Debug, Clone, Copy)]
+struct ReplayWindow {
+    max_index: u64,
+    /// Bit `n` is set if index `max_index - n` was received.
+    mask: u64,
+}
+
+/// AES-CM key derivation function with key derivation rate zero (RFC 3711 section 4.3).
+fn derive_session_key(master_key: &[u8], master_salt: &[u8], label: u8, len: usize) -> Vec<u8> {
+    // The 96-bit salt of AEAD profiles is padded with zeros (RFC 7714 section 11).
+    let mut iv = [0; 16];
+    iv[..master_salt.len()].copy_from_slice(master_salt);
+    iv[7] ^= label;
+    let mut key = vec![0; len];
+    if master_key.len() == 32 {
+        Aes256Ctr::new(master_key.into(), &iv.into()).apply_keystream(&mut key);
+    } else {
+        Aes128Ctr::new(master_key.into(), &iv.into()).apply_keystream(&mut key);
+    }
+    key
+}
+
+/// Encrypt or decrypt with AES in counter mode (RFC 3711 section 4.1.1).
+fn aes_cm_apply_keystream(
+    cipher: &Aes128,
+    salt: &[u8; 14],
+    ssrc: u32,
+    index: u64,
+    data: &mut [u8],
+) {
+    let mut iv = [0; 16];
+    iv[..14].copy_from_slice(salt);
+    xor(&mut iv[4..8], &ssrc.to_be_bytes());
+    xor(&mut iv[8..14], &index.to_be_bytes()[2..]);
+    Aes128Ctr::from_core(Aes128CtrCore::inner_iv_init(cipher.clone(), &iv.into()))
+        .apply_keystream(data);
+}
+
+/// AES-GCM initialization vector (RFC 7714 sections 8.1 and 9.1).
+fn gcm_nonce(salt: &[u8; 12], ssrc: u32, index: u64) -> [u8; 12] {
+    let mut nonce = [0; 12];
+    nonce[2..6].copy_from_slice(&ssrc.to_be_bytes());
+    nonce[6..].copy_from_slice(&index.to_be_bytes()[2..]);
+    xor(&mut nonce, salt);
+    nonce
+}
+
+fn xor(dst: &mut [u8], src: &[u8]) {
+    dst.iter_mut().zip(src).for_each(|(dst, src)| *dst ^= src);
+}
+
+/// Estimate packet index from sequence number (RFC 3711 section 3.3.1).
+fn estimate_index(window: Option<&ReplayWindow>, sequence_number: u16) -> Result<u64> {
+    let Some(window) = window else {
+        return Ok(sequence_number as u64);
+    };
+    let roc = window.max_index >> 16;
+    let last = window.max_index as u16;
+    let sequence_number = sequence_number as u64;
+    let roc = if last < 0x8000 {
+        if sequence_number > last as u64 + 0x8000 {
+            // Packet from before the last rollover.
+            roc.checked_sub(1).ok_or(Error::SrtpPacketReplayed {
+                index: sequence_number,
+            })?
+        } else {
+            roc
+        }
+    } else if (last as u64 - 0x8000) > sequence_number {
+        roc + 1
+    } else {
+        roc
+    };
+    Ok((roc << 16) | sequence_number)
+}
+
+#[inline]
+fn rollover_counter(index: u64) -> u32 {
+    (index >> 16) as u32
+}
+
+fn ensure_not_replayed(window: Option<&ReplayWindow>, index: u64) -> Result<()> {
+    match window {
+        Some(window) if index <= window.max_index => {
+            let delta = window.max_index - index;
+            if delta >= REPLAY_WINDOW_SIZE || window.mask & (1 << delta) != 0 {
+                Err(Error::SrtpPacketReplayed { index })
+            } else {
+                Ok(())
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+fn update_window(
+    streams: &mut HashMap<u32, ReplayWindow>,
+    ssrc: u32,
+    window: Option<ReplayWindow>,
+    index: u64,
+) {
+    let window = match window {
+        // Indexes that fell out of the window (such as retransmissions) leave it unchanged.
+        Some(window) if index <= window.max_index => match window.max_index - index {
+            delta if delta < REPLAY_WINDOW_SIZE => ReplayWindow {
+                mask: window.mask | (1 << delta),
+                ..window
+            },
+            _ => window,
+        },
+        Some(window) if index - window.max_index < REPLAY_WINDOW_SIZE => ReplayWindow {
+            max_index: index,
+            mask: (window.mask << (index - window.max_index)) | 1,
+        },
+        _ => ReplayWindow {
+            max_index: index,
+            mask: 1,
+        },
+    };
+    streams.insert(ssrc, window);
+}
+
+/// SSRC of sender of first packet in compound RTCP packet.
+fn rtcp_ssrc(data: &[u8]) -> Result<u32> {
+    match data {
+        [_, _, _, _, a, b, c, d, ..] => Ok(u32::from_be_bytes([*a, *b, *c, *d])),
+        _ => Err(Error::NotEnoughData {
+            have: data.len(),
+            need: 8,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::packet::Version;
+    use crate::rtcp::{Packet as RtcpPacket, ReceiverReport};
+
+    const KEY_AND_SALT: [u8; 30] = [
+        0xe1, 0xf9, 0x7a, 0x0d, 0x3e, 0x01, 0x8b, 0xe0, 0xd6, 0x4f, 0xa3, 0x2c, 0x06, 0xde, 0x41,
+        0x39, 0x0e, 0xc6, 0x75, 0xad, 0x49, 0x8a, 0xfe, 0xeb, 0xb6, 0x96, 0x0b, 0x3a, 0xab, 0xe6,
+    ];
+
+    fn packet(sequence_number: u16) -> Packet {
+        Packet::new(
+            Header {
+                version: Version::Version2,
+                padding: false,
+                marker: false,
+                payload_type: 15,
+                sequence_number,
+                timestamp: 0xdecafbad,
+                ssrc: 0xcafebabe,
+                csrc: Vec::new(),
+                extension: None,
+            },
+            Bytes::from_static(&[0xab; 16]),
+        )
+    }
+
+    fn contexts(profile: ProtectionProfile) -> (Context, Context) {
+        let key_and_salt = &[KEY_AND_SALT, KEY_AND_SALT].concat()
+            [..profile.master_key_len() + profile.master_salt_len()];
+        (
+            Context::from_key_and_salt(profile, key_and_salt).unwrap(),
+            Context::from_key_and_salt(profile, key_and_salt).unwrap(),
+        )
+    }
+
+    #[test]
+    fn key_derivation() {
+        // RFC 3711 appendix B.3
+        let (master_key, master_salt) = KEY_AND_SALT.split_at(16);
+        assert_eq!(
+            derive_session_key(master_key, master_salt, 0x00, 16),
+            [
+                0xc6, 0x1e, 0x7a, 0x93, 0x74, 0x4f, 0x39, 0xee, 0x10, 0x73, 0x4a, 0xfe, 0x3f, 0xf7,
+                0xa0, 0x87,
+            ]
+        );
+        assert_eq!(
+            derive_session_key(master_key, master_salt, 0x02, 14),
+            [0x30, 0xcb, 0xbc, 0x08, 0x86, 0x3d, 0x8c, 0x85, 0xd4, 0x9d, 0xb3, 0x4a, 0x9a, 0xe1,]
+        );
+        assert_eq!(
+            derive_session_key(master_key, master_salt, 0x01, 20),
+            [
+                0xce, 0xbe, 0x32, 0x1f, 0x6f, 0xf7, 0x71, 0x6b, 0x6f, 0xd4, 0xab, 0x49, 0xaf, 0x25,
+                0x6a, 0x15, 0x6d, 0x38, 0xba, 0xa4,
+            ]
+        );
+    }
+
+    #[test]
+    fn protect_aes_cm() {
+        let (mut sender, mut receiver) = contexts(ProtectionProfile::AesCm128HmacSha1_80);
+        let protected = sender.protect(packet(0x1234)).unwrap();
+        assert_eq!(
+            &protected[..],
+            &[
+                0x80, 0x0f, 0x12, 0x34, 0xde, 0xca, 0xfb, 0xad, 0xca, 0xfe, 0xba, 0xbe, 0x4e, 0x55,
+                0xdc, 0x4c, 0xe7, 0x99, 0x78, 0xd8, 0x8c, 0xa4, 0xd2, 0x15, 0x94, 0x9d, 0x24, 0x02,
+                0xb7, 0x8d, 0x6a, 0xcc, 0x99, 0xea, 0x17, 0x9b, 0x8d, 0xbb,
+            ]
+        );
+        assert_eq!(
+            receiver.unprotect(protected.clone()).unwrap(),
+            packet(0x1234)
+        );
+        assert!(matches!(
+            receiver.unprotect(protected),
+            Err(Error::SrtpPacketReplayed { index: 0x1234 })
+        ));
+    }
+
+    #[test]
+    fn round_trip() {
+        for profile in [
+            ProtectionProfile::AesCm128HmacSha1_80,
+            ProtectionProfile::AesCm128HmacSha1_32,
+            ProtectionProfile::AeadAes128Gcm,
+            ProtectionProfile::AeadAes256Gcm,
+        ] {
+            let (mut sender, mut receiver) = contexts(profile);
+            let protected = (0..4)
+                .map(|i| sender.protect(packet(0xfffe_u16.wrapping_add(i))).unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(sender.rollover_counter(0xcafebabe), Some(1));
+            assert_eq!(protected[0].len(), 12 + 16 + profile.rtp_overhead());
+
+            // Out of order across rollover.
+            for i in [1, 0, 3, 2] {
+                assert_eq!(
+                    receiver.unprotect(protected[i].clone()).unwrap(),
+                    packet(0xfffe_u16.wrapping_add(i as u16))
+                );
+            }
+            assert_eq!(receiver.rollover_counter(0xcafebabe), Some(1));
+
+            let mut tampered = BytesMut::from(&protected[0][..]);
+            tampered[20] ^= 0x01;
+            let (_, mut receiver) = contexts(profile);
+            assert!(matches!(
+                receiver.unprotect(tampered.freeze()),
+                Err(Error::SrtpAuthenticationFailed)
+            ));
+
+            let compound = Compound(vec![RtcpPacket::ReceiverReport(ReceiverReport {
+                ssrc: 0xcafebabe,
+                reports: Vec::new(),
+            })]);
+            let protected = sender.protect_rtcp(compound.clone()).unwrap();
+            assert_eq!(protected.len(), 8 + profile.rtcp_overhead());
+            assert_eq!(
+                receiver.unprotect_rtcp(protected.clone()).unwrap(),
+                compound
+            );
+            assert!(receiver.unprotect_rtcp(protected).is_err());
+        }
+    }
+
+    #[test]
+    fn replay_window() {
+        let (mut sender, mut receiver) = contexts(ProtectionProfile::AeadAes128Gcm);
+        let protected = (0..100)
+            .map(|i| sender.protect(packet(i)).unwrap())
+            .collect::<Vec<_>>();
+        receiver.unprotect(protected[99].clone()).unwrap();
+        receiver.unprotect(protected[50].clone()).unwrap();
+        assert!(receiver.unprotect(protected[50].clone()).is_err());
+        assert!(receiver.unprotect(protected[10].clone()).is_err());
+    }
+
+    #[test]
+    fn protect_behind_replay_window() {
+        let (mut sender, mut receiver) = contexts(ProtectionProfile::AesCm128HmacSha1_80);
+        sender.protect(packet(200)).unwrap();
+        // Retransmission of a packet that fell out of the replay window.
+        let protected = sender.protect(packet(100)).unwrap();
+        assert_eq!(sender.rollover_counter(0xcafebabe), Some(0));
+        assert_eq!(receiver.unprotect(protected).unwrap(), packet(100));
+        sender.protect(packet(201)).unwrap();
+    }
+}
@@ -0,0 +1,559 @@
+use base64::Engine;
+
+use md5::{Digest, Md5};
+use sha2::Sha256;
+
+/// Credentials for authenticating with an RTSP server.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub username: String,
+    pub password: String,
+}
+
+impl Credentials {
+    /// Create credentials.
+    ///
+    /// # Arguments
+    ///
+    /// * `username` - Username.
+    /// * `password` - Password.
+    pub fn new(username: impl ToString, password: impl ToString) -> Self {
+        Self {
+            username: username.to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    /// Extract credentials from userinfo component of URI authority.
+    ///
+    /// The username and password are percent-decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `authority` - URI authority such as `user:pass@host:554`.
+    ///
+    /// # Return value
+    ///
+    /// Credentials, or `None` if the authority does not contain userinfo.
+    pub fn from_authority(authority: &http::uri::Authority) -> Option<Self> {
+        let (userinfo, _) = authority.as_str().rsplit_once('@')?;
+        let (username, password) = userinfo.split_once(':').unwrap_or((userinfo, ""));
+        Some(Self::new(
+            percent_decode(username),
+            percent_decode(password),
+        ))
+    }
+}
+
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("username", &self.username)
+            .field("password", &"<redacted>")
+            .finish()
+    }
+}
+
+/// Produces `Authorization` headers for requests.
+///
+/// The authenticator is created from the challenges in the `WWW-Authenticate` header of a
+/// `401 Unauthorized` response, and then signs every subsequent request.
+#[derive(Debug)]
+pub(crate) enum Authenticator {
+    Basic {
+        credentials: Credentials,
+    },
+    Digest {
+        credentials: Credentials,
+        challenge: DigestChallenge,
+        nonce_count: u32,
+    },
+}
+
+impl Authenticator {
+    /// Pick the strongest supported challenge.
+    ///
+    /// Digest is preferred over Basic, and SHA-256 is preferred over MD5.
+    ///
+    /// # Arguments
+    ///
+    /// * `credentials` - Credentials to authenticate with.
+    /// * `www_authenticate` - Values of `WWW-Authenticate` headers.
+    ///
+    /// # Return value
+    ///
+    /// Authenticator, or `None` if the server did not offer any supported scheme.
+    pub(crate) fn from_challenges<'a>(
+        credentials: Credentials,
+        www_authenticate: impl IntoIterator<Item = &'a str>,
+    ) -> Option<Self> {
+        let challenges = www_authenticate
+            .into_iter()
+            .flat_map(parse_challenges)
+            .collect::<Vec<_>>();
+        let digest = challenges
+            .iter()
+            .filter(|challenge| challenge.scheme.eq_ignore_ascii_case("Digest"))
+            .filter_map(DigestChallenge::from_challenge)
+            .max_by_key(|challenge| challenge.algorithm.strength());
+        if let Some(challenge) = digest {
+            return Some(Authenticator::Digest {
+                credentials,
+                challenge,
+                nonce_count: 0,
+            });
+        }
+
+        challenges
+            .iter()
+            .any(|challenge| challenge.scheme.eq_ignore_ascii_case("Basic"))
+            .then_some(Authenticator::Basic { credentials })
+    }
+
+    /// Produce value for `Authorization` header.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - Request method.
+    /// * `uri` - Request URI.
+    pub(crate) fn authorization(&mut self, method: &str, uri: &str) -> String {
+        match self {
+            Authenticator::Basic { credentials } => basic(credentials),
+            Authenticator::Digest {
+                credentials,
+                challenge,
+                nonce_count,
+            } => {
+                *nonce_count = nonce_count.wrapping_add(1);
+                let cnonce = format!("{:016x}", rand::random::<u64>());
+                digest(credentials, challenge, method, uri, *nonce_count, &cnonce)
+            }
+        }
+    }
+}
+
+/// Parameters of a `Digest` challenge (RFC 7616).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DigestChallenge {
+    realm: String,
+    nonce: String,
+    opaque: Option<String>,
+    algorithm: Algorithm,
+    /// Whether or not the server supports `qop=auth`.
+    qop_auth: bool,
+}
+
+impl DigestChallenge {
+    fn from_challenge(challenge: &Challenge) -> Option<Self> {
+        let algorithm = match challenge.param("algorithm") {
+            Some(algorithm) => algorithm.parse().ok()?,
+            None => Algorithm::Md5,
+        };
+        let qop_auth = match challenge.param("qop") {
+            Some(qop) => {
+                if !qop.split(',').any(|qop| qop.trim() == "auth") {
+                    // Only `auth-int` offered, which is not supported.
+                    return None;
+                }
+                true
+            }
+            None => false,
+        };
+        Some(Self {
+            realm: challenge.param("realm")?.to_string(),
+            nonce: challenge.param("nonce")?.to_string(),
+            opaque: challenge.param("opaque").map(str::to_string),
+            algorithm,
+            qop_auth,
+        })
+    }
+}
+
+/// Digest hash algorithm.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Algorithm {
+    Md5,
+    Md5Sess,
+    Sha256,
+    Sha256Sess,
+}
+
+impl Algorithm {
+    fn hash(&self, data: &str) -> String {
+        match self {
+            Algorithm::Md5 | Algorithm::Md5Sess => hex(&Md5::digest(data.as_bytes())),
+            Algorithm::Sha256 | Algorithm::Sha256Sess => hex(&Sha256::digest(data.as_bytes())),
+        }
+    }
+
+    fn is_session(&self) -> bool {
+        matches!(self, Algorithm::Md5Sess | Algorithm::Sha256Sess)
+    }
+
+    fn strength(&self) -> usize {
+        match self {
+            Algorithm::Md5 | Algorithm::Md5Sess => 0,
+            Algorithm::Sha256 | Algorithm::Sha256Sess => 1,
+        }
+    }
+}
+
+impl std::fmt::Display for Algorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Algorithm::Md5 => write!(f, "MD5"),
+            Algorithm::Md5Sess => write!(f, "MD5-sess"),
+            Algorithm::Sha256 => write!(f, "SHA-256"),
+            Algorithm::Sha256Sess => write!(f, "SHA-256-sess"),
+        }
+    }
+}
+
+impl std::str::FromStr for Algorithm {
+    type Err = ();
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_uppercase().as_str() {
+            "MD5" => Ok(Algorithm::Md5),
+            "MD5-SESS" => Ok(Algorithm::Md5Sess),
+            "SHA-256" => Ok(Algorithm::Sha256),
+            "SHA-256-SESS" => Ok(Algorithm::Sha256Sess),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Authentication challenge in `WWW-Authenticate` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Challenge {
+    scheme: String,
+    params: Vec<(String, String)>,
+}
+
+impl Challenge {
+    fn param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(var, _)| var.eq_ignore_ascii_case(name))
+            .map(|(_, val)| val.as_str())
+    }
+}
+
+/// Parse challenges in `WWW-Authenticate` header.
+///
+/// A single header may contain multiple challenges, each consisting of a scheme followed by a
+/// comma-separated list of parameters (RFC 7235, section 4.1).
+fn parse_challenges(value: &str) -> Vec<Challenge> {
+    let mut challenges: Vec<Challenge> = Vec::new();
+    let mut rest = value;
+    loop {
+        rest = rest.trim_start_matches(|c: char| c == ',' || c.is_whitespace());
+        if rest.is_empty() {
+            break;
+        }
+
+        let token_len = rest
+            .find(|c: char| c == ',' || c == '=' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let (token, after_token) = rest.split_at(token_len);
+        let after_whitespace = after_token.trim_start();
+
+        if let Some(after_equals) = after_whitespace.strip_prefix('=') {
+            let (val, after_val) = parse_param_value(after_equals.trim_start());
+            if let Some(challenge) = challenges.last_mut() {
+                challenge.params.push((token.to_string(), val));
+            }
+            rest = after_val;
+        } else {
+            challenges.push(Challenge {
+                scheme: token.to_string(),
+                params: Vec::new(),
+            });
+            rest = after_token;
+        }
+    }
+    challenges
+}
+
+/// Parse token or quoted string.
+///
+/// # Return value
+///
+/// Unquoted value and the rest of the input.
+fn parse_param_value(s: &str) -> (String, &str) {
+    match s.strip_prefix('"') {
+        Some(quoted) => {
+            let mut val = String::new();
+            let mut chars = quoted.char_indices();
+            while let Some((index, c)) = chars.next() {
+                match c {
+                    '\\' => {
+                        if let Some((_, escaped)) = chars.next() {
+                            val.push(escaped);
+                        }
+                    }
+                    '"' => return (val, &quoted[index + 1..]),
+                    c => val.push(c),
+                }
+            }
+            (val, "")
+        }
+        None => {
+            let len = s.find(',').unwrap_or(s.len());
+            (s[..len].trim_end().to_string(), &s[len..])
+        }
+    }
+}
+
+fn basic(credentials: &Credentials) -> String {
+    let userpass = format!("{}:{}", credentials.username, credentials.password);
+    format!(
+        "Basic {}",
+        base64::engine::general_purpose::STANDARD.encode(userpass)
+    )
+}
+
+fn digest(
+    credentials: &Credentials,
+    challenge: &DigestChallenge,
+    method: &str,
+    uri: &str,
+    nonce_count: u32,
+    cnonce: &str,
+) -> String {
+    let algorithm = challenge.algorithm;
+    let nc = format!("{nonce_count:08x}");
+
+    let mut ha1 = algorithm.hash(&format!(
+        "{}:{}:{}",
+        credentials.username, challenge.realm, credentials.password
+    ));
+    if algorithm.is_session() {
+        ha1 = algorithm.hash(&format!("{ha1}:{}:{cnonce}", challenge.nonce));
+    }
+    let ha2 = algorithm.hash(&format!("{method}:{uri}"));
+    let response = if challenge.qop_auth {
+        algorithm.hash(&format!(
+            "{ha1}:{}:{nc}:{cnonce}:auth:{ha2}",
+            challenge.nonce
+        ))
+    } else {
+        algorithm.hash(&format!("{ha1}:{}:{ha2}", challenge.nonce))
+    };
+
+    let mut authorization = format!(
+        "Digest username=\"{}\", realm=\"{}\", nonce=\"{}\", uri=\"{}\", response=\"{}\", \
+        algorithm={}",
+        quote(&credentials.username),
+        quote(&challenge.realm),
+        quote(&challenge.nonce),
+        quote(uri),
+        response,
+        algorithm,
+    );
+    if let Some(opaque) = challenge.opaque.as_ref() {
+        authorization.push_str(&format!(", opaque=\"{}\"", quote(opaque)));
+    }
+    if challenge.qop_auth {
+        authorization.push_str(&format!(", qop=auth, nc={nc}, cnonce=\"{cnonce}\""));
+    }
+    authorization
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+fn quote(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let byte = match bytes[index] {
+            b'%' => s
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            }
+            None => {
+                decoded.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_multiple_challenges() {
+        let challenges = parse_challenges(
+            "Digest realm=\"camera\", nonce=\"a,b\", qop=\"auth,auth-int\", Basic realm=\"x\"",
+        );
+        assert_eq!(
+            challenges,
+            vec![
+                Challenge {
+                    scheme: "Digest".to_string(),
+                    params: vec![
+                        ("realm".to_string(), "camera".to_string()),
+                        ("nonce".to_string(), "a,b".to_string()),
+                        ("qop".to_string(), "auth,auth-int".to_string()),
+                    ],
+                },
+                Challenge {
+                    scheme: "Basic".to_string(),
+                    params: vec![("realm".to_string(), "x".to_string())],
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn parse_unquoted_params() {
+        let challenges = parse_challenges("Digest realm=camera,nonce=123 , stale=TRUE");
+        assert_eq!(challenges.len(), 1);
+        assert_eq!(challenges[0].param("realm"), Some("camera"));
+        assert_eq!(challenges[0].param("nonce"), Some("123"));
+        assert_eq!(challenges[0].param("stale"), Some("TRUE"));
+    }
+
+    #[test]
+    fn prefer_strongest_challenge() {
+        let credentials = Credentials::new("user", "pass");
+        let authenticator = Authenticator::from_challenges(
+            credentials.clone(),
+            [
+                "Basic realm=\"x\", Digest realm=\"x\", nonce=\"1\"",
+                "Digest realm=\"x\", nonce=\"2\", algorithm=SHA-256",
+            ],
+        )
+        .unwrap();
+        assert!(matches!(
+            authenticator,
+            Authenticator::Digest {
+                challenge: DigestChallenge {
+                    algorithm: Algorithm::Sha256,
+                    ..
+                },
+                ..
+            }
+        ));
+
+        let authenticator =
+            Authenticator::from_challenges(credentials.clone(), ["Basic realm=\"x\""]).unwrap();
+        assert!(matches!(authenticator, Authenticator::Basic { .. }));
+
+        assert!(Authenticator::from_challenges(credentials, ["Bearer realm=\"x\""]).is_none());
+    }
+
+    #[test]
+    fn basic_rfc7617_example() {
+        assert_eq!(
+            basic(&Credentials::new("Aladdin", "open sesame")),
+            "Basic QWxhZGRpbjpvcGVuIHNlc2FtZQ==",
+        );
+    }
+
+    #[test]
+    fn digest_rfc2617_example() {
+        let challenge = DigestChallenge {
+            realm: "testrealm@host.com".to_string(),
+            nonce: "dcd98b7102dd2f0e8b11d0f600bfb0c093".to_string(),
+            opaque: Some("5ccc069c403ebaf9f0171e9517f40e41".to_string()),
+            algorithm: Algorithm::Md5,
+            qop_auth: true,
+        };
+        let authorization = digest(
+            &Credentials::new("Mufasa", "Circle Of Life"),
+            &challenge,
+            "GET",
+            "/dir/index.html",
+            1,
+            "0a4f113b",
+        );
+        assert_eq!(
+            authorization,
+            "Digest username=\"Mufasa\", realm=\"testrealm@host.com\", \
+            nonce=\"dcd98b7102dd2f0e8b11d0f600bfb0c093\", uri=\"/dir/index.html\", \
+            response=\"6629fae49393a05397450978507c4ef1\", algorithm=MD5, \
+            opaque=\"5ccc069c403ebaf9f0171e9517f40e41\", qop=auth, nc=00000001, \
+            cnonce=\"0a4f113b\"",
+        );
+    }
+
+    #[test]
+    fn digest_rfc7616_examples() {
+        let credentials = Credentials::new("Mufasa", "Circle of Life");
+        let mut challenge = DigestChallenge {
+            realm: "http-auth@example.org".to_string(),
+            nonce: "7ypf/xlj9XXwfDPEoM4URrv/xwf94BcCAzFZH4GiTo0v".to_string(),
+            opaque: Some("FQhe/qaU925kfnzjCev0ciny7QMkPqMAFRtzCUYo5tdS".to_string()),
+            algorithm: Algorithm::Md5,
+            qop_auth: true,
+        };
+        let cnonce = "f2/wE4q74E6zIJEtWaHKaf5wv/H5QzzpXusqGemxURZJ";
+
+        let authorization = digest(
+            &credentials,
+            &challenge,
+            "GET",
+            "/dir/index.html",
+            1,
+            cnonce,
+        );
+        assert!(authorization.contains("response=\"8ca523f5e9506fed4657c9700eebdbec\""));
+
+        challenge.algorithm = Algorithm::Sha256;
+        let authorization = digest(
+            &credentials,
+            &challenge,
+            "GET",
+            "/dir/index.html",
+            1,
+            cnonce,
+        );
+        assert!(authorization.contains(
+            "response=\"753927fa0e85d155564e2e272a28d1802ca10daf4496794697cf8db5856cb6c1\""
+        ));
+        assert!(authorization.contains("algorithm=SHA-256"));
+    }
+
+    #[test]
+    fn nonce_count_increments() {
+        let mut authenticator = Authenticator::from_challenges(
+            Credentials::new("user", "pass"),
+            ["Digest realm=\"x\", nonce=\"1\", qop=\"auth\""],
+        )
+        .unwrap();
+        assert!(authenticator
+            .authorization("DESCRIBE", "rtsp://localhost/")
+            .contains("nc=00000001"));
+        assert!(authenticator
+            .authorization("SETUP", "rtsp://localhost/")
+            .contains("nc=00000002"));
+    }
+
+    #[test]
+    fn credentials_from_authority() {
+        let authority: http::uri::Authority = "admin:p%40ss@camera:554".parse().unwrap();
+        assert_eq!(
+            Credentials::from_authority(&authority),
+            Some(Credentials::new("admin", "p@ss")),
+        );
+        let authority: http::uri::Authority = "camera:554".parse().unwrap();
+        assert_eq!(Credentials::from_authority(&authority), None);
+    }
+}
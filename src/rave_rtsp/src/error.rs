@@ -1,8 +1,9 @@
-use crate::message::Uri;
+use crate::message::{Method, Uri, Version};
 
 pub type Result<T> = std::result::Result<T, Error>;
 
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Error {
     /// An error occurred decoding the header due to incorrect usage of text encoding by the sender.
     Encoding,
@@ -52,6 +53,9 @@ pub enum Error {
     NotDone,
     /// This occurs when trying to serialize a request that does not have a known version.
     VersionUnknown,
+    /// This occurs when trying to serialize a request with a method that does not exist in its
+    /// version, such as `RECORD` in RTSP 2.0.
+    MethodNotSupportedByVersion { method: Method, version: Version },
     /// Transport header does not have protocol and profile string. The transport must start with
     /// `RTP/AVP`, where `RTP` denotes the protocol and `AVP` the profile.
     TransportProtocolProfileMissing { value: String },
@@ -70,6 +74,9 @@ pub enum Error {
     TransportChannelMalformed { value: String },
     /// Transport header port is malformed.
     TransportPortMalformed { value: String },
+    /// Transport header address (in `dest_addr` or `src_addr`) is malformed. Addresses must be
+    /// quoted and consist of a host and/or a port.
+    TransportAddressMalformed { value: String },
     /// Tried to parse interleaved data but there is no interleaved header. Interleaved packets
     /// always start with `$` (0x24).
     InterleavedInvalid,
@@ -95,6 +102,16 @@ pub enum Error {
     RtpInfoParameterInvalid { value: String },
     /// RTP Info contains unexpected extra parameter.
     RtpInfoParameterUnexpected { value: String },
+    /// Session header does not contain a session identifier.
+    SessionIdMissing { value: String },
+    /// Session timeout is not a number of seconds.
+    SessionTimeoutInvalid { value: String },
+    /// Media-Properties header value malformed.
+    MediaPropertiesMalformed { value: String },
+    /// Seek-Style header contains unknown policy.
+    SeekStyleUnknown { value: String },
+    /// Pipelined-Requests header is not a 32-bit unsigned integer.
+    PipelinedRequestsInvalid { value: String },
     /// Underlying socket was shut down. This is not really an error and consumers are expected to
     /// handle it gracefully.
     Shutdown,
@@ -144,6 +161,9 @@ impl std::fmt::Display for Error {
             Error::MetadataNotParsed => write!(f, "metadata not parsed"),
             Error::NotDone => write!(f, "parser not done yet"),
             Error::VersionUnknown => write!(f, "response has unknown version"),
+            Error::MethodNotSupportedByVersion { method, version } => {
+                write!(f, "method {method} not supported by rtsp version {version}")
+            }
             Error::TransportProtocolProfileMissing { value } => {
                 write!(f, "transport protocol and/or profile missing: {}", &value)
             }
@@ -172,6 +192,9 @@ impl std::fmt::Display for Error {
             Error::TransportPortMalformed { value } => {
                 write!(f, "transport port malformed: {}", &value)
             }
+            Error::TransportAddressMalformed { value } => {
+                write!(f, "transport address malformed: {value}")
+            }
             Error::InterleavedInvalid => write!(
                 f,
                 "interleaved data does not have valid header magic character"
@@ -197,6 +220,17 @@ impl std::fmt::Display for Error {
             Error::RtpInfoParameterUnexpected { value } => {
                 write!(f, "rtp info contains unexpected parameter: {}", &value)
             }
+            Error::SessionIdMissing { value } => write!(f, "session id missing: {value}"),
+            Error::SessionTimeoutInvalid { value } => {
+                write!(f, "session timeout invalid: {value}")
+            }
+            Error::MediaPropertiesMalformed { value } => {
+                write!(f, "media properties malformed: {value}")
+            }
+            Error::SeekStyleUnknown { value } => write!(f, "seek style unknown: {value}"),
+            Error::PipelinedRequestsInvalid { value } => {
+                write!(f, "pipelined requests invalid: {value}")
+            }
             Error::Shutdown => write!(f, "underlying socket was shut down"),
             Error::Io(err) => write!(f, "{err}"),
         }
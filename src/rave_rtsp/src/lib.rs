@@ -1,31 +1,65 @@
 #[cfg(feature = "client")]
+pub mod auth;
+#[cfg(feature = "client")]
 pub mod client;
 pub mod error;
 pub mod interleaved;
 pub mod io;
+pub mod media_properties;
 pub mod message;
 pub mod parse;
+#[cfg(feature = "client")]
+pub mod presentation;
 pub mod range;
 pub mod request;
 pub mod response;
 pub mod rtp_info;
 pub mod serialize;
+#[cfg(feature = "server")]
+pub mod server;
+pub mod session;
+#[cfg(feature = "client")]
+pub mod supervisor;
+#[cfg(feature = "tls")]
+pub mod tls;
 pub mod tokio_codec;
 pub mod transport;
 
 mod buffer;
+#[cfg(any(feature = "client", feature = "server"))]
+mod task;
+#[cfg(all(test, any(feature = "client", feature = "server")))]
+mod testing;
+#[cfg(any(feature = "client", feature = "server"))]
+mod tunnel;
+#[cfg(any(feature = "client", feature = "server"))]
+mod udp;
 
 #[cfg(feature = "client")]
-pub use client::Client;
+pub use auth::Credentials;
+#[cfg(feature = "client")]
+pub use client::{Client, InterleavedStream, NotificationStream};
 pub use error::{Error, Result};
 pub use interleaved::{MaybeInterleaved, RequestMaybeInterleaved, ResponseMaybeInterleaved};
 pub use io::{AsClient, AsServer, Target};
+pub use media_properties::{MediaProperties, MediaProperty, Scale};
 pub use message::{Headers, Message, Method, Status, StatusCategory, StatusCode, Uri, Version};
 pub use parse::{RequestParser, ResponseParser, Status as ParserStatus};
-pub use range::{NptTime, Range};
+#[cfg(feature = "client")]
+pub use presentation::{Presentation, Track};
+pub use range::{NptTime, Range, SeekStyle};
 pub use request::Request;
 pub use response::Response;
 pub use rtp_info::RtpInfo;
 pub use serialize::Serialize;
+#[cfg(feature = "server")]
+pub use server::Server;
+pub use session::Session;
+#[cfg(feature = "client")]
+pub use supervisor::Supervisor;
+#[cfg(feature = "tls")]
+pub use tls::TlsConfig;
 pub use tokio_codec::Codec;
-pub use transport::{Channel, Lower, Parameter, Port, Transport};
+pub use transport::{Address, Channel, Lower, Parameter, Port, Transport};
+#[cfg(feature = "client")]
+pub use udp::{RtcpStream, RtpStream, UdpTrack};
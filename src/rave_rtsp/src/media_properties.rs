@@ -0,0 +1,302 @@
+use crate::Error;
+
+/// Value of `Media-Properties` header (RFC 7826, section 18.29).
+///
+/// Describes how media can be accessed, such as whether seeking is possible, whether the content
+/// changes over time and which scales are supported.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MediaProperties {
+    pub properties: Vec<MediaProperty>,
+}
+
+impl MediaProperties {
+    /// Create media properties from list of properties.
+    pub fn new(properties: impl IntoIterator<Item = MediaProperty>) -> Self {
+        Self {
+            properties: properties.into_iter().collect(),
+        }
+    }
+
+    /// Random access property, which is one of [`MediaProperty::RandomAccess`],
+    /// [`MediaProperty::BeginningOnly`] and [`MediaProperty::NoSeeking`].
+    pub fn random_access(&self) -> Option<&MediaProperty> {
+        self.properties.iter().find(|property| {
+            matches!(
+                property,
+                MediaProperty::RandomAccess(_)
+                    | MediaProperty::BeginningOnly
+                    | MediaProperty::NoSeeking
+            )
+        })
+    }
+
+    /// Supported scales, if the server announced them.
+    pub fn scales(&self) -> Option<&[Scale]> {
+        self.properties.iter().find_map(|property| match property {
+            MediaProperty::Scales(scales) => Some(scales.as_slice()),
+            _ => None,
+        })
+    }
+}
+
+impl std::fmt::Display for MediaProperties {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let properties = self
+            .properties
+            .iter()
+            .map(|property| property.to_string())
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(f, "{properties}")
+    }
+}
+
+impl std::str::FromStr for MediaProperties {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The list of scales is quoted and contains commas itself.
+        let mut properties = Vec::new();
+        let mut quoted = false;
+        let mut start = 0;
+        for (index, c) in s.char_indices() {
+            match c {
+                '"' => quoted = !quoted,
+                ',' if !quoted => {
+                    properties.push(s[start..index].parse()?);
+                    start = index + 1;
+                }
+                _ => {}
+            }
+        }
+        if !s[start..].trim().is_empty() {
+            properties.push(s[start..].parse()?);
+        }
+        Ok(MediaProperties { properties })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MediaProperty {
+    /// Media can be accessed at any position, with the maximum distance in seconds between
+    /// random access points, if known.
+    RandomAccess(Option<f64>),
+    /// Media can only be played from the beginning.
+    BeginningOnly,
+    /// Media cannot be seeked.
+    NoSeeking,
+    /// Content does not change.
+    Immutable,
+    /// Content may change, but not because time progresses.
+    Dynamic,
+    /// Content changes as time progresses, such as live media.
+    TimeProgressing,
+    /// Media remains available indefinitely.
+    Unlimited,
+    /// Media remains available until the given time (UTC, such as `20081128T165900Z`).
+    TimeLimited(String),
+    /// Media remains available for the given number of seconds.
+    TimeDuration(f64),
+    /// Scales at which media can be played.
+    Scales(Vec<Scale>),
+    /// Property not known by this implementation.
+    Extension(String),
+}
+
+impl std::fmt::Display for MediaProperty {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            MediaProperty::RandomAccess(Some(distance)) => write!(f, "Random-Access={distance}"),
+            MediaProperty::RandomAccess(None) => write!(f, "Random-Access"),
+            MediaProperty::BeginningOnly => write!(f, "Beginning-Only"),
+            MediaProperty::NoSeeking => write!(f, "No-Seeking"),
+            MediaProperty::Immutable => write!(f, "Immutable"),
+            MediaProperty::Dynamic => write!(f, "Dynamic"),
+            MediaProperty::TimeProgressing => write!(f, "Time-Progressing"),
+            MediaProperty::Unlimited => write!(f, "Unlimited"),
+            MediaProperty::TimeLimited(time) => write!(f, "Time-Limited={time}"),
+            MediaProperty::TimeDuration(duration) => write!(f, "Time-Duration={duration}"),
+            MediaProperty::Scales(scales) => {
+                let scales = scales
+                    .iter()
+                    .map(|scale| scale.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(f, "Scales=\"{scales}\"")
+            }
+            MediaProperty::Extension(extension) => write!(f, "{extension}"),
+        }
+    }
+}
+
+impl std::str::FromStr for MediaProperty {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || Error::MediaPropertiesMalformed {
+            value: s.to_string(),
+        };
+        let s = s.trim();
+        let (name, value) = match s.split_once('=') {
+            Some((name, value)) => (name.trim(), Some(value.trim())),
+            None => (s, None),
+        };
+        let float = |value: Option<&str>| {
+            value
+                .ok_or_else(malformed)?
+                .parse::<f64>()
+                .map_err(|_| malformed())
+        };
+        match (name, value) {
+            ("Random-Access", None) => Ok(MediaProperty::RandomAccess(None)),
+            ("Random-Access", value) => Ok(MediaProperty::RandomAccess(Some(float(value)?))),
+            ("Beginning-Only", None) => Ok(MediaProperty::BeginningOnly),
+            ("No-Seeking", None) => Ok(MediaProperty::NoSeeking),
+            ("Immutable", None) => Ok(MediaProperty::Immutable),
+            ("Dynamic", None) => Ok(MediaProperty::Dynamic),
+            ("Time-Progressing", None) => Ok(MediaProperty::TimeProgressing),
+            ("Unlimited", None) => Ok(MediaProperty::Unlimited),
+            ("Time-Limited", Some(time)) => Ok(MediaProperty::TimeLimited(time.to_string())),
+            ("Time-Duration", value) => Ok(MediaProperty::TimeDuration(float(value)?)),
+            ("Scales", Some(scales)) => {
+                let scales = scales
+                    .strip_prefix('"')
+                    .and_then(|scales| scales.strip_suffix('"'))
+                    .ok_or_else(malformed)?;
+                Ok(MediaProperty::Scales(
+                    scales
+                        .split(',')
+                        .map(|scale| scale.parse().map_err(|_| malformed()))
+                        .collect::<Result<Vec<_>, _>>()?,
+                ))
+            }
+            (
+                "Beginning-Only" | "No-Seeking" | "Immutable" | "Dynamic" | "Time-Progressing"
+                | "Unlimited" | "Time-Limited" | "Scales",
+                _,
+            )
+            | ("", _) => Err(malformed()),
+            _ => Ok(MediaProperty::Extension(s.to_string())),
+        }
+    }
+}
+
+/// Supported scale, or range of supported scales.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Scale {
+    Value(f64),
+    Range(f64, f64),
+}
+
+impl std::fmt::Display for Scale {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Scale::Value(scale) => write!(f, "{scale}"),
+            Scale::Range(from, to) => write!(f, "{from}:{to}"),
+        }
+    }
+}
+
+impl std::str::FromStr for Scale {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || Error::MediaPropertiesMalformed {
+            value: s.to_string(),
+        };
+        let scale = |value: &str| value.trim().parse::<f64>().map_err(|_| malformed());
+        match s.split_once(':') {
+            Some((from, to)) => Ok(Scale::Range(scale(from)?, scale(to)?)),
+            None => Ok(Scale::Value(scale(s)?)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rfc7826_section_18_29_examples() {
+        assert_eq!(
+            "Random-Access=2.5, Unlimited, Immutable"
+                .parse::<MediaProperties>()
+                .unwrap(),
+            MediaProperties::new([
+                MediaProperty::RandomAccess(Some(2.5)),
+                MediaProperty::Unlimited,
+                MediaProperty::Immutable,
+            ]),
+        );
+        assert_eq!(
+            "No-Seeking, Time-Progressing, Time-Duration=0.0"
+                .parse::<MediaProperties>()
+                .unwrap(),
+            MediaProperties::new([
+                MediaProperty::NoSeeking,
+                MediaProperty::TimeProgressing,
+                MediaProperty::TimeDuration(0.0),
+            ]),
+        );
+    }
+
+    #[test]
+    fn parse_scales() {
+        let media_properties = "Random-Access, Scales=\"-20, -10, 0.5:1.5, 4\", Dynamic"
+            .parse::<MediaProperties>()
+            .unwrap();
+        assert_eq!(
+            media_properties.random_access(),
+            Some(&MediaProperty::RandomAccess(None)),
+        );
+        assert_eq!(
+            media_properties.scales(),
+            Some(
+                [
+                    Scale::Value(-20.0),
+                    Scale::Value(-10.0),
+                    Scale::Range(0.5, 1.5),
+                    Scale::Value(4.0),
+                ]
+                .as_slice()
+            ),
+        );
+        assert_eq!(media_properties.properties[2], MediaProperty::Dynamic,);
+    }
+
+    #[test]
+    fn parse_extension() {
+        assert_eq!(
+            "Unlimited, x-custom=1".parse::<MediaProperties>().unwrap(),
+            MediaProperties::new([
+                MediaProperty::Unlimited,
+                MediaProperty::Extension("x-custom=1".to_string()),
+            ]),
+        );
+    }
+
+    #[test]
+    fn parse_malformed() {
+        assert!(matches!(
+            "Random-Access=fast".parse::<MediaProperties>(),
+            Err(Error::MediaPropertiesMalformed { value: _ }),
+        ));
+        assert!(matches!(
+            "Scales=1, 2".parse::<MediaProperties>(),
+            Err(Error::MediaPropertiesMalformed { value: _ }),
+        ));
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(
+            MediaProperties::new([
+                MediaProperty::RandomAccess(Some(2.5)),
+                MediaProperty::TimeLimited("20081128T165900Z".to_string()),
+                MediaProperty::Scales(vec![Scale::Value(-1.0), Scale::Range(0.5, 2.0)]),
+            ])
+            .to_string(),
+            "Random-Access=2.5, Time-Limited=20081128T165900Z, Scales=\"-1, 0.5:2\"",
+        );
+    }
+}
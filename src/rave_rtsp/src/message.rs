@@ -1,3 +1,4 @@
+use std::collections::btree_map::Entry;
 use std::collections::BTreeMap;
 
 use crate::error::Error;
@@ -16,12 +17,15 @@ pub trait Message: Serialize + std::fmt::Display {
 #[derive(Debug, Clone, Default, PartialEq, Eq)]
 pub struct Headers {
     map: BTreeMap<String, String>,
+    /// Further values of headers that occur more than once. The first value is in `map`.
+    repeated: BTreeMap<String, Vec<String>>,
 }
 
 impl Headers {
     pub fn new() -> Self {
         Self {
             map: BTreeMap::new(),
+            repeated: BTreeMap::new(),
         }
     }
 
@@ -36,30 +40,75 @@ impl Headers {
         ])
     }
 
+    /// Set value of header, replacing all values it had before.
     pub fn insert(&mut self, key: String, value: String) -> Option<String> {
+        self.repeated.remove(&key);
         self.map.insert(key, value)
     }
 
+    /// Add value to header, keeping the values it had before.
+    ///
+    /// The values are kept separately, since not every header that occurs more than once can be
+    /// combined into a single comma-separated value (such as `WWW-Authenticate` with multiple
+    /// challenges).
+    pub fn append(&mut self, key: String, value: String) {
+        match self.map.entry(key) {
+            Entry::Occupied(entry) => {
+                self.repeated
+                    .entry(entry.key().clone())
+                    .or_default()
+                    .push(value);
+            }
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+        }
+    }
+
     pub fn extend(&mut self, headers: Headers) {
-        self.map.extend(headers.map)
+        for (key, value) in headers.map {
+            self.insert(key, value);
+        }
+        self.repeated.extend(headers.repeated);
     }
 
     pub fn contains(&self, key: &str) -> bool {
         self.map.contains_key(key)
     }
 
+    /// First value of header.
     pub fn get(&self, key: &str) -> Option<&str> {
         self.map.get(key).map(|s| s.as_str())
     }
 
+    /// All values of header, in order.
+    pub fn get_all<'a>(&'a self, key: &str) -> impl Iterator<Item = &'a str> + 'a {
+        self.map
+            .get(key)
+            .into_iter()
+            .chain(self.repeated.get(key).into_iter().flatten())
+            .map(String::as_str)
+    }
+
+    /// All headers, ordered by name, with every value of headers that occur more than once.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.map.iter().flat_map(|(key, value)| {
+            std::iter::once(value)
+                .chain(self.repeated.get(key).into_iter().flatten())
+                .map(move |value| (key.as_str(), value.as_str()))
+        })
+    }
+
     pub fn is_empty(&self) -> bool {
         self.map.is_empty()
     }
 
+    /// Headers by name. Only the first value of headers that occur more than once is included.
     pub fn into_map(self) -> BTreeMap<String, String> {
         self.map
     }
 
+    /// Headers by name. Only the first value of headers that occur more than once is included.
     pub fn as_map(&self) -> &BTreeMap<String, String> {
         &self.map
     }
@@ -68,7 +117,10 @@ impl Headers {
 impl From<BTreeMap<String, String>> for Headers {
     #[inline]
     fn from(map: BTreeMap<String, String>) -> Self {
-        Self { map }
+        Self {
+            map,
+            repeated: BTreeMap::new(),
+        }
     }
 }
 
@@ -76,10 +128,21 @@ impl std::iter::FromIterator<(String, String)> for Headers {
     fn from_iter<I: IntoIterator<Item = (String, String)>>(headers: I) -> Self {
         Self {
             map: BTreeMap::from_iter(headers),
+            repeated: BTreeMap::new(),
         }
     }
 }
 
+/// Parse value of `Pipelined-Requests` header (RFC 7826, section 18.33).
+pub(crate) fn parse_pipelined_requests(value: &str) -> Result<u32, Error> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| Error::PipelinedRequestsInvalid {
+            value: value.to_string(),
+        })
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Method {
     Describe,
@@ -93,6 +156,20 @@ pub enum Method {
     Teardown,
     GetParameter,
     SetParameter,
+    PlayNotify,
+}
+
+impl Method {
+    /// Whether method exists in RTSP version.
+    ///
+    /// RTSP 2.0 (RFC 7826) removed `RECORD` and `ANNOUNCE`, and introduced `PLAY_NOTIFY`.
+    pub fn is_supported_by(&self, version: Version) -> bool {
+        match self {
+            Method::Announce | Method::Record => version != Version::V2,
+            Method::PlayNotify => version != Version::V1,
+            _ => true,
+        }
+    }
 }
 
 impl std::fmt::Display for Method {
@@ -109,6 +186,7 @@ impl std::fmt::Display for Method {
             Method::Teardown => write!(f, "TEARDOWN"),
             Method::GetParameter => write!(f, "GET_PARAMETER"),
             Method::SetParameter => write!(f, "SET_PARAMETER"),
+            Method::PlayNotify => write!(f, "PLAY_NOTIFY"),
         }
     }
 }
@@ -129,6 +207,7 @@ impl std::str::FromStr for Method {
             "TEARDOWN" => Ok(Method::Teardown),
             "GET_PARAMETER" => Ok(Method::GetParameter),
             "SET_PARAMETER" => Ok(Method::SetParameter),
+            "PLAY_NOTIFY" => Ok(Method::PlayNotify),
             _ => Err(Error::MethodUnknown {
                 method: s.to_string(),
             }),
@@ -204,6 +283,12 @@ pub enum Status {
     OnlyAggregateOperationAllowed,
     UnsupportedTransport,
     DestinationUnreachable,
+    DataTransmissionChannelNotEstablished,
+    NotificationReasonUnknown,
+    KeyManagementError,
+    ConnectionAuthorizationRequired,
+    ConnectionCredentialsNotAccepted,
+    FailureToEstablishSecureConnection,
     InternalServerError,
     NotImplemented,
     BadGateway,
@@ -211,6 +296,7 @@ pub enum Status {
     GatewayTimeout,
     RTSPVersionNotSupported,
     OptionNotSupported,
+    ProxyUnavailable,
 }
 
 impl std::fmt::Display for Status {
@@ -257,6 +343,12 @@ pub(crate) fn status_to_code(status: Status) -> StatusCode {
         Status::OnlyAggregateOperationAllowed => 460,
         Status::UnsupportedTransport => 461,
         Status::DestinationUnreachable => 462,
+        Status::DataTransmissionChannelNotEstablished => 464,
+        Status::NotificationReasonUnknown => 465,
+        Status::KeyManagementError => 466,
+        Status::ConnectionAuthorizationRequired => 470,
+        Status::ConnectionCredentialsNotAccepted => 471,
+        Status::FailureToEstablishSecureConnection => 472,
         Status::InternalServerError => 500,
         Status::NotImplemented => 501,
         Status::BadGateway => 502,
@@ -264,6 +356,7 @@ pub(crate) fn status_to_code(status: Status) -> StatusCode {
         Status::GatewayTimeout => 504,
         Status::RTSPVersionNotSupported => 505,
         Status::OptionNotSupported => 551,
+        Status::ProxyUnavailable => 553,
     }
 }
 
@@ -305,6 +398,12 @@ pub(crate) fn status_from_code(code: StatusCode) -> Option<Status> {
         460 => Some(Status::OnlyAggregateOperationAllowed),
         461 => Some(Status::UnsupportedTransport),
         462 => Some(Status::DestinationUnreachable),
+        464 => Some(Status::DataTransmissionChannelNotEstablished),
+        465 => Some(Status::NotificationReasonUnknown),
+        466 => Some(Status::KeyManagementError),
+        470 => Some(Status::ConnectionAuthorizationRequired),
+        471 => Some(Status::ConnectionCredentialsNotAccepted),
+        472 => Some(Status::FailureToEstablishSecureConnection),
         500 => Some(Status::InternalServerError),
         501 => Some(Status::NotImplemented),
         502 => Some(Status::BadGateway),
@@ -312,6 +411,7 @@ pub(crate) fn status_from_code(code: StatusCode) -> Option<Status> {
         504 => Some(Status::GatewayTimeout),
         505 => Some(Status::RTSPVersionNotSupported),
         551 => Some(Status::OptionNotSupported),
+        553 => Some(Status::ProxyUnavailable),
         _ => None,
     }
 }
@@ -354,6 +454,14 @@ pub(crate) fn status_to_reason(status: Status) -> &'static str {
         Status::OnlyAggregateOperationAllowed => "Only Aggregate Operation Allowed",
         Status::UnsupportedTransport => "Unsupported Transport",
         Status::DestinationUnreachable => "Destination Unreachable",
+        Status::DataTransmissionChannelNotEstablished => {
+            "Data Transmission Channel Not Established"
+        }
+        Status::NotificationReasonUnknown => "Notification Reason Unknown",
+        Status::KeyManagementError => "Key Management Error",
+        Status::ConnectionAuthorizationRequired => "Connection Authorization Required",
+        Status::ConnectionCredentialsNotAccepted => "Connection Credentials Not Accepted",
+        Status::FailureToEstablishSecureConnection => "Failure to Establish Secure Connection",
         Status::InternalServerError => "Internal Server Error",
         Status::NotImplemented => "Not Implemented",
         Status::BadGateway => "Bad Gateway",
@@ -361,5 +469,6 @@ pub(crate) fn status_to_reason(status: Status) -> &'static str {
         Status::GatewayTimeout => "Gateway Timeout",
         Status::RTSPVersionNotSupported => "RTSP Version Not Supported",
         Status::OptionNotSupported => "Option Not Supported",
+        Status::ProxyUnavailable => "Proxy Unavailable",
     }
 }
@@ -121,7 +121,7 @@ impl<M: Message> Parser<M> {
             Head::Header => {
                 Ok(if !line.is_empty() {
                     let (var, val) = parse_header(line)?;
-                    headers.insert(var, val);
+                    headers.append(var, val);
                     Head::Header
                 } else {
                     // The line is empty, so we got CRLF, which signals end of headers for this
@@ -378,6 +378,39 @@ Public: DESCRIBE, SETUP, TEARDOWN, PLAY, PAUSE
         );
     }
 
+    #[test]
+    fn parse_response_repeated_header() {
+        let response = br###"RTSP/1.0 401 Unauthorized
+CSeq: 1
+WWW-Authenticate: Digest realm="camera", nonce="abc"
+WWW-Authenticate: Basic realm="camera"
+
+"###;
+
+        let response = ResponseParser::new()
+            .parse_and_into_response(response.as_slice())
+            .unwrap();
+        assert_eq!(response.status, 401);
+        assert_eq!(
+            response.headers.get("WWW-Authenticate"),
+            Some("Digest realm=\"camera\", nonce=\"abc\"")
+        );
+        assert_eq!(
+            response
+                .headers
+                .get_all("WWW-Authenticate")
+                .collect::<Vec<_>>(),
+            vec![
+                "Digest realm=\"camera\", nonce=\"abc\"",
+                "Basic realm=\"camera\""
+            ],
+        );
+        assert_eq!(
+            response.headers.get_all("CSeq").collect::<Vec<_>>(),
+            vec!["1"]
+        );
+    }
+
     #[test]
     fn parse_options_response_error() {
         let response = br###"RTSP/1.0 404 Stream Not Found
@@ -0,0 +1,224 @@
+use rave_sdp::sdp::MediaItem;
+use rave_sdp::{Attribute, Sdp};
+
+use crate::message::Uri;
+use crate::rtp_info::RtpInfo;
+use crate::transport::Transport;
+use crate::udp::UdpTrack;
+
+/// Presentation with one or more tracks that were set up in a single session.
+///
+/// Produced by [`Client::setup_presentation`](crate::client::Client::setup_presentation).
+#[derive(Debug)]
+pub struct Presentation {
+    /// Session description of presentation.
+    pub sdp: Sdp,
+    /// Aggregate control URI. Used to control all tracks at once (`PLAY`, `PAUSE`, `TEARDOWN`).
+    pub uri: Uri,
+    /// Tracks that were set up.
+    pub tracks: Vec<Track>,
+}
+
+impl Presentation {
+    /// Find track by index of media item in session description.
+    pub fn track(&self, index: usize) -> Option<&Track> {
+        self.tracks.iter().find(|track| track.index == index)
+    }
+
+    /// Find track by index of media item in session description.
+    pub fn track_mut(&mut self, index: usize) -> Option<&mut Track> {
+        self.tracks.iter_mut().find(|track| track.index == index)
+    }
+
+    /// Assign entries of `RTP-Info` header to tracks.
+    ///
+    /// Entries are matched to tracks by URL. Some servers do not echo the exact control URL, so if
+    /// that fails, entries are matched on the last path segment.
+    pub(crate) fn assign_rtp_info(&mut self, rtp_info: Vec<RtpInfo>) {
+        for rtp_info in rtp_info {
+            let track = self
+                .tracks
+                .iter()
+                .position(|track| track.uri.to_string() == rtp_info.url)
+                .or_else(|| {
+                    let control = last_segment(&rtp_info.url);
+                    self.tracks
+                        .iter()
+                        .position(|track| last_segment(&track.uri.to_string()) == control)
+                });
+            if let Some(track) = track {
+                self.tracks[track].rtp_info = Some(rtp_info);
+            }
+        }
+    }
+}
+
+/// Single track of a presentation.
+#[derive(Debug)]
+pub struct Track {
+    /// Index of media item in session description.
+    pub index: usize,
+    /// Control URI of track.
+    pub uri: Uri,
+    /// Transport chosen by server.
+    pub transport: Transport,
+    /// Sequence number and RTP timestamp of first packet. Available after playing.
+    pub rtp_info: Option<RtpInfo>,
+    /// Streams of received RTP and RTCP packets (UDP only).
+    pub udp: Option<UdpTrack>,
+}
+
+impl Track {
+    /// Retrieve media item of track from session description.
+    pub fn media<'sdp>(&self, sdp: &'sdp Sdp) -> Option<&'sdp MediaItem> {
+        sdp.media.get(self.index)
+    }
+}
+
+/// Retrieve value of `control` attribute.
+pub(crate) fn control(attributes: &[Attribute]) -> Option<&str> {
+    attributes.iter().find_map(|attribute| match attribute {
+        Attribute::Value(var, val) if var == "control" => Some(val.as_str()),
+        _ => None,
+    })
+}
+
+/// Resolve control URL against base URL (RFC 2326, appendix C.1.1).
+///
+/// The base URL is always treated as a directory, since many servers do not include a trailing
+/// slash in `Content-Base` but still expect relative control URLs to be appended to it. A query
+/// in the base URL is kept, and the control URL is appended after it, like other RTSP clients do
+/// (`rtsp://camera/realmonitor?channel=1/trackID=0`).
+///
+/// # Arguments
+///
+/// * `base` - Base URL (`Content-Base`, `Content-Location` or request URL).
+/// * `control` - Control URL from session description, if any.
+///
+/// # Return value
+///
+/// Resolved URL or `None` if the result is not a valid URI.
+pub(crate) fn resolve(base: &Uri, control: Option<&str>) -> Option<Uri> {
+    let control = match control {
+        None | Some("*") | Some("") => return Some(base.clone()),
+        Some(control) => control,
+    };
+
+    if control.contains("://") {
+        return control.parse().ok();
+    }
+
+    let mut parts = base.clone().into_parts();
+    let path = match control.strip_prefix('/') {
+        Some(_) => control.to_string(),
+        None => {
+            let base_path = parts
+                .path_and_query
+                .as_ref()
+                .map(|path_and_query| path_and_query.as_str())
+                .unwrap_or("/");
+            format!("{}/{}", base_path.trim_end_matches('/'), control)
+        }
+    };
+    parts.path_and_query = Some(path.parse().ok()?);
+    Uri::from_parts(parts).ok()
+}
+
+fn last_segment(url: &str) -> &str {
+    url.trim_end_matches('/').rsplit('/').next().unwrap_or(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn uri(s: &str) -> Uri {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn resolve_relative() {
+        assert_eq!(
+            resolve(&uri("rtsp://camera/stream/"), Some("trackID=1")),
+            Some(uri("rtsp://camera/stream/trackID=1")),
+        );
+        assert_eq!(
+            resolve(&uri("rtsp://camera:8554/stream"), Some("trackID=1")),
+            Some(uri("rtsp://camera:8554/stream/trackID=1")),
+        );
+    }
+
+    #[test]
+    fn resolve_relative_with_query() {
+        assert_eq!(
+            resolve(
+                &uri("rtsp://camera/cam/realmonitor?channel=1&subtype=0/"),
+                Some("trackID=0")
+            ),
+            Some(uri(
+                "rtsp://camera/cam/realmonitor?channel=1&subtype=0/trackID=0"
+            )),
+        );
+        assert_eq!(
+            resolve(&uri("rtsp://camera/live?channel=2"), Some("trackID=1")),
+            Some(uri("rtsp://camera/live?channel=2/trackID=1")),
+        );
+    }
+
+    #[test]
+    fn resolve_absolute_path() {
+        assert_eq!(
+            resolve(&uri("rtsp://camera/stream/"), Some("/other/track1")),
+            Some(uri("rtsp://camera/other/track1")),
+        );
+    }
+
+    #[test]
+    fn resolve_absolute_url() {
+        assert_eq!(
+            resolve(
+                &uri("rtsp://camera/stream/"),
+                Some("rtsp://192.168.1.64/Streaming/Channels/101/trackID=1"),
+            ),
+            Some(uri("rtsp://192.168.1.64/Streaming/Channels/101/trackID=1")),
+        );
+    }
+
+    #[test]
+    fn resolve_aggregate() {
+        assert_eq!(
+            resolve(&uri("rtsp://camera/stream/"), Some("*")),
+            Some(uri("rtsp://camera/stream/")),
+        );
+        assert_eq!(
+            resolve(&uri("rtsp://camera/stream/"), None),
+            Some(uri("rtsp://camera/stream/")),
+        );
+    }
+
+    #[test]
+    fn assign_rtp_info() {
+        let track = |index, url: &str| Track {
+            index,
+            uri: uri(url),
+            transport: Transport::new(),
+            rtp_info: None,
+            udp: None,
+        };
+        let mut presentation = Presentation {
+            sdp: Sdp::parse("v=0\r\no=- 0 0 IN IP4 127.0.0.1\r\ns=-\r\nt=0 0\r\n").unwrap(),
+            uri: uri("rtsp://camera/stream/"),
+            tracks: vec![
+                track(0, "rtsp://camera/stream/trackID=0"),
+                track(1, "rtsp://camera/stream/trackID=1"),
+            ],
+        };
+        presentation.assign_rtp_info(vec![
+            RtpInfo::new_with_timing("rtsp://camera/stream/trackID=0", 1, 2),
+            RtpInfo::new_with_timing("rtsp://10.0.0.1/stream/trackID=1", 3, 4),
+        ]);
+        let seq = |index| presentation.track(index).unwrap().rtp_info.as_ref()?.seq;
+        assert_eq!(seq(0), Some(1));
+        assert_eq!(seq(1), Some(3));
+    }
+}
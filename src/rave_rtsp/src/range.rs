@@ -140,3 +140,45 @@ impl std::str::FromStr for NptTime {
         }
     }
 }
+
+/// Value of `Seek-Style` header (RFC 7826, section 18.47).
+///
+/// Policy the server uses to pick the position to start from when seeking.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SeekStyle {
+    /// Start from the nearest random access point before the requested position.
+    Rap,
+    /// Like [`SeekStyle::Rap`], but the random access point must be common to all media.
+    CoRap,
+    /// Start from the first frame that is at or before the requested position.
+    FirstPrior,
+    /// Start from the first random access point after the requested position.
+    Next,
+}
+
+impl std::fmt::Display for SeekStyle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            SeekStyle::Rap => write!(f, "RAP"),
+            SeekStyle::CoRap => write!(f, "CoRAP"),
+            SeekStyle::FirstPrior => write!(f, "First-Prior"),
+            SeekStyle::Next => write!(f, "Next"),
+        }
+    }
+}
+
+impl std::str::FromStr for SeekStyle {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim() {
+            "RAP" => Ok(SeekStyle::Rap),
+            "CoRAP" => Ok(SeekStyle::CoRap),
+            "First-Prior" => Ok(SeekStyle::FirstPrior),
+            "Next" => Ok(SeekStyle::Next),
+            _ => Err(Error::SeekStyleUnknown {
+                value: s.to_string(),
+            }),
+        }
+    }
+}
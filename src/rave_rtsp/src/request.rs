@@ -1,5 +1,6 @@
-use crate::message::{Bytes, Headers, Message, Method, Uri, Version};
-use crate::range::Range;
+use crate::media_properties::MediaProperties;
+use crate::message::{parse_pipelined_requests, Bytes, Headers, Message, Method, Uri, Version};
+use crate::range::{Range, SeekStyle};
 use crate::transport::Transport;
 use crate::Error;
 
@@ -84,6 +85,17 @@ impl Request {
         )
     }
 
+    /// Notify client of event in session, such as the end of the stream (RTSP 2.0 only).
+    pub fn play_notify(uri: &Uri, cseq: usize, session: &str, reason: &str) -> Self {
+        let mut headers = Headers::with_cseq_and_session(cseq, session);
+        headers.insert("Notify-Reason".to_string(), reason.to_string());
+        Request::new(
+            RequestMetadata::new(Method::PlayNotify, uri.clone(), Version::V2),
+            headers,
+            None,
+        )
+    }
+
     // FIXME: implement request constructors for remaining RTSP methods.
 
     pub fn uri(&self) -> &Uri {
@@ -123,6 +135,29 @@ impl Request {
     pub fn range(&self) -> Option<Result<Range, Error>> {
         self.headers.get("Range").map(|value| value.parse())
     }
+
+    pub fn seek_style(&self) -> Option<Result<SeekStyle, Error>> {
+        self.headers.get("Seek-Style").map(|value| value.parse())
+    }
+
+    pub fn media_properties(&self) -> Option<Result<MediaProperties, Error>> {
+        self.headers
+            .get("Media-Properties")
+            .map(|value| value.parse())
+    }
+
+    /// Identifier that groups requests that are sent without waiting for responses (RTSP 2.0).
+    pub fn pipelined_requests(&self) -> Option<Result<u32, Error>> {
+        self.headers
+            .get("Pipelined-Requests")
+            .map(parse_pipelined_requests)
+    }
+
+    /// Reason of `PLAY_NOTIFY` request, such as `end-of-stream`, `media-properties-update` or
+    /// `scale-change`.
+    pub fn notify_reason(&self) -> Option<&str> {
+        self.headers.get("Notify-Reason")
+    }
 }
 
 impl std::fmt::Display for Request {
@@ -135,7 +170,7 @@ impl std::fmt::Display for Request {
 
         if !self.headers.is_empty() {
             writeln!(f, "\nHeaders:")?;
-            for (var, val) in self.headers.as_map() {
+            for (var, val) in self.headers.iter() {
                 writeln!(f, " - {}: {}", &var, &val)?;
             }
         }
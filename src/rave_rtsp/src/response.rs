@@ -1,9 +1,13 @@
+use crate::media_properties::MediaProperties;
 use crate::message::{
-    status_to_code, status_to_reason, Bytes, Headers, Message, Status, StatusCategory, StatusCode,
-    Version,
+    parse_pipelined_requests, status_to_code, status_to_reason, Bytes, Headers, Message, Status,
+    StatusCategory, StatusCode, Version,
 };
+use crate::range::SeekStyle;
 use crate::request::Request;
 use crate::rtp_info::RtpInfo;
+use crate::session::Session;
+use crate::Error;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Response {
@@ -37,6 +41,35 @@ impl Response {
         ResponseBuilder::error(status)
     }
 
+    pub fn session(&self) -> Option<Result<Session, Error>> {
+        self.headers.get("Session").map(|value| value.parse())
+    }
+
+    pub fn media_properties(&self) -> Option<Result<MediaProperties, Error>> {
+        self.headers
+            .get("Media-Properties")
+            .map(|value| value.parse())
+    }
+
+    /// Range units that the server supports for the resource, such as `npt` and `clock`.
+    pub fn accept_ranges(&self) -> Vec<&str> {
+        self.headers
+            .get("Accept-Ranges")
+            .map(|val| val.split(',').map(|part| part.trim()).collect::<Vec<_>>())
+            .unwrap_or_default()
+    }
+
+    pub fn seek_style(&self) -> Option<Result<SeekStyle, Error>> {
+        self.headers.get("Seek-Style").map(|value| value.parse())
+    }
+
+    /// Identifier that groups requests that are sent without waiting for responses (RTSP 2.0).
+    pub fn pipelined_requests(&self) -> Option<Result<u32, Error>> {
+        self.headers
+            .get("Pipelined-Requests")
+            .map(parse_pipelined_requests)
+    }
+
     pub fn status(&self) -> StatusCategory {
         match self.status {
             s if s >= 600 => StatusCategory::Unknown,
@@ -60,7 +93,7 @@ impl std::fmt::Display for Response {
 
         if !self.headers.is_empty() {
             writeln!(f, "\nHeaders:")?;
-            for (var, val) in self.headers.as_map() {
+            for (var, val) in self.headers.iter() {
                 writeln!(f, " - {}: {}", &var, &val)?;
             }
         }
@@ -143,6 +176,11 @@ impl ResponseBuilder {
         self
     }
 
+    pub fn with_version(mut self, version: Version) -> ResponseBuilder {
+        self.response.version = version;
+        self
+    }
+
     pub fn with_body(mut self, body: Bytes, content_type: &str) -> ResponseBuilder {
         self = self
             .with_header("Content-Length", body.len())
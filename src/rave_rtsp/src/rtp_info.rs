@@ -33,6 +33,24 @@ impl RtpInfo {
         self.rtptime = Some(rtptime);
         self
     }
+
+    /// Parse `RTP-Info` header value with one or more comma-separated entries.
+    ///
+    /// Commas that are part of a URL are tolerated: a new entry only starts with `url=`.
+    pub fn parse_list(s: &str) -> Result<Vec<RtpInfo>, Error> {
+        let mut entries: Vec<String> = Vec::new();
+        for part in s.split(',') {
+            let part = part.trim();
+            match entries.last_mut() {
+                Some(entry) if !part.starts_with("url=") => {
+                    entry.push(',');
+                    entry.push_str(part);
+                }
+                _ => entries.push(part.to_string()),
+            }
+        }
+        entries.iter().map(|entry| entry.parse()).collect()
+    }
 }
 
 impl std::fmt::Display for RtpInfo {
@@ -74,6 +92,30 @@ impl std::str::FromStr for RtpInfo {
             }
         }
 
+        // RTSP 2.0 quotes the URL and groups parameters by SSRC:
+        // `url="rtsp://example.com/foo" ssrc=0A13C760:seq=45102;rtptime=12345678`.
+        // Only the parameters of the first SSRC are used.
+        if let Some(rest) = s.trim().strip_prefix("url=\"") {
+            let (url, rest) = rest
+                .split_once('"')
+                .ok_or_else(|| Error::RtpInfoUrlMissing {
+                    value: s.to_string(),
+                })?;
+            let mut rtp_info = RtpInfo::new(url);
+            if let Some(ssrc_parameter) = rest.split_whitespace().next() {
+                let (_, parameters) = ssrc_parameter
+                    .strip_prefix("ssrc=")
+                    .and_then(|ssrc_parameter| ssrc_parameter.split_once(':'))
+                    .ok_or_else(|| Error::RtpInfoParameterUnknown {
+                        value: ssrc_parameter.to_string(),
+                    })?;
+                for part in parameters.split(';') {
+                    parse_parameter(part.trim(), &mut rtp_info)?;
+                }
+            }
+            return Ok(rtp_info);
+        }
+
         let mut parts = s.split(';');
         if let Some(url) = parts.next() {
             if let Some(url) = url.strip_prefix("url=") {
@@ -106,3 +148,59 @@ impl std::str::FromStr for RtpInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_rtsp1_list() {
+        // RFC 2326 section 12.33
+        assert_eq!(
+            RtpInfo::parse_list(
+                "url=rtsp://foo.com/bar.avi/streamid=0;seq=45102,\
+                 url=rtsp://foo.com/bar.avi/streamid=1;seq=30211",
+            )
+            .unwrap(),
+            vec![
+                RtpInfo::new("rtsp://foo.com/bar.avi/streamid=0").with_seq(45102),
+                RtpInfo::new("rtsp://foo.com/bar.avi/streamid=1").with_seq(30211),
+            ],
+        );
+    }
+
+    #[test]
+    fn parse_rtsp2_list() {
+        // RFC 7826 section 18.45
+        assert_eq!(
+            RtpInfo::parse_list(
+                "url=\"rtsp://example.com/foo/audio\" ssrc=0A13C760:seq=45102;rtptime=12345678,\
+                 url=\"rtsp://example.com/foo/video\" ssrc=9A9DE123:seq=30211;rtptime=29567112",
+            )
+            .unwrap(),
+            vec![
+                RtpInfo::new_with_timing("rtsp://example.com/foo/audio", 45102, 12345678),
+                RtpInfo::new_with_timing("rtsp://example.com/foo/video", 30211, 29567112),
+            ],
+        );
+    }
+
+    #[test]
+    fn parse_rtsp2() {
+        // Only the parameters of the first SSRC are used.
+        assert_eq!(
+            "url=\"rtsp://example.com/foo\" ssrc=0A13C760:seq=1 ssrc=9A9DE123:seq=2"
+                .parse::<RtpInfo>()
+                .unwrap(),
+            RtpInfo::new("rtsp://example.com/foo").with_seq(1),
+        );
+        assert_eq!(
+            "url=\"rtsp://example.com/foo\"".parse::<RtpInfo>().unwrap(),
+            RtpInfo::new("rtsp://example.com/foo"),
+        );
+        assert!("url=\"rtsp://example.com/foo".parse::<RtpInfo>().is_err());
+        assert!("url=\"rtsp://example.com/foo\" seq=1"
+            .parse::<RtpInfo>()
+            .is_err());
+    }
+}
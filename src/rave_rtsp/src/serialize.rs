@@ -11,6 +11,13 @@ pub trait Serialize {
 
 impl Serialize for Request {
     fn serialize(self, dst: &mut BytesMut) -> Result<()> {
+        if !self.method.is_supported_by(self.version) {
+            return Err(Error::MethodNotSupportedByVersion {
+                method: self.method,
+                version: self.version,
+            });
+        }
+
         self.method.serialize(dst)?;
         dst.put_u8(b' ');
         self.uri.serialize(dst)?;
@@ -19,7 +26,7 @@ impl Serialize for Request {
         dst.put_u8(b'\r');
         dst.put_u8(b'\n');
 
-        for (var, val) in self.headers.into_map() {
+        for (var, val) in self.headers.iter() {
             dst.put(format!("{var}: {val}\r\n").as_bytes());
         }
 
@@ -43,7 +50,7 @@ impl Serialize for Response {
         dst.put_u8(b'\r');
         dst.put_u8(b'\n');
 
-        for (var, val) in self.headers.into_map() {
+        for (var, val) in self.headers.iter() {
             dst.put(format!("{var}: {val}\r\n").as_bytes());
         }
 
@@ -84,6 +91,7 @@ impl Serialize for Method {
             Method::Teardown => b"TEARDOWN".as_slice(),
             Method::GetParameter => b"GET_PARAMETER".as_slice(),
             Method::SetParameter => b"SET_PARAMETER".as_slice(),
+            Method::PlayNotify => b"PLAY_NOTIFY".as_slice(),
         };
 
         dst.put(method);
@@ -324,6 +332,59 @@ CSeq: 2\r\n\
         ))
     }
 
+    #[test]
+    fn serialize_play_notify_request_v2() {
+        let request_bytes = Bytes::from(
+            b"PLAY_NOTIFY rtsp://example.com/media.mp4 RTSP/2.0\r\n\
+CSeq: 854\r\n\
+Notify-Reason: end-of-stream\r\n\
+Session: uZ3ci0K+Ld-M\r\n\
+\r\n\
+"
+            .as_slice(),
+        );
+
+        let request = Request::new(
+            RequestMetadata::new(
+                Method::PlayNotify,
+                "rtsp://example.com/media.mp4".try_into().unwrap(),
+                Version::V2,
+            ),
+            Headers::from_iter([
+                ("CSeq".to_string(), "854".to_string()),
+                ("Notify-Reason".to_string(), "end-of-stream".to_string()),
+                ("Session".to_string(), "uZ3ci0K+Ld-M".to_string()),
+            ]),
+            None,
+        );
+
+        let mut request_serialized = BytesMut::new();
+        request.serialize(&mut request_serialized).unwrap();
+        assert_eq!(request_serialized, request_bytes);
+    }
+
+    #[test]
+    fn serialize_record_request_v2_errors() {
+        let request = Request::new(
+            RequestMetadata::new(
+                Method::Record,
+                "rtsp://example.com/media.mp4".try_into().unwrap(),
+                Version::V2,
+            ),
+            Headers::from_iter([("CSeq".to_string(), "3".to_string())]),
+            None,
+        );
+
+        let mut request_serialized = BytesMut::new();
+        assert!(matches!(
+            request.serialize(&mut request_serialized),
+            Err(Error::MethodNotSupportedByVersion {
+                method: Method::Record,
+                version: Version::V2,
+            })
+        ))
+    }
+
     #[test]
     fn serialize_describe_response() {
         let response_bytes = Bytes::from(
@@ -0,0 +1,857 @@
+use std::net::SocketAddr;
+use std::sync::{Arc, Weak};
+
+use bytes::BytesMut;
+
+use futures::SinkExt;
+
+use rave_rtp::packet::Packet;
+use rave_rtp::serialize::Serialize as SerializeRtp;
+use rave_sdp::{Attribute, Sdp};
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::{broadcast, mpsc, oneshot};
+
+use tokio_stream::StreamExt;
+
+use crate::interleaved::{ChannelId, MaybeInterleaved, ResponseMaybeInterleaved};
+use crate::io::AsServer;
+use crate::message::{Method, Status, Version};
+use crate::range::Range;
+use crate::request::Request;
+use crate::response::{Response, ResponseBuilder};
+use crate::rtp_info::RtpInfo;
+use crate::task::AbortOnDrop;
+use crate::tokio_codec::Codec;
+use crate::transport::{Channel, Lower, Parameter, Port, Transport};
+use crate::udp;
+
+use super::session::{ConnectionId, SessionId, Sink, Track};
+use super::source::Source;
+use super::Shared;
+
+type HandlerResult = std::result::Result<ResponseBuilder, Status>;
+
+/// Number of outgoing messages (responses and interleaved packets) buffered per connection.
+const OUTGOING_CAPACITY: usize = 1024;
+
+/// Methods supported by the server.
+const PUBLIC: [Method; 7] = [
+    Method::Options,
+    Method::Describe,
+    Method::Setup,
+    Method::Play,
+    Method::Pause,
+    Method::Teardown,
+    Method::GetParameter,
+];
+
+/// Serve a single RTSP connection until it is closed.
+///
+/// # Arguments
+///
+/// * `read` - Read half of connection.
+/// * `write` - Write half of connection.
+/// * `peer` - Address of client.
+/// * `shared` - Server state.
+pub(super) async fn serve<S, R, W>(read: R, write: W, peer: SocketAddr, shared: Arc<Shared<S>>)
+where
+    S: Source,
+    R: AsyncRead + Unpin + Send + 'static,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    // Responses and interleaved packets are written by a single task, so that streams of different
+    // sessions and the request handler can share the connection.
+    let (sender, mut receiver) = mpsc::channel::<ResponseMaybeInterleaved>(OUTGOING_CAPACITY);
+    let _writer = AbortOnDrop::spawn(async move {
+        let mut write = tokio_util::codec::FramedWrite::new(write, Codec::<AsServer>::new());
+        while let Some(message) = receiver.recv().await {
+            if write.send(message).await.is_err() {
+                break;
+            }
+        }
+    });
+
+    let mut read = tokio_util::codec::FramedRead::new(read, Codec::<AsServer>::new());
+    let mut connection = Connection {
+        id: shared.sessions.connection_id(),
+        peer,
+        shared,
+        sender,
+        sessions: Vec::new(),
+        next_channel: Some(0),
+        pending_streams: Vec::new(),
+    };
+
+    while let Some(Ok(message)) = read.next().await {
+        match message {
+            MaybeInterleaved::Message(request) => {
+                let response = connection.handle(request).await;
+                if connection.sender.send(response.into()).await.is_err() {
+                    break;
+                }
+                connection.start_streams();
+            }
+            // Clients send RTCP receiver reports interleaved, which count as session activity.
+            MaybeInterleaved::Interleaved { .. } => connection.touch(),
+        }
+    }
+
+    connection.close();
+}
+
+struct Connection<S: Source> {
+    /// Identifier of this connection, which owns the sessions it creates.
+    id: ConnectionId,
+    peer: SocketAddr,
+    shared: Arc<Shared<S>>,
+    sender: mpsc::Sender<ResponseMaybeInterleaved>,
+    /// Sessions that were created over this connection.
+    sessions: Vec<SessionId>,
+    /// Next free interleaved channel.
+    next_channel: Option<ChannelId>,
+    /// Streams that start once the response to the `PLAY` request that started them is queued.
+    pending_streams: Vec<oneshot::Sender<()>>,
+}
+
+impl<S: Source> Connection<S> {
+    async fn handle(&mut self, request: Request) -> Response {
+        let response = match self.handle_inner(&request).await {
+            Ok(response) => response,
+            Err(status) => Response::error(status),
+        };
+        response.with_cseq_of(&request).build()
+    }
+
+    async fn handle_inner(&mut self, request: &Request) -> HandlerResult {
+        if !request.headers.contains("CSeq") {
+            return Err(Status::BadRequest);
+        }
+
+        // Clients that try RTSP 2.0 fall back to 1.0 when they receive this status.
+        if request.version != Version::V1 {
+            return Err(Status::RTSPVersionNotSupported);
+        }
+
+        if let Some(require) = request.require() {
+            return Ok(
+                Response::error(Status::OptionNotSupported).with_header("Unsupported", require)
+            );
+        }
+
+        // Any request that refers to a session keeps it alive.
+        if let Some(id) = session_id(request) {
+            self.shared.sessions.touch(id, self.id);
+        }
+
+        match request.method {
+            Method::Options => Ok(self.options()),
+            Method::Describe => self.describe(request),
+            Method::Setup => self.setup(request).await,
+            Method::Play => self.play(request),
+            Method::Pause => self.pause(request),
+            Method::Teardown => self.teardown(request),
+            Method::GetParameter => self.get_parameter(request),
+            _ => Err(Status::NotImplemented),
+        }
+    }
+
+    fn options(&self) -> ResponseBuilder {
+        Response::ok().with_header(
+            "Public",
+            PUBLIC
+                .iter()
+                .map(|method| method.to_string())
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    }
+
+    fn describe(&self, request: &Request) -> HandlerResult {
+        let sdp = self
+            .shared
+            .source
+            .describe(request.path())
+            .ok_or(Status::NotFound)?;
+        Ok(Response::ok()
+            .with_header(
+                "Content-Base",
+                format!("{}/", request.uri.to_string().trim_end_matches('/')),
+            )
+            .with_sdp(with_controls(sdp).to_string()))
+    }
+
+    async fn setup(&mut self, request: &Request) -> HandlerResult {
+        let (path, track) = self.resolve_track(request.path())?;
+        let transports = request.transport().map_err(|_| Status::BadRequest)?;
+
+        let id = match session_id(request) {
+            Some(id) => {
+                match self.shared.sessions.with(id, self.id, |session| {
+                    (session.path == path, session.playing)
+                }) {
+                    None => return Err(Status::SessionNotFound),
+                    Some((false, _)) => return Err(Status::AggregateOperationNotAllowed),
+                    Some((true, true)) => return Err(Status::MethodNotValidInThisState),
+                    Some((true, false)) => Some(id.to_string()),
+                }
+            }
+            None => None,
+        };
+
+        let (transport, sink, rtcp) = self.negotiate_transport(&transports).await?;
+
+        // Only create a session once the transport is agreed on.
+        let id = match id {
+            Some(id) => id,
+            None => {
+                let id = self.shared.sessions.create(&path, self.id);
+                self.sessions.push(id.clone());
+                id
+            }
+        };
+
+        let rtcp = rtcp.map(|rtcp| {
+            let shared = Arc::downgrade(&self.shared);
+            AbortOnDrop::spawn(receive_rtcp(rtcp, shared, id.clone(), self.id))
+        });
+
+        let track_setup = Track {
+            uri: request.uri.clone(),
+            sink,
+            stream: None,
+            _rtcp: rtcp,
+        };
+        self.shared
+            .sessions
+            .with(&id, self.id, |session| {
+                session.tracks.insert(track, track_setup)
+            })
+            .ok_or(Status::SessionNotFound)?;
+
+        Ok(Response::ok()
+            .with_header("Transport", transport)
+            .with_header("Session", self.shared.sessions.header(&id)))
+    }
+
+    fn play(&mut self, request: &Request) -> HandlerResult {
+        let id = session_id(request).ok_or(Status::SessionNotFound)?;
+        // Only live presentations are served, so the range can be ignored as long as it is valid.
+        if let Some(range) = request.range() {
+            range.map_err(|_| Status::InvalidRange)?;
+        }
+
+        let shared = &self.shared;
+        let mut pending_streams = Vec::new();
+        let rtp_info = shared
+            .sessions
+            .with(id, self.id, |session| {
+                if session.tracks.is_empty() {
+                    return Err(Status::MethodNotValidInThisState);
+                }
+
+                let mut rtp_info = Vec::with_capacity(session.tracks.len());
+                for (index, track) in session.tracks.iter_mut() {
+                    let mut item = RtpInfo::new(&track.uri.to_string());
+                    if track.stream.is_none() {
+                        let subscription = shared
+                            .source
+                            .subscribe(&session.path, *index)
+                            .ok_or(Status::NotFound)?;
+                        item.seq = subscription.seq;
+                        item.rtptime = subscription.rtptime;
+                        let (start, started) = oneshot::channel();
+                        let sink = track.sink.clone();
+                        track.stream = Some(AbortOnDrop::spawn(async move {
+                            if started.await.is_ok() {
+                                stream(subscription.packets, sink).await;
+                            }
+                        }));
+                        pending_streams.push(start);
+                    }
+                    rtp_info.push(item);
+                }
+                session.playing = true;
+
+                Ok(rtp_info)
+            })
+            .ok_or(Status::SessionNotFound)??;
+        self.pending_streams.extend(pending_streams);
+
+        Ok(Response::ok()
+            .with_header("Session", shared.sessions.header(id))
+            .with_header("Range", Range::new_for_live())
+            .with_rtp_info(rtp_info))
+    }
+
+    fn pause(&mut self, request: &Request) -> HandlerResult {
+        let id = session_id(request).ok_or(Status::SessionNotFound)?;
+        self.shared
+            .sessions
+            .with(id, self.id, |session| {
+                for track in session.tracks.values_mut() {
+                    track.stream = None;
+                }
+                session.playing = false;
+            })
+            .ok_or(Status::SessionNotFound)?;
+
+        Ok(Response::ok().with_header("Session", self.shared.sessions.header(id)))
+    }
+
+    fn teardown(&mut self, request: &Request) -> HandlerResult {
+        let id = session_id(request).ok_or(Status::SessionNotFound)?;
+        self.shared
+            .sessions
+            .remove(id, self.id)
+            .ok_or(Status::SessionNotFound)?;
+        self.sessions.retain(|session_id| session_id != id);
+
+        Ok(Response::ok())
+    }
+
+    fn get_parameter(&self, request: &Request) -> HandlerResult {
+        // No parameters are supported, so this is only useful as a keep-alive.
+        match session_id(request) {
+            Some(id) if self.shared.sessions.touch(id, self.id) => {
+                Ok(Response::ok().with_header("Session", self.shared.sessions.header(id)))
+            }
+            Some(_) => Err(Status::SessionNotFound),
+            None => Ok(Response::ok()),
+        }
+    }
+
+    /// Resolve path in request URI to presentation path and track index.
+    ///
+    /// The path either refers to a track directly (through the track's control attribute), or it
+    /// refers to a presentation with just a single track.
+    fn resolve_track(&self, path: &str) -> Result<(String, usize), Status> {
+        let source = &self.shared.source;
+        if let Some(sdp) = source.describe(path) {
+            return if sdp.media.len() == 1 {
+                Ok((path.to_string(), 0))
+            } else {
+                Err(Status::AggregateOperationNotAllowed)
+            };
+        }
+
+        let (parent, control) = path.rsplit_once('/').ok_or(Status::NotFound)?;
+        let sdp = source.describe(parent).ok_or(Status::NotFound)?;
+        sdp.media
+            .iter()
+            .enumerate()
+            .position(|(index, media)| track_control(index, &media.attributes) == control)
+            .map(|index| (parent.to_string(), index))
+            .ok_or(Status::NotFound)
+    }
+
+    /// Pick the first transport proposed by the client that the server supports.
+    ///
+    /// # Return value
+    ///
+    /// Transport to respond with, sink to send packets to and RTCP socket (UDP only).
+    async fn negotiate_transport(
+        &mut self,
+        transports: &[Transport],
+    ) -> Result<(Transport, Sink, Option<tokio::net::UdpSocket>), Status> {
+        for transport in transports {
+            if transport
+                .parameters_iter()
+                .any(|parameter| *parameter == Parameter::Multicast)
+            {
+                continue;
+            }
+
+            match transport.lower_protocol() {
+                Some(Lower::Tcp) => {
+                    let (rtp_channel, rtcp_channel) = match transport.interleaved_channel() {
+                        Some(Channel::Range(rtp_channel, rtcp_channel)) => {
+                            (*rtp_channel, *rtcp_channel)
+                        }
+                        Some(Channel::Single(rtp_channel)) => match rtp_channel.checked_add(1) {
+                            Some(rtcp_channel) => (*rtp_channel, rtcp_channel),
+                            // There is no channel left for RTCP.
+                            None => continue,
+                        },
+                        None => match self.next_channel {
+                            Some(rtp_channel) if rtp_channel < ChannelId::MAX => {
+                                (rtp_channel, rtp_channel + 1)
+                            }
+                            _ => continue,
+                        },
+                    };
+                    self.next_channel = self
+                        .next_channel
+                        .max(rtcp_channel.checked_add(1))
+                        .filter(|_| rtcp_channel < ChannelId::MAX);
+
+                    return Ok((
+                        Transport::new()
+                            .with_lower_protocol(Lower::Tcp)
+                            .with_parameter(Parameter::Unicast)
+                            .with_parameter(Parameter::Interleaved(Channel::Range(
+                                rtp_channel,
+                                rtcp_channel,
+                            ))),
+                        Sink::Interleaved {
+                            channel: rtp_channel,
+                            sender: self.sender.clone(),
+                        },
+                        None,
+                    ));
+                }
+                None | Some(Lower::Udp) => {
+                    let (client_rtp_port, client_rtcp_port) = match transport.client_port() {
+                        Some(Port::Range(rtp_port, rtcp_port)) => (*rtp_port, *rtcp_port),
+                        Some(Port::Single(rtp_port)) => (*rtp_port, rtp_port.saturating_add(1)),
+                        None => continue,
+                    };
+
+                    let local_ip: std::net::IpAddr = match self.peer {
+                        SocketAddr::V4(_) => std::net::Ipv4Addr::UNSPECIFIED.into(),
+                        SocketAddr::V6(_) => std::net::Ipv6Addr::UNSPECIFIED.into(),
+                    };
+                    let (rtp, rtcp) = udp::bind_pair(local_ip)
+                        .await
+                        .map_err(|_| Status::InternalServerError)?;
+                    let server_rtp_port = rtp
+                        .local_addr()
+                        .map_err(|_| Status::InternalServerError)?
+                        .port();
+
+                    let mut response_transport = Transport::new();
+                    if let Some(lower) = transport.lower_protocol() {
+                        response_transport = response_transport.with_lower_protocol(lower.clone());
+                    }
+                    let response_transport = response_transport
+                        .with_parameter(Parameter::Unicast)
+                        .with_parameter(Parameter::ClientPort(Port::Range(
+                            client_rtp_port,
+                            client_rtcp_port,
+                        )))
+                        .with_parameter(Parameter::ServerPort(Port::Range(
+                            server_rtp_port,
+                            server_rtp_port + 1,
+                        )));
+
+                    return Ok((
+                        response_transport,
+                        Sink::Udp {
+                            socket: Arc::new(rtp),
+                            destination: SocketAddr::new(self.peer.ip(), client_rtp_port),
+                        },
+                        Some(rtcp),
+                    ));
+                }
+            }
+        }
+
+        Err(Status::UnsupportedTransport)
+    }
+
+    /// Start streams of `PLAY` request. Must be called after the response was queued, so that
+    /// clients never receive packets before the response.
+    fn start_streams(&mut self) {
+        for start in self.pending_streams.drain(..) {
+            let _ = start.send(());
+        }
+    }
+
+    /// Mark all sessions of this connection as active.
+    fn touch(&self) {
+        for id in &self.sessions {
+            self.shared.sessions.touch(id, self.id);
+        }
+    }
+
+    /// Tear down sessions of this connection that stream over it.
+    fn close(&mut self) {
+        for id in self.sessions.drain(..) {
+            if let Some(true) = self
+                .shared
+                .sessions
+                .with(&id, self.id, |session| session.is_interleaved())
+            {
+                self.shared.sessions.remove(&id, self.id);
+            }
+        }
+    }
+}
+
+/// Stream packets from subscription to sink.
+async fn stream(mut packets: broadcast::Receiver<Packet>, sink: Sink) {
+    loop {
+        let packet = match packets.recv().await {
+            Ok(packet) => packet,
+            // The client is too slow. Skipping packets is better than holding up the source.
+            Err(broadcast::error::RecvError::Lagged(_)) => continue,
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
+        let mut payload = BytesMut::new();
+        if packet.serialize(&mut payload).is_err() {
+            continue;
+        }
+
+        match &sink {
+            Sink::Interleaved { channel, sender } => {
+                let message = MaybeInterleaved::Interleaved {
+                    channel: *channel,
+                    payload: payload.freeze(),
+                };
+                if sender.send(message).await.is_err() {
+                    break;
+                }
+            }
+            Sink::Udp {
+                socket,
+                destination,
+            } => {
+                // Datagrams may get lost anyway, so failing to send one is not fatal.
+                let _ = socket.send_to(&payload, destination).await;
+            }
+        }
+    }
+}
+
+/// Receive RTCP packets from client and count them as session activity.
+async fn receive_rtcp<S: Source>(
+    socket: tokio::net::UdpSocket,
+    shared: Weak<Shared<S>>,
+    id: SessionId,
+    owner: ConnectionId,
+) {
+    let mut buffer = [0_u8; 2048];
+    while socket.recv(&mut buffer).await.is_ok() {
+        match shared.upgrade() {
+            Some(shared) => {
+                shared.sessions.touch(&id, owner);
+            }
+            None => break,
+        }
+    }
+}
+
+/// Extract session identifier from `Session` header of request.
+fn session_id(request: &Request) -> Option<&str> {
+    request
+        .session()
+        .and_then(|session| session.split(';').next())
+        .map(|id| id.trim())
+}
+
+/// Retrieve relative control URL of track.
+///
+/// If the media item has no `control` attribute, the control URL is derived from the index. This
+/// matches the attribute that [`with_controls`] adds.
+fn track_control(index: usize, attributes: &[Attribute]) -> String {
+    attributes
+        .iter()
+        .find_map(|attribute| match attribute {
+            Attribute::Value(var, val) if var == "control" => {
+                Some(val.rsplit('/').next().unwrap_or(val).to_string())
+            }
+            _ => None,
+        })
+        .unwrap_or_else(|| format!("trackID={index}"))
+}
+
+/// Add `control` attributes to session description where missing.
+fn with_controls(mut sdp: Sdp) -> Sdp {
+    fn has_control(attributes: &[Attribute]) -> bool {
+        attributes
+            .iter()
+            .any(|attribute| matches!(attribute, Attribute::Value(var, _) if var == "control"))
+    }
+
+    if !has_control(&sdp.attributes) {
+        sdp.attributes
+            .push(Attribute::Value("control".to_string(), "*".to_string()));
+    }
+    for (index, media) in sdp.media.iter_mut().enumerate() {
+        if !has_control(&media.attributes) {
+            media.attributes.push(Attribute::Value(
+                "control".to_string(),
+                track_control(index, &media.attributes),
+            ));
+        }
+    }
+
+    sdp
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::net::TcpStream;
+    use tokio_util::codec::Framed;
+
+    use crate::io::AsClient;
+    use crate::message::Uri;
+    use crate::response::Response;
+    use crate::server::{Broadcast, Server, Subscription};
+    use crate::testing::{packet, sdp, Camera};
+
+    fn control(attributes: &[Attribute]) -> Option<&str> {
+        attributes.iter().find_map(|attribute| match attribute {
+            Attribute::Value(var, val) if var == "control" => Some(val.as_str()),
+            _ => None,
+        })
+    }
+
+    async fn request(
+        framed: &mut Framed<TcpStream, Codec<AsClient>>,
+        request: Request,
+    ) -> Response {
+        framed.send(request.into()).await.unwrap();
+        match framed.next().await.unwrap().unwrap() {
+            MaybeInterleaved::Message(response) => response,
+            MaybeInterleaved::Interleaved { .. } => panic!("unexpected interleaved data"),
+        }
+    }
+
+    #[test]
+    fn with_controls_adds_missing_controls() {
+        let sdp = with_controls(sdp(2));
+        assert_eq!(control(&sdp.attributes), Some("*"));
+        assert_eq!(control(&sdp.media[0].attributes), Some("trackID=0"));
+        assert_eq!(control(&sdp.media[1].attributes), Some("trackID=1"));
+    }
+
+    #[test]
+    fn with_controls_keeps_existing_controls() {
+        let mut sdp = sdp(1);
+        sdp.media[0]
+            .attributes
+            .push(Attribute::Value("control".to_string(), "video".to_string()));
+        let sdp = with_controls(sdp);
+        assert_eq!(control(&sdp.media[0].attributes), Some("video"));
+        assert_eq!(track_control(0, &sdp.media[0].attributes), "video");
+    }
+
+    #[tokio::test]
+    async fn play_interleaved() {
+        let video = Broadcast::default();
+        let server = Server::bind("127.0.0.1:0", Camera::new(video.clone()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        let _server = AbortOnDrop::spawn(async move {
+            let _ = server.run().await;
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut framed = Framed::new(stream, Codec::<AsClient>::new());
+        let uri: Uri = format!("rtsp://{addr}/camera").parse().unwrap();
+
+        let response = request(&mut framed, Request::options(&uri, 1)).await;
+        assert_eq!(response.status, 200);
+        assert!(response.headers.get("Public").unwrap().contains("SETUP"));
+
+        let response = request(&mut framed, Request::describe(&uri, 2)).await;
+        assert_eq!(response.status, 200);
+        assert_eq!(
+            response.headers.get("Content-Base"),
+            Some(format!("rtsp://{addr}/camera/").as_str()),
+        );
+
+        let track_uri: Uri = format!("rtsp://{addr}/camera/trackID=0").parse().unwrap();
+        let transport = Transport::new()
+            .with_lower_protocol(Lower::Tcp)
+            .with_parameter(Parameter::Unicast)
+            .with_parameter(Parameter::Interleaved(Channel::Range(0, 1)));
+        let response = request(&mut framed, Request::setup(&track_uri, 3, transport, None)).await;
+        assert_eq!(response.status, 200);
+        let session = response.headers.get("Session").unwrap();
+        assert!(session.ends_with(";timeout=60"));
+        let session = session.split(';').next().unwrap().to_string();
+
+        let response = request(
+            &mut framed,
+            Request::play(&uri, 4, &session, Range::new_for_live()),
+        )
+        .await;
+        assert_eq!(response.status, 200);
+        assert!(response.headers.contains("RTP-Info"));
+
+        let packet = packet(1);
+        let mut expected = BytesMut::new();
+        packet.clone().serialize(&mut expected).unwrap();
+        video.send([packet]);
+
+        match framed.next().await.unwrap().unwrap() {
+            MaybeInterleaved::Interleaved { channel, payload } => {
+                assert_eq!(channel, 0);
+                assert_eq!(payload, expected.freeze());
+            }
+            MaybeInterleaved::Message(_) => panic!("expected interleaved data"),
+        }
+
+        let response = request(&mut framed, Request::teardown(&uri, 5, &session)).await;
+        assert_eq!(response.status, 200);
+        let response = request(&mut framed, Request::teardown(&uri, 6, &session)).await;
+        assert_eq!(response.status, 454);
+    }
+
+    #[tokio::test]
+    async fn setup_unknown_session() {
+        let server = Server::bind("127.0.0.1:0", Camera::new(Broadcast::default()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        let _server = AbortOnDrop::spawn(async move {
+            let _ = server.run().await;
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut framed = Framed::new(stream, Codec::<AsClient>::new());
+        let uri: Uri = format!("rtsp://{addr}/camera").parse().unwrap();
+
+        let transport = Transport::new()
+            .with_lower_protocol(Lower::Tcp)
+            .with_parameter(Parameter::Interleaved(Channel::Range(0, 1)));
+        let response = request(&mut framed, Request::setup(&uri, 1, transport, Some("1234"))).await;
+        assert_eq!(response.status, 454);
+    }
+
+    #[tokio::test]
+    async fn reject_session_of_other_connection() {
+        let server = Server::bind("127.0.0.1:0", Camera::new(Broadcast::default()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        let _server = AbortOnDrop::spawn(async move {
+            let _ = server.run().await;
+        });
+
+        let uri: Uri = format!("rtsp://{addr}/camera").parse().unwrap();
+        let mut owner = Framed::new(
+            TcpStream::connect(addr).await.unwrap(),
+            Codec::<AsClient>::new(),
+        );
+        let transport = Transport::new()
+            .with_lower_protocol(Lower::Tcp)
+            .with_parameter(Parameter::Interleaved(Channel::Range(0, 1)));
+        let response = request(&mut owner, Request::setup(&uri, 1, transport, None)).await;
+        assert_eq!(response.status, 200);
+        let session = response.session().unwrap().unwrap().id;
+
+        // Other connections cannot control the session, even if they know its identifier.
+        let mut other = Framed::new(
+            TcpStream::connect(addr).await.unwrap(),
+            Codec::<AsClient>::new(),
+        );
+        let requests = [
+            Request::play(&uri, 1, &session, Range::new_for_live()),
+            Request::pause(&uri, 2, &session),
+            Request::teardown(&uri, 3, &session),
+        ];
+        for other_request in requests {
+            assert_eq!(request(&mut other, other_request).await.status, 454);
+        }
+
+        let play = Request::play(&uri, 2, &session, Range::new_for_live());
+        assert_eq!(request(&mut owner, play).await.status, 200);
+        let teardown = Request::teardown(&uri, 3, &session);
+        assert_eq!(request(&mut owner, teardown).await.status, 200);
+    }
+
+    #[tokio::test]
+    async fn setup_interleaved_without_rtcp_channel() {
+        let server = Server::bind("127.0.0.1:0", Camera::new(Broadcast::default()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        let _server = AbortOnDrop::spawn(async move {
+            let _ = server.run().await;
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut framed = Framed::new(stream, Codec::<AsClient>::new());
+        let uri: Uri = format!("rtsp://{addr}/camera").parse().unwrap();
+
+        let transport = Transport::new()
+            .with_lower_protocol(Lower::Tcp)
+            .with_parameter(Parameter::Interleaved(Channel::Single(255)));
+        let response = request(&mut framed, Request::setup(&uri, 1, transport, None)).await;
+        assert_eq!(response.status, 461);
+    }
+
+    /// Source that has a packet ready as soon as it is subscribed to.
+    struct EagerCamera {
+        video: Broadcast,
+    }
+
+    impl Source for EagerCamera {
+        fn describe(&self, path: &str) -> Option<Sdp> {
+            (path == "/camera").then(|| sdp(1))
+        }
+
+        fn subscribe(&self, path: &str, track: usize) -> Option<Subscription> {
+            (path == "/camera" && track == 0).then(|| {
+                let subscription = self.video.subscribe();
+                self.video.send([packet(1)]);
+                subscription
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn play_response_precedes_packets() {
+        let server = Server::bind(
+            "127.0.0.1:0",
+            EagerCamera {
+                video: Broadcast::default(),
+            },
+        )
+        .await
+        .unwrap();
+        let addr = server.local_addr().unwrap();
+        let _server = AbortOnDrop::spawn(async move {
+            let _ = server.run().await;
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut framed = Framed::new(stream, Codec::<AsClient>::new());
+        let uri: Uri = format!("rtsp://{addr}/camera").parse().unwrap();
+
+        let transport = Transport::new()
+            .with_lower_protocol(Lower::Tcp)
+            .with_parameter(Parameter::Interleaved(Channel::Range(0, 1)));
+        let response = request(&mut framed, Request::setup(&uri, 1, transport, None)).await;
+        let session = response.session().unwrap().unwrap();
+
+        // Panics if the packet arrives before the response.
+        let response = request(
+            &mut framed,
+            Request::play(&uri, 2, &session.id, Range::new_for_live()),
+        )
+        .await;
+        assert_eq!(response.status, 200);
+        assert!(matches!(
+            framed.next().await.unwrap().unwrap(),
+            MaybeInterleaved::Interleaved { channel: 0, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn connections_end_with_server() {
+        let server = Server::bind("127.0.0.1:0", Camera::new(Broadcast::default()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        let server = AbortOnDrop::spawn(async move {
+            let _ = server.run().await;
+        });
+
+        let stream = TcpStream::connect(addr).await.unwrap();
+        let mut framed = Framed::new(stream, Codec::<AsClient>::new());
+        let uri: Uri = format!("rtsp://{addr}/camera").parse().unwrap();
+        let response = request(&mut framed, Request::options(&uri, 1)).await;
+        assert_eq!(response.status, 200);
+
+        drop(server);
+        assert!(framed.next().await.is_none());
+    }
+}
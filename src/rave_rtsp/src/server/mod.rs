@@ -0,0 +1,157 @@
+mod connection;
+mod session;
+mod source;
+mod tunnel;
+
+pub use source::{Broadcast, Source, Subscription};
+
+use std::sync::{Arc, Weak};
+use std::time::Duration;
+
+use tokio::net::TcpListener;
+use tokio::task::JoinSet;
+
+use crate::task::AbortOnDrop;
+
+use session::Sessions;
+use tunnel::Tunnels;
+
+/// RTSP server.
+///
+/// Serves the presentations of a [`Source`] to RTSP clients. The [`Server`] accepts connections,
+/// handles `OPTIONS`, `DESCRIBE`, `SETUP`, `PLAY`, `PAUSE`, `TEARDOWN` and `GET_PARAMETER`
+/// requests, keeps track of sessions and streams RTP packets to clients, either interleaved over
+/// the RTSP connection or over UDP.
+///
+/// Sessions that do not show any activity for longer than the session timeout are torn down.
+/// Sessions that stream over the RTSP connection are also torn down when the connection closes.
+/// Sessions can only be controlled over the connection that set them up.
+///
+/// Clients can also tunnel RTSP over HTTP on the same port, for networks that only allow HTTP. The
+/// client then sends requests over an HTTP `POST` connection, and receives responses and media
+/// over an HTTP `GET` connection.
+///
+/// # Example
+///
+/// ```no_run
+/// use rave_rtsp::server::{Broadcast, Server, Source, Subscription};
+/// use rave_sdp::Sdp;
+///
+/// struct Camera {
+///     sdp: Sdp,
+///     video: Broadcast,
+/// }
+///
+/// impl Source for Camera {
+///     fn describe(&self, path: &str) -> Option<Sdp> {
+///         (path == "/camera").then(|| self.sdp.clone())
+///     }
+///
+///     fn subscribe(&self, path: &str, track: usize) -> Option<Subscription> {
+///         (path == "/camera" && track == 0).then(|| self.video.subscribe())
+///     }
+/// }
+///
+/// # async fn example(sdp: Sdp) -> std::io::Result<()> {
+/// let video = Broadcast::default();
+/// // Feed packetizer output into `video.send(...)` from another task.
+/// let server = Server::bind("0.0.0.0:554", Camera { sdp, video }).await?;
+/// server.run().await
+/// # }
+/// ```
+pub struct Server<S: Source> {
+    listener: TcpListener,
+    source: S,
+    session_timeout: Duration,
+}
+
+impl<S: Source> Server<S> {
+    /// Default session timeout.
+    pub const DEFAULT_SESSION_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Interval at which timed out sessions are cleaned up.
+    const REAP_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Create server that listens on `addr`.
+    ///
+    /// # Arguments
+    ///
+    /// * `addr` - Address to listen on.
+    /// * `source` - Source of presentations to serve.
+    pub async fn bind(addr: impl tokio::net::ToSocketAddrs, source: S) -> std::io::Result<Self> {
+        Ok(Self::new(TcpListener::bind(addr).await?, source))
+    }
+
+    /// Create server from existing listener.
+    ///
+    /// # Arguments
+    ///
+    /// * `listener` - Listener to accept connections from.
+    /// * `source` - Source of presentations to serve.
+    pub fn new(listener: TcpListener, source: S) -> Self {
+        Self {
+            listener,
+            source,
+            session_timeout: Self::DEFAULT_SESSION_TIMEOUT,
+        }
+    }
+
+    /// Set session timeout.
+    ///
+    /// The timeout is communicated to clients in the `Session` header.
+    #[inline]
+    pub fn with_session_timeout(mut self, timeout: Duration) -> Self {
+        self.session_timeout = timeout;
+        self
+    }
+
+    /// Local address the server is listening on.
+    #[inline]
+    pub fn local_addr(&self) -> std::io::Result<std::net::SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Accept and serve connections.
+    ///
+    /// This only returns if accepting a connection fails. Connections are served until they are
+    /// closed, or until the returned future is dropped.
+    pub async fn run(self) -> std::io::Result<()> {
+        let shared = Arc::new(Shared {
+            source: self.source,
+            sessions: Sessions::new(self.session_timeout),
+            tunnels: Tunnels::new(),
+        });
+        let _reaper = AbortOnDrop::spawn(reap(Arc::downgrade(&shared)));
+        // Connections are aborted when the set is dropped.
+        let mut connections = JoinSet::new();
+        loop {
+            tokio::select! {
+                accepted = self.listener.accept() => {
+                    let (stream, peer) = accepted?;
+                    connections.spawn(tunnel::serve(stream, peer, shared.clone()));
+                }
+                // Forget about connections that were closed.
+                Some(_) = connections.join_next() => {}
+            }
+        }
+    }
+}
+
+/// State shared between all connections of a server.
+struct Shared<S: Source> {
+    source: S,
+    sessions: Sessions,
+    tunnels: Tunnels,
+}
+
+/// Periodically clean up timed out sessions until the server stops.
+async fn reap<S: Source>(shared: Weak<Shared<S>>) {
+    let mut interval = tokio::time::interval(Server::<S>::REAP_INTERVAL);
+    loop {
+        interval.tick().await;
+        match shared.upgrade() {
+            Some(shared) => shared.sessions.reap(),
+            None => break,
+        }
+    }
+}
@@ -0,0 +1,169 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::interleaved::{ChannelId, ResponseMaybeInterleaved};
+use crate::message::Uri;
+use crate::task::AbortOnDrop;
+
+pub(super) type SessionId = String;
+
+/// Identifier of the connection that owns a session.
+pub(super) type ConnectionId = u64;
+
+/// Registry of all sessions of a server.
+///
+/// Sessions can only be used from the connection that created them. To other connections, they
+/// appear not to exist, so that clients cannot control sessions of other clients.
+pub(super) struct Sessions {
+    map: Mutex<HashMap<SessionId, Session>>,
+    timeout: Duration,
+    next_connection_id: AtomicU64,
+}
+
+impl Sessions {
+    pub(super) fn new(timeout: Duration) -> Self {
+        Self {
+            map: Mutex::new(HashMap::new()),
+            timeout,
+            next_connection_id: AtomicU64::new(0),
+        }
+    }
+
+    /// Allocate identifier for new connection.
+    pub(super) fn connection_id(&self) -> ConnectionId {
+        self.next_connection_id.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Create new session for presentation at `path`, owned by connection `owner`.
+    ///
+    /// # Return value
+    ///
+    /// Identifier of new session.
+    pub(super) fn create(&self, path: &str, owner: ConnectionId) -> SessionId {
+        let mut map = self.map.lock().unwrap();
+        let id = loop {
+            let id = format!("{:016X}", rand::random::<u64>());
+            if !map.contains_key(&id) {
+                break id;
+            }
+        };
+        map.insert(
+            id.clone(),
+            Session {
+                path: path.to_string(),
+                tracks: BTreeMap::new(),
+                playing: false,
+                owner,
+                last_activity: Instant::now(),
+            },
+        );
+        id
+    }
+
+    /// Run `f` on session of connection `owner` and mark session as active.
+    ///
+    /// # Return value
+    ///
+    /// Return value of `f` or `None` if session does not exist or belongs to another connection.
+    pub(super) fn with<T>(
+        &self,
+        id: &str,
+        owner: ConnectionId,
+        f: impl FnOnce(&mut Session) -> T,
+    ) -> Option<T> {
+        self.map
+            .lock()
+            .unwrap()
+            .get_mut(id)
+            .filter(|session| session.owner == owner)
+            .map(|session| {
+                session.last_activity = Instant::now();
+                f(session)
+            })
+    }
+
+    /// Mark session of connection `owner` as active to prevent it from timing out.
+    ///
+    /// # Return value
+    ///
+    /// `true` if session exists and belongs to connection.
+    #[inline]
+    pub(super) fn touch(&self, id: &str, owner: ConnectionId) -> bool {
+        self.with(id, owner, |_| ()).is_some()
+    }
+
+    /// Remove session of connection `owner`. Any streams that belong to the session are stopped.
+    pub(super) fn remove(&self, id: &str, owner: ConnectionId) -> Option<Session> {
+        let mut map = self.map.lock().unwrap();
+        match map.get(id) {
+            Some(session) if session.owner == owner => map.remove(id),
+            _ => None,
+        }
+    }
+
+    /// Remove all sessions that have not been active for longer than the session timeout.
+    pub(super) fn reap(&self) {
+        let timeout = self.timeout;
+        self.map
+            .lock()
+            .unwrap()
+            .retain(|_, session| session.last_activity.elapsed() <= timeout);
+    }
+
+    /// Format value of `Session` header for session.
+    pub(super) fn header(&self, id: &str) -> String {
+        crate::session::Session::new(id)
+            .with_timeout(self.timeout)
+            .to_string()
+    }
+}
+
+/// Server-side RTSP session.
+pub(super) struct Session {
+    /// Path of presentation that the session belongs to.
+    pub(super) path: String,
+    /// Tracks that were set up, by track index.
+    pub(super) tracks: BTreeMap<usize, Track>,
+    pub(super) playing: bool,
+    /// Connection that created the session.
+    owner: ConnectionId,
+    last_activity: Instant,
+}
+
+impl Session {
+    /// Whether or not one or more tracks are sent interleaved over the RTSP connection.
+    pub(super) fn is_interleaved(&self) -> bool {
+        self.tracks
+            .values()
+            .any(|track| matches!(track.sink, Sink::Interleaved { .. }))
+    }
+}
+
+/// Track that was set up in a session.
+pub(super) struct Track {
+    /// Request URI used to set up the track. Used in `RTP-Info` header.
+    pub(super) uri: Uri,
+    /// Where to send packets.
+    pub(super) sink: Sink,
+    /// Task that streams packets to sink while playing.
+    pub(super) stream: Option<AbortOnDrop>,
+    /// Task that receives RTCP packets (UDP only).
+    pub(super) _rtcp: Option<AbortOnDrop>,
+}
+
+/// Destination of RTP packets.
+#[derive(Clone)]
+pub(super) enum Sink {
+    /// Send packets interleaved over the RTSP connection.
+    Interleaved {
+        channel: ChannelId,
+        sender: tokio::sync::mpsc::Sender<ResponseMaybeInterleaved>,
+    },
+    /// Send packets to client over UDP.
+    Udp {
+        socket: std::sync::Arc<tokio::net::UdpSocket>,
+        destination: std::net::SocketAddr,
+    },
+}
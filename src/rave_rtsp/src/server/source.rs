@@ -0,0 +1,120 @@
+use std::sync::{Arc, Mutex};
+
+use rave_rtp::packet::Packet;
+use rave_sdp::Sdp;
+
+use tokio::sync::broadcast;
+
+/// Source of media served by [`Server`](super::Server).
+///
+/// The source describes the available presentations and produces the RTP packets for each of
+/// their tracks. Presentations are identified by the path of the request URI, and tracks are
+/// identified by the index of the corresponding media item in the session description.
+pub trait Source: Send + Sync + 'static {
+    /// Retrieve session description of the presentation at `path`.
+    ///
+    /// The server adds `control` attributes to the media items that do not have one already, so
+    /// the source does not need to provide them.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of presentation.
+    ///
+    /// # Return value
+    ///
+    /// Session description, or `None` if there is no presentation at `path`.
+    fn describe(&self, path: &str) -> Option<Sdp>;
+
+    /// Subscribe to the RTP packets of one of the tracks of the presentation at `path`.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - Path of presentation.
+    /// * `track` - Index of media item in session description.
+    ///
+    /// # Return value
+    ///
+    /// Subscription to RTP packets of the track, or `None` if there is no such track.
+    fn subscribe(&self, path: &str, track: usize) -> Option<Subscription>;
+}
+
+/// Subscription to the RTP packets of a single track.
+#[derive(Debug)]
+pub struct Subscription {
+    /// Receiver for RTP packets.
+    pub packets: broadcast::Receiver<Packet>,
+    /// Sequence number of the first packet the subscriber will receive (if known).
+    pub seq: Option<u16>,
+    /// RTP timestamp of the first packet the subscriber will receive (if known).
+    pub rtptime: Option<u32>,
+}
+
+/// Broadcasts the RTP packets of a single track to all subscribers.
+///
+/// This is a convenience type for implementing [`Source`]. Feed the output of a packetizer (such as
+/// `H264Packetizer`) into [`Broadcast::send`] and return [`Broadcast::subscribe`] from
+/// [`Source::subscribe`].
+#[derive(Debug, Clone)]
+pub struct Broadcast {
+    sender: broadcast::Sender<Packet>,
+    last: Arc<Mutex<Option<(u16, u32)>>>,
+}
+
+impl Broadcast {
+    /// Default number of packets buffered per subscriber.
+    pub const DEFAULT_CAPACITY: usize = 1024;
+
+    /// Create new broadcast.
+    ///
+    /// # Arguments
+    ///
+    /// * `capacity` - Number of packets buffered per subscriber. Subscribers that fall behind more
+    ///   than this number of packets skip the oldest packets.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            sender: broadcast::channel(capacity).0,
+            last: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    /// Send one or more RTP packets to all subscribers.
+    ///
+    /// # Arguments
+    ///
+    /// * `packets` - Packets to send.
+    pub fn send(&self, packets: impl IntoIterator<Item = Packet>) {
+        let mut last = self.last.lock().unwrap();
+        for packet in packets {
+            *last = Some((packet.header.sequence_number, packet.header.timestamp));
+            // An error only means there are no subscribers right now, which is fine.
+            let _ = self.sender.send(packet);
+        }
+    }
+
+    /// Subscribe to packets.
+    ///
+    /// The sequence number and RTP timestamp of the subscription are derived from the last packet
+    /// that was sent. The sequence number is exact, the RTP timestamp is that of the last packet
+    /// (the next packet has the same or a later timestamp).
+    pub fn subscribe(&self) -> Subscription {
+        let last = self.last.lock().unwrap();
+        Subscription {
+            packets: self.sender.subscribe(),
+            seq: last.map(|(seq, _)| seq.wrapping_add(1)),
+            rtptime: last.map(|(_, rtptime)| rtptime),
+        }
+    }
+
+    /// Number of active subscribers.
+    #[inline]
+    pub fn num_subscribers(&self) -> usize {
+        self.sender.receiver_count()
+    }
+}
+
+impl Default for Broadcast {
+    #[inline]
+    fn default() -> Self {
+        Self::new(Self::DEFAULT_CAPACITY)
+    }
+}
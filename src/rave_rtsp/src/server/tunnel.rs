@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use bytes::BytesMut;
+
+use tokio::io::AsyncWriteExt;
+use tokio::net::tcp::OwnedReadHalf;
+use tokio::net::TcpStream;
+use tokio::sync::oneshot;
+
+use crate::tunnel::{self, Base64Reader, Head, Prefixed};
+
+use super::connection;
+use super::source::Source;
+use super::Shared;
+
+/// Requests received over the `POST` connection of a tunnel.
+type TunnelRead = Base64Reader<Prefixed<OwnedReadHalf>>;
+
+/// Time to wait for the `POST` connection after the `GET` connection of a tunnel was opened.
+const POST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Registry of tunnels that are waiting for their `POST` connection, by session cookie.
+pub(super) struct Tunnels {
+    map: Mutex<HashMap<String, oneshot::Sender<TunnelRead>>>,
+}
+
+impl Tunnels {
+    pub(super) fn new() -> Self {
+        Self {
+            map: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+/// Serve connection, which is either a plain RTSP connection, or one of the two connections of
+/// an RTSP over HTTP tunnel.
+///
+/// # Arguments
+///
+/// * `stream` - Connection.
+/// * `peer` - Address of client.
+/// * `shared` - Server state.
+pub(super) async fn serve<S: Source>(stream: TcpStream, peer: SocketAddr, shared: Arc<Shared<S>>) {
+    let (mut read, mut write) = stream.into_split();
+    let mut buf = BytesMut::new();
+    let Ok(line_end) = tunnel::read_until(&mut read, &mut buf, b"\r\n").await else {
+        return;
+    };
+    if !tunnel::is_http_request_line(&buf[..line_end]) {
+        connection::serve(tunnel::prefixed(buf, read), write, peer, shared).await;
+        return;
+    }
+
+    let Ok(head_end) = tunnel::read_until(&mut read, &mut buf, b"\r\n\r\n").await else {
+        return;
+    };
+    let head = Head::parse(&buf.split_to(head_end));
+    let method = head.start_line.split(' ').next().unwrap_or_default();
+    let cookie = head.header(tunnel::SESSION_COOKIE).map(str::to_string);
+    match (method, cookie) {
+        ("GET", Some(cookie)) => {
+            let (sender, receiver) = oneshot::channel();
+            shared
+                .tunnels
+                .map
+                .lock()
+                .unwrap()
+                .insert(cookie.clone(), sender);
+            let response = format!(
+                "HTTP/1.0 200 OK\r\n\
+                 Connection: close\r\n\
+                 Cache-Control: no-store\r\n\
+                 Pragma: no-cache\r\n\
+                 Content-Type: {}\r\n\r\n",
+                tunnel::CONTENT_TYPE,
+            );
+            let post = match write.write_all(response.as_bytes()).await {
+                Ok(()) => tokio::time::timeout(POST_TIMEOUT, receiver).await,
+                Err(_) => return,
+            };
+            if let Ok(Ok(read)) = post {
+                connection::serve(read, write, peer, shared).await;
+            } else {
+                // Remove the tunnel, unless the cookie has been reused by another tunnel since.
+                let mut map = shared.tunnels.map.lock().unwrap();
+                if map.get(&cookie).is_some_and(|sender| sender.is_closed()) {
+                    map.remove(&cookie);
+                }
+            }
+        }
+        ("POST", Some(cookie)) => {
+            let sender = shared.tunnels.map.lock().unwrap().remove(&cookie);
+            match sender {
+                Some(sender) => {
+                    let _ = sender.send(Base64Reader::new(tunnel::prefixed(buf, read)));
+                }
+                None => {
+                    let _ = write.write_all(b"HTTP/1.0 404 Not Found\r\n\r\n").await;
+                }
+            }
+        }
+        _ => {
+            let _ = write.write_all(b"HTTP/1.0 400 Bad Request\r\n\r\n").await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tokio::io::AsyncReadExt;
+    use tokio::net::TcpListener;
+
+    use crate::server::session::Sessions;
+    use crate::server::{Broadcast, Server};
+    use crate::testing::Camera;
+
+    fn shared() -> Arc<Shared<Camera>> {
+        Arc::new(Shared {
+            source: Camera::new(Broadcast::default()),
+            sessions: Sessions::new(Server::<Camera>::DEFAULT_SESSION_TIMEOUT),
+            tunnels: Tunnels::new(),
+        })
+    }
+
+    /// Send `request` on a new connection served with `shared`, and read everything the server
+    /// writes until it closes the connection.
+    async fn exchange(shared: Arc<Shared<Camera>>, request: &str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let mut client = TcpStream::connect(listener.local_addr().unwrap())
+            .await
+            .unwrap();
+        let (stream, peer) = listener.accept().await.unwrap();
+        let server = tokio::spawn(serve(stream, peer, shared));
+        client.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        client.read_to_string(&mut response).await.unwrap();
+        server.await.unwrap();
+        response
+    }
+
+    #[tokio::test]
+    async fn reject_request_without_cookie() {
+        let response = exchange(shared(), "GET /camera HTTP/1.0\r\n\r\n").await;
+        assert_eq!(response, "HTTP/1.0 400 Bad Request\r\n\r\n");
+    }
+
+    #[tokio::test]
+    async fn reject_post_with_unknown_cookie() {
+        let response = exchange(
+            shared(),
+            "POST /camera HTTP/1.0\r\nx-sessioncookie: unknown\r\n\r\n",
+        )
+        .await;
+        assert_eq!(response, "HTTP/1.0 404 Not Found\r\n\r\n");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn close_tunnel_without_post() {
+        let shared = shared();
+        let response = exchange(
+            shared.clone(),
+            "GET /camera HTTP/1.0\r\nx-sessioncookie: cookie\r\n\r\n",
+        )
+        .await;
+        assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+        assert!(shared.tunnels.map.lock().unwrap().is_empty());
+
+        // The cookie can no longer be used for the `POST` connection.
+        let response = exchange(
+            shared,
+            "POST /camera HTTP/1.0\r\nx-sessioncookie: cookie\r\n\r\n",
+        )
+        .await;
+        assert_eq!(response, "HTTP/1.0 404 Not Found\r\n\r\n");
+    }
+}
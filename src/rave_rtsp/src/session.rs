@@ -0,0 +1,135 @@
+use std::time::Duration;
+
+use crate::Error;
+
+/// Value of `Session` header (RFC 2326, section 12.37).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Session {
+    /// Session identifier.
+    pub id: String,
+    /// Time after which the server drops the session if there is no activity.
+    pub timeout: Option<Duration>,
+}
+
+impl Session {
+    /// Timeout that applies when the server does not specify one.
+    pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+    /// Create session with identifier and no timeout.
+    pub fn new(id: impl ToString) -> Self {
+        Session {
+            id: id.to_string(),
+            timeout: None,
+        }
+    }
+
+    /// Set session timeout.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Timeout of session, or the default timeout if the server did not specify one.
+    #[inline]
+    pub fn timeout_or_default(&self) -> Duration {
+        self.timeout.unwrap_or(Self::DEFAULT_TIMEOUT)
+    }
+}
+
+impl std::fmt::Display for Session {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.id)?;
+        if let Some(timeout) = self.timeout {
+            write!(f, ";timeout={}", timeout.as_secs())?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Session {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = s.split(';').map(str::trim);
+        let id =
+            parts
+                .next()
+                .filter(|id| !id.is_empty())
+                .ok_or_else(|| Error::SessionIdMissing {
+                    value: s.to_string(),
+                })?;
+        let mut session = Session::new(id);
+        for part in parts {
+            // Unknown parameters are ignored for compatibility.
+            if let Some(timeout) = part.strip_prefix("timeout=") {
+                let timeout =
+                    timeout
+                        .trim()
+                        .parse::<u64>()
+                        .map_err(|_| Error::SessionTimeoutInvalid {
+                            value: part.to_string(),
+                        })?;
+                session.timeout = Some(Duration::from_secs(timeout));
+            }
+        }
+        Ok(session)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_id() {
+        assert_eq!(
+            "12345678".parse::<Session>().unwrap(),
+            Session::new("12345678"),
+        );
+    }
+
+    #[test]
+    fn parse_id_and_timeout() {
+        let session = "47112344;timeout=30".parse::<Session>().unwrap();
+        assert_eq!(
+            session,
+            Session::new("47112344").with_timeout(Duration::from_secs(30)),
+        );
+        assert_eq!(session.timeout_or_default(), Duration::from_secs(30));
+    }
+
+    #[test]
+    fn parse_whitespace_and_unknown_parameters() {
+        assert_eq!(
+            " 47112344 ; foo=bar ; timeout=30"
+                .parse::<Session>()
+                .unwrap(),
+            Session::new("47112344").with_timeout(Duration::from_secs(30)),
+        );
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!("".parse::<Session>().is_err());
+        assert!("1234;timeout=abc".parse::<Session>().is_err());
+    }
+
+    #[test]
+    fn default_timeout() {
+        assert_eq!(
+            Session::new("1234").timeout_or_default(),
+            Session::DEFAULT_TIMEOUT,
+        );
+    }
+
+    #[test]
+    fn format() {
+        assert_eq!(Session::new("1234").to_string(), "1234");
+        assert_eq!(
+            Session::new("1234")
+                .with_timeout(Duration::from_secs(60))
+                .to_string(),
+            "1234;timeout=60",
+        );
+    }
+}
@@ -0,0 +1,474 @@
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use rave_rtp::packet::Packet;
+
+use rave_sdp::sdp::MediaItem;
+use rave_sdp::Sdp;
+
+use tokio::sync::mpsc;
+
+use tokio_stream::{Stream, StreamExt};
+
+use crate::auth::Credentials;
+use crate::client::{Client, ClientError};
+use crate::message::Uri;
+use crate::presentation::Presentation;
+use crate::task::AbortOnDrop;
+use crate::transport::{Channel, Lower, Parameter, Transport};
+
+type Result<T> = std::result::Result<T, ClientError>;
+
+type TransportFor = Box<dyn FnMut(usize, &MediaItem) -> Option<Transport> + Send>;
+
+type PacketStream = Pin<Box<dyn Stream<Item = Result<(usize, Packet)>> + Send>>;
+
+/// Number of events buffered before the supervisor waits for the consumer.
+const EVENTS_CAPACITY: usize = 1024;
+
+/// Time to wait for a `TEARDOWN` response before giving up on a silent session.
+const TEARDOWN_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Keeps an RTSP presentation playing, reconnecting when needed.
+///
+/// The supervisor connects to the server, sets up all tracks of the presentation and starts
+/// playing. When the connection is closed, or when no RTP packets arrive for longer than the
+/// silence timeout, it reconnects and repeats `DESCRIBE`, `SETUP` and `PLAY`. Failed attempts are
+/// retried with exponential backoff.
+///
+/// Every time playback (re)starts, the supervisor emits an [`Event::Discontinuity`] that carries
+/// the new `RTP-Info` sequence number and RTP timestamp of each track. Consumers should reset any
+/// depacketizers and jitter buffers when they receive it.
+///
+/// # Example
+///
+/// ```no_run
+/// # async fn example(uri: rave_rtsp::Uri) {
+/// use rave_rtsp::supervisor::{Event, Supervisor};
+/// use tokio_stream::StreamExt;
+///
+/// let mut events = Supervisor::new(uri).start();
+/// while let Some(event) = events.next().await {
+///     match event {
+///         Event::Discontinuity(discontinuity) => println!("playing: {discontinuity:?}"),
+///         Event::Packet { track, packet } => println!("track {track}: {packet:?}"),
+///         Event::Disconnected { error, retry_in } => {
+///             println!("disconnected ({error}), retrying in {retry_in:?}")
+///         }
+///         _ => {}
+///     }
+/// }
+/// # }
+/// ```
+pub struct Supervisor {
+    uri: Uri,
+    credentials: Option<Credentials>,
+    /// Picks transport for each track, or `None` to send all tracks interleaved.
+    transport_for: Option<TransportFor>,
+    silence_timeout: Duration,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Supervisor {
+    /// Default time without RTP packets after which the supervisor reconnects.
+    pub const DEFAULT_SILENCE_TIMEOUT: Duration = Duration::from_secs(10);
+
+    /// Default delay before the first reconnect attempt.
+    pub const DEFAULT_INITIAL_BACKOFF: Duration = Duration::from_secs(1);
+
+    /// Default maximum delay between reconnect attempts.
+    pub const DEFAULT_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+    /// Create supervisor for presentation at `uri`.
+    ///
+    /// By default, all tracks are set up interleaved over the RTSP connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - URI of presentation. May contain credentials.
+    pub fn new(uri: Uri) -> Self {
+        Self {
+            uri,
+            credentials: None,
+            transport_for: None,
+            silence_timeout: Self::DEFAULT_SILENCE_TIMEOUT,
+            initial_backoff: Self::DEFAULT_INITIAL_BACKOFF,
+            max_backoff: Self::DEFAULT_MAX_BACKOFF,
+        }
+    }
+
+    /// Set credentials to authenticate with.
+    #[inline]
+    pub fn with_credentials(mut self, credentials: Credentials) -> Self {
+        self.credentials = Some(credentials);
+        self
+    }
+
+    /// Set function that picks the transport for each track.
+    ///
+    /// The function is called for every media item in the session description, on every
+    /// connection attempt. Tracks for which it returns `None` are not set up. See
+    /// [`Client::setup_presentation`].
+    pub fn with_transport(
+        mut self,
+        transport_for: impl FnMut(usize, &MediaItem) -> Option<Transport> + Send + 'static,
+    ) -> Self {
+        self.transport_for = Some(Box::new(transport_for));
+        self
+    }
+
+    /// Set time without RTP packets after which the supervisor reconnects.
+    #[inline]
+    pub fn with_silence_timeout(mut self, timeout: Duration) -> Self {
+        self.silence_timeout = timeout;
+        self
+    }
+
+    /// Set backoff between reconnect attempts.
+    ///
+    /// The delay starts at `initial` and doubles after each failed attempt, up to `max`. It is
+    /// reset once packets are received again.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial` - Delay before the first reconnect attempt.
+    /// * `max` - Maximum delay between reconnect attempts.
+    #[inline]
+    pub fn with_backoff(mut self, initial: Duration, max: Duration) -> Self {
+        self.initial_backoff = initial;
+        self.max_backoff = max;
+        self
+    }
+
+    /// Start supervising in the background.
+    ///
+    /// # Return value
+    ///
+    /// Stream of events. The supervisor stops when the stream is dropped.
+    pub fn start(self) -> Events {
+        let (tx, receiver) = mpsc::channel(EVENTS_CAPACITY);
+        Events {
+            receiver,
+            _task: AbortOnDrop::spawn(supervise(self, tx)),
+        }
+    }
+}
+
+/// Event produced by [`Supervisor`].
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Event {
+    /// Playback started or restarted.
+    Discontinuity(Box<Discontinuity>),
+    /// RTP packet received.
+    Packet {
+        /// Index of media item of track in session description.
+        track: usize,
+        /// Received packet.
+        packet: Packet,
+    },
+    /// Playback stopped because of an error.
+    Disconnected {
+        /// Reason.
+        error: ClientError,
+        /// Delay before the next attempt.
+        retry_in: Duration,
+    },
+}
+
+/// Start of a new, unrelated run of packets.
+///
+/// Sequence numbers and RTP timestamps of packets after a discontinuity do not continue from those
+/// before it.
+#[derive(Debug, Clone)]
+pub struct Discontinuity {
+    /// Session description of presentation, which may have changed since the last connection.
+    pub sdp: Sdp,
+    /// Tracks that are playing.
+    pub tracks: Vec<TrackStart>,
+}
+
+/// Timing of the first packet of a track after a discontinuity, from the `RTP-Info` header.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackStart {
+    /// Index of media item of track in session description.
+    pub track: usize,
+    /// Sequence number of first packet.
+    pub seq: Option<u16>,
+    /// RTP timestamp of first packet.
+    pub rtptime: Option<u32>,
+}
+
+/// Stream of events of a running [`Supervisor`].
+pub struct Events {
+    receiver: mpsc::Receiver<Event>,
+    _task: AbortOnDrop,
+}
+
+impl Stream for Events {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.receiver.poll_recv(cx)
+    }
+}
+
+/// Keep presentation playing until the consumer goes away.
+async fn supervise(mut supervisor: Supervisor, events: mpsc::Sender<Event>) {
+    let mut backoff = supervisor.initial_backoff;
+    loop {
+        let error = match play(&mut supervisor, &events, &mut backoff).await {
+            Ok(()) => break,
+            Err(error) => error,
+        };
+        let event = Event::Disconnected {
+            error,
+            retry_in: backoff,
+        };
+        if events.send(event).await.is_err() {
+            break;
+        }
+        tokio::time::sleep(backoff).await;
+        backoff = (backoff * 2).min(supervisor.max_backoff);
+    }
+}
+
+/// Connect, play and forward packets until something goes wrong.
+///
+/// # Return value
+///
+/// `Ok` if the consumer went away, or the reason why playback stopped.
+async fn play(
+    supervisor: &mut Supervisor,
+    events: &mpsc::Sender<Event>,
+    backoff: &mut Duration,
+) -> Result<()> {
+    let mut client = Client::connect(&supervisor.uri).await?;
+    if let Some(credentials) = supervisor.credentials.clone() {
+        client.set_credentials(credentials);
+    }
+    let mut presentation = match supervisor.transport_for.as_mut() {
+        Some(transport_for) => client.setup_presentation(transport_for).await?,
+        None => {
+            let mut unavailable = None;
+            let presentation = client
+                .setup_presentation(|index, _| {
+                    let transport = interleaved_transport(index);
+                    if transport.is_none() {
+                        unavailable.get_or_insert(index);
+                    }
+                    transport
+                })
+                .await?;
+            if let Some(track) = unavailable {
+                return Err(ClientError::InterleavedChannelUnavailable { track });
+            }
+            presentation
+        }
+    };
+    client.play_presentation(&mut presentation, None).await?;
+
+    let discontinuity = Discontinuity {
+        sdp: presentation.sdp.clone(),
+        tracks: presentation
+            .tracks
+            .iter()
+            .map(|track| TrackStart {
+                track: track.index,
+                seq: track.rtp_info.as_ref().and_then(|rtp_info| rtp_info.seq),
+                rtptime: track
+                    .rtp_info
+                    .as_ref()
+                    .and_then(|rtp_info| rtp_info.rtptime),
+            })
+            .collect(),
+    };
+    if events
+        .send(Event::Discontinuity(Box::new(discontinuity)))
+        .await
+        .is_err()
+    {
+        return Ok(());
+    }
+
+    let mut packets = packets(&mut client, &mut presentation);
+    let closed = client.closed();
+    tokio::pin!(closed);
+    loop {
+        let packet = tokio::select! {
+            _ = &mut closed => return Err(ClientError::ConnectionClosed),
+            packet = tokio::time::timeout(supervisor.silence_timeout, packets.next()) => packet,
+        };
+        match packet {
+            Ok(Some(Ok((track, packet)))) => {
+                *backoff = supervisor.initial_backoff;
+                if events.send(Event::Packet { track, packet }).await.is_err() {
+                    return Ok(());
+                }
+            }
+            // Skip packets that cannot be parsed.
+            Ok(Some(Err(ClientError::InvalidRtp(_)))) => {}
+            Ok(Some(Err(error))) => return Err(error),
+            Ok(None) => return Err(ClientError::ConnectionClosed),
+            Err(_) => {
+                // The server may still think the session is alive.
+                let _ = tokio::time::timeout(
+                    TEARDOWN_TIMEOUT,
+                    client.teardown_presentation(&presentation),
+                )
+                .await;
+                return Err(ClientError::Silence {
+                    timeout: supervisor.silence_timeout,
+                });
+            }
+        }
+    }
+}
+
+/// Transport that sends track with index `index` interleaved over the RTSP connection, on channels
+/// `2 * index` (RTP) and `2 * index + 1` (RTCP).
+///
+/// # Return value
+///
+/// Transport, or `None` if there are not enough channels for the track.
+fn interleaved_transport(index: usize) -> Option<Transport> {
+    let channel = u8::try_from(index * 2).ok()?;
+    Some(
+        Transport::new()
+            .with_lower_protocol(Lower::Tcp)
+            .with_parameter(Parameter::Interleaved(Channel::Range(channel, channel + 1))),
+    )
+}
+
+/// Merge packets of all tracks into a single stream.
+fn packets(client: &mut Client, presentation: &mut Presentation) -> PacketStream {
+    let mut streams: Vec<PacketStream> = Vec::new();
+
+    let channels = presentation
+        .tracks
+        .iter()
+        .filter_map(|track| {
+            let channel = match track.transport.interleaved_channel()? {
+                Channel::Single(channel) | Channel::Range(channel, _) => *channel,
+            };
+            Some((channel, track.index))
+        })
+        .collect::<Vec<_>>();
+    if !channels.is_empty() {
+        if let Some(interleaved) = client.interleaved() {
+            streams.push(Box::pin(interleaved.filter_map(move |item| {
+                match item {
+                    Ok((channel, packet)) => channels
+                        .iter()
+                        .find(|(track_channel, _)| *track_channel == channel)
+                        .map(|(_, track)| Ok((*track, packet))),
+                    Err(error) => Some(Err(error)),
+                }
+            })));
+        }
+    }
+
+    for track in &mut presentation.tracks {
+        if let Some(udp) = track.udp.take() {
+            let index = track.index;
+            streams.push(Box::pin(
+                udp.rtp
+                    .map(move |packet| packet.map(|packet| (index, packet))),
+            ));
+        }
+    }
+
+    Box::pin(futures::stream::select_all(streams))
+}
+
+#[cfg(all(test, feature = "server"))]
+mod tests {
+    use super::*;
+
+    use crate::server::{Broadcast, Server};
+    use crate::testing::{packet, Camera};
+
+    #[tokio::test]
+    async fn reconnect_after_silence() {
+        let video = Broadcast::default();
+        video.send([packet(9)]);
+        let server = Server::bind("127.0.0.1:0", Camera::new(video.clone()))
+            .await
+            .unwrap();
+        let addr = server.local_addr().unwrap();
+        let _server = AbortOnDrop::spawn(async move {
+            let _ = server.run().await;
+        });
+
+        let uri: Uri = format!("rtsp://{addr}/camera").parse().unwrap();
+        let mut events = Supervisor::new(uri)
+            .with_silence_timeout(Duration::from_millis(200))
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(100))
+            .start();
+
+        let Some(Event::Discontinuity(discontinuity)) = events.next().await else {
+            panic!("expected discontinuity");
+        };
+        assert_eq!(
+            discontinuity.tracks,
+            vec![TrackStart {
+                track: 0,
+                seq: Some(10),
+                rtptime: Some(1234),
+            }],
+        );
+
+        video.send([packet(10)]);
+        let Some(Event::Packet { track, packet }) = events.next().await else {
+            panic!("expected packet");
+        };
+        assert_eq!(track, 0);
+        assert_eq!(packet.header.sequence_number, 10);
+
+        let Some(Event::Disconnected { error, .. }) = events.next().await else {
+            panic!("expected disconnect");
+        };
+        assert!(matches!(error, ClientError::Silence { .. }));
+
+        let Some(Event::Discontinuity(discontinuity)) = events.next().await else {
+            panic!("expected discontinuity");
+        };
+        assert_eq!(discontinuity.tracks[0].seq, Some(11));
+    }
+
+    #[test]
+    fn interleaved_channels_of_track() {
+        assert_eq!(
+            interleaved_transport(127).unwrap().interleaved_channel(),
+            Some(&Channel::Range(254, 255)),
+        );
+        assert!(interleaved_transport(128).is_none());
+    }
+
+    #[tokio::test]
+    async fn backoff_when_connection_closed() {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        let _server = AbortOnDrop::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                drop(stream);
+            }
+        });
+
+        let uri: Uri = format!("rtsp://{addr}/camera").parse().unwrap();
+        let mut events = Supervisor::new(uri)
+            .with_backoff(Duration::from_millis(10), Duration::from_millis(40))
+            .start();
+
+        let mut delays = Vec::new();
+        for _ in 0..4 {
+            let Some(Event::Disconnected { retry_in, .. }) = events.next().await else {
+                panic!("expected disconnect");
+            };
+            delays.push(retry_in.as_millis());
+        }
+        assert_eq!(delays, vec![10, 20, 40, 40]);
+    }
+}
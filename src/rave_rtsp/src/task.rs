@@ -0,0 +1,23 @@
+/// Handle to a spawned background task that aborts the task when dropped.
+///
+/// Background tasks (such as streaming media to a client) must not outlive the object that owns
+/// them. Wrapping the [`tokio::task::JoinHandle`] in this type ties the lifetime of the task to the
+/// lifetime of the handle.
+#[derive(Debug)]
+pub(crate) struct AbortOnDrop(tokio::task::JoinHandle<()>);
+
+impl AbortOnDrop {
+    /// Spawn future as background task that is aborted when the returned handle is dropped.
+    pub(crate) fn spawn<F>(future: F) -> Self
+    where
+        F: std::future::Future<Output = ()> + Send + 'static,
+    {
+        Self(tokio::spawn(future))
+    }
+}
+
+impl Drop for AbortOnDrop {
+    fn drop(&mut self) {
+        self.0.abort();
+    }
+}
@@ -0,0 +1,81 @@
+//! Fixtures shared by the tests of the client, server and supervisor.
+
+use bytes::Bytes;
+
+use rave_rtp::packet::{Header, Packet, Version as RtpVersion};
+#[cfg(feature = "server")]
+use rave_sdp::{Builder, Direction, H264Parameters, Kind, Protocol, Sdp, TimeRange};
+
+#[cfg(feature = "server")]
+use crate::server::{Broadcast, Source, Subscription};
+
+#[cfg(feature = "server")]
+const SPS: &[u8] = &[0x67, 0x64, 0x00, 0x1f];
+#[cfg(feature = "server")]
+const PPS: &[u8] = &[0x68, 0xee, 0x3c, 0x80];
+
+/// Session description with `num_tracks` H.264 video tracks.
+#[cfg(feature = "server")]
+pub(crate) fn sdp(num_tracks: usize) -> Sdp {
+    let localhost = std::net::Ipv4Addr::LOCALHOST.into();
+    let mut builder = Builder::new("test", localhost, localhost, TimeRange::live());
+    for _ in 0..num_tracks {
+        builder
+            .add_media(
+                Kind::Video,
+                "video",
+                0,
+                Protocol::RtpAvp,
+                Direction::ReceiveOnly,
+                H264Parameters::new(SPS, &[PPS], 1),
+            )
+            .unwrap();
+    }
+    builder.build()
+}
+
+/// Source that serves `/camera`, with every track subscribed to the same broadcast.
+#[cfg(feature = "server")]
+pub(crate) struct Camera {
+    pub(crate) video: Broadcast,
+    pub(crate) num_tracks: usize,
+}
+
+#[cfg(feature = "server")]
+impl Camera {
+    pub(crate) fn new(video: Broadcast) -> Self {
+        Self {
+            video,
+            num_tracks: 1,
+        }
+    }
+}
+
+#[cfg(feature = "server")]
+impl Source for Camera {
+    fn describe(&self, path: &str) -> Option<Sdp> {
+        (path == "/camera").then(|| sdp(self.num_tracks))
+    }
+
+    fn subscribe(&self, path: &str, track: usize) -> Option<Subscription> {
+        (path == "/camera" && track < self.num_tracks).then(|| self.video.subscribe())
+    }
+}
+
+/// H.264 packet with sequence number `sequence_number`.
+pub(crate) fn packet(sequence_number: u16) -> Packet {
+    Packet::new(
+        Header {
+            version: RtpVersion::Version2,
+            padding: false,
+            marker: true,
+            payload_type: 96,
+            sequence_number,
+            timestamp: 1234,
+            ssrc: 1,
+            csrc: Vec::new(),
+            extension: None,
+        },
+        Bytes::from_static(&[0x65, 0x01, 0x02]),
+    )
+}
@@ -0,0 +1,186 @@
+use std::sync::Arc;
+
+use rustls::client::danger::{HandshakeSignatureValid, ServerCertVerified, ServerCertVerifier};
+use rustls::client::WebPkiServerVerifier;
+use rustls::crypto::{self, CryptoProvider};
+use rustls::pki_types::{CertificateDer, ServerName, UnixTime};
+use rustls::{
+    CertificateError, ClientConfig, DigitallySignedStruct, RootCertStore, SignatureScheme,
+};
+
+/// TLS configuration for connecting to servers over RTSP over TLS (`rtsps`).
+///
+/// By default, server certificates are verified against the Mozilla root certificates.
+#[derive(Debug, Clone)]
+pub struct TlsConfig {
+    roots: RootCertStore,
+    accept_self_signed: bool,
+}
+
+impl TlsConfig {
+    pub fn new() -> Self {
+        Self {
+            roots: RootCertStore {
+                roots: webpki_roots::TLS_SERVER_ROOTS.to_vec(),
+            },
+            accept_self_signed: false,
+        }
+    }
+
+    /// Replace root certificates.
+    ///
+    /// # Arguments
+    ///
+    /// * `roots` - Root certificates to verify server certificates against.
+    pub fn with_root_certificates(mut self, roots: RootCertStore) -> Self {
+        self.roots = roots;
+        self
+    }
+
+    /// Add root certificate, for example the certificate of a private certificate authority.
+    ///
+    /// # Arguments
+    ///
+    /// * `certificate` - DER-encoded root certificate.
+    pub fn add_root_certificate(
+        &mut self,
+        certificate: CertificateDer<'static>,
+    ) -> Result<(), rustls::Error> {
+        self.roots.add(certificate)
+    }
+
+    /// Add root certificate, for example the certificate of a private certificate authority.
+    ///
+    /// # Arguments
+    ///
+    /// * `certificate` - DER-encoded root certificate.
+    pub fn with_root_certificate(
+        mut self,
+        certificate: CertificateDer<'static>,
+    ) -> Result<Self, rustls::Error> {
+        self.add_root_certificate(certificate)?;
+        Ok(self)
+    }
+
+    /// Accept self-signed server certificates that are not issued by any of the root
+    /// certificates.
+    ///
+    /// The certificate must still be valid for the server name and not be expired. This is meant
+    /// for testing and for cameras that ship with a self-signed certificate: it provides no
+    /// protection against an attacker who presents their own self-signed certificate.
+    pub fn with_accept_self_signed(mut self, accept_self_signed: bool) -> Self {
+        self.accept_self_signed = accept_self_signed;
+        self
+    }
+
+    pub(crate) fn connector(&self) -> tokio_rustls::TlsConnector {
+        let provider = Arc::new(crypto::ring::default_provider());
+        let builder = ClientConfig::builder_with_provider(provider.clone())
+            .with_safe_default_protocol_versions()
+            .expect("crypto provider supports default protocol versions");
+        let config = if self.accept_self_signed {
+            builder
+                .dangerous()
+                .with_custom_certificate_verifier(Arc::new(SelfSignedVerifier {
+                    verifier: WebPkiServerVerifier::builder_with_provider(
+                        Arc::new(self.roots.clone()),
+                        provider.clone(),
+                    )
+                    .build()
+                    .ok(),
+                    provider,
+                }))
+                .with_no_client_auth()
+        } else {
+            builder
+                .with_root_certificates(self.roots.clone())
+                .with_no_client_auth()
+        };
+        tokio_rustls::TlsConnector::from(Arc::new(config))
+    }
+}
+
+impl Default for TlsConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Verifies server certificates against root certificates, and falls back to accepting
+/// certificates that are signed by themselves.
+#[derive(Debug)]
+struct SelfSignedVerifier {
+    /// Verifier for root certificates, or `None` if there are no root certificates.
+    verifier: Option<Arc<WebPkiServerVerifier>>,
+    provider: Arc<CryptoProvider>,
+}
+
+impl ServerCertVerifier for SelfSignedVerifier {
+    fn verify_server_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        server_name: &ServerName<'_>,
+        ocsp_response: &[u8],
+        now: UnixTime,
+    ) -> Result<ServerCertVerified, rustls::Error> {
+        let result = match self.verifier.as_ref() {
+            Some(verifier) => verifier.verify_server_cert(
+                end_entity,
+                intermediates,
+                server_name,
+                ocsp_response,
+                now,
+            ),
+            None => Err(rustls::Error::InvalidCertificate(
+                CertificateError::UnknownIssuer,
+            )),
+        };
+        match result {
+            Err(rustls::Error::InvalidCertificate(CertificateError::UnknownIssuer)) => {
+                // Verify the certificate with itself as the only root certificate.
+                let mut roots = RootCertStore::empty();
+                roots.add(end_entity.clone().into_owned())?;
+                WebPkiServerVerifier::builder_with_provider(Arc::new(roots), self.provider.clone())
+                    .build()
+                    .map_err(|err| rustls::Error::General(err.to_string()))?
+                    .verify_server_cert(end_entity, &[], server_name, ocsp_response, now)
+            }
+            result => result,
+        }
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls12_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        crypto::verify_tls13_signature(
+            message,
+            cert,
+            dss,
+            &self.provider.signature_verification_algorithms,
+        )
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.provider
+            .signature_verification_algorithms
+            .supported_schemes()
+    }
+}
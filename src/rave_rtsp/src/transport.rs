@@ -90,6 +90,34 @@ impl Transport {
             .next()
     }
 
+    /// Addresses that media is sent to (RTSP 2.0). For RTP, these are the RTP and RTCP address,
+    /// in that order.
+    pub fn dest_addr(&self) -> Option<&[Address]> {
+        self.parameters_iter()
+            .filter_map(|parameter| {
+                if let Parameter::DestAddr(addresses) = parameter {
+                    Some(addresses.as_slice())
+                } else {
+                    None
+                }
+            })
+            .next()
+    }
+
+    /// Addresses that media is sent from (RTSP 2.0). For RTP, these are the RTP and RTCP address,
+    /// in that order.
+    pub fn src_addr(&self) -> Option<&[Address]> {
+        self.parameters_iter()
+            .filter_map(|parameter| {
+                if let Parameter::SrcAddr(addresses) = parameter {
+                    Some(addresses.as_slice())
+                } else {
+                    None
+                }
+            })
+            .next()
+    }
+
     pub fn interleaved_channel(&self) -> Option<&Channel> {
         self.parameters_iter()
             .filter_map(|parameter| {
@@ -127,6 +155,8 @@ impl std::str::FromStr for Transport {
     type Err = Error;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // RTSP 2.0 allows whitespace around separators.
+        let s = s.trim();
         let (spec, params) = s
             .split_once(';')
             .map(|(spec, params)| (spec, Some(params)))
@@ -143,7 +173,7 @@ impl std::str::FromStr for Transport {
                 .map(|params| {
                     params
                         .split(';')
-                        .map(|p| p.parse())
+                        .map(|p| p.trim().parse())
                         .collect::<Result<Vec<_>, _>>()
                 })
                 .transpose()?
@@ -201,6 +231,9 @@ pub enum Parameter {
     ServerPort(Port),
     Ssrc(String),
     Mode(Method),
+    DestAddr(Vec<Address>),
+    SrcAddr(Vec<Address>),
+    RtcpMux,
 }
 
 impl std::fmt::Display for Parameter {
@@ -242,6 +275,15 @@ impl std::fmt::Display for Parameter {
             Parameter::Mode(method) => {
                 write!(f, "mode=\"{method}\"")
             }
+            Parameter::DestAddr(addresses) => {
+                write!(f, "dest_addr={}", format_addresses(addresses))
+            }
+            Parameter::SrcAddr(addresses) => {
+                write!(f, "src_addr={}", format_addresses(addresses))
+            }
+            Parameter::RtcpMux => {
+                write!(f, "RTCP-mux")
+            }
         }
     }
 }
@@ -326,6 +368,15 @@ impl std::str::FromStr for Parameter {
                 let method = parse_or_err(var, val)?;
                 Ok(Parameter::Mode(method))
             }
+            "dest_addr" => {
+                let val = val_or_err()?;
+                Ok(Parameter::DestAddr(parse_addresses(val)?))
+            }
+            "src_addr" => {
+                let val = val_or_err()?;
+                Ok(Parameter::SrcAddr(parse_addresses(val)?))
+            }
+            "RTCP-mux" => Ok(Parameter::RtcpMux),
             _ => Err(Error::TransportParameterUnknown {
                 var: var.to_string(),
             }),
@@ -333,6 +384,103 @@ impl std::str::FromStr for Parameter {
     }
 }
 
+/// Address in `dest_addr` or `src_addr` parameter (RFC 7826, section 18.54).
+///
+/// Either the host or the port can be left out. When the host is left out, the address of the
+/// RTSP connection applies.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Address {
+    /// Host name or IP address. IPv6 addresses are not enclosed in brackets.
+    pub host: Option<String>,
+    pub port: Option<u16>,
+}
+
+impl Address {
+    /// Create address with only a port.
+    pub fn from_port(port: u16) -> Self {
+        Self {
+            host: None,
+            port: Some(port),
+        }
+    }
+
+    /// Create address from IP address and port.
+    pub fn from_socket_addr(addr: std::net::SocketAddr) -> Self {
+        Self {
+            host: Some(addr.ip().to_string()),
+            port: Some(addr.port()),
+        }
+    }
+
+    /// Socket address, if the address has both a port and a host that is an IP address.
+    pub fn socket_addr(&self) -> Option<std::net::SocketAddr> {
+        let ip = self.host.as_ref()?.parse::<std::net::IpAddr>().ok()?;
+        Some(std::net::SocketAddr::new(ip, self.port?))
+    }
+}
+
+impl std::fmt::Display for Address {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "\"")?;
+        match self.host.as_ref() {
+            Some(host) if host.contains(':') => write!(f, "[{host}]")?,
+            Some(host) => write!(f, "{host}")?,
+            None => {}
+        }
+        if let Some(port) = self.port {
+            write!(f, ":{port}")?;
+        }
+        write!(f, "\"")
+    }
+}
+
+impl std::str::FromStr for Address {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let malformed = || Error::TransportAddressMalformed {
+            value: s.to_string(),
+        };
+        let addr = s
+            .strip_prefix('"')
+            .and_then(|addr| addr.strip_suffix('"'))
+            .unwrap_or(s);
+        let (host, port) = if let Some(addr) = addr.strip_prefix('[') {
+            let (host, rest) = addr.split_once(']').ok_or_else(malformed)?;
+            let port = match rest {
+                "" => None,
+                rest => Some(rest.strip_prefix(':').ok_or_else(malformed)?),
+            };
+            (host, port)
+        } else {
+            match addr.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (addr, None),
+            }
+        };
+        let port = port
+            .map(|port| port.parse::<u16>().map_err(|_| malformed()))
+            .transpose()?;
+        let host = (!host.is_empty()).then(|| host.to_string());
+        if host.is_none() && port.is_none() {
+            return Err(malformed());
+        }
+        Ok(Address { host, port })
+    }
+}
+
+fn format_addresses(addresses: &[Address]) -> String {
+    addresses
+        .iter()
+        .map(|address| address.to_string())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+fn parse_addresses(s: &str) -> Result<Vec<Address>, Error> {
+    s.split('/').map(|address| address.parse()).collect()
+}
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum Channel {
     Single(u8),
@@ -581,6 +729,60 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parse_rfc7826_section_18_54_examples() {
+        assert_eq!(
+            "RTP/AVP/UDP; unicast; dest_addr=\":4588\"/\":4589\""
+                .parse::<Transport>()
+                .unwrap(),
+            Transport::new()
+                .with_lower_protocol(Lower::Udp)
+                .with_parameter(Parameter::Unicast)
+                .with_parameter(Parameter::DestAddr(vec![
+                    Address::from_port(4588),
+                    Address::from_port(4589),
+                ])),
+        );
+        let transport = "RTP/AVP/UDP; unicast; dest_addr=\"192.0.2.53:4588\"/\"192.0.2.53:4589\"; \
+            src_addr=\"198.51.100.241:6256\"/\"198.51.100.241:6257\"; ssrc=2A3F93ED"
+            .parse::<Transport>()
+            .unwrap();
+        assert_eq!(
+            transport.dest_addr().unwrap()[1].socket_addr(),
+            Some(([192, 0, 2, 53], 4589).into()),
+        );
+        assert_eq!(
+            transport.src_addr().unwrap()[0].socket_addr(),
+            Some(([198, 51, 100, 241], 6256).into()),
+        );
+    }
+
+    #[test]
+    fn parse_address() {
+        assert_eq!(
+            "\"[2001:db8::1]:8000\"".parse::<Address>().unwrap(),
+            Address {
+                host: Some("2001:db8::1".to_string()),
+                port: Some(8000),
+            },
+        );
+        assert_eq!(
+            "\"example.com\"".parse::<Address>().unwrap(),
+            Address {
+                host: Some("example.com".to_string()),
+                port: None,
+            },
+        );
+        assert!(matches!(
+            "\":port\"".parse::<Address>(),
+            Err(Error::TransportAddressMalformed { value: _ }),
+        ));
+        assert!(matches!(
+            "\"\"".parse::<Address>(),
+            Err(Error::TransportAddressMalformed { value: _ }),
+        ));
+    }
+
     #[test]
     fn format_minimal() {
         assert_eq!(&Transport::new().to_string(), "RTP/AVP",);
@@ -633,6 +835,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn format_rtsp2_addresses() {
+        assert_eq!(
+            &Transport::new()
+                .with_lower_protocol(Lower::Udp)
+                .with_parameter(Parameter::Unicast)
+                .with_parameter(Parameter::DestAddr(vec![
+                    Address::from_socket_addr(([192, 0, 2, 53], 4588).into()),
+                    Address::from_port(4589),
+                ]))
+                .with_parameter(Parameter::SrcAddr(vec![Address::from_socket_addr(
+                    "[2001:db8::1]:6256".parse().unwrap(),
+                )]))
+                .with_parameter(Parameter::RtcpMux)
+                .to_string(),
+            "RTP/AVP/UDP;unicast;dest_addr=\"192.0.2.53:4588\"/\":4589\";\
+                src_addr=\"[2001:db8::1]:6256\";RTCP-mux",
+        );
+    }
+
     #[test]
     fn format_all_parameters() {
         assert_eq!(
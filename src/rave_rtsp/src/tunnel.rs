@@ -0,0 +1,323 @@
+//! RTSP over HTTP tunneling, as introduced by QuickTime.
+//!
+//! The client opens two HTTP connections to the server. The `GET` connection carries responses
+//! and interleaved data from the server to the client, and the `POST` connection carries
+//! base64-encoded requests from the client to the server. The server links the two by the
+//! `x-sessioncookie` header that the client sends on both. After the HTTP request (and response
+//! for `GET`), both connections carry plain RTSP traffic, apart from the base64 encoding.
+
+use std::pin::Pin;
+use std::task::{ready, Context, Poll};
+
+use base64::Engine;
+
+use bytes::{Buf, BytesMut};
+
+#[cfg(feature = "client")]
+use tokio::io::AsyncWrite;
+#[cfg(feature = "server")]
+use tokio::io::ReadBuf;
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+/// Header that links the `GET` and `POST` connection of a tunnel.
+pub(crate) const SESSION_COOKIE: &str = "x-sessioncookie";
+
+/// Header with which the server can tell the client to which address to send the `POST` request.
+#[cfg(feature = "client")]
+pub(crate) const SERVER_IP_ADDRESS: &str = "x-server-ip-address";
+
+/// Content type of tunneled RTSP.
+pub(crate) const CONTENT_TYPE: &str = "application/x-rtsp-tunnelled";
+
+/// Maximum size of HTTP request or response head.
+const MAX_HEAD_LEN: usize = 8192;
+
+/// Size of chunks read from the underlying stream.
+const READ_CHUNK_SIZE: usize = 4096;
+
+/// Stream prefixed with data that was already read from it.
+pub(crate) type Prefixed<R> = tokio::io::Chain<std::io::Cursor<BytesMut>, R>;
+
+/// Prefix stream with data that was already read from it.
+pub(crate) fn prefixed<R: AsyncRead>(buf: BytesMut, read: R) -> Prefixed<R> {
+    AsyncReadExt::chain(std::io::Cursor::new(buf), read)
+}
+
+/// Create random session cookie.
+#[cfg(feature = "client")]
+pub(crate) fn session_cookie() -> String {
+    format!("{:016x}", rand::random::<u64>())
+}
+
+/// Read from `read` into `buf` until `buf` contains `delimiter`.
+///
+/// # Return value
+///
+/// Position in `buf` right after the first occurrence of `delimiter`.
+pub(crate) async fn read_until<R: AsyncRead + Unpin>(
+    read: &mut R,
+    buf: &mut BytesMut,
+    delimiter: &[u8],
+) -> std::io::Result<usize> {
+    loop {
+        if let Some(pos) = buf
+            .windows(delimiter.len())
+            .position(|window| window == delimiter)
+        {
+            return Ok(pos + delimiter.len());
+        }
+        if buf.len() >= MAX_HEAD_LEN {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                "http head too large",
+            ));
+        }
+        buf.reserve(READ_CHUNK_SIZE);
+        if read.read_buf(buf).await? == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+    }
+}
+
+/// Read HTTP request or response head.
+///
+/// # Return value
+///
+/// Head, and the stream prefixed with any data that was read past the head.
+#[cfg(feature = "client")]
+pub(crate) async fn read_head<R: AsyncRead + Unpin>(
+    mut read: R,
+) -> std::io::Result<(Head, Prefixed<R>)> {
+    let mut buf = BytesMut::new();
+    let end = read_until(&mut read, &mut buf, b"\r\n\r\n").await?;
+    let head = Head::parse(&buf.split_to(end));
+    Ok((head, prefixed(buf, read)))
+}
+
+/// Head of HTTP request or response.
+pub(crate) struct Head {
+    /// Request line or status line.
+    pub(crate) start_line: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Head {
+    pub(crate) fn parse(head: &[u8]) -> Self {
+        let head = String::from_utf8_lossy(head);
+        let mut lines = head.split("\r\n");
+        let start_line = lines.next().unwrap_or_default().to_string();
+        let headers = lines
+            .filter_map(|line| line.split_once(':'))
+            .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+            .collect();
+        Self {
+            start_line,
+            headers,
+        }
+    }
+
+    /// Get value of header. Header names are case-insensitive.
+    pub(crate) fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Whether `line` is the request line of an HTTP (rather than RTSP) request.
+#[cfg(feature = "server")]
+pub(crate) fn is_http_request_line(line: &[u8]) -> bool {
+    line.trim_ascii_end()
+        .rsplit(|byte| *byte == b' ')
+        .next()
+        .is_some_and(|version| version.starts_with(b"HTTP/"))
+}
+
+/// Decodes base64 from the underlying stream.
+///
+/// Whitespace is ignored, and padding may occur anywhere in the stream, since clients encode each
+/// request separately.
+#[cfg(feature = "server")]
+pub(crate) struct Base64Reader<R> {
+    inner: R,
+    /// Incomplete group of base64 characters.
+    group: Vec<u8>,
+    /// Decoded data that was not read yet.
+    decoded: BytesMut,
+}
+
+#[cfg(feature = "server")]
+impl<R> Base64Reader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            group: Vec::with_capacity(4),
+            decoded: BytesMut::new(),
+        }
+    }
+
+    fn decode(&mut self, encoded: &[u8]) -> std::io::Result<()> {
+        for byte in encoded.iter().filter(|byte| !byte.is_ascii_whitespace()) {
+            self.group.push(*byte);
+            if self.group.len() == 4 {
+                let mut decoded = [0; 3];
+                let len = base64::engine::general_purpose::STANDARD
+                    .decode_slice(&self.group, &mut decoded)
+                    .map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+                self.decoded.extend_from_slice(&decoded[..len]);
+                self.group.clear();
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "server")]
+impl<R: AsyncRead + Unpin> AsyncRead for Base64Reader<R> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        while this.decoded.is_empty() {
+            let mut encoded = [0; READ_CHUNK_SIZE];
+            let mut encoded = ReadBuf::new(&mut encoded);
+            ready!(Pin::new(&mut this.inner).poll_read(cx, &mut encoded))?;
+            if encoded.filled().is_empty() {
+                return Poll::Ready(Ok(()));
+            }
+            this.decode(encoded.filled())?;
+        }
+        let len = this.decoded.len().min(buf.remaining());
+        buf.put_slice(&this.decoded[..len]);
+        this.decoded.advance(len);
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Encodes everything that is written to it as base64 to the underlying stream.
+#[cfg(feature = "client")]
+pub(crate) struct Base64Writer<W> {
+    inner: W,
+    /// Encoded data that was not written to the underlying stream yet.
+    encoded: BytesMut,
+}
+
+#[cfg(feature = "client")]
+impl<W: AsyncWrite + Unpin> Base64Writer<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            inner,
+            encoded: BytesMut::new(),
+        }
+    }
+
+    fn poll_write_encoded(&mut self, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        while !self.encoded.is_empty() {
+            let len = ready!(Pin::new(&mut self.inner).poll_write(cx, &self.encoded))?;
+            if len == 0 {
+                return Poll::Ready(Err(std::io::ErrorKind::WriteZero.into()));
+            }
+            self.encoded.advance(len);
+        }
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(feature = "client")]
+impl<W: AsyncWrite + Unpin> AsyncWrite for Base64Writer<W> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<std::io::Result<usize>> {
+        let this = self.get_mut();
+        // Only accept more data once earlier data was written, to bound the buffer.
+        ready!(this.poll_write_encoded(cx))?;
+        let encoded = base64::engine::general_purpose::STANDARD.encode(buf);
+        this.encoded.extend_from_slice(encoded.as_bytes());
+        if let Poll::Ready(Err(err)) = this.poll_write_encoded(cx) {
+            return Poll::Ready(Err(err));
+        }
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_encoded(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        ready!(this.poll_write_encoded(cx))?;
+        Pin::new(&mut this.inner).poll_shutdown(cx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(feature = "server")]
+    use tokio::io::AsyncWriteExt;
+
+    #[cfg(feature = "server")]
+    #[tokio::test]
+    async fn base64_reader_decodes_separately_encoded_chunks() {
+        // Two separately encoded requests, both padded, split at arbitrary positions.
+        let (mut write, read) = tokio::io::duplex(64);
+        let mut read = Base64Reader::new(read);
+        let writer = async move {
+            for chunk in ["T1BU", "SU9O\r\nUw", "==R0VU", "IA=", "="] {
+                write.write_all(chunk.as_bytes()).await.unwrap();
+            }
+        };
+        let mut decoded = Vec::new();
+        let (_, result) = tokio::join!(writer, read.read_to_end(&mut decoded));
+        result.unwrap();
+        assert_eq!(decoded, b"OPTIONSGET ");
+    }
+
+    #[cfg(feature = "server")]
+    #[tokio::test]
+    async fn base64_reader_rejects_invalid_data() {
+        let mut read = Base64Reader::new(&b"T1B!"[..]);
+        let mut decoded = Vec::new();
+        let err = read.read_to_end(&mut decoded).await.unwrap_err();
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(all(feature = "client", feature = "server"))]
+    #[tokio::test]
+    async fn base64_writer_roundtrip() {
+        let (write, read) = tokio::io::duplex(16);
+        let mut write = Base64Writer::new(write);
+        let mut read = Base64Reader::new(read);
+        let payload = (0..=255).collect::<Vec<u8>>();
+        let writer = async move {
+            for chunk in payload.chunks(7) {
+                write.write_all(chunk).await.unwrap();
+                write.flush().await.unwrap();
+            }
+            write.shutdown().await.unwrap();
+        };
+        let mut decoded = Vec::new();
+        let (_, result) = tokio::join!(writer, read.read_to_end(&mut decoded));
+        result.unwrap();
+        assert_eq!(decoded, (0..=255).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn parse_head() {
+        let head = Head::parse(
+            b"GET /camera HTTP/1.0\r\nX-SessionCookie: abc\r\nAccept: application/x-rtsp-tunnelled\r\n\r\n",
+        );
+        assert_eq!(head.start_line, "GET /camera HTTP/1.0");
+        assert_eq!(head.header(SESSION_COOKIE), Some("abc"));
+        assert_eq!(head.header("accept"), Some(CONTENT_TYPE));
+        assert_eq!(head.header("content-length"), None);
+    }
+}
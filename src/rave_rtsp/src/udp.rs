@@ -0,0 +1,239 @@
+use tokio::net::UdpSocket;
+
+/// Maximum number of attempts to find a free pair of ports.
+const MAX_ATTEMPTS: usize = 64;
+
+/// Bind a pair of UDP sockets for RTP and RTCP.
+///
+/// RFC 3550 recommends that RTP uses an even port and that RTCP uses the next (odd) port. This
+/// function keeps binding to ephemeral ports until it finds such a pair.
+///
+/// # Arguments
+///
+/// * `ip` - Local address to bind to.
+///
+/// # Return value
+///
+/// RTP socket (even port) and RTCP socket (odd port).
+pub(crate) async fn bind_pair(ip: std::net::IpAddr) -> std::io::Result<(UdpSocket, UdpSocket)> {
+    for _ in 0..MAX_ATTEMPTS {
+        let rtp = UdpSocket::bind((ip, 0)).await?;
+        let port = rtp.local_addr()?.port();
+        if port % 2 != 0 {
+            continue;
+        }
+        if let Ok(rtcp) = UdpSocket::bind((ip, port + 1)).await {
+            return Ok((rtp, rtcp));
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::AddrInUse,
+        "failed to find pair of free consecutive ports for rtp and rtcp",
+    ))
+}
+
+/// Bind a pair of UDP sockets for RTP and RTCP to specific ports.
+///
+/// # Arguments
+///
+/// * `ip` - Local address to bind to.
+/// * `port` - Ports to bind to. If only a single port is given, RTCP uses the next port.
+///
+/// # Return value
+///
+/// RTP socket and RTCP socket.
+#[cfg(feature = "client")]
+pub(crate) async fn bind_ports(
+    ip: std::net::IpAddr,
+    port: &crate::transport::Port,
+) -> std::io::Result<(UdpSocket, UdpSocket)> {
+    let (rtp_port, rtcp_port) = match port {
+        crate::transport::Port::Single(rtp_port) => (*rtp_port, rtp_port.wrapping_add(1)),
+        crate::transport::Port::Range(rtp_port, rtcp_port) => (*rtp_port, *rtcp_port),
+    };
+    let rtp = UdpSocket::bind((ip, rtp_port)).await?;
+    let rtcp = UdpSocket::bind((ip, rtcp_port)).await?;
+    Ok((rtp, rtcp))
+}
+
+#[cfg(feature = "client")]
+pub(crate) use self::client::Remote;
+#[cfg(feature = "client")]
+pub use self::client::{RtcpStream, RtpStream, UdpTrack};
+
+#[cfg(feature = "client")]
+mod client {
+    use std::net::{IpAddr, SocketAddr};
+    use std::pin::Pin;
+    use std::task::{ready, Context, Poll};
+
+    use bytes::Bytes;
+
+    use rave_rtp::packet::Packet;
+    use rave_rtp::parse::Parse;
+    use rave_rtp::rtcp::Compound;
+
+    use tokio::io::ReadBuf;
+    use tokio::net::UdpSocket;
+
+    use tokio_stream::Stream;
+
+    use crate::client::ClientError;
+
+    /// Maximum size of a UDP datagram.
+    const MAX_DATAGRAM_SIZE: usize = 65536;
+
+    /// Media track that is received over UDP.
+    ///
+    /// Produced by [`Client::udp_tracks`](crate::client::Client::udp_tracks) for each track that
+    /// was set up over UDP.
+    #[derive(Debug)]
+    pub struct UdpTrack {
+        /// Stream of RTP packets.
+        pub rtp: RtpStream,
+        /// Stream of RTCP packets.
+        pub rtcp: RtcpStream,
+    }
+
+    /// Address that the server sends packets from. Parts that are not known match any address.
+    #[derive(Debug, Clone, Copy, Default)]
+    pub(crate) struct Remote {
+        pub(crate) ip: Option<IpAddr>,
+        pub(crate) port: Option<u16>,
+    }
+
+    impl Remote {
+        fn matches(&self, addr: SocketAddr) -> bool {
+            self.ip
+                .is_none_or(|ip| ip.to_canonical() == addr.ip().to_canonical())
+                && self.port.is_none_or(|port| port == addr.port())
+        }
+    }
+
+    /// Stream of RTP packets received over UDP.
+    ///
+    /// Datagrams that do not come from the server are discarded.
+    #[derive(Debug)]
+    pub struct RtpStream(Receiver);
+
+    impl RtpStream {
+        pub(crate) fn new(socket: UdpSocket, remote: Remote) -> Self {
+            Self(Receiver::new(socket, remote))
+        }
+
+        /// Local port the stream receives on.
+        pub fn local_port(&self) -> std::io::Result<u16> {
+            Ok(self.0.socket.local_addr()?.port())
+        }
+    }
+
+    impl Stream for RtpStream {
+        type Item = Result<Packet, ClientError>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.0.poll_recv(cx).map(|datagram| {
+                Some(datagram.and_then(|mut datagram| {
+                    Packet::parse(&mut datagram).map_err(ClientError::InvalidRtp)
+                }))
+            })
+        }
+    }
+
+    /// Stream of compound RTCP packets received over UDP.
+    ///
+    /// Datagrams that do not come from the server are discarded.
+    #[derive(Debug)]
+    pub struct RtcpStream(Receiver);
+
+    impl RtcpStream {
+        pub(crate) fn new(socket: UdpSocket, remote: Remote) -> Self {
+            Self(Receiver::new(socket, remote))
+        }
+
+        /// Local port the stream receives on.
+        pub fn local_port(&self) -> std::io::Result<u16> {
+            Ok(self.0.socket.local_addr()?.port())
+        }
+    }
+
+    impl Stream for RtcpStream {
+        type Item = Result<Compound, ClientError>;
+
+        fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+            self.0.poll_recv(cx).map(|datagram| {
+                Some(datagram.and_then(|mut datagram| {
+                    Compound::parse(&mut datagram).map_err(ClientError::InvalidRtcp)
+                }))
+            })
+        }
+    }
+
+    #[derive(Debug)]
+    struct Receiver {
+        socket: UdpSocket,
+        remote: Remote,
+        buffer: Box<[u8]>,
+    }
+
+    impl Receiver {
+        fn new(socket: UdpSocket, remote: Remote) -> Self {
+            Self {
+                socket,
+                remote,
+                buffer: vec![0; MAX_DATAGRAM_SIZE].into_boxed_slice(),
+            }
+        }
+
+        fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Result<Bytes, ClientError>> {
+            loop {
+                let mut buffer = ReadBuf::new(&mut self.buffer);
+                let addr =
+                    ready!(self.socket.poll_recv_from(cx, &mut buffer)).map_err(ClientError::Io)?;
+                if self.remote.matches(addr) {
+                    return Poll::Ready(Ok(Bytes::copy_from_slice(buffer.filled())));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "client"))]
+mod tests {
+    use super::*;
+
+    use rave_rtp::rtcp::{Compound, Packet, ReceiverReport};
+
+    use tokio_stream::StreamExt;
+
+    #[tokio::test]
+    async fn receive_rtcp_from_server_only() {
+        let localhost = std::net::IpAddr::from(std::net::Ipv4Addr::LOCALHOST);
+        let (_, socket) = bind_pair(localhost).await.unwrap();
+        let server = UdpSocket::bind((localhost, 0)).await.unwrap();
+        let other = UdpSocket::bind((localhost, 0)).await.unwrap();
+        let mut stream = RtcpStream::new(
+            socket,
+            Remote {
+                ip: Some(localhost),
+                port: Some(server.local_addr().unwrap().port()),
+            },
+        );
+        let port = stream.local_port().unwrap();
+
+        // The truncated packet from another host is discarded, the receiver report without report
+        // blocks from the server is not.
+        let report = [0x80, 0xc9, 0x00, 0x01, 0xca, 0xfe, 0xba, 0xbe];
+        other
+            .send_to(&[0x80, 0xc9, 0x00], (localhost, port))
+            .await
+            .unwrap();
+        server.send_to(&report, (localhost, port)).await.unwrap();
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            Compound(vec![Packet::ReceiverReport(ReceiverReport {
+                ssrc: 0xcafebabe,
+                reports: Vec::new(),
+            })]),
+        );
+    }
+}
@@ -148,14 +148,20 @@ impl Builder {
         direction: Direction,
         codec_parameters: impl CodecParameters,
     ) -> Result<()> {
-        let dynamic_payload_type = self.dynamic_payload_type_counter;
-        if !Self::DYNAMIC_PAYLOAD_TYPE_RANGE.contains(&dynamic_payload_type) {
-            return Err(Error::TooManyMediaItems);
-        }
-
-        self.dynamic_payload_type_counter += 1;
-
-        let mut attributes = codec_parameters.media_attributes(dynamic_payload_type);
+        let payload_type = match codec_parameters.static_payload_type() {
+            Some(static_payload_type) => static_payload_type,
+            None => {
+                let dynamic_payload_type = self.dynamic_payload_type_counter;
+                if !Self::DYNAMIC_PAYLOAD_TYPE_RANGE.contains(&dynamic_payload_type) {
+                    return Err(Error::TooManyMediaItems);
+                }
+
+                self.dynamic_payload_type_counter += 1;
+                dynamic_payload_type
+            }
+        };
+
+        let mut attributes = codec_parameters.media_attributes(payload_type);
         attributes.push(Attribute::Property(direction.to_string()));
 
         self.sdp.media.push(MediaItem {
@@ -163,7 +169,7 @@ impl Builder {
                 kind,
                 port,
                 protocol,
-                format: dynamic_payload_type,
+                format: payload_type,
             },
             title: Some(title.to_string()),
             connection: None,
@@ -0,0 +1,136 @@
+use crate::codec::{Parameters as ParametersTrait, Rtpmap};
+use crate::sdp::Attribute;
+
+/// Holds G.711 codec-specific parameters.
+///
+/// G.711 uses static payload types (0 for µ-law and 8 for A-law), so no dynamic payload type is
+/// assigned to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Parameters {
+    law: Law,
+    packet_time: Option<u32>,
+}
+
+/// G.711 companding law.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Law {
+    /// µ-law (PCMU).
+    MuLaw,
+    /// A-law (PCMA).
+    ALaw,
+}
+
+impl Parameters {
+    /// Initialize codec-specific information for a G.711 µ-law (PCMU) stream.
+    pub fn pcmu() -> Self {
+        Self {
+            law: Law::MuLaw,
+            packet_time: None,
+        }
+    }
+
+    /// Initialize codec-specific information for a G.711 A-law (PCMA) stream.
+    pub fn pcma() -> Self {
+        Self {
+            law: Law::ALaw,
+            packet_time: None,
+        }
+    }
+
+    /// Signal duration of audio in each packet (`a=ptime`) in milliseconds.
+    pub fn with_packet_time(mut self, packet_time: u32) -> Self {
+        self.packet_time = Some(packet_time);
+        self
+    }
+
+    /// Generate `rtpmap` attribute.
+    ///
+    /// # Return value
+    ///
+    /// `rtpmap` attribute for SDP.
+    fn rtpmap_attribute(&self) -> Attribute {
+        let encoding_name = match self.law {
+            Law::MuLaw => "PCMU",
+            Law::ALaw => "PCMA",
+        };
+        Rtpmap {
+            payload_type: self.payload_type(),
+            encoding_name: encoding_name.to_string(),
+            clock_rate: 8000,
+            encoding_parameters: None,
+        }
+        .to_attribute()
+    }
+
+    #[inline]
+    fn payload_type(&self) -> u8 {
+        match self.law {
+            Law::MuLaw => 0,
+            Law::ALaw => 8,
+        }
+    }
+}
+
+impl ParametersTrait for Parameters {
+    /// Retrieve corresponding media attributes.
+    ///
+    /// The dynamic payload type is ignored, since G.711 uses a static payload type.
+    ///
+    /// # Arguments
+    ///
+    /// * `dynamic_payload_type` - Ignored.
+    ///
+    /// # Return value
+    ///
+    /// One or more media attributes.
+    fn media_attributes(&self, _dynamic_payload_type: u8) -> Vec<Attribute> {
+        let mut attributes = vec![self.rtpmap_attribute()];
+        if let Some(packet_time) = self.packet_time {
+            attributes.push(Attribute::Value(
+                "ptime".to_string(),
+                packet_time.to_string(),
+            ));
+        }
+        attributes
+    }
+
+    fn static_payload_type(&self) -> Option<u8> {
+        Some(self.payload_type())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn media_attributes() {
+        let attributes = Parameters::pcmu().media_attributes(96);
+        assert_eq!(
+            attributes,
+            vec![Attribute::Value(
+                "rtpmap".to_string(),
+                "0 PCMU/8000".to_string()
+            )]
+        );
+        assert_eq!(
+            Rtpmap::find(&attributes, 0).unwrap().unwrap(),
+            Rtpmap {
+                payload_type: 0,
+                encoding_name: "PCMU".to_string(),
+                clock_rate: 8000,
+                encoding_parameters: None,
+            }
+        );
+
+        let parameters = Parameters::pcma().with_packet_time(20);
+        assert_eq!(parameters.static_payload_type(), Some(8));
+        assert_eq!(
+            parameters.media_attributes(96),
+            vec![
+                Attribute::Value("rtpmap".to_string(), "8 PCMA/8000".to_string()),
+                Attribute::Value("ptime".to_string(), "20".to_string()),
+            ]
+        );
+    }
+}
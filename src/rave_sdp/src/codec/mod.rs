@@ -1,4 +1,9 @@
+pub mod g711;
 pub mod h264;
+pub mod opus;
+
+use crate::error::{Error, Result};
+use crate::sdp::Attribute;
 
 /// Codec parameters.
 ///
@@ -17,4 +22,247 @@ pub trait Parameters {
     ///
     /// One or more media attributes.
     fn media_attributes(&self, dynamic_payload_type: u8) -> Vec<crate::sdp::Attribute>;
+
+    /// Static payload type of codec, if it has one.
+    ///
+    /// Codecs with a static payload type (RFC 3551) do not use up a dynamic payload type. Their
+    /// media attributes receive the static payload type instead.
+    fn static_payload_type(&self) -> Option<u8> {
+        None
+    }
+}
+
+/// RTP map attribute (`a=rtpmap`).
+///
+/// Maps a payload type to an encoding name, clock rate and optional encoding parameters (the
+/// number of channels for audio).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rtpmap {
+    pub payload_type: u8,
+    pub encoding_name: String,
+    pub clock_rate: u32,
+    pub encoding_parameters: Option<u32>,
+}
+
+impl Rtpmap {
+    /// Find RTP map for payload type in media attributes.
+    ///
+    /// # Arguments
+    ///
+    /// * `attributes` - Media attributes.
+    /// * `payload_type` - Payload type to find RTP map for.
+    ///
+    /// # Return value
+    ///
+    /// RTP map, or `None` if there is none for the payload type.
+    pub fn find(attributes: &[Attribute], payload_type: u8) -> Result<Option<Self>> {
+        find_value(attributes, "rtpmap", payload_type)
+            .map(str::parse)
+            .transpose()
+    }
+
+    /// Convert to `rtpmap` attribute.
+    pub fn to_attribute(&self) -> Attribute {
+        let value = self.to_string();
+        Attribute::Value("rtpmap".to_string(), value)
+    }
+}
+
+impl std::fmt::Display for Rtpmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}/{}",
+            self.payload_type, self.encoding_name, self.clock_rate
+        )?;
+        if let Some(encoding_parameters) = self.encoding_parameters {
+            write!(f, "/{encoding_parameters}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Rtpmap {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::RtpmapInvalid {
+            rtpmap: s.to_string(),
+        };
+        let (payload_type, encoding) = s.trim().split_once(' ').ok_or_else(invalid)?;
+        let mut encoding = encoding.trim().split('/');
+        let encoding_name = encoding.next().ok_or_else(invalid)?.to_string();
+        let clock_rate = encoding
+            .next()
+            .and_then(|clock_rate| clock_rate.parse().ok())
+            .ok_or_else(invalid)?;
+        let encoding_parameters = encoding
+            .next()
+            .map(|encoding_parameters| encoding_parameters.parse().map_err(|_| invalid()))
+            .transpose()?;
+        Ok(Rtpmap {
+            payload_type: payload_type.parse().map_err(|_| invalid())?,
+            encoding_name,
+            clock_rate,
+            encoding_parameters,
+        })
+    }
+}
+
+/// Format parameters attribute (`a=fmtp`).
+///
+/// Holds the format-specific parameters for a payload type, as a list of `name=value` pairs
+/// separated by semicolons.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fmtp {
+    pub payload_type: u8,
+    pub parameters: Vec<(String, String)>,
+}
+
+impl Fmtp {
+    /// Find format parameters for payload type in media attributes.
+    ///
+    /// # Arguments
+    ///
+    /// * `attributes` - Media attributes.
+    /// * `payload_type` - Payload type to find format parameters for.
+    ///
+    /// # Return value
+    ///
+    /// Format parameters, or `None` if there are none for the payload type.
+    pub fn find(attributes: &[Attribute], payload_type: u8) -> Result<Option<Self>> {
+        find_value(attributes, "fmtp", payload_type)
+            .map(str::parse)
+            .transpose()
+    }
+
+    /// Get value of parameter. Parameter names are case-insensitive.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.parameters
+            .iter()
+            .find(|(other, _)| other.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Convert to `fmtp` attribute.
+    pub fn to_attribute(&self) -> Attribute {
+        let value = self.to_string();
+        Attribute::Value("fmtp".to_string(), value)
+    }
+}
+
+impl std::fmt::Display for Fmtp {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let parameters = self
+            .parameters
+            .iter()
+            .map(|(name, value)| format!("{name}={value}"))
+            .collect::<Vec<_>>()
+            .join(";");
+        write!(f, "{} {}", self.payload_type, parameters)
+    }
+}
+
+impl std::str::FromStr for Fmtp {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::FmtpInvalid {
+            fmtp: s.to_string(),
+        };
+        let (payload_type, parameters) = s.trim().split_once(' ').unwrap_or((s.trim(), ""));
+        let parameters = parameters
+            .split(';')
+            .map(str::trim)
+            .filter(|parameter| !parameter.is_empty())
+            .map(|parameter| match parameter.split_once('=') {
+                Some((name, value)) => (name.trim().to_string(), value.trim().to_string()),
+                None => (parameter.to_string(), String::new()),
+            })
+            .collect();
+        Ok(Fmtp {
+            payload_type: payload_type.parse().map_err(|_| invalid())?,
+            parameters,
+        })
+    }
+}
+
+/// Find value of attribute that starts with the given payload type.
+fn find_value<'a>(attributes: &'a [Attribute], name: &str, payload_type: u8) -> Option<&'a str> {
+    attributes.iter().find_map(|attribute| match attribute {
+        Attribute::Value(variable, value)
+            if variable == name
+                && value
+                    .split_whitespace()
+                    .next()
+                    .is_some_and(|format| format.parse() == Ok(payload_type)) =>
+        {
+            Some(value.as_str())
+        }
+        _ => None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rtpmap_round_trip() {
+        let rtpmap = "96 opus/48000/2".parse::<Rtpmap>().unwrap();
+        assert_eq!(
+            rtpmap,
+            Rtpmap {
+                payload_type: 96,
+                encoding_name: "opus".to_string(),
+                clock_rate: 48000,
+                encoding_parameters: Some(2),
+            }
+        );
+        assert_eq!(rtpmap.to_string(), "96 opus/48000/2");
+
+        let rtpmap = "97 H264/90000".parse::<Rtpmap>().unwrap();
+        assert_eq!(rtpmap.encoding_parameters, None);
+        assert_eq!(rtpmap.to_string(), "97 H264/90000");
+
+        assert!("96 opus".parse::<Rtpmap>().is_err());
+        assert!("opus/48000/2".parse::<Rtpmap>().is_err());
+        assert!("96 opus/48000/two".parse::<Rtpmap>().is_err());
+    }
+
+    #[test]
+    fn fmtp_round_trip() {
+        let fmtp = "96 stereo=1; useinbandfec=1".parse::<Fmtp>().unwrap();
+        assert_eq!(fmtp.payload_type, 96);
+        assert_eq!(fmtp.get("STEREO"), Some("1"));
+        assert_eq!(fmtp.get("useinbandfec"), Some("1"));
+        assert_eq!(fmtp.get("usedtx"), None);
+        assert_eq!(fmtp.to_string(), "96 stereo=1;useinbandfec=1");
+        assert_eq!(fmtp.to_string().parse::<Fmtp>().unwrap(), fmtp);
+
+        assert!("x stereo=1".parse::<Fmtp>().is_err());
+    }
+
+    #[test]
+    fn find_in_attributes() {
+        let attributes = vec![
+            Attribute::Value("rtpmap".to_string(), "96 opus/48000/2".to_string()),
+            Attribute::Value("fmtp".to_string(), "96 stereo=1".to_string()),
+            Attribute::Value("ptime".to_string(), "20".to_string()),
+        ];
+        assert_eq!(
+            Rtpmap::find(&attributes, 96)
+                .unwrap()
+                .unwrap()
+                .encoding_name,
+            "opus"
+        );
+        assert_eq!(
+            Fmtp::find(&attributes, 96).unwrap().unwrap().get("stereo"),
+            Some("1")
+        );
+        // Static payload types (RFC 3551) need not have an RTP map.
+        assert_eq!(Rtpmap::find(&attributes, 0).unwrap(), None);
+        assert_eq!(Fmtp::find(&attributes, 0).unwrap(), None);
+    }
 }
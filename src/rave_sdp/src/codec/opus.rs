@@ -0,0 +1,186 @@
+use crate::codec::{Fmtp, Parameters as ParametersTrait, Rtpmap};
+use crate::sdp::Attribute;
+
+/// Holds Opus codec-specific parameters (RFC 7587 section 7).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Parameters {
+    /// Whether the receiver prefers stereo (`stereo`).
+    pub stereo: bool,
+    /// Whether the sender is likely to send stereo (`sprop-stereo`).
+    pub sprop_stereo: bool,
+    /// Maximum output sampling rate the receiver can render (`maxplaybackrate`).
+    pub max_playback_rate: Option<u32>,
+    /// Maximum average bitrate the receiver can handle (`maxaveragebitrate`).
+    pub max_average_bitrate: Option<u32>,
+    /// Whether the receiver can use in-band forward error correction (`useinbandfec`).
+    pub use_inband_fec: bool,
+    /// Whether the receiver prefers discontinuous transmission (`usedtx`).
+    pub use_dtx: bool,
+    /// Minimum duration of audio in each packet in milliseconds (`minptime`).
+    pub min_packet_time: Option<u32>,
+}
+
+impl Parameters {
+    /// Initialize codec-specific information for an Opus stream, with all parameters set to their
+    /// defaults.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Signal stereo, both as preferred by the receiver and as sent by the sender.
+    pub fn with_stereo(mut self, stereo: bool) -> Self {
+        self.stereo = stereo;
+        self.sprop_stereo = stereo;
+        self
+    }
+
+    /// Signal in-band forward error correction.
+    pub fn with_inband_fec(mut self, use_inband_fec: bool) -> Self {
+        self.use_inband_fec = use_inband_fec;
+        self
+    }
+
+    /// Signal discontinuous transmission.
+    pub fn with_dtx(mut self, use_dtx: bool) -> Self {
+        self.use_dtx = use_dtx;
+        self
+    }
+
+    /// Retrieve parameters from format parameters. Missing and invalid parameters take their
+    /// default values.
+    ///
+    /// # Arguments
+    ///
+    /// * `fmtp` - Format parameters.
+    pub fn from_fmtp(fmtp: &Fmtp) -> Self {
+        let flag = |name| fmtp.get(name) == Some("1");
+        let number = |name| fmtp.get(name).and_then(|value| value.parse().ok());
+        Self {
+            stereo: flag("stereo"),
+            sprop_stereo: flag("sprop-stereo"),
+            max_playback_rate: number("maxplaybackrate"),
+            max_average_bitrate: number("maxaveragebitrate"),
+            use_inband_fec: flag("useinbandfec"),
+            use_dtx: flag("usedtx"),
+            min_packet_time: number("minptime"),
+        }
+    }
+
+    /// Generate `fmtp` attribute, if any parameter differs from its default.
+    ///
+    /// # Return value
+    ///
+    /// `fmtp` attribute for SDP.
+    fn fmtp_attribute(&self, payload_type: u8) -> Option<Attribute> {
+        let flags = [
+            ("stereo", self.stereo),
+            ("sprop-stereo", self.sprop_stereo),
+            ("useinbandfec", self.use_inband_fec),
+            ("usedtx", self.use_dtx),
+        ];
+        let numbers = [
+            ("maxplaybackrate", self.max_playback_rate),
+            ("maxaveragebitrate", self.max_average_bitrate),
+            ("minptime", self.min_packet_time),
+        ];
+        let parameters: Vec<(String, String)> = flags
+            .into_iter()
+            .filter(|(_, value)| *value)
+            .map(|(name, _)| (name.to_string(), "1".to_string()))
+            .chain(numbers.into_iter().filter_map(|(name, value)| {
+                value.map(|value| (name.to_string(), value.to_string()))
+            }))
+            .collect();
+        if parameters.is_empty() {
+            None
+        } else {
+            Some(
+                Fmtp {
+                    payload_type,
+                    parameters,
+                }
+                .to_attribute(),
+            )
+        }
+    }
+
+    /// Generate `rtpmap` attribute.
+    ///
+    /// The clock rate is always 48000 and the number of channels is always 2, regardless of the
+    /// actual sampling rate and number of channels.
+    ///
+    /// # Return value
+    ///
+    /// `rtpmap` attribute for SDP.
+    #[inline]
+    fn rtpmap_attribute(payload_type: u8) -> Attribute {
+        Rtpmap {
+            payload_type,
+            encoding_name: "opus".to_string(),
+            clock_rate: 48000,
+            encoding_parameters: Some(2),
+        }
+        .to_attribute()
+    }
+}
+
+impl ParametersTrait for Parameters {
+    /// Retrieve corresponding media attributes.
+    ///
+    /// These attributes are added to the media item to signal media information to the receiver of
+    /// the SDP file.
+    ///
+    /// # Arguments
+    ///
+    /// * `dynamic_payload_type` - Dynamic payload type to associate with media item.
+    ///
+    /// # Return value
+    ///
+    /// One or more media attributes.
+    fn media_attributes(&self, dynamic_payload_type: u8) -> Vec<Attribute> {
+        std::iter::once(Self::rtpmap_attribute(dynamic_payload_type))
+            .chain(self.fmtp_attribute(dynamic_payload_type))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn media_attributes() {
+        assert_eq!(
+            Parameters::new().media_attributes(111),
+            vec![Attribute::Value(
+                "rtpmap".to_string(),
+                "111 opus/48000/2".to_string()
+            )],
+        );
+
+        let parameters = Parameters {
+            max_average_bitrate: Some(64000),
+            ..Parameters::new().with_stereo(true).with_inband_fec(true)
+        };
+        let attributes = parameters.media_attributes(111);
+        assert_eq!(
+            Rtpmap::find(&attributes, 111).unwrap().unwrap().to_string(),
+            "111 opus/48000/2"
+        );
+        let fmtp = Fmtp::find(&attributes, 111).unwrap().unwrap();
+        assert_eq!(
+            fmtp.to_string(),
+            "111 stereo=1;sprop-stereo=1;useinbandfec=1;maxaveragebitrate=64000"
+        );
+        assert_eq!(Parameters::from_fmtp(&fmtp), parameters);
+    }
+
+    #[test]
+    fn from_fmtp_ignores_invalid_parameters() {
+        let fmtp = "111 stereo=yes;minptime=ten;usedtx=1".parse().unwrap();
+        assert_eq!(
+            Parameters::from_fmtp(&fmtp),
+            Parameters::new().with_dtx(true)
+        );
+    }
+}
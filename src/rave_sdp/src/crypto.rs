@@ -0,0 +1,309 @@
+use base64::engine::general_purpose::{GeneralPurpose, GeneralPurposeConfig};
+use base64::engine::DecodePaddingMode;
+use base64::Engine;
+
+use crate::error::{Error, Result};
+use crate::sdp::Attribute;
+
+/// Base64 engine for SDES keys. Padding is written, but optional when reading.
+const BASE64: GeneralPurpose = GeneralPurpose::new(
+    &base64::alphabet::STANDARD,
+    GeneralPurposeConfig::new().with_decode_padding_mode(DecodePaddingMode::Indifferent),
+);
+
+/// SDES crypto attribute (`a=crypto`, RFC 4568).
+///
+/// Carries the crypto suite and master key for SRTP, for media with protocol `RTP/SAVP`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crypto {
+    /// Identifier of the attribute within the media item, used to select it in the answer.
+    pub tag: u32,
+    /// Crypto suite, such as `AES_CM_128_HMAC_SHA1_80`.
+    pub suite: String,
+    /// One or more master keys.
+    pub key_params: Vec<KeyParams>,
+    /// Session parameters, such as `UNENCRYPTED_SRTCP`.
+    pub session_params: Vec<String>,
+}
+
+impl Crypto {
+    /// Create crypto attribute with single inline master key.
+    ///
+    /// # Arguments
+    ///
+    /// * `tag` - Identifier of attribute.
+    /// * `suite` - Crypto suite.
+    /// * `key_and_salt` - Master key followed by master salt.
+    pub fn new(tag: u32, suite: impl ToString, key_and_salt: Vec<u8>) -> Self {
+        Self {
+            tag,
+            suite: suite.to_string(),
+            key_params: vec![KeyParams {
+                key_and_salt,
+                lifetime: None,
+                mki: None,
+            }],
+            session_params: Vec::new(),
+        }
+    }
+
+    /// Find all crypto attributes in media attributes.
+    ///
+    /// # Arguments
+    ///
+    /// * `attributes` - Media attributes.
+    ///
+    /// # Return value
+    ///
+    /// Crypto attributes in order of preference.
+    pub fn find_all(attributes: &[Attribute]) -> Result<Vec<Self>> {
+        attributes
+            .iter()
+            .filter_map(|attribute| match attribute {
+                Attribute::Value(variable, value) if variable == "crypto" => Some(value.parse()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Get master key followed by master salt, to create SRTP context with.
+    ///
+    /// SRTP contexts hold a single master key and do not carry a master key identifier (MKI) in
+    /// packets, so attributes with more than one key or with an MKI cannot be used.
+    ///
+    /// # Return value
+    ///
+    /// Master key followed by master salt, or an error if the key parameters are not supported.
+    pub fn key_and_salt(&self) -> Result<&[u8]> {
+        match self.key_params.as_slice() {
+            [KeyParams {
+                key_and_salt,
+                mki: None,
+                ..
+            }] => Ok(key_and_salt),
+            _ => Err(Error::CryptoKeyUnsupported {
+                crypto: self.to_string(),
+            }),
+        }
+    }
+
+    /// Convert to `crypto` attribute.
+    pub fn to_attribute(&self) -> Attribute {
+        let value = self.to_string();
+        Attribute::Value("crypto".to_string(), value)
+    }
+}
+
+impl std::fmt::Display for Crypto {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let key_params = self
+            .key_params
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(";");
+        write!(f, "{} {} {}", self.tag, self.suite, key_params)?;
+        for session_param in &self.session_params {
+            write!(f, " {session_param}")?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for Crypto {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::CryptoInvalid {
+            crypto: s.to_string(),
+        };
+        let mut parts = s.split_whitespace();
+        let tag = parts
+            .next()
+            .and_then(|tag| tag.parse().ok())
+            .ok_or_else(invalid)?;
+        let suite = parts.next().ok_or_else(invalid)?.to_string();
+        let key_params = parts
+            .next()
+            .ok_or_else(invalid)?
+            .split(';')
+            .map(|key_params| key_params.parse().map_err(|_| invalid()))
+            .collect::<Result<Vec<_>>>()?;
+        let session_params = parts.map(str::to_string).collect();
+        Ok(Crypto {
+            tag,
+            suite,
+            key_params,
+            session_params,
+        })
+    }
+}
+
+/// Inline key parameters of crypto attribute (`inline:<key||salt>[|<lifetime>][|<MKI>:<length>]`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyParams {
+    /// Master key followed by master salt.
+    pub key_and_salt: Vec<u8>,
+    /// Maximum number of packets protected with the master key.
+    pub lifetime: Option<u64>,
+    /// Master key identifier.
+    pub mki: Option<Mki>,
+}
+
+impl std::fmt::Display for KeyParams {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "inline:{}", BASE64.encode(&self.key_and_salt))?;
+        if let Some(lifetime) = self.lifetime {
+            if lifetime.is_power_of_two() {
+                write!(f, "|2^{}", lifetime.trailing_zeros())?;
+            } else {
+                write!(f, "|{lifetime}")?;
+            }
+        }
+        if let Some(mki) = self.mki {
+            write!(f, "|{}:{}", mki.value, mki.len)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::str::FromStr for KeyParams {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::CryptoInvalid {
+            crypto: s.to_string(),
+        };
+        let key_info = s.strip_prefix("inline:").ok_or_else(invalid)?;
+        let mut parts = key_info.split('|');
+        let key_and_salt = BASE64
+            .decode(parts.next().ok_or_else(invalid)?)
+            .map_err(|_| invalid())?;
+        let mut lifetime = None;
+        let mut mki = None;
+        for part in parts {
+            // Lifetime can be left out, but the MKI can always be recognized by its colon.
+            if let Some((value, len)) = part.split_once(':') {
+                mki = Some(Mki {
+                    value: value.parse().map_err(|_| invalid())?,
+                    len: len.parse().map_err(|_| invalid())?,
+                });
+            } else if let Some(exponent) = part.strip_prefix("2^") {
+                let exponent = exponent.parse().map_err(|_| invalid())?;
+                lifetime = Some(1_u64.checked_shl(exponent).ok_or_else(invalid)?);
+            } else {
+                lifetime = Some(part.parse().map_err(|_| invalid())?);
+            }
+        }
+        Ok(KeyParams {
+            key_and_salt,
+            lifetime,
+            mki,
+        })
+    }
+}
+
+/// Master key identifier and its length in bytes in SRTP packets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Mki {
+    pub value: u32,
+    pub len: u8,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 4568 section 9.1
+    const OFFER: [&str; 2] = [
+        "1 AES_CM_128_HMAC_SHA1_80 inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR|2^20|1:32",
+        "2 AES_CM_128_HMAC_SHA1_32 inline:NzB4d1BINUAvLEw6UzF3WSJ+PSdFcGdUJShpX1Zj|2^20|1:32",
+    ];
+    const ANSWER: &str =
+        "1 AES_CM_128_HMAC_SHA1_80 inline:d0RmdmcmVCspeEc3QGZiNWpVLFJhQX1cfHAwJSoj|2^20|1:32";
+
+    #[test]
+    fn parse_and_format_rfc4568_example() {
+        let attributes = OFFER
+            .iter()
+            .map(|value| Attribute::Value("crypto".to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+        let offer = Crypto::find_all(&attributes).unwrap();
+        assert_eq!(offer.len(), 2);
+        assert_eq!(offer[0].tag, 1);
+        assert_eq!(offer[0].suite, "AES_CM_128_HMAC_SHA1_80");
+        assert_eq!(offer[1].tag, 2);
+        assert_eq!(offer[1].suite, "AES_CM_128_HMAC_SHA1_32");
+        assert_eq!(
+            offer[0].key_params,
+            vec![KeyParams {
+                key_and_salt: BASE64
+                    .decode("PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR")
+                    .unwrap(),
+                lifetime: Some(1 << 20),
+                mki: Some(Mki { value: 1, len: 32 }),
+            }]
+        );
+        assert_eq!(offer[0].key_params[0].key_and_salt.len(), 30);
+        for (crypto, value) in offer.iter().zip(OFFER) {
+            assert_eq!(crypto.to_string(), value);
+            assert_eq!(
+                crypto.to_attribute(),
+                Attribute::Value("crypto".to_string(), value.to_string())
+            );
+        }
+
+        let answer = ANSWER.parse::<Crypto>().unwrap();
+        assert_eq!(answer.to_string(), ANSWER);
+    }
+
+    #[test]
+    fn parse_and_format_key_params() {
+        let crypto = "1 AES_CM_128_HMAC_SHA1_80 \
+            inline:PS1uQCVeeCFCanVmcjkpPywjNWhcYD0mXXtxaVBR|1066:4;\
+            inline:NzB4d1BINUAvLEw6UzF3WSJ+PSdFcGdUJShpX1Zj|2^20 \
+            UNENCRYPTED_SRTCP"
+            .parse::<Crypto>()
+            .unwrap();
+        assert_eq!(crypto.key_params.len(), 2);
+        assert_eq!(crypto.key_params[0].lifetime, None);
+        assert_eq!(
+            crypto.key_params[0].mki,
+            Some(Mki {
+                value: 1066,
+                len: 4
+            })
+        );
+        assert_eq!(crypto.key_params[1].lifetime, Some(1 << 20));
+        assert_eq!(crypto.key_params[1].mki, None);
+        assert_eq!(crypto.session_params, vec!["UNENCRYPTED_SRTCP"]);
+        assert_eq!(crypto.to_string().parse::<Crypto>().unwrap(), crypto);
+
+        let crypto = Crypto::new(1, "AEAD_AES_128_GCM", vec![0x01; 28]);
+        assert_eq!(
+            crypto.to_string(),
+            "1 AEAD_AES_128_GCM inline:AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQ=="
+        );
+        assert_eq!(crypto.to_string().parse::<Crypto>().unwrap(), crypto);
+
+        assert!("1 AES_CM_128_HMAC_SHA1_80".parse::<Crypto>().is_err());
+        assert!("1 AES_CM_128_HMAC_SHA1_80 key:AQEB"
+            .parse::<Crypto>()
+            .is_err());
+        assert!("1 AES_CM_128_HMAC_SHA1_80 inline:AQEB|2^64"
+            .parse::<Crypto>()
+            .is_err());
+    }
+
+    #[test]
+    fn key_and_salt_without_mki() {
+        let crypto = Crypto::new(1, "AES_CM_128_HMAC_SHA1_80", vec![0x01; 30]);
+        assert_eq!(crypto.key_and_salt().unwrap(), &[0x01; 30]);
+
+        let crypto = OFFER[0].parse::<Crypto>().unwrap();
+        assert!(matches!(
+            crypto.key_and_salt(),
+            Err(Error::CryptoKeyUnsupported { .. })
+        ));
+    }
+}
@@ -11,7 +11,10 @@ pub enum Error {
     ConnectionAddressMulticastInvalid { multicast: String },
     ConnectionLineInvalid { line: String },
     ConnectionMissing,
+    CryptoInvalid { crypto: String },
+    CryptoKeyUnsupported { crypto: String },
     DirectionUnknown { direction: String },
+    FmtpInvalid { fmtp: String },
     KindUnknown { kind: String },
     LinePrefixInvalid { line: String },
     MediaFormatInvalid { line: String },
@@ -23,6 +26,7 @@ pub enum Error {
     OriginUnicastAddressInvalid { unicast_address: String },
     ProtocolUnknown { protocol: String },
     RepeatTimesLineMalformed { line: String },
+    RtpmapInvalid { rtpmap: String },
     SessionNameMissing,
     TimeDescriptionInvalid { time: String },
     TimeInvalid { time: String },
@@ -68,7 +72,26 @@ impl std::fmt::Display for Error {
                 f,
                 "connection missing in global info or one or more media items"
             ),
+            Error::CryptoInvalid { crypto } => {
+                write!(
+                    f,
+                    "crypto attribute invalid (must be in format \
+                        <tag> <crypto suite> inline:<key||salt>[|<lifetime>][|<mki>:<length>]): {crypto}"
+                )
+            }
+            Error::CryptoKeyUnsupported { crypto } => {
+                write!(
+                    f,
+                    "crypto attribute must have single key without master key identifier: {crypto}"
+                )
+            }
             Error::DirectionUnknown { direction } => write!(f, "direction unknown: {direction}"),
+            Error::FmtpInvalid { fmtp } => {
+                write!(
+                    f,
+                    "fmtp attribute invalid (must be in format <format> <parameters>): {fmtp}"
+                )
+            }
             Error::KindUnknown { kind } => write!(f, "media kind unknown: {kind}"),
             Error::LinePrefixInvalid { line } => {
                 write!(f, "line does not start with a valid prefix: {line}")
@@ -105,6 +128,13 @@ impl std::fmt::Display for Error {
             Error::RepeatTimesLineMalformed { line } => {
                 write!(f, "repeat times line malformed: {line}")
             }
+            Error::RtpmapInvalid { rtpmap } => {
+                write!(
+                    f,
+                    "rtpmap attribute invalid (must be in format \
+                        <payload type> <encoding name>/<clock rate>[/<encoding parameters>]): {rtpmap}"
+                )
+            }
             Error::SessionNameMissing => write!(f, "session name missing"),
             Error::TimeDescriptionInvalid { time } => {
                 write!(f, "time description not a valid integer: {time}")
@@ -1,5 +1,6 @@
 pub mod builder;
 pub mod codec;
+pub mod crypto;
 pub mod error;
 pub mod reader;
 pub mod sdp;
@@ -8,7 +9,11 @@ pub mod time_range;
 mod time_utils;
 
 pub use builder::Builder;
+pub use codec::g711::Parameters as G711Parameters;
 pub use codec::h264::Parameters as H264Parameters;
+pub use codec::opus::Parameters as OpusParameters;
+pub use codec::{Fmtp, Rtpmap};
+pub use crypto::Crypto;
 pub use error::Error;
 pub use reader::Reader;
 pub use sdp::{
@@ -279,7 +279,7 @@ impl From<std::net::IpAddr> for Origin {
 
 impl std::fmt::Display for Origin {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        writeln!(
+        write!(
             f,
             "{} {} {} {} {} {}",
             self.username,
@@ -342,7 +342,7 @@ impl From<std::net::IpAddr> for Connection {
 
 impl std::fmt::Display for Connection {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        writeln!(
+        write!(
             f,
             "{} {} {}{}{}",
             self.network_type,
@@ -702,7 +702,7 @@ pub struct Media {
 
 impl std::fmt::Display for Media {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        writeln!(
+        write!(
             f,
             "{} {} {} {}",
             self.kind, self.port, self.protocol, self.format,
@@ -901,7 +901,7 @@ impl std::str::FromStr for NetworkType {
 
     fn from_str(s: &str) -> Result<Self> {
         match s {
-            "IP4" => Ok(NetworkType::Internet),
+            "IN" => Ok(NetworkType::Internet),
             _ => Err(Error::NetworkTypeUnknown {
                 network_type: s.to_string(),
             }),
@@ -971,3 +971,43 @@ fn parse_time<Integer: std::str::FromStr + std::ops::Mul<Output = Integer> + Fro
         parse_time_seconds(ts)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_network_type() {
+        assert_eq!("IN".parse::<NetworkType>().unwrap(), NetworkType::Internet);
+        assert!(matches!(
+            "IP4".parse::<NetworkType>(),
+            Err(Error::NetworkTypeUnknown { network_type }) if network_type == "IP4"
+        ));
+        let origin = "- 1 0 IN IP4 10.0.0.1".parse::<Origin>().unwrap();
+        assert_eq!(origin.network_type, NetworkType::Internet);
+        assert_eq!(origin.address_type, AddressType::IpV4);
+    }
+
+    #[test]
+    fn format_lines_without_line_ending() {
+        let origin = "- 1 0 IN IP4 10.0.0.1".parse::<Origin>().unwrap();
+        assert_eq!(origin.to_string(), "- 1 0 IN IP4 10.0.0.1");
+        let connection = "IN IP4 224.2.36.42/127".parse::<Connection>().unwrap();
+        assert_eq!(connection.to_string(), "IN IP4 224.2.36.42/127");
+        let media = "video 0 RTP/AVP 96".parse::<Media>().unwrap();
+        assert_eq!(media.to_string(), "video 0 RTP/AVP 96");
+
+        let sdp = Sdp::parse(
+            "v=0\r\n\
+             o=- 1 0 IN IP4 10.0.0.1\r\n\
+             s=Stream\r\n\
+             c=IN IP4 10.0.0.1\r\n\
+             t=0 0\r\n\
+             m=video 0 RTP/AVP 96\r\n",
+        )
+        .unwrap();
+        let formatted = sdp.to_string();
+        assert!(!formatted.lines().any(str::is_empty));
+        assert_eq!(Sdp::parse(&formatted).unwrap(), sdp);
+    }
+}
@@ -0,0 +1,116 @@
+pub trait SampleFormat: Copy + Clone + PartialEq + Eq {
+    const ID: &'static str;
+    const PLANAR: bool;
+
+    type T;
+    type Data;
+
+    /// Number of samples per channel in data with the given number of channels.
+    fn num_samples(data: &Self::Data, num_channels: usize) -> usize;
+}
+
+/// Signed 16-bit samples, interleaved.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct S16;
+
+impl SampleFormat for S16 {
+    const ID: &'static str = "s16";
+    const PLANAR: bool = false;
+
+    type T = i16;
+    type Data = Vec<Self::T>;
+
+    fn num_samples(data: &Self::Data, num_channels: usize) -> usize {
+        data.len() / num_channels.max(1)
+    }
+}
+
+/// Signed 16-bit samples, planar (one plane per channel).
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct S16p;
+
+impl SampleFormat for S16p {
+    const ID: &'static str = "s16p";
+    const PLANAR: bool = true;
+
+    type T = i16;
+    type Data = Vec<Vec<Self::T>>;
+
+    fn num_samples(data: &Self::Data, _num_channels: usize) -> usize {
+        data.first().map(Vec::len).unwrap_or(0)
+    }
+}
+
+/// 32-bit floating point samples, interleaved.
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct F32;
+
+impl SampleFormat for F32 {
+    const ID: &'static str = "f32";
+    const PLANAR: bool = false;
+
+    type T = f32;
+    type Data = Vec<Self::T>;
+
+    fn num_samples(data: &Self::Data, num_channels: usize) -> usize {
+        data.len() / num_channels.max(1)
+    }
+}
+
+/// 32-bit floating point samples, planar (one plane per channel).
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct F32p;
+
+impl SampleFormat for F32p {
+    const ID: &'static str = "f32p";
+    const PLANAR: bool = true;
+
+    type T = f32;
+    type Data = Vec<Vec<Self::T>>;
+
+    fn num_samples(data: &Self::Data, _num_channels: usize) -> usize {
+        data.first().map(Vec::len).unwrap_or(0)
+    }
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ChannelLayout {
+    Mono,
+    Stereo,
+    /// Any number of channels without a specific speaker position.
+    Discrete(usize),
+}
+
+impl ChannelLayout {
+    /// Create channel layout for number of channels.
+    pub fn from_num_channels(num_channels: usize) -> Self {
+        match num_channels {
+            1 => ChannelLayout::Mono,
+            2 => ChannelLayout::Stereo,
+            _ => ChannelLayout::Discrete(num_channels),
+        }
+    }
+
+    pub fn num_channels(&self) -> usize {
+        match self {
+            ChannelLayout::Mono => 1,
+            ChannelLayout::Stereo => 2,
+            ChannelLayout::Discrete(num_channels) => *num_channels,
+        }
+    }
+}
+
+macro_rules! impl_display_for {
+    ($f:ty) => {
+        impl std::fmt::Display for $f {
+            fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                write!(f, "{}", Self::ID)
+            }
+        }
+    };
+}
+
+impl_display_for!(S16);
+impl_display_for!(S16p);
+impl_display_for!(F32);
+impl_display_for!(F32p);
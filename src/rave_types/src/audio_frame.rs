@@ -0,0 +1,41 @@
+use crate::audio_format::{ChannelLayout, F32p, S16p, SampleFormat, F32, S16};
+use crate::device::{Device, Local};
+
+// convenience aliases
+pub type S16AudioFrame = AudioFrame<Local, S16>;
+pub type S16pAudioFrame = AudioFrame<Local, S16p>;
+pub type F32AudioFrame = AudioFrame<Local, F32>;
+pub type F32pAudioFrame = AudioFrame<Local, F32p>;
+
+pub struct AudioFrame<D: Device, F: SampleFormat> {
+    pub data: D::Container<F::Data>,
+    pub sample_rate: u32,
+    pub channel_layout: ChannelLayout,
+}
+
+impl<D: Device, F: SampleFormat> AudioFrame<D, F> {
+    pub fn new(
+        data: D::Container<F::Data>,
+        sample_rate: u32,
+        channel_layout: ChannelLayout,
+    ) -> Self {
+        Self {
+            data,
+            sample_rate,
+            channel_layout,
+        }
+    }
+
+    #[inline]
+    pub fn num_channels(&self) -> usize {
+        self.channel_layout.num_channels()
+    }
+}
+
+impl<F: SampleFormat> AudioFrame<Local, F> {
+    /// Number of samples per channel.
+    #[inline]
+    pub fn num_samples(&self) -> usize {
+        F::num_samples(&self.data, self.num_channels())
+    }
+}
@@ -13,3 +13,77 @@ impl Codec for H264 {
 
     type Data = Bytes;
 }
+
+pub struct H265;
+
+impl Codec for H265 {
+    const ID: &'static str = "h265";
+
+    type Data = Bytes;
+}
+
+pub struct Vp8;
+
+impl Codec for Vp8 {
+    const ID: &'static str = "vp8";
+
+    type Data = Bytes;
+}
+
+pub struct Vp9;
+
+impl Codec for Vp9 {
+    const ID: &'static str = "vp9";
+
+    type Data = Bytes;
+}
+
+pub struct Av1;
+
+impl Codec for Av1 {
+    const ID: &'static str = "av1";
+
+    type Data = Bytes;
+}
+
+pub struct Jpeg;
+
+impl Codec for Jpeg {
+    const ID: &'static str = "jpeg";
+
+    type Data = Bytes;
+}
+
+pub struct Aac;
+
+impl Codec for Aac {
+    const ID: &'static str = "aac";
+
+    type Data = Bytes;
+}
+
+pub struct Opus;
+
+impl Codec for Opus {
+    const ID: &'static str = "opus";
+
+    type Data = Bytes;
+}
+
+/// G.711 µ-law.
+pub struct Pcmu;
+
+impl Codec for Pcmu {
+    const ID: &'static str = "pcmu";
+
+    type Data = Bytes;
+}
+
+/// G.711 A-law.
+pub struct Pcma;
+
+impl Codec for Pcma {
+    const ID: &'static str = "pcma";
+
+    type Data = Bytes;
+}
@@ -1,3 +1,5 @@
+use crate::audio_format::SampleFormat;
+use crate::audio_frame::AudioFrame;
 use crate::codec::Codec;
 use crate::device::Device;
 use crate::format::Format;
@@ -18,3 +20,18 @@ pub trait Decode {
         unit: Unit<Self::Codec>,
     ) -> DecodeResult<Self::Device, Self::Format, Self::Error>;
 }
+
+pub type DecodeAudioResult<Device, SampleFormat, Error> =
+    std::result::Result<Option<AudioFrame<Device, SampleFormat>>, Error>;
+
+pub trait DecodeAudio {
+    type Device: Device;
+    type Codec: Codec;
+    type SampleFormat: SampleFormat;
+    type Error;
+
+    fn decode(
+        &mut self,
+        unit: Unit<Self::Codec>,
+    ) -> DecodeAudioResult<Self::Device, Self::SampleFormat, Self::Error>;
+}
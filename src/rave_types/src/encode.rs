@@ -1,3 +1,5 @@
+use crate::audio_format::SampleFormat;
+use crate::audio_frame::AudioFrame;
 use crate::codec::Codec;
 use crate::device::Device;
 use crate::format::Format;
@@ -15,3 +17,15 @@ pub trait Encode {
         frame: Frame<Self::Device, Self::Format>,
     ) -> Result<Vec<Unit<Self::Codec>>, Self::Error>;
 }
+
+pub trait EncodeAudio {
+    type Device: Device;
+    type Codec: Codec;
+    type SampleFormat: SampleFormat;
+    type Error;
+
+    fn encode(
+        &mut self,
+        frame: AudioFrame<Self::Device, Self::SampleFormat>,
+    ) -> Result<Vec<Unit<Self::Codec>>, Self::Error>;
+}
@@ -3,6 +3,8 @@
 // TODO: use crate::error::Error;
 // TODO: feature flag for cuda stuff
 
+pub mod audio_format;
+pub mod audio_frame;
 pub mod codec;
 pub mod decode;
 pub mod device;
@@ -11,10 +13,12 @@ pub mod format;
 pub mod frame;
 pub mod unit;
 
-pub use codec::{Codec, H264};
-pub use decode::Decode;
+pub use audio_format::{ChannelLayout, F32p, S16p, SampleFormat, F32, S16};
+pub use audio_frame::{AudioFrame, F32AudioFrame, F32pAudioFrame, S16AudioFrame, S16pAudioFrame};
+pub use codec::{Aac, Av1, Codec, Jpeg, Opus, Pcma, Pcmu, Vp8, Vp9, H264, H265};
+pub use decode::{Decode, DecodeAudio};
 pub use device::{Cuda, Device, Local};
-pub use encode::Encode;
+pub use encode::{Encode, EncodeAudio};
 pub use format::{Format, Planar, Plane, Rgb24, Yuv420p};
 pub use frame::{Frame, RgbFrame, Yuv420pFrame};
 pub use unit::Unit;